@@ -0,0 +1,31 @@
+use actix_web::{App, HttpResponse, HttpServer};
+use dotenvy;
+use mal_api::oauth::MalOauthState;
+
+// Set MAL_REDIRECT_URL to http://localhost:8080/mal/callback for this example
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    dotenvy::dotenv().ok();
+
+    let state = MalOauthState::try_from_env().unwrap();
+
+    println!("Visit http://localhost:8080/mal/login to log in with MAL");
+    HttpServer::new(move || {
+        App::new().service(mal_api::oauth::actix::scope(
+            "/mal",
+            state.clone(),
+            |client| async move {
+                // Save credentials to config to be re-used later
+                let _ = client.save_to_config(".mal/config.toml");
+
+                HttpResponse::Ok().body(format!(
+                    "Logged in! Your access token expires at {}",
+                    client.get_expires_at()
+                ))
+            },
+        ))
+    })
+    .bind(("127.0.0.1", 8080))?
+    .run()
+    .await
+}
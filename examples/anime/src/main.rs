@@ -1,6 +1,7 @@
 use dotenvy;
 use mal_api::oauth::MalClientId;
 use mal_api::prelude::*;
+use mal_api::well_known::GINTAMA_PRIME_ANIME_ID;
 
 #[tokio::main]
 async fn main() {
@@ -18,7 +19,7 @@ async fn main() {
         .unwrap();
     let response = api_client.get_anime_list(&query).await;
     if let Ok(response) = response {
-        println!("Received response: {}\n", response);
+        println!("Received response: {}\n", response.to_json());
         for entry in response.data.iter() {
             println!(
                 "Anime Title: {}  Anime ID: {}",
@@ -27,13 +28,13 @@ async fn main() {
         }
     }
 
-    let query = GetAnimeDetails::builder(9969)
+    let query = GetAnimeDetails::builder(GINTAMA_PRIME_ANIME_ID)
         .fields(&detail_fields)
         .build()
         .unwrap();
     let response = api_client.get_anime_details(&query).await;
     if let Ok(response) = response {
-        println!("Received response: {}\n", response);
+        println!("Received response: {}\n", response.to_json());
     }
 
     let query = GetAnimeRanking::builder(RankingType::ByPopularity)
@@ -43,7 +44,7 @@ async fn main() {
         .build();
     let response = api_client.get_anime_ranking(&query).await;
     if let Ok(response) = response {
-        println!("Received response: {}\n", response);
+        println!("Received response: {}\n", response.to_json());
     }
 
     let query = GetSeasonalAnime::builder(2022, Season::Summer)
@@ -54,6 +55,6 @@ async fn main() {
         .build();
     let response = api_client.get_seasonal_anime(&query).await;
     if let Ok(response) = response {
-        println!("Received response: {}\n", response);
+        println!("Received response: {}\n", response.to_json());
     }
 }
@@ -69,25 +69,25 @@ async fn endpoints(oauth_client: &OauthClient<Authenticated>) {
         .build();
     let response = anime_api_client.get_suggested_anime(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let query = GetUserAnimeList::builder("@me").limit(5).build().unwrap();
     let response = anime_api_client.get_user_anime_list(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let query = GetUserMangaList::builder("@me").limit(5).build().unwrap();
     let response = manga_api_client.get_user_manga_list(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let user_fields = mal_api::user::all_fields();
     let query = GetUserInformation::new(Some(&user_fields));
     let response = user_api_client.get_my_user_information(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 }
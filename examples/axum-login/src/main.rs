@@ -0,0 +1,32 @@
+use axum::{routing::get, Router};
+use dotenvy;
+use mal_api::oauth::axum::{login, MalCallback};
+use mal_api::oauth::MalOauthState;
+
+// Set MAL_REDIRECT_URL to http://localhost:8080/callback for this example
+async fn callback(MalCallback(client): MalCallback) -> String {
+    // Save credentials to config to be re-used later
+    let _ = client.save_to_config(".mal/config.toml");
+
+    format!(
+        "Logged in! Your access token expires at {}",
+        client.get_expires_at()
+    )
+}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    let state = MalOauthState::try_from_env().unwrap();
+    let app = Router::new()
+        .route("/login", get(login))
+        .route("/callback", get(callback))
+        .with_state(state);
+
+    println!("Visit http://localhost:8080/login to log in with MAL");
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:8080")
+        .await
+        .unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
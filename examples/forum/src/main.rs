@@ -12,13 +12,13 @@ async fn main() {
 
     let topics = api_client.get_forum_boards().await;
     if let Ok(topics) = topics {
-        println!("Topics: {}\n", topics);
+        println!("Topics: {}\n", topics.to_json());
     }
 
     let query = GetForumTopicDetail::new(481, limit, None).unwrap();
     let response = api_client.get_forum_topic_detail(&query).await;
     if let Ok(response) = response {
-        println!("Forum topic detail: {}\n", response);
+        println!("Forum topic detail: {}\n", response.to_json());
     }
 
     let query = GetForumTopics::builder()
@@ -29,6 +29,6 @@ async fn main() {
         .unwrap();
     let response = api_client.get_forum_topics(&query).await;
     if let Ok(response) = response {
-        println!("Forum topics: {}", response)
+        println!("Forum topics: {}", response.to_json())
     }
 }
@@ -1,6 +1,7 @@
 use dotenvy;
 use mal_api::oauth::MalClientId;
 use mal_api::prelude::*;
+use mal_api::well_known::ONE_PUNCH_MAN_MANGA_ID;
 
 #[tokio::main]
 async fn main() {
@@ -18,16 +19,16 @@ async fn main() {
         .unwrap();
     let response = api_client.get_manga_list(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
-    let query = GetMangaDetails::builder(44347)
+    let query = GetMangaDetails::builder(ONE_PUNCH_MAN_MANGA_ID)
         .fields(&detail_fields)
         .build()
         .unwrap();
     let response = api_client.get_manga_details(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let query = GetMangaRanking::builder(MangaRankingType::All)
@@ -37,6 +38,6 @@ async fn main() {
         .build();
     let response = api_client.get_manga_ranking(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 }
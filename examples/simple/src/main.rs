@@ -22,7 +22,7 @@ async fn main() {
         .build()
         .unwrap();
     let result = api_client.get_anime_list(&query).await.unwrap();
-    println!("Received response: {}\n", result);
+    println!("Received response: {}\n", result.to_json());
     for entry in result.data.iter() {
         println!(
             "Anime Title: {}  Anime ID: {}",
@@ -32,10 +32,10 @@ async fn main() {
 
     // Example iterating through pages
     let result = api_client.next(&result).await.unwrap();
-    println!("\nNext result: {}", &result);
+    println!("\nNext result: {}", result.to_json());
 
     let result = api_client.prev(&result).await.unwrap();
-    println!("\nPrev result: {}", &result);
+    println!("\nPrev result: {}", result.to_json());
 
     // Manga API example
     let api_client = MangaApiClient::from(&client_id);
@@ -46,5 +46,5 @@ async fn main() {
     let limit = Some(5);
     let query = GetMangaList::new("one".to_string(), nsfw, Some(&fields), limit, None).unwrap();
     let result = api_client.get_manga_list(&query).await.unwrap();
-    println!("Result: {}", result);
+    println!("Result: {}", result.to_json());
 }
@@ -1,5 +1,5 @@
 use dotenvy;
-use mal_api::{oauth::RedirectResponse, prelude::*};
+use mal_api::{oauth::RedirectResponse, prelude::*, well_known::ONE_PIECE_ANIME_ID};
 use std::io;
 
 #[tokio::main]
@@ -38,13 +38,13 @@ async fn main() {
     let manga_api_client = MangaApiClient::from(&authenticated_oauth_client);
 
     // Update One Piece episodes watched
-    let query = UpdateMyAnimeListStatus::builder(21)
+    let query = UpdateMyAnimeListStatus::builder(ONE_PIECE_ANIME_ID)
         .num_watched_episodes(1069)
         .build()
         .unwrap();
     let response = anime_api_client.update_anime_list_status(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let query = UpdateMyMangaListStatus::builder(91941)
@@ -53,18 +53,18 @@ async fn main() {
         .unwrap();
     let response = manga_api_client.update_manga_list_status(&query).await;
     if let Ok(response) = response {
-        println!("Response: {}\n", response);
+        println!("Response: {}\n", response.to_json());
     }
 
     let query = DeleteMyAnimeListItem::new(52619);
     let response = anime_api_client.delete_anime_list_item(&query).await;
-    if let Ok(_) = response {
-        println!("Deleted anime entry");
+    if let Ok(outcome) = response {
+        println!("Deleted anime entry: {:?}", outcome);
     }
 
     let query = DeleteMyMangaListItem::new(48881);
     let response = manga_api_client.delete_manga_list_item(&query).await;
-    if let Ok(_) = response {
-        println!("Deleted manga entry");
+    if let Ok(outcome) = response {
+        println!("Deleted manga entry: {:?}", outcome);
     }
 }
@@ -0,0 +1,111 @@
+//! Derive macros for [mal-api](https://docs.rs/mal-api)
+//!
+//! Use them through the `derive` feature of `mal-api` instead of depending on
+//! this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident};
+
+/// Derive `MalProjection` for a struct with a subset of the anime or manga fields
+///
+/// See the `mal_api::projection` module for details.
+#[proc_macro_derive(MalProjection, attributes(mal))]
+pub fn derive_mal_projection(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "MalProjection can't be derived for generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "MalProjection can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "MalProjection can only be derived for structs",
+            ))
+        }
+    };
+    let idents: Vec<&Ident> = fields.iter().filter_map(|f| f.ident.as_ref()).collect();
+    let names: Vec<String> = idents.iter().map(|i| i.to_string()).collect();
+
+    let field_type = match kind(input)?.as_str() {
+        "anime" => quote!(::mal_api::anime::requests::AnimeField),
+        "anime_details" => quote!(::mal_api::anime::requests::AnimeDetail),
+        "manga" => quote!(::mal_api::manga::requests::MangaField),
+        "manga_details" => quote!(::mal_api::manga::requests::MangaDetail),
+        _ => unreachable!(),
+    };
+
+    Ok(quote! {
+        impl ::mal_api::projection::MalProjection for #name {
+            type Field = #field_type;
+
+            fn fields() -> ::mal_api::common::FieldSet<Self::Field> {
+                ::mal_api::common::FieldSet::new(::std::vec![#(#field_type::#idents),*])
+            }
+        }
+
+        impl<'de> ::mal_api::projection::__private::Deserialize<'de> for #name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: ::mal_api::projection::__private::Deserializer<'de>,
+            {
+                let mut object = ::mal_api::projection::__private::object(deserializer)?;
+                ::std::result::Result::Ok(Self {
+                    #(#idents: ::mal_api::projection::__private::take(&mut object, #names)?,)*
+                })
+            }
+        }
+    })
+}
+
+// The endpoints the struct is projected from, set with `#[mal(anime)]`,
+// `#[mal(anime_details)]`, `#[mal(manga)]`, or `#[mal(manga_details)]`
+fn kind(input: &DeriveInput) -> syn::Result<String> {
+    let mut kind = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("mal")) {
+        attr.parse_nested_meta(|meta| {
+            let ident = meta.path.get_ident().map(|i| i.to_string());
+            match ident.as_deref() {
+                Some(name @ ("anime" | "anime_details" | "manga" | "manga_details")) => {
+                    if kind.is_some() {
+                        return Err(meta.error(
+                            "only one of anime, anime_details, manga, or manga_details can be set",
+                        ));
+                    }
+                    kind = Some(name.to_string());
+                    Ok(())
+                }
+                _ => {
+                    Err(meta.error("expected one of anime, anime_details, manga, or manga_details"))
+                }
+            }
+        })?;
+    }
+    kind.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "missing #[mal(anime)], #[mal(anime_details)], #[mal(manga)], or #[mal(manga_details)]",
+        )
+    })
+}
@@ -0,0 +1,58 @@
+// Compares parsing a response body from bytes against decoding it into a String
+// first, which is what the clients did before reading responses with `bytes()`
+use criterion::{criterion_group, criterion_main, Criterion};
+use mal_api::anime::responses::AnimeList;
+use serde_json::json;
+
+// A user anime list page with 1000 entries, the largest page MAL returns
+fn anime_list_page() -> Vec<u8> {
+    let data: Vec<_> = (1..=1000)
+        .map(|id| {
+            json!({
+                "node": {
+                    "id": id,
+                    "title": format!("Anime {}", id),
+                    "main_picture": {
+                        "medium": format!("https://cdn.myanimelist.net/images/anime/{}/1.jpg", id),
+                        "large": format!("https://cdn.myanimelist.net/images/anime/{}/1l.jpg", id)
+                    },
+                    "alternative_titles": {
+                        "synonyms": [format!("Synonym {}", id)],
+                        "en": format!("English title {}", id),
+                        "ja": "アニメ"
+                    },
+                    "synopsis": "A synopsis that is a few sentences long. ".repeat(20),
+                    "mean": 8.5,
+                    "num_episodes": 12,
+                    "genres": [{ "id": 1, "name": "Action" }, { "id": 2, "name": "Adventure" }]
+                },
+                "list_status": {
+                    "status": "completed",
+                    "score": 8,
+                    "num_episodes_watched": 12,
+                    "is_rewatching": false,
+                    "updated_at": "2023-01-01T00:00:00+00:00"
+                }
+            })
+        })
+        .collect();
+    serde_json::to_vec(&json!({ "data": data, "paging": {} })).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let body = anime_list_page();
+    let mut group = c.benchmark_group("parse anime list page");
+    group.bench_function("from_slice", |b| {
+        b.iter(|| serde_json::from_slice::<AnimeList>(&body).unwrap())
+    });
+    group.bench_function("decode then from_str", |b| {
+        b.iter(|| {
+            let text = String::from_utf8_lossy(&body).into_owned();
+            serde_json::from_str::<AnimeList>(&text).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+use cfg_aliases::cfg_aliases;
+
+fn main() {
+    cfg_aliases! {
+        // Any of the API clients
+        api: { any(feature = "anime", feature = "manga", feature = "forum", feature = "user") },
+        // The anime or manga clients, which share the list types
+        lists: { any(feature = "anime", feature = "manga") },
+        // The clients requesting user endpoints, e.g. the user lists
+        users: { any(feature = "anime", feature = "manga", feature = "user") },
+    }
+}
@@ -0,0 +1,133 @@
+//! Module for reconstructing a user's activity from their list
+//!
+//! MAL doesn't expose a user's history, but every list entry records when it was
+//! last updated. That is enough for a rough activity timeline, such as the
+//! heatmaps on profile pages. Only the latest update of each entry is known, so
+//! older activity on entries that were updated again is lost.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetUserAnimeList::builder("@me").fields(&fields).limit(1000).build().unwrap();
+//! let list = api_client.get_user_anime_list(&query).await.unwrap();
+//!
+//! for bucket in activity::activity_counts(&list.data, ActivityPeriod::Week) {
+//!     println!("Week of {}: {} updates", bucket.start, bucket.count);
+//! }
+//! ```
+
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use crate::{
+    dates::{civil_from_days, days_from_civil, parse_timestamp},
+    diff::ListEntry,
+};
+
+/// Length of the buckets of [activity_counts]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityPeriod {
+    Day,
+
+    /// Weeks starting on Monday
+    Week,
+
+    Month,
+}
+
+/// Number of list entries last updated within a period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityBucket {
+    /// First day of the period in UTC, formatted as `YYYY-MM-DD`
+    pub start: String,
+
+    pub count: u32,
+}
+
+/// Order the entries by when they were last updated, newest first
+///
+/// Entries without a valid `updated_at` are left out
+pub fn timeline<T: ListEntry>(entries: &[T]) -> Vec<&T> {
+    let mut timeline: Vec<(i64, &T)> = entries
+        .iter()
+        .filter_map(|e| Some((parse_timestamp(e.list_updated_at()?)?, e)))
+        .collect();
+    timeline.sort_by_key(|(timestamp, _)| Reverse(*timestamp));
+    timeline.into_iter().map(|(_, e)| e).collect()
+}
+
+/// Count the entries last updated within each period, oldest first
+///
+/// Periods without any updates are left out
+pub fn activity_counts<T: ListEntry>(entries: &[T], period: ActivityPeriod) -> Vec<ActivityBucket> {
+    let mut counts: BTreeMap<i64, u32> = BTreeMap::new();
+    for entry in entries {
+        let timestamp = match entry.list_updated_at().and_then(parse_timestamp) {
+            Some(timestamp) => timestamp,
+            None => continue,
+        };
+        *counts.entry(period_start(timestamp, period)).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(start, count)| {
+            let (year, month, day) = civil_from_days(start);
+            ActivityBucket {
+                start: format!("{:04}-{:02}-{:02}", year, month, day),
+                count,
+            }
+        })
+        .collect()
+}
+
+// Day number since the Unix Epoch of the first day of the period
+fn period_start(timestamp: i64, period: ActivityPeriod) -> i64 {
+    let day = timestamp.div_euclid(86400);
+    match period {
+        ActivityPeriod::Day => day,
+        // The Unix Epoch was a Thursday
+        ActivityPeriod::Week => day - (day + 3).rem_euclid(7),
+        ActivityPeriod::Month => {
+            let (year, month, _) = civil_from_days(day);
+            days_from_civil(year, month, 1)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::anime::responses::AnimeListNode;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, updated_at: &str) -> AnimeListNode {
+        anime_entry(id).updated_at(updated_at).build()
+    }
+
+    #[test]
+    fn test_activity() {
+        let entries = vec![
+            entry(1, "2023-01-31T23:00:00+00:00"),
+            entry(2, "2023-02-01T10:00:00+00:00"),
+            entry(3, "2023-02-03T10:00:00+00:00"),
+            entry(4, "not a date"),
+        ];
+
+        let ids: Vec<u32> = timeline(&entries).iter().map(|e| e.node.id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+
+        let bucket = |start: &str, count| ActivityBucket {
+            start: start.to_string(),
+            count,
+        };
+        assert_eq!(
+            activity_counts(&entries, ActivityPeriod::Month),
+            vec![bucket("2023-01-01", 1), bucket("2023-02-01", 2)]
+        );
+        assert_eq!(
+            activity_counts(&entries, ActivityPeriod::Week),
+            vec![bucket("2023-01-30", 3)]
+        );
+        assert_eq!(activity_counts(&entries, ActivityPeriod::Day).len(), 3);
+    }
+}
@@ -1,34 +1,132 @@
 //! Module for interacting with the `anime` and `user animelist` endpoints
 
 use self::requests::{AnimeCommonFields, AnimeDetail, AnimeDetailFields, AnimeField};
-use strum::IntoEnumIterator;
 
 /// Anime API client
 pub mod api;
 
+/// Anime search autocompletion
+pub mod autocomplete;
+
 /// Anime API errors
 pub mod error;
 
+/// Anime details loader
+pub mod loader;
+
+/// Seasonal backlog planning
+pub mod planner;
+
+/// Rich presence formatting
+pub mod presence;
+
 /// Anime API request structs
 pub mod requests;
 
 /// Anime API responses
 pub mod responses;
 
+/// Rewatch candidate ranking
+pub mod rewatch;
+
+/// Anime search over several query variants
+pub mod search;
+
+/// Seasonal anime cache
+pub mod seasonal;
+
+/// Missing sequel detection
+pub mod sequels;
+
+/// Suggested anime filtering
+pub mod suggestions;
+
+/// Anime opening and ending theme parsing
+pub mod themes;
+
+/// Anime episode count and airing status change detection
+pub mod updates;
+
+/// All of the possible [AnimeField] fields
+pub const ANIME_COMMON_FIELDS: &[AnimeField] = &[
+    AnimeField::id,
+    AnimeField::title,
+    AnimeField::main_picture,
+    AnimeField::alternative_titles,
+    AnimeField::start_date,
+    AnimeField::end_date,
+    AnimeField::synopsis,
+    AnimeField::mean,
+    AnimeField::rank,
+    AnimeField::popularity,
+    AnimeField::num_list_users,
+    AnimeField::num_scoring_users,
+    AnimeField::nsfw,
+    AnimeField::genres,
+    AnimeField::created_at,
+    AnimeField::updated_at,
+    AnimeField::media_type,
+    AnimeField::status,
+    AnimeField::my_list_status,
+    AnimeField::num_episodes,
+    AnimeField::start_season,
+    AnimeField::broadcast,
+    AnimeField::source,
+    AnimeField::average_episode_duration,
+    AnimeField::rating,
+    AnimeField::studios,
+];
+
+/// All of the possible [AnimeField] fields, formatted for the `fields` query parameter
+pub const ANIME_COMMON_FIELDS_STR: &str = "id,title,main_picture,alternative_titles,start_date,end_date,synopsis,mean,rank,popularity,num_list_users,num_scoring_users,nsfw,genres,created_at,updated_at,media_type,status,my_list_status,num_episodes,start_season,broadcast,source,average_episode_duration,rating,studios";
+
+/// All of the possible [AnimeDetail] fields
+pub const ANIME_DETAIL_FIELDS: &[AnimeDetail] = &[
+    AnimeDetail::id,
+    AnimeDetail::title,
+    AnimeDetail::main_picture,
+    AnimeDetail::alternative_titles,
+    AnimeDetail::start_date,
+    AnimeDetail::end_date,
+    AnimeDetail::synopsis,
+    AnimeDetail::mean,
+    AnimeDetail::rank,
+    AnimeDetail::popularity,
+    AnimeDetail::num_list_users,
+    AnimeDetail::num_scoring_users,
+    AnimeDetail::nsfw,
+    AnimeDetail::genres,
+    AnimeDetail::created_at,
+    AnimeDetail::updated_at,
+    AnimeDetail::media_type,
+    AnimeDetail::status,
+    AnimeDetail::my_list_status,
+    AnimeDetail::num_episodes,
+    AnimeDetail::start_season,
+    AnimeDetail::broadcast,
+    AnimeDetail::source,
+    AnimeDetail::average_episode_duration,
+    AnimeDetail::rating,
+    AnimeDetail::studios,
+    AnimeDetail::pictures,
+    AnimeDetail::background,
+    AnimeDetail::related_anime,
+    AnimeDetail::related_manga,
+    AnimeDetail::recommendations,
+    AnimeDetail::statistics,
+    AnimeDetail::opening_themes,
+    AnimeDetail::ending_themes,
+];
+
+/// All of the possible [AnimeDetail] fields, formatted for the `fields` query parameter
+pub const ANIME_DETAIL_FIELDS_STR: &str = "id,title,main_picture,alternative_titles,start_date,end_date,synopsis,mean,rank,popularity,num_list_users,num_scoring_users,nsfw,genres,created_at,updated_at,media_type,status,my_list_status,num_episodes,start_season,broadcast,source,average_episode_duration,rating,studios,pictures,background,related_anime,related_manga,recommendations,statistics,opening_themes,ending_themes";
+
 /// Return all of the possible [AnimeField] fields
 pub fn all_common_fields() -> AnimeCommonFields {
-    let mut vec = Vec::with_capacity(AnimeField::iter().len());
-    for variant in AnimeField::iter() {
-        vec.push(variant);
-    }
-    AnimeCommonFields(vec)
+    AnimeCommonFields::with_formatted(ANIME_COMMON_FIELDS.to_vec(), ANIME_COMMON_FIELDS_STR)
 }
 
 /// Return all of the possible [AnimeDetail] fields
 pub fn all_detail_fields() -> AnimeDetailFields {
-    let mut vec = Vec::with_capacity(AnimeDetail::iter().len());
-    for variant in AnimeDetail::iter() {
-        vec.push(variant);
-    }
-    AnimeDetailFields(vec)
+    AnimeDetailFields::with_formatted(ANIME_DETAIL_FIELDS.to_vec(), ANIME_DETAIL_FIELDS_STR)
 }
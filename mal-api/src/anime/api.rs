@@ -4,31 +4,50 @@ use super::{
     responses::AnimeListStatus,
 };
 use async_trait::async_trait;
-use oauth2::{AccessToken, ClientId};
+use bytes::Bytes;
+use futures::future::join_all;
 use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
 use std::marker::{PhantomData, Send, Sync};
+use std::pin::Pin;
+use std::sync::Arc;
 
 use crate::{
-    common::{struct_to_form_data, PagingIter},
-    oauth::{Authenticated, MalClientId, OauthClient},
+    bulk::{run_bulk, BulkOptions, BulkResult},
+    codes::ErrorCode,
+    common::{
+        parse_response, struct_to_form_data, unexpected_response, DeleteOutcome, EntryLookup,
+        Paging, PagingIter,
+    },
+    http::{self, SendThrough, Transport},
+    oauth::{
+        provider::{AuthProvider, AuthState},
+        MalAccessToken, MalClientId,
+    },
     ANIME_URL, USER_URL,
 };
 
+#[cfg(feature = "oauth")]
+use crate::oauth::{Authenticated, OauthClient};
+
 use super::{
     requests::{
-        GetAnimeDetails, GetAnimeList, GetAnimeRanking, GetSeasonalAnime, GetSuggestedAnime,
+        AnimeDetail, AnimeDetailFields, GetAnimeDetails, GetAnimeList, GetAnimeRanking,
+        GetSeasonalAnime, GetSuggestedAnime,
+    },
+    responses::{
+        AnimeDetails, AnimeList, AnimeListNode, AnimeRanking, SeasonalAnime, SuggestedAnime,
     },
-    responses::{AnimeDetails, AnimeList, AnimeRanking, SeasonalAnime, SuggestedAnime},
+    suggestions::{FranchiseWalker, SuggestionFilter},
 };
-use reqwest;
+use reqwest::{self, RequestBuilder};
 
-#[doc(hidden)]
-#[derive(Debug)]
-pub struct Client {}
+#[cfg(feature = "stream-parse")]
+use crate::stream::ListStream;
 
 #[doc(hidden)]
-#[derive(Debug)]
-pub struct Oauth {}
+pub use crate::oauth::provider::{Client, Oauth};
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -50,6 +69,7 @@ pub struct None {}
 /// use dotenvy;
 /// use mal_api::oauth::MalClientId;
 /// use mal_api::prelude::*;
+/// use mal_api::well_known::GINTAMA_PRIME_ANIME_ID;
 ///
 /// #[tokio::main]
 /// async fn main() {
@@ -67,19 +87,19 @@ pub struct None {}
 ///         .unwrap();
 ///     let response = api_client.get_anime_list(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Received response: {}\n", response);
+///         println!("Received response: {}\n", response.to_json());
 ///         for entry in response.data.iter() {
 ///             println!("Id: {}", entry.node.id);
 ///         }
 ///     }
 ///
-///     let query = GetAnimeDetails::builder(9969)
+///     let query = GetAnimeDetails::builder(GINTAMA_PRIME_ANIME_ID)
 ///         .fields(&detail_fields)
 ///         .build()
 ///         .unwrap();
 ///     let response = api_client.get_anime_details(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Received response: {}\n", response);
+///         println!("Received response: {}\n", response.to_json());
 ///     }
 /// }
 /// ```
@@ -87,72 +107,303 @@ pub struct None {}
 #[derive(Debug, Clone)]
 pub struct AnimeApiClient<State = None> {
     client: reqwest::Client,
-    client_id: Option<String>,
-    access_token: Option<String>,
+    transport: Transport,
+    auth: Arc<dyn AuthProvider<State = State>>,
+    adaptive_limit: bool,
     state: PhantomData<State>,
 }
 
-impl From<&AccessToken> for AnimeApiClient<Oauth> {
-    fn from(value: &AccessToken) -> Self {
-        AnimeApiClient::<Oauth> {
-            client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.secret().clone()),
-            state: PhantomData::<Oauth>,
-        }
-    }
-}
-
-impl From<&ClientId> for AnimeApiClient<Client> {
-    fn from(value: &ClientId) -> Self {
-        AnimeApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.clone().to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+impl From<&MalAccessToken> for AnimeApiClient<Oauth> {
+    fn from(value: &MalAccessToken) -> Self {
+        AnimeApiClient::from_auth_provider(value.clone())
     }
 }
 
 impl From<&MalClientId> for AnimeApiClient<Client> {
     fn from(value: &MalClientId) -> Self {
-        AnimeApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.0.to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+        AnimeApiClient::from_auth_provider(value.clone())
     }
 }
 
+#[cfg(feature = "oauth")]
 impl From<&OauthClient<Authenticated>> for AnimeApiClient<Oauth> {
     fn from(value: &OauthClient<Authenticated>) -> Self {
+        AnimeApiClient::from_auth_provider(value.access_token())
+    }
+}
+
+impl<State: AuthState> AnimeApiClient<State> {
+    /// Create an AnimeApiClient that gets its credentials from the provider before
+    /// every request
+    ///
+    /// The client gets the [State](AuthProvider::State) of the provider, which
+    /// decides the available endpoints. See the [provider](crate::oauth::provider) module
+    pub fn from_auth_provider<P: AuthProvider<State = State> + 'static>(provider: P) -> Self {
         AnimeApiClient {
             client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.get_access_token().secret().clone()),
+            transport: Transport::default(),
+            auth: Arc::new(provider),
+            adaptive_limit: false,
+            state: PhantomData::<State>,
+        }
+    }
+
+    /// Send requests through the given HTTP client instead of the default one
+    ///
+    /// Use [HttpClientBuilder](crate::http::HttpClientBuilder) to configure it
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send requests through the given tower service instead of the HTTP client
+    ///
+    /// Wrap a [MalService](crate::service::MalService) in layers to apply them
+    /// to every request of this client. The HTTP client of the service sends
+    /// the requests then, so configure that one instead of
+    /// [with_http_client](Self::with_http_client). See the [service](crate::service) module
+    #[cfg(feature = "tower")]
+    pub fn with_service<S>(mut self, service: S) -> Self
+    where
+        S: tower_service::Service<
+                crate::service::MalRequest,
+                Response = crate::service::MalResponse,
+            > + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<crate::service::BoxError>,
+        S::Future: Send,
+    {
+        self.transport = Transport::with_service(crate::service::SharedService::new(service));
+        self
+    }
+
+    /// Fetch full lists with the largest page size each endpoint allows
+    ///
+    /// By default, [get_full_user_anime_list](AnimeApi::get_full_user_anime_list)
+    /// follows the pages with the `limit` of the query, which is 100 unless set.
+    /// With the adaptive limit, it requests pages of
+    /// [USER_LIST_MAX](crate::limits::USER_LIST_MAX) entries instead, so a full
+    /// list takes a tenth of the requests. The `limit` of the query is
+    /// still used for single page requests.
+    pub fn with_adaptive_limit(mut self) -> Self {
+        self.adaptive_limit = true;
+        self
+    }
+
+    /// Open a connection to the MAL API ahead of the first request
+    ///
+    /// Resolves DNS and completes the TLS handshake so that the first request your
+    /// users wait on doesn't pay for them. The connection stays in the client's pool.
+    pub async fn warm_up(&self) -> Result<(), AnimeApiError> {
+        http::warm_up(&self.client, &self.transport)
+            .await
+            .map_err(|err| {
+                AnimeApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to warm up connection: {}", err),
+                )
+            })
+    }
+
+    // Adds the credentials of the auth provider to the request
+    async fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, AnimeApiError> {
+        let header = self.auth.auth_header().await?;
+        Ok(header.apply(request))
+    }
+}
+
+impl AnimeApiClient<Client> {
+    /// Create an Oauth AnimeApiClient after the user logs in
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    #[cfg(feature = "oauth")]
+    pub fn upgrade(&self, oauth_client: &OauthClient<Authenticated>) -> AnimeApiClient<Oauth> {
+        self.upgrade_with_token(&oauth_client.access_token())
+    }
+
+    /// Create an Oauth AnimeApiClient from an access token, sharing the HTTP client
+    /// and the adaptive limit setting
+    pub fn upgrade_with_token(&self, access_token: &MalAccessToken) -> AnimeApiClient<Oauth> {
+        AnimeApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(access_token.clone()),
+            adaptive_limit: self.adaptive_limit,
             state: PhantomData::<Oauth>,
         }
     }
 }
 
+impl AnimeApiClient<Oauth> {
+    /// Create a Client AnimeApiClient, e.g. after the user logs out
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    pub fn downgrade(&self, client_id: &MalClientId) -> AnimeApiClient<Client> {
+        AnimeApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(client_id.clone()),
+            adaptive_limit: self.adaptive_limit,
+            state: PhantomData::<Client>,
+        }
+    }
+}
+
 /// This trait defines the common request methods available to both
 /// Client and Oauth AnimeApiClients
 #[async_trait]
 pub trait Request {
-    async fn get<T>(&self, query: &T) -> Result<String, AnimeApiError>
+    async fn get<T>(&self, query: &T) -> Result<Bytes, AnimeApiError>
     where
         T: Serialize + Send + Sync;
 
-    async fn get_details(&self, query: &GetAnimeDetails) -> Result<String, AnimeApiError>;
+    async fn get_details(&self, query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError>;
+
+    async fn get_ranking(&self, query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError>;
+
+    async fn get_seasonal(&self, query: &GetSeasonalAnime) -> Result<Bytes, AnimeApiError>;
+
+    async fn get_user(&self, query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError>;
+
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, AnimeApiError>;
+
+    /// Returns true if full lists should be fetched with the largest page size
+    /// allowed. See [AnimeApiClient::with_adaptive_limit]
+    fn adaptive_limit(&self) -> bool {
+        false
+    }
+}
+
+/// The kind of AnimeApiClient a request is previewed for, which decides how it
+/// authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientKind {
+    /// Sends the `X-MAL-CLIENT-ID` header, like an AnimeApiClient created from a [MalClientId]
+    ClientId,
+
+    /// Sends a bearer token, like an AnimeApiClient created from an [OauthClient]
+    Oauth,
+}
+
+/// A query for one of the shared anime endpoints that can be sent
+/// with [AnimeApi::get_raw]
+#[async_trait]
+pub trait AnimeQuery: Send + Sync {
+    #[doc(hidden)]
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync;
+
+    /// The URL this query is sent to, without the query parameters
+    fn endpoint(&self) -> String;
+
+    /// The full URL this query is sent to, including the query parameters
+    fn to_url(&self) -> String
+    where
+        Self: Serialize,
+    {
+        let query = serde_urlencoded::to_string(self).unwrap_or_default();
+        if query.is_empty() {
+            self.endpoint()
+        } else {
+            format!("{}?{}", self.endpoint(), query)
+        }
+    }
+
+    /// A curl command that sends the same request as this query
+    ///
+    /// The credentials are read from the `MAL_CLIENT_ID` or `MAL_ACCESS_TOKEN`
+    /// environment variables by the shell, so the command never contains secrets
+    /// and can be shared when debugging differences with the raw API
+    fn to_curl(&self, client_kind: ClientKind) -> String
+    where
+        Self: Serialize,
+    {
+        let header = match client_kind {
+            ClientKind::ClientId => "X-MAL-CLIENT-ID: $MAL_CLIENT_ID",
+            ClientKind::Oauth => "Authorization: Bearer $MAL_ACCESS_TOKEN",
+        };
+        format!("curl '{}' -H \"{}\"", self.to_url(), header)
+    }
+}
+
+#[async_trait]
+impl AnimeQuery for GetAnimeList {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get(self).await
+    }
+
+    fn endpoint(&self) -> String {
+        ANIME_URL.to_string()
+    }
+}
+
+#[async_trait]
+impl AnimeQuery for GetAnimeDetails {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_details(self).await
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/{}", ANIME_URL, self.anime_id)
+    }
+}
+
+#[async_trait]
+impl AnimeQuery for GetAnimeRanking {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_ranking(self).await
+    }
 
-    async fn get_ranking(&self, query: &GetAnimeRanking) -> Result<String, AnimeApiError>;
+    fn endpoint(&self) -> String {
+        format!("{}/ranking", ANIME_URL)
+    }
+}
 
-    async fn get_seasonal(&self, query: &GetSeasonalAnime) -> Result<String, AnimeApiError>;
+#[async_trait]
+impl AnimeQuery for GetSeasonalAnime {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_seasonal(self).await
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/season/{}/{}", ANIME_URL, self.year, self.season)
+    }
+}
 
-    async fn get_user(&self, query: &GetUserAnimeList) -> Result<String, AnimeApiError>;
+#[async_trait]
+impl AnimeQuery for GetUserAnimeList {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, AnimeApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        if self.statuses.len() > 1 {
+            return Err(AnimeApiError::new(
+                "Raw lists can only be filtered by one status".to_string(),
+            ));
+        }
+        request.get_user(self).await
+    }
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, AnimeApiError>;
+    fn endpoint(&self) -> String {
+        format!("{}/{}/animelist", USER_URL, self.user_name)
+    }
 }
 
 /// This trait defines the shared endpoints for Client and Oauth
@@ -170,10 +421,8 @@ pub trait AnimeApi {
             .get_self()
             .get(query)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed to get anime list: {}", err)))?;
-        let result: AnimeList = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
+            .map_err(|err| err.context("Failed to get anime list"))?;
+        let result: AnimeList = parse_response("Anime List", &response)?;
         Ok(result)
     }
 
@@ -184,16 +433,48 @@ pub trait AnimeApi {
         &self,
         query: &GetAnimeDetails,
     ) -> Result<AnimeDetails, AnimeApiError> {
-        let response =
-            self.get_self().get_details(query).await.map_err(|err| {
-                AnimeApiError::new(format!("Failed to get anime details: {}", err))
-            })?;
-        let result: AnimeDetails = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime Details result: {}", err))
-        })?;
+        let response = self
+            .get_self()
+            .get_details(query)
+            .await
+            .map_err(|err| err.context("Failed to get anime details"))?;
+        let result: AnimeDetails = parse_response("Anime Details", &response)?;
         Ok(result)
     }
 
+    /// Get the details of an anime, telling apart entries that moved or are gone
+    ///
+    /// Use this when the id may be stale, e.g. when importing old lists
+    async fn resolve_anime_details(
+        &self,
+        query: &GetAnimeDetails,
+    ) -> Result<EntryLookup<AnimeDetails>, AnimeApiError> {
+        match self.get_anime_details(query).await {
+            Ok(details) if details.shared_fields.id != query.anime_id => Ok(EntryLookup::Moved {
+                new_id: details.shared_fields.id,
+                entry: details,
+            }),
+            Ok(details) => Ok(EntryLookup::Found(details)),
+            Err(err) if err.code() == ErrorCode::NotFound => Ok(EntryLookup::Gone),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check if an anime with the given id exists, fetching only its id
+    ///
+    /// Returns `Ok(false)` for ids MAL doesn't know, such as deleted entries, and an
+    /// error for anything else, such as network errors, so stale ids can be told
+    /// apart from transient failures
+    async fn anime_exists(&self, anime_id: u32) -> Result<bool, AnimeApiError> {
+        let fields = AnimeDetailFields::new(vec![AnimeDetail::id]);
+        let query = GetAnimeDetails::new(anime_id, Some(&fields))?;
+        match self.get_self().get_details(&query).await {
+            Ok(_) => Ok(true),
+            Err(err) if err.code() == ErrorCode::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Get the ranking of anime
     ///
     /// Corresponds to the [Get anime ranking](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_ranking_get) endpoint
@@ -201,13 +482,12 @@ pub trait AnimeApi {
         &self,
         query: &GetAnimeRanking,
     ) -> Result<AnimeRanking, AnimeApiError> {
-        let response =
-            self.get_self().get_ranking(query).await.map_err(|err| {
-                AnimeApiError::new(format!("Failed to get anime ranking: {}", err))
-            })?;
-        let result: AnimeRanking = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime Ranking result: {}", err))
-        })?;
+        let response = self
+            .get_self()
+            .get_ranking(query)
+            .await
+            .map_err(|err| err.context("Failed to get anime ranking"))?;
+        let result: AnimeRanking = parse_response("Anime Ranking", &response)?;
         Ok(result)
     }
 
@@ -218,13 +498,91 @@ pub trait AnimeApi {
         &self,
         query: &GetSeasonalAnime,
     ) -> Result<SeasonalAnime, AnimeApiError> {
-        let response =
-            self.get_self().get_seasonal(query).await.map_err(|err| {
-                AnimeApiError::new(format!("Failed to get seasonal anime: {}", err))
-            })?;
-        let result: SeasonalAnime = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Seasonal Anime result: {}", err))
+        let response = self
+            .get_self()
+            .get_seasonal(query)
+            .await
+            .map_err(|err| err.context("Failed to get seasonal anime"))?;
+        let result: SeasonalAnime = parse_response("Seasonal Anime", &response)?;
+        Ok(result)
+    }
+
+    /// Get a users anime list
+    ///
+    /// You **cannot** get the anime list of `@me` with a [ClientId] AnimeApiClient
+    ///
+    /// Corresponds to the [Get user anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_animelist_get) endpoint
+    async fn get_user_anime_list(
+        &self,
+        query: &GetUserAnimeList,
+    ) -> Result<AnimeList, AnimeApiError> {
+        if query.user_name == "@me".to_string() {
+            return Err(AnimeApiError::with_code(
+                ErrorCode::Unauthorized,
+                "You can only get your '@me' list via an Oauth client".to_string(),
+            ));
+        }
+        if query.statuses.len() > 1 {
+            let queries = query.split_statuses();
+            let pages = join_all(queries.iter().map(|q| self.get_user_anime_list(q))).await;
+            return merge_status_pages(query, pages);
+        }
+        let response = self.get_self().get_user(query).await.map_err(|err| {
+            err.context(&format!("Failed to fetch {}'s anime list", query.user_name))
         })?;
+        let result: AnimeList = parse_response("Anime List", &response)?;
+        Ok(result)
+    }
+
+    /// Get every entry of a users anime list by following the pages of the response
+    ///
+    /// Use a large `limit` in the query to keep the number of requests down, or
+    /// enable [with_adaptive_limit](AnimeApiClient::with_adaptive_limit) on the client
+    async fn get_full_user_anime_list(
+        &self,
+        query: &GetUserAnimeList,
+    ) -> Result<Vec<AnimeListNode>, AnimeApiError> {
+        let adapted;
+        let query = if self.get_self().adaptive_limit() {
+            adapted = query.with_max_limit();
+            &adapted
+        } else {
+            query
+        };
+        if query.statuses.len() > 1 {
+            let queries = query.split_statuses();
+            let lists = join_all(queries.iter().map(|q| self.get_full_user_anime_list(q))).await;
+            let pages = lists.into_iter().map(|list| {
+                list.map(|data| AnimeList {
+                    data,
+                    paging: Paging::default(),
+                })
+            });
+            return Ok(merge_status_pages(query, pages)?.data);
+        }
+        let mut page = self.get_user_anime_list(query).await?;
+        let mut entries = std::mem::take(&mut page.data);
+        while page.next_page().is_some() {
+            page = self.next(&page).await?;
+            entries.append(&mut page.data);
+        }
+        Ok(entries)
+    }
+
+    /// Send the query and deserialize the response into your own type
+    ///
+    /// Use this if your application has its own models for the MAL responses
+    /// instead of the ones provided by this crate
+    async fn get_raw<Q, T>(&self, query: &Q) -> Result<T, AnimeApiError>
+    where
+        Q: AnimeQuery,
+        T: DeserializeOwned,
+    {
+        let response = query
+            .send(self.get_self())
+            .await
+            .map_err(|err| err.context("Failed to send query"))?;
+        let result: T = parse_response("raw", &response)?;
         Ok(result)
     }
 
@@ -237,9 +595,8 @@ pub trait AnimeApi {
             .get_self()
             .get_next_or_prev(response.next_page())
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed to fetch next page: {}", err)))?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| AnimeApiError::new(format!("Failed to fetch next page: {}", err)))?;
+            .map_err(|err| err.context("Failed to fetch next page"))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -252,9 +609,8 @@ pub trait AnimeApi {
             .get_self()
             .get_next_or_prev(response.prev_page())
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed to fetch previous page: {}", err)))?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| AnimeApiError::new(format!("Failed to parse page: {}", err)))?;
+            .map_err(|err| err.context("Failed to fetch previous page"))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -262,184 +618,283 @@ pub trait AnimeApi {
     fn get_self(&self) -> &Self::State;
 }
 
-#[async_trait]
-impl Request for AnimeApiClient<Client> {
-    async fn get<T>(&self, query: &T) -> Result<String, AnimeApiError>
-    where
-        T: Serialize + Send + Sync,
-    {
-        let response = self
-            .client
-            .get(ANIME_URL)
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+/// Future returned by the [DynAnimeApi] methods
+pub type AnimeFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AnimeApiError>> + Send + 'a>>;
 
-        handle_response(response).await
+/// Object-safe version of the [AnimeApi] endpoints
+///
+/// [AnimeApi] has an associated type and generic methods, so it can't be used as a
+/// trait object. This trait is implemented for every [AnimeApi] client, which lets
+/// applications store Client and Oauth AnimeApiClients in the same field, e.g. as a
+/// `Box<dyn DynAnimeApi>`.
+///
+/// The methods are named after the [AnimeApi] ones with a `dyn_` prefix, so both
+/// traits can be imported in the same module.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api_client: Box<dyn DynAnimeApi> = match access_token {
+///     Some(token) => Box::new(AnimeApiClient::from(&token)),
+///     None => Box::new(AnimeApiClient::from(&client_id)),
+/// };
+/// let details = api_client.dyn_get_anime_details(&query).await.unwrap();
+/// ```
+pub trait DynAnimeApi: Send + Sync {
+    /// See [AnimeApi::get_anime_list]
+    fn dyn_get_anime_list<'a>(&'a self, query: &'a GetAnimeList) -> AnimeFuture<'a, AnimeList>;
+
+    /// See [AnimeApi::get_anime_details]
+    fn dyn_get_anime_details<'a>(
+        &'a self,
+        query: &'a GetAnimeDetails,
+    ) -> AnimeFuture<'a, AnimeDetails>;
+
+    /// See [AnimeApi::resolve_anime_details]
+    fn dyn_resolve_anime_details<'a>(
+        &'a self,
+        query: &'a GetAnimeDetails,
+    ) -> AnimeFuture<'a, EntryLookup<AnimeDetails>>;
+
+    /// See [AnimeApi::anime_exists]
+    fn dyn_anime_exists(&self, anime_id: u32) -> AnimeFuture<'_, bool>;
+
+    /// See [AnimeApi::get_anime_ranking]
+    fn dyn_get_anime_ranking<'a>(
+        &'a self,
+        query: &'a GetAnimeRanking,
+    ) -> AnimeFuture<'a, AnimeRanking>;
+
+    /// See [AnimeApi::get_seasonal_anime]
+    fn dyn_get_seasonal_anime<'a>(
+        &'a self,
+        query: &'a GetSeasonalAnime,
+    ) -> AnimeFuture<'a, SeasonalAnime>;
+
+    /// See [AnimeApi::get_user_anime_list]
+    fn dyn_get_user_anime_list<'a>(
+        &'a self,
+        query: &'a GetUserAnimeList,
+    ) -> AnimeFuture<'a, AnimeList>;
+
+    /// See [AnimeApi::get_full_user_anime_list]
+    fn dyn_get_full_user_anime_list<'a>(
+        &'a self,
+        query: &'a GetUserAnimeList,
+    ) -> AnimeFuture<'a, Vec<AnimeListNode>>;
+
+    /// Return the next page of an anime list
+    ///
+    /// See [AnimeApi::next]
+    fn dyn_next_anime_list_page<'a>(
+        &'a self,
+        response: &'a AnimeList,
+    ) -> AnimeFuture<'a, AnimeList>;
+
+    /// Return the previous page of an anime list
+    ///
+    /// See [AnimeApi::prev]
+    fn dyn_prev_anime_list_page<'a>(
+        &'a self,
+        response: &'a AnimeList,
+    ) -> AnimeFuture<'a, AnimeList>;
+}
+
+impl<A> DynAnimeApi for A
+where
+    A: AnimeApi + Send + Sync,
+{
+    fn dyn_get_anime_list<'a>(&'a self, query: &'a GetAnimeList) -> AnimeFuture<'a, AnimeList> {
+        AnimeApi::get_anime_list(self, query)
     }
 
-    async fn get_details(&self, query: &GetAnimeDetails) -> Result<String, AnimeApiError> {
-        let response = self
-            .client
-            .get(format!("{}/{}", ANIME_URL, query.anime_id))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+    fn dyn_get_anime_details<'a>(
+        &'a self,
+        query: &'a GetAnimeDetails,
+    ) -> AnimeFuture<'a, AnimeDetails> {
+        AnimeApi::get_anime_details(self, query)
+    }
 
-        handle_response(response).await
+    fn dyn_resolve_anime_details<'a>(
+        &'a self,
+        query: &'a GetAnimeDetails,
+    ) -> AnimeFuture<'a, EntryLookup<AnimeDetails>> {
+        AnimeApi::resolve_anime_details(self, query)
     }
 
-    async fn get_ranking(&self, query: &GetAnimeRanking) -> Result<String, AnimeApiError> {
-        let response = self
-            .client
-            .get(format!("{}/ranking", ANIME_URL))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+    fn dyn_anime_exists(&self, anime_id: u32) -> AnimeFuture<'_, bool> {
+        AnimeApi::anime_exists(self, anime_id)
+    }
 
-        handle_response(response).await
+    fn dyn_get_anime_ranking<'a>(
+        &'a self,
+        query: &'a GetAnimeRanking,
+    ) -> AnimeFuture<'a, AnimeRanking> {
+        AnimeApi::get_anime_ranking(self, query)
     }
 
-    async fn get_seasonal(&self, query: &GetSeasonalAnime) -> Result<String, AnimeApiError> {
-        let response = self
-            .client
-            .get(format!(
-                "{}/season/{}/{}",
-                ANIME_URL, query.year, query.season
-            ))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+    fn dyn_get_seasonal_anime<'a>(
+        &'a self,
+        query: &'a GetSeasonalAnime,
+    ) -> AnimeFuture<'a, SeasonalAnime> {
+        AnimeApi::get_seasonal_anime(self, query)
+    }
 
-        handle_response(response).await
+    fn dyn_get_user_anime_list<'a>(
+        &'a self,
+        query: &'a GetUserAnimeList,
+    ) -> AnimeFuture<'a, AnimeList> {
+        AnimeApi::get_user_anime_list(self, query)
     }
 
-    async fn get_user(&self, query: &GetUserAnimeList) -> Result<String, AnimeApiError> {
-        let response = self
-            .client
-            .get(format!("{}/{}/animelist", USER_URL, query.user_name))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+    fn dyn_get_full_user_anime_list<'a>(
+        &'a self,
+        query: &'a GetUserAnimeList,
+    ) -> AnimeFuture<'a, Vec<AnimeListNode>> {
+        AnimeApi::get_full_user_anime_list(self, query)
+    }
 
-        handle_response(response).await
+    fn dyn_next_anime_list_page<'a>(
+        &'a self,
+        response: &'a AnimeList,
+    ) -> AnimeFuture<'a, AnimeList> {
+        AnimeApi::next(self, response)
     }
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, AnimeApiError> {
-        if let Some(itr) = query {
-            let response = self
-                .client
-                .get(itr)
-                .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-                .send()
-                .await
-                .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+    fn dyn_prev_anime_list_page<'a>(
+        &'a self,
+        response: &'a AnimeList,
+    ) -> AnimeFuture<'a, AnimeList> {
+        AnimeApi::prev(self, response)
+    }
+}
 
-            handle_response(response).await
-        } else {
-            Err(AnimeApiError::new("Page does not exist".to_string()))
-        }
+// Merges the responses of the statuses of a multi-status query, in the order of
+// the query's sort
+fn merge_status_pages(
+    query: &GetUserAnimeList,
+    pages: impl IntoIterator<Item = Result<AnimeList, AnimeApiError>>,
+) -> Result<AnimeList, AnimeApiError> {
+    let mut list = AnimeList {
+        data: vec![],
+        paging: Paging::default(),
+    };
+    for page in pages {
+        list.data.append(&mut page?.data);
+    }
+    if let Some((key, order)) = query.sort_order() {
+        list.sort_by(key, order);
     }
+    Ok(list)
 }
 
 #[async_trait]
-impl Request for AnimeApiClient<Oauth> {
-    async fn get<T>(&self, query: &T) -> Result<String, AnimeApiError>
+impl<State: AuthState> Request for AnimeApiClient<State> {
+    async fn get<T>(&self, query: &T) -> Result<Bytes, AnimeApiError>
     where
         T: Serialize + Send + Sync,
     {
         let response = self
-            .client
-            .get(ANIME_URL)
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(self.client.get(ANIME_URL))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_details(&self, query: &GetAnimeDetails) -> Result<String, AnimeApiError> {
+    async fn get_details(&self, query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
         let response = self
-            .client
-            .get(format!("{}/{}", ANIME_URL, query.anime_id))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/{}", ANIME_URL, query.anime_id)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_ranking(&self, query: &GetAnimeRanking) -> Result<String, AnimeApiError> {
+    async fn get_ranking(&self, query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
         let response = self
-            .client
-            .get(format!("{}/ranking", ANIME_URL))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/ranking", ANIME_URL)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_seasonal(&self, query: &GetSeasonalAnime) -> Result<String, AnimeApiError> {
+    async fn get_seasonal(&self, query: &GetSeasonalAnime) -> Result<Bytes, AnimeApiError> {
         let response = self
-            .client
-            .get(format!(
+            .authorize(self.client.get(format!(
                 "{}/season/{}/{}",
                 ANIME_URL, query.year, query.season
-            ))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            )))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_user(&self, query: &GetUserAnimeList) -> Result<String, AnimeApiError> {
+    async fn get_user(&self, query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
         let response = self
-            .client
-            .get(format!("{}/{}/animelist", USER_URL, query.user_name))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/animelist", USER_URL, query.user_name)),
+            )
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, AnimeApiError> {
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, AnimeApiError> {
         if let Some(itr) = query {
             let response = self
-                .client
-                .get(itr)
-                .bearer_auth(&self.access_token.as_ref().unwrap())
-                .send()
+                .authorize(self.client.get(itr))
+                .await?
+                .send_through(&self.transport)
                 .await
-                .map_err(|err| AnimeApiError::new(format!("Failed get request: {}", err)))?;
+                .map_err(|err| {
+                    AnimeApiError::with_code(
+                        ErrorCode::Request,
+                        format!("Failed get request: {}", err),
+                    )
+                })?;
 
             handle_response(response).await
         } else {
-            Err(AnimeApiError::new("Page does not exist".to_string()))
+            Err(AnimeApiError::with_code(
+                ErrorCode::NoPage,
+                "Page does not exist".to_string(),
+            ))
         }
     }
+
+    fn adaptive_limit(&self) -> bool {
+        self.adaptive_limit
+    }
 }
 
 #[async_trait]
@@ -454,28 +909,53 @@ impl AnimeApi for AnimeApiClient<Client> {
 impl AnimeApiClient<Client> {
     /// Get a users anime list
     ///
-    /// You **cannot** get the anime list of `@me` with a [ClientId] AnimeApiClient
-    ///
-    /// Corresponds to the [Get user anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_animelist_get) endpoint
+    /// Same as [AnimeApi::get_user_anime_list], callable without importing the trait
     pub async fn get_user_anime_list(
         &self,
         query: &GetUserAnimeList,
     ) -> Result<AnimeList, AnimeApiError> {
-        if query.user_name == "@me".to_string() {
-            return Err(AnimeApiError::new(
+        AnimeApi::get_user_anime_list(self, query).await
+    }
+
+    /// Get a users anime list, decoding the entries as they are received
+    ///
+    /// Use this instead of `get_user_anime_list` for large pages, such as when
+    /// exporting lists with `limit=1000`. See the [stream](crate::stream) module.
+    #[cfg(feature = "stream-parse")]
+    pub async fn get_user_anime_list_stream(
+        &self,
+        query: &GetUserAnimeList,
+    ) -> Result<ListStream<AnimeListNode>, AnimeApiError> {
+        if query.user_name == "@me" {
+            return Err(AnimeApiError::with_code(
+                ErrorCode::Unauthorized,
                 "You can only get your '@me' list via an Oauth client".to_string(),
             ));
         }
-        let response = self.get_self().get_user(query).await.map_err(|err| {
-            AnimeApiError::new(format!(
-                "Failed to fetch {}'s anime list: {}",
-                query.user_name, err
-            ))
-        })?;
-        let result: AnimeList = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
-        Ok(result)
+        if query.statuses.len() > 1 {
+            return Err(AnimeApiError::new(
+                "Streamed lists can only be filtered by one status".to_string(),
+            ));
+        }
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/animelist", USER_URL, query.user_name)),
+            )
+            .await?
+            .query(&query)
+            .send_through(&self.transport)
+            .await
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(ListStream::new(response)),
+            _ => Err(unexpected_response("Did not recieve OK response", response)
+                .await
+                .into()),
+        }
     }
 }
 
@@ -486,9 +966,47 @@ impl AnimeApi for AnimeApiClient<Oauth> {
     fn get_self(&self) -> &Self::State {
         self
     }
+
+    /// Get a users Anime list
+    ///
+    /// You **can** get the anime list of `@me` with an [OauthClient] AnimeApiClient
+    ///
+    /// Corresponds to the [Get user anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_animelist_get) endpoint
+    async fn get_user_anime_list(
+        &self,
+        query: &GetUserAnimeList,
+    ) -> Result<AnimeList, AnimeApiError> {
+        if query.statuses.len() > 1 {
+            let queries = query.split_statuses();
+            let pages = join_all(
+                queries
+                    .iter()
+                    .map(|q| AnimeApi::get_user_anime_list(self, q)),
+            )
+            .await;
+            return merge_status_pages(query, pages);
+        }
+        let response = self
+            .get_self()
+            .get_user(query)
+            .await
+            .map_err(|err| err.context("Failed to get user anime list"))?;
+        let result: AnimeList = parse_response("Anime List", &response)?;
+        Ok(result)
+    }
 }
 
 impl AnimeApiClient<Oauth> {
+    /// Get a users anime list
+    ///
+    /// Same as [AnimeApi::get_user_anime_list], callable without importing the trait
+    pub async fn get_user_anime_list(
+        &self,
+        query: &GetUserAnimeList,
+    ) -> Result<AnimeList, AnimeApiError> {
+        AnimeApi::get_user_anime_list(self, query).await
+    }
+
     /// Get a list of suggested anime
     ///
     /// Corresponds to the [Get suggested anime](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_suggestions_get) endpoint
@@ -497,41 +1015,93 @@ impl AnimeApiClient<Oauth> {
         query: &GetSuggestedAnime,
     ) -> Result<SuggestedAnime, AnimeApiError> {
         let response = self
-            .client
-            .get(format!("{}/suggestions", ANIME_URL))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/suggestions", ANIME_URL)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
             .map_err(|err| {
-                AnimeApiError::new(format!("Failed to fetch suggested anime: {}", err))
+                AnimeApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to fetch suggested anime: {}", err),
+                )
             })?;
 
         let response = handle_response(response).await?;
 
-        let result: SuggestedAnime = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Suggested Anime result: {}", err))
-        })?;
+        let result: SuggestedAnime = parse_response("Suggested Anime", &response)?;
         Ok(result)
     }
 
-    /// Get a users Anime list
+    /// Get a list of suggested anime, without the franchises of anime the user dropped
     ///
-    /// You **can** get the anime list of `@me` with an [OauthClient] AnimeApiClient
+    /// Suggestions related to an anime in one of the filter's excluded statuses,
+    /// e.g. the sequel of a dropped show, are removed from the result. See the
+    /// [suggestions](super::suggestions) module for the cost of the lookups
+    pub async fn get_filtered_suggested_anime(
+        &self,
+        query: &GetSuggestedAnime,
+        filter: &SuggestionFilter,
+    ) -> Result<SuggestedAnime, AnimeApiError> {
+        let mut suggestions = self.get_suggested_anime(query).await?;
+
+        let mut excluded = HashSet::new();
+        for status in filter.excluded_statuses() {
+            let query = GetUserAnimeList::builder("@me")
+                .status(*status)
+                .limit(1000)
+                .build()?;
+            let entries = AnimeApi::get_full_user_anime_list(self, &query).await?;
+            excluded.extend(entries.iter().map(|e| e.node.id));
+        }
+        if excluded.is_empty() {
+            return Ok(suggestions);
+        }
+
+        let mut walker = FranchiseWalker::new(self, filter.depth);
+        let mut kept = Vec::with_capacity(suggestions.data.len());
+        for suggestion in suggestions.data {
+            if !walker.reaches(suggestion.node.id, &excluded).await? {
+                kept.push(suggestion);
+            }
+        }
+        suggestions.data = kept;
+        Ok(suggestions)
+    }
+
+    /// Get a users anime list, decoding the entries as they are received
     ///
-    /// Corresponds to the [Get user anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_animelist_get) endpoint
-    pub async fn get_user_anime_list(
+    /// Use this instead of `get_user_anime_list` for large pages, such as when
+    /// exporting lists with `limit=1000`. See the [stream](crate::stream) module.
+    #[cfg(feature = "stream-parse")]
+    pub async fn get_user_anime_list_stream(
         &self,
         query: &GetUserAnimeList,
-    ) -> Result<AnimeList, AnimeApiError> {
-        let response =
-            self.get_self().get_user(query).await.map_err(|err| {
-                AnimeApiError::new(format!("Failed to get user anime list: {}", err))
+    ) -> Result<ListStream<AnimeListNode>, AnimeApiError> {
+        if query.statuses.len() > 1 {
+            return Err(AnimeApiError::new(
+                "Streamed lists can only be filtered by one status".to_string(),
+            ));
+        }
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/animelist", USER_URL, query.user_name)),
+            )
+            .await?
+            .query(&query)
+            .send_through(&self.transport)
+            .await
+            .map_err(|err| {
+                AnimeApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
             })?;
-        let result: AnimeList = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
-        Ok(result)
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(ListStream::new(response)),
+            _ => Err(unexpected_response("Did not recieve OK response", response)
+                .await
+                .into()),
+        }
     }
 
     /// Update the status of an anime for the OAuth user's anime list
@@ -545,67 +1115,357 @@ impl AnimeApiClient<Oauth> {
             AnimeApiError::new(format!("Failed to turn request into form data: {}", err))
         })?;
         let response = self
-            .client
-            .put(format!("{}/{}/my_list_status", ANIME_URL, query.anime_id))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(
+                self.client
+                    .put(format!("{}/{}/my_list_status", ANIME_URL, query.anime_id)),
+            )
+            .await?
             .form(&form_data)
-            .send()
+            .send_through(&self.transport)
             .await
             .map_err(|err| {
-                AnimeApiError::new(format!(
-                    "Failed to update user's anime list status: {}",
-                    err
-                ))
+                AnimeApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to update user's anime list status: {}", err),
+                )
             })?;
 
         let response = handle_response(response).await?;
-        let result: AnimeListStatus = serde_json::from_str(response.as_str()).map_err(|err| {
-            AnimeApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
+        let result: AnimeListStatus = parse_response("Anime List Status", &response)?;
         Ok(result)
     }
 
+    /// Send many anime list status updates, one at a time
+    ///
+    /// Updates are sent in order, paced by `options.limiter`. Depending on
+    /// `options.policy`, a failed update either aborts the remaining updates or is
+    /// collected with the other results.
+    pub async fn update_anime_list_bulk(
+        &self,
+        queries: Vec<UpdateMyAnimeListStatus>,
+        options: &BulkOptions,
+    ) -> BulkResult<AnimeListStatus, AnimeApiError> {
+        run_bulk(
+            queries,
+            options,
+            |query| query.anime_id,
+            |query| async move { self.update_anime_list_status(&query).await },
+        )
+        .await
+    }
+
     /// Delete an anime entry from the OAuth user's anime list
     ///
+    /// Deleting an anime that is not in the list is not an error, and returns
+    /// [DeleteOutcome::NotInList]
+    ///
     /// Corresponds to the [Delete my anime list item](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_anime_id_my_list_status_delete) endpoint
     pub async fn delete_anime_list_item(
         &self,
         query: &DeleteMyAnimeListItem,
-    ) -> Result<(), AnimeApiError> {
+    ) -> Result<DeleteOutcome, AnimeApiError> {
         let response = self
-            .client
-            .delete(format!("{}/{}/my_list_status", ANIME_URL, query.anime_id))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
-            .send()
+            .authorize(
+                self.client
+                    .delete(format!("{}/{}/my_list_status", ANIME_URL, query.anime_id)),
+            )
+            .await?
+            .send_through(&self.transport)
             .await
             .map_err(|err| {
-                AnimeApiError::new(format!("Failed to delete the anime list item: {}", err))
+                AnimeApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to delete the anime list item: {}", err),
+                )
             })?;
 
         match response.status() {
-            reqwest::StatusCode::OK => Ok(()),
-            reqwest::StatusCode::NOT_FOUND => Err(AnimeApiError::new(
-                "Anime does not exist in user's anime list".to_string(),
-            )),
-            _ => Err(AnimeApiError::new(format!(
-                "Did not recieve expected response: {}",
-                response.status()
-            ))),
+            reqwest::StatusCode::OK => Ok(DeleteOutcome::Deleted),
+            reqwest::StatusCode::NOT_FOUND => Ok(DeleteOutcome::NotInList),
+            _ => Err(
+                unexpected_response("Did not recieve expected response", response)
+                    .await
+                    .into(),
+            ),
         }
     }
 }
 
-async fn handle_response(response: reqwest::Response) -> Result<String, AnimeApiError> {
+async fn handle_response(response: reqwest::Response) -> Result<Bytes, AnimeApiError> {
     match response.status() {
         reqwest::StatusCode::OK => {
-            let content = response.text().await.map_err(|err| {
-                AnimeApiError::new(format!("Failed to get content from response: {}", err))
+            let content = response.bytes().await.map_err(|err| {
+                AnimeApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to get content from response: {}", err),
+                )
             })?;
             Ok(content)
         }
-        _ => Err(AnimeApiError::new(format!(
-            "Did not recieve OK response: {}",
-            response.status()
-        ))),
+        _ => Err(unexpected_response("Did not recieve OK response", response)
+            .await
+            .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anime::requests::{UserAnimeListSort, UserAnimeListStatus};
+    use crate::fixtures;
+    use crate::oauth::provider::AuthHeader;
+
+    // Serves a two page anime list, without sending requests
+    struct PagedAnimeList;
+
+    #[async_trait]
+    impl Request for PagedAnimeList {
+        async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            Ok(anime_list_page(
+                &[1, 2],
+                Some("https://api.myanimelist.net/v2/anime?offset=2"),
+            ))
+        }
+
+        async fn get_details(&self, _query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_seasonal(&self, _query: &GetSeasonalAnime) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_user(&self, _query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+            match query {
+                Some("https://api.myanimelist.net/v2/anime?offset=2") => {
+                    Ok(anime_list_page(&[3], None))
+                }
+                _ => Err(AnimeApiError::new("Page does not exist".to_string())),
+            }
+        }
+    }
+
+    impl AnimeApi for PagedAnimeList {
+        type State = PagedAnimeList;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    fn anime_list_page(ids: &[u32], next: Option<&str>) -> Bytes {
+        let data: Vec<_> = ids
+            .iter()
+            .map(|id| serde_json::json!({ "node": { "id": id, "title": format!("Anime {}", id) } }))
+            .collect();
+        let paging = match next {
+            Some(next) => serde_json::json!({ "next": next }),
+            None => serde_json::json!({}),
+        };
+        serde_json::to_vec(&serde_json::json!({ "data": data, "paging": paging }))
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_dyn_anime_api() {
+        let client_id = MalClientId::new("id");
+        let access_token = MalAccessToken::new("token");
+        let clients: Vec<Box<dyn DynAnimeApi>> = vec![
+            Box::new(AnimeApiClient::from(&client_id)),
+            Box::new(AnimeApiClient::from(&access_token)),
+            Box::new(PagedAnimeList),
+        ];
+
+        let query = GetAnimeList::new("frieren", false, None, None, None).unwrap();
+        let list = clients[2].dyn_get_anime_list(&query).await.unwrap();
+        let ids: Vec<u32> = list.data.iter().map(|e| e.node.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        let next = clients[2].dyn_next_anime_list_page(&list).await.unwrap();
+        let ids: Vec<u32> = next.data.iter().map(|e| e.node.id).collect();
+        assert_eq!(ids, vec![3]);
+        assert!(clients[2].dyn_prev_anime_list_page(&next).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_status_pages() {
+        let page = |entries: &[(u32, u8)]| -> Result<AnimeList, AnimeApiError> {
+            let data: Vec<_> = entries
+                .iter()
+                .map(|(id, score)| {
+                    fixtures::anime_entry(*id)
+                        .status("watching")
+                        .score(*score)
+                        .build()
+                })
+                .collect();
+            Ok(fixtures::anime_list(data))
+        };
+        let pages = join_all(vec![
+            std::future::ready(page(&[(1, 9), (2, 5)])),
+            std::future::ready(page(&[(3, 7), (4, 0)])),
+        ])
+        .await;
+
+        let query = GetUserAnimeList::builder("hello")
+            .statuses(&[UserAnimeListStatus::Watching, UserAnimeListStatus::OnHold])
+            .sort(UserAnimeListSort::ListScore)
+            .build()
+            .unwrap();
+        let list = merge_status_pages(&query, pages).unwrap();
+        let ids: Vec<u32> = list.data.iter().map(|e| e.node.id).collect();
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+        assert!(list.paging.next.is_none());
+    }
+
+    // Records the query strings of the user list requests, answering with an empty page
+    #[derive(Default)]
+    struct RecordedUserList {
+        adaptive_limit: bool,
+        queries: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Request for RecordedUserList {
+        async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            unimplemented!()
+        }
+
+        async fn get_details(&self, _query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_seasonal(&self, _query: &GetSeasonalAnime) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_user(&self, query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+            let query = query.to_query_string().unwrap();
+            self.queries.lock().unwrap().push(query);
+            Ok(anime_list_page(&[], None))
+        }
+
+        async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        fn adaptive_limit(&self) -> bool {
+            self.adaptive_limit
+        }
+    }
+
+    impl AnimeApi for RecordedUserList {
+        type State = RecordedUserList;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_multiple_statuses() {
+        let api = RecordedUserList::default();
+        let query = GetUserAnimeList::builder("hello")
+            .statuses(&[UserAnimeListStatus::Watching, UserAnimeListStatus::OnHold])
+            .build()
+            .unwrap();
+        let result: Result<AnimeList, _> = api.get_raw(&query).await;
+        assert!(result.is_err());
+        assert!(api.queries.lock().unwrap().is_empty());
+
+        let query = GetUserAnimeList::builder("hello")
+            .statuses(&[UserAnimeListStatus::Watching])
+            .build()
+            .unwrap();
+        let result: Result<AnimeList, _> = api.get_raw(&query).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            api.queries.lock().unwrap().as_slice(),
+            ["nsfw=false&status=watching&limit=100&offset=0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limit() {
+        let client_id = MalClientId::new("id");
+        let client = AnimeApiClient::from(&client_id);
+        assert!(!client.adaptive_limit());
+
+        let client = client.with_adaptive_limit();
+        assert!(client.adaptive_limit());
+        let upgraded = client.upgrade_with_token(&MalAccessToken::new("token"));
+        assert!(upgraded.adaptive_limit());
+
+        let query = GetUserAnimeList::builder("hello").build().unwrap();
+        for adaptive_limit in [false, true] {
+            let api = RecordedUserList {
+                adaptive_limit,
+                ..Default::default()
+            };
+            api.get_full_user_anime_list(&query).await.unwrap();
+            let limit = if adaptive_limit {
+                "limit=1000"
+            } else {
+                "limit=100"
+            };
+            let queries = api.queries.lock().unwrap();
+            assert_eq!(queries.len(), 1);
+            assert!(queries[0].split('&').any(|param| param == limit));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_and_downgrade() {
+        let client_id = MalClientId::new("id");
+        let api_client = AnimeApiClient::from(&client_id);
+
+        let upgraded = api_client.upgrade_with_token(&MalAccessToken::new("token"));
+        match upgraded.auth.auth_header().await.unwrap() {
+            AuthHeader::Bearer(token) => assert_eq!(token.secret(), "token"),
+            header => panic!("unexpected header {:?}", header),
+        }
+
+        let downgraded = upgraded.downgrade(&client_id);
+        match downgraded.auth.auth_header().await.unwrap() {
+            AuthHeader::ClientId(client_id) => assert_eq!(client_id.as_str(), "id"),
+            header => panic!("unexpected header {:?}", header),
+        }
+    }
+
+    #[test]
+    fn test_to_curl() {
+        let query = GetAnimeList::new("one piece", false, None, Some(10), None).unwrap();
+        assert_eq!(
+            query.to_curl(ClientKind::ClientId),
+            "curl 'https://api.myanimelist.net/v2/anime?q=one+piece&nsfw=false&limit=10&offset=0' -H \"X-MAL-CLIENT-ID: $MAL_CLIENT_ID\""
+        );
+
+        let query = GetAnimeDetails::new(5114, None).unwrap();
+        assert_eq!(query.to_url(), "https://api.myanimelist.net/v2/anime/5114");
+
+        let query = GetUserAnimeList::builder("@me").limit(5).build().unwrap();
+        assert_eq!(
+            query.to_curl(ClientKind::Oauth),
+            "curl 'https://api.myanimelist.net/v2/users/@me/animelist?nsfw=false&limit=5&offset=0' -H \"Authorization: Bearer $MAL_ACCESS_TOKEN\""
+        );
     }
 }
@@ -0,0 +1,154 @@
+use std::{future::Future, sync::Mutex};
+
+use tokio::task::AbortHandle;
+
+use super::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeCommonFields, AnimeField, GetAnimeList},
+    responses::AnimeList,
+};
+use crate::common::Paging;
+
+/// The fields requested by [Autocomplete]
+pub const AUTOCOMPLETE_FIELDS: &[AnimeField] = &[
+    AnimeField::id,
+    AnimeField::title,
+    AnimeField::alternative_titles,
+    AnimeField::main_picture,
+];
+
+// MAL rejects shorter queries
+const MIN_QUERY_LENGTH: usize = 3;
+
+/// Anime search suggestions for search boxes
+///
+/// Meant to be called on every keystroke. Only the minimal [AUTOCOMPLETE_FIELDS]
+/// are requested, and starting a new search cancels the one still in flight, whose
+/// call then returns `Ok(None)`. Queries shorter than 3 characters return no
+/// suggestions without sending a request.
+///
+/// Requests are spawned onto the current Tokio runtime.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api_client = AnimeApiClient::from(&client_id);
+/// let autocomplete = Autocomplete::new(api_client);
+///
+/// // Called from the search box's input handler
+/// if let Some(suggestions) = autocomplete.autocomplete("frier", 10).await.unwrap() {
+///     for suggestion in suggestions.data {
+///         println!("{}", suggestion.node.title);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Autocomplete<C> {
+    client: C,
+    fields: AnimeCommonFields,
+    in_flight: Mutex<Option<AbortHandle>>,
+}
+
+impl<C> Autocomplete<C>
+where
+    C: AnimeApi + Clone + Send + Sync + 'static,
+{
+    /// Create a new [Autocomplete] around the given AnimeApiClient
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            fields: AnimeCommonFields::new(AUTOCOMPLETE_FIELDS.to_vec()),
+            in_flight: Mutex::new(None),
+        }
+    }
+
+    /// Get up to `limit` suggestions for `q`
+    ///
+    /// Returns `Ok(None)` if a newer search or [Autocomplete::cancel] cancelled this one
+    pub async fn autocomplete(
+        &self,
+        q: &str,
+        limit: u16,
+    ) -> Result<Option<AnimeList>, AnimeApiError> {
+        let q = q.trim();
+        if q.chars().count() < MIN_QUERY_LENGTH {
+            self.cancel();
+            return Ok(Some(AnimeList {
+                data: vec![],
+                paging: Paging::default(),
+            }));
+        }
+
+        let query = GetAnimeList::new(q, false, Some(&self.fields), Some(limit), None)?;
+        let client = self.client.clone();
+        self.run(async move { client.get_anime_list(&query).await })
+            .await
+    }
+}
+
+impl<C> Autocomplete<C> {
+    /// Cancel the search in flight, if there is one
+    pub fn cancel(&self) {
+        if let Some(previous) = self.in_flight.lock().unwrap().take() {
+            previous.abort();
+        }
+    }
+
+    async fn run<F>(&self, fetch: F) -> Result<Option<AnimeList>, AnimeApiError>
+    where
+        F: Future<Output = Result<AnimeList, AnimeApiError>> + Send + 'static,
+    {
+        let task = tokio::spawn(fetch);
+        let previous = self.in_flight.lock().unwrap().replace(task.abort_handle());
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+
+        match task.await {
+            Ok(result) => result.map(Some),
+            Err(err) if err.is_cancelled() => Ok(None),
+            Err(err) => Err(AnimeApiError::new(format!(
+                "Autocomplete request failed: {}",
+                err
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestions(id: u32) -> AnimeList {
+        serde_json::from_value(serde_json::json!({
+            "data": [{ "node": { "id": id, "title": format!("Anime {}", id) } }],
+            "paging": {}
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_newer_search_cancels_previous() {
+        let autocomplete = Autocomplete {
+            client: (),
+            fields: AnimeCommonFields::new(AUTOCOMPLETE_FIELDS.to_vec()),
+            in_flight: Mutex::new(None),
+        };
+
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let slow = autocomplete.run(async move {
+            let _ = receiver.await;
+            Ok(suggestions(1))
+        });
+        let fast = async {
+            tokio::task::yield_now().await;
+            autocomplete.run(async { Ok(suggestions(2)) }).await
+        };
+
+        let (slow, fast) = tokio::join!(slow, fast);
+        drop(sender);
+        assert!(slow.unwrap().is_none());
+        assert_eq!(fast.unwrap().unwrap().data[0].node.id, 2);
+    }
+}
@@ -0,0 +1,199 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::cache::Loader;
+
+use super::{
+    api::AnimeApi, error::AnimeApiError, requests::GetAnimeDetails, responses::AnimeDetails,
+};
+
+/// Memoizing loader for the [Get anime details](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_anime_id_get) endpoint
+///
+/// Concurrent loads of the same anime (with the same fields) are coalesced into
+/// a single request, and the results are cached for the given time-to-live.
+/// This is useful for backends rendering lists that share entries, such as
+/// several titles from the same franchise.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api_client = AnimeApiClient::from(&client_id);
+/// let loader = DetailsLoader::new(api_client, Duration::from_secs(300));
+///
+/// let query = GetAnimeDetails::builder(9969).build().unwrap();
+/// let (a, b) = tokio::join!(loader.load(&query), loader.load(&query));
+/// ```
+#[derive(Debug)]
+pub struct DetailsLoader<C> {
+    client: C,
+    loader: Loader<(u32, Option<String>), AnimeDetails>,
+}
+
+impl<C> DetailsLoader<C>
+where
+    C: AnimeApi + Send + Sync,
+{
+    /// Create a new [DetailsLoader] around the given AnimeApiClient, caching results for `ttl`
+    pub fn new(client: C, ttl: Duration) -> Self {
+        Self {
+            client,
+            loader: Loader::new(ttl),
+        }
+    }
+
+    /// Get the details of an anime, using the cached result if it exists
+    pub async fn load(&self, query: &GetAnimeDetails) -> Result<Arc<AnimeDetails>, AnimeApiError> {
+        self.loader
+            .load_with(Self::key(query), || self.client.get_anime_details(query))
+            .await
+    }
+
+    /// Remove the cached result for the given query
+    pub fn invalidate(&self, query: &GetAnimeDetails) {
+        self.loader.invalidate(&Self::key(query));
+    }
+
+    /// Remove all of the cached results
+    pub fn clear(&self) {
+        self.loader.clear();
+    }
+
+    /// Get a reference to the underlying AnimeApiClient
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    fn key(query: &GetAnimeDetails) -> (u32, Option<String>) {
+        (query.anime_id, query.fields.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        anime::{
+            api::Request,
+            requests::{GetAnimeRanking, GetSeasonalAnime, GetUserAnimeList},
+        },
+        codes::ErrorCode,
+    };
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use serde::Serialize;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Serves the details of any Anime, failing the first `failures` requests
+    struct Details {
+        requests: AtomicU32,
+        failures: u32,
+    }
+
+    #[async_trait]
+    impl Request for Details {
+        async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            unimplemented!()
+        }
+
+        async fn get_details(&self, query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+            let request = self.requests.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            if request < self.failures {
+                return Err(AnimeApiError::with_code(
+                    ErrorCode::Server,
+                    "server error".to_string(),
+                ));
+            }
+            let details = serde_json::json!({
+                "id": query.anime_id,
+                "title": format!("Anime {}", query.anime_id),
+            });
+            Ok(Bytes::from(details.to_string()))
+        }
+        async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_seasonal(&self, _query: &GetSeasonalAnime) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_user(&self, _query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+
+        async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+            unimplemented!()
+        }
+    }
+
+    impl AnimeApi for Details {
+        type State = Details;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    fn loader(failures: u32, ttl: Duration) -> DetailsLoader<Details> {
+        let client = Details {
+            requests: AtomicU32::new(0),
+            failures,
+        };
+        DetailsLoader::new(client, ttl)
+    }
+
+    fn requests(loader: &DetailsLoader<Details>) -> u32 {
+        loader.client().requests.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_loader_collapses_loads() {
+        let loader = loader(0, Duration::from_secs(60));
+        let query = GetAnimeDetails::builder(1).build().unwrap();
+        let (a, b) = tokio::join!(loader.load(&query), loader.load(&query));
+        assert_eq!(a.unwrap().shared_fields.title, "Anime 1");
+        assert_eq!(b.unwrap().shared_fields.title, "Anime 1");
+        assert_eq!(requests(&loader), 1);
+
+        // Cached until invalidated
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 1);
+        loader.invalidate(&query);
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 2);
+
+        let other = GetAnimeDetails::builder(2).build().unwrap();
+        loader.load(&other).await.unwrap();
+        assert_eq!(requests(&loader), 3);
+    }
+
+    #[tokio::test]
+    async fn test_loader_refetches_after_ttl() {
+        let loader = loader(0, Duration::from_millis(20));
+        let query = GetAnimeDetails::builder(1).build().unwrap();
+        loader.load(&query).await.unwrap();
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 1);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 2);
+    }
+
+    #[tokio::test]
+    async fn test_loader_does_not_cache_errors() {
+        let loader = loader(1, Duration::from_secs(60));
+        let query = GetAnimeDetails::builder(1).build().unwrap();
+        let err = loader.load(&query).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Server);
+
+        assert_eq!(
+            loader.load(&query).await.unwrap().shared_fields.title,
+            "Anime 1"
+        );
+        assert_eq!(requests(&loader), 2);
+    }
+}
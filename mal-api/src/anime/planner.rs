@@ -0,0 +1,199 @@
+//! Find the anime a user planned to watch that air in a season
+//!
+//! [plan_season] intersects a user's plan to watch list with the anime of a
+//! season, usually the upcoming one. Besides the planned anime themselves, it
+//! finds sequels of planned anime by following the `prequel` and `parent_story`
+//! relations of the season's anime, so a planned first season shows up when its
+//! second season is about to air.
+//!
+//! Every anime of the season that isn't planned costs up to one details request
+//! per relation followed, within the given depth. Clients with the
+//! [adaptive limit](super::api::AnimeApiClient::with_adaptive_limit) fetch the
+//! season in pages of 500 anime.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetSeasonalAnime::builder(2024, Season::Spring).limit(500).build().unwrap();
+//! let plan = planner::plan_season(&api_client, "@me", &query, 2).await.unwrap();
+//! print!("{}", plan.to_markdown());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    api::{AnimeApi, Request},
+    error::AnimeApiError,
+    requests::{GetSeasonalAnime, GetUserAnimeList, UserAnimeListStatus},
+    responses::AnimeFields,
+    suggestions::FranchiseWalker,
+};
+use crate::common::{PagingIter, RelationType};
+
+/// Why an anime of the season is part of a [SeasonPlan]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanReason {
+    /// The anime itself is on the plan to watch list
+    Planned,
+
+    /// The anime continues a planned anime, such as its next season
+    SequelOf { anime_id: u32, title: String },
+}
+
+/// An anime of the season the user planned to watch, directly or through a prequel
+#[derive(Debug)]
+pub struct PlannedAnime {
+    /// The anime as returned by the seasonal endpoint
+    pub anime: AnimeFields,
+    pub reason: PlanReason,
+}
+
+/// The anime of a season that the user planned to watch
+#[derive(Debug, Default)]
+pub struct SeasonPlan {
+    /// In the order of the seasonal response
+    pub entries: Vec<PlannedAnime>,
+}
+
+impl SeasonPlan {
+    /// The anime that are on the plan to watch list themselves
+    pub fn planned(&self) -> impl Iterator<Item = &PlannedAnime> {
+        self.entries
+            .iter()
+            .filter(|e| e.reason == PlanReason::Planned)
+    }
+
+    /// The anime that continue a planned anime
+    pub fn sequels(&self) -> impl Iterator<Item = &PlannedAnime> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.reason, PlanReason::SequelOf { .. }))
+    }
+
+    /// Write the plan as a markdown list, e.g. for a "what you planned is airing
+    /// next season" message
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.entries {
+            let title = entry.anime.display_title();
+            match &entry.reason {
+                PlanReason::Planned => output.push_str(&format!("- {}\n", title)),
+                PlanReason::SequelOf { title: planned, .. } => {
+                    output.push_str(&format!("- {} (continues {})\n", title, planned))
+                }
+            }
+        }
+        output
+    }
+}
+
+// Relations that lead from a sequel back to the anime it continues
+fn is_prequel_relation(relation_type: RelationType) -> bool {
+    matches!(
+        relation_type,
+        RelationType::Prequel | RelationType::ParentStory
+    )
+}
+
+/// Find the anime of the season that `user_name` planned to watch
+///
+/// Every page of the seasonal query is fetched. Use `@me` as the `user_name`
+/// with an [Oauth](super::api::Oauth) client for your own list
+pub async fn plan_season<C>(
+    client: &C,
+    user_name: &str,
+    query: &GetSeasonalAnime,
+    depth: u8,
+) -> Result<SeasonPlan, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    let list_query = GetUserAnimeList::builder(user_name)
+        .status(UserAnimeListStatus::PlanToWatch)
+        .limit(1000)
+        .build()?;
+    let planned: HashMap<u32, String> = client
+        .get_full_user_anime_list(&list_query)
+        .await?
+        .into_iter()
+        .map(|e| (e.node.id, e.node.display_title().to_string()))
+        .collect();
+    let planned_ids: HashSet<u32> = planned.keys().copied().collect();
+
+    let adapted;
+    let query = if client.get_self().adaptive_limit() {
+        adapted = query.with_max_limit();
+        &adapted
+    } else {
+        query
+    };
+    let mut page = client.get_seasonal_anime(query).await?;
+    let mut season = std::mem::take(&mut page.data);
+    while page.next_page().is_some() {
+        page = client.next(&page).await?;
+        season.append(&mut page.data);
+    }
+
+    let mut walker = FranchiseWalker::new(client, depth).following(is_prequel_relation);
+    let mut plan = SeasonPlan::default();
+    let mut seen = HashSet::new();
+    for entry in season {
+        // Anime airing for several seasons can show up on more than one page
+        if !seen.insert(entry.node.id) {
+            continue;
+        }
+        let reason = if planned.contains_key(&entry.node.id) {
+            PlanReason::Planned
+        } else {
+            match walker.find(entry.node.id, &planned_ids).await? {
+                Some(anime_id) => PlanReason::SequelOf {
+                    anime_id,
+                    title: planned[&anime_id].clone(),
+                },
+                None => continue,
+            }
+        };
+        plan.entries.push(PlannedAnime {
+            anime: entry.node,
+            reason,
+        });
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_fields;
+
+    fn anime(id: u32) -> AnimeFields {
+        anime_fields(id, serde_json::json!({}))
+    }
+
+    #[test]
+    fn test_season_plan() {
+        let plan = SeasonPlan {
+            entries: vec![
+                PlannedAnime {
+                    anime: anime(1),
+                    reason: PlanReason::Planned,
+                },
+                PlannedAnime {
+                    anime: anime(3),
+                    reason: PlanReason::SequelOf {
+                        anime_id: 2,
+                        title: "Anime 2".to_string(),
+                    },
+                },
+            ],
+        };
+        assert_eq!(plan.planned().count(), 1);
+        assert_eq!(plan.sequels().next().unwrap().anime.id, 3);
+        assert_eq!(
+            plan.to_markdown(),
+            "- Anime 1\n- Anime 3 (continues Anime 2)\n"
+        );
+        assert!(is_prequel_relation(RelationType::ParentStory));
+        assert!(!is_prequel_relation(RelationType::Sequel));
+    }
+}
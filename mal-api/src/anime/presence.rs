@@ -0,0 +1,150 @@
+//! Format the anime a user is watching for rich presence displays
+//!
+//! [now_watching] picks the most recently updated entry that's being watched
+//! or rewatched, and formats it the way Discord rich presence integrations
+//! show it: the title as the details line, the episode progress as the state
+//! line, and the artwork as the large image.
+//!
+//! Request the `num_episodes` and `main_picture` fields for the episode count
+//! and the artwork.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetUserAnimeList::builder("@me")
+//!     .fields(&fields)
+//!     .sort(UserAnimeListSort::ListUpdatedAt)
+//!     .build()
+//!     .unwrap();
+//! let list = api_client.get_user_anime_list(&query).await.unwrap();
+//!
+//! if let Some(presence) = presence::now_watching(&list.data) {
+//!     discord.set_activity(&presence.details, &presence.state, presence.large_image.as_deref());
+//! }
+//! ```
+
+use super::{requests::UserAnimeListStatus, responses::AnimeListNode};
+use crate::{activity::timeline, urls::anime_url};
+
+/// Maximum number of characters Discord shows in the details and state lines
+pub const PRESENCE_TEXT_CHARS: usize = 128;
+
+/// An anime formatted for a rich presence display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presence {
+    pub anime_id: u32,
+
+    /// The title, in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub details: String,
+
+    /// The episode progress, e.g. `Episode 3 of 12`, or `Episode 3` if the
+    /// number of episodes is unknown
+    pub state: String,
+
+    /// URL of the artwork, the large version if available
+    pub large_image: Option<String>,
+
+    /// URL of the anime's page on myanimelist.net, e.g. for a button
+    pub url: String,
+}
+
+impl Presence {
+    /// Format a list entry, whatever its status
+    pub fn from_entry(entry: &AnimeListNode) -> Self {
+        let status = entry.list_status.as_ref();
+        let watched = status.map(|s| s.num_episodes_watched).unwrap_or_default();
+        let mut state = match entry.node.num_episodes.filter(|n| *n > 0) {
+            Some(total) => format!("Episode {} of {}", watched, total),
+            None => format!("Episode {}", watched),
+        };
+        if status.is_some_and(|s| s.is_rewatching) {
+            state.insert_str(0, "Rewatching, ");
+        }
+
+        let large_image = entry.node.main_picture.as_ref().and_then(|picture| {
+            [&picture.large, &picture.medium]
+                .into_iter()
+                .find(|url| !url.is_empty())
+                .cloned()
+        });
+
+        Self {
+            anime_id: entry.node.id,
+            details: fit(entry.node.display_title()),
+            state: fit(&state),
+            large_image,
+            url: anime_url(entry.node.id),
+        }
+    }
+}
+
+/// Format the most recently updated entry of `list` that's being watched or
+/// rewatched
+///
+/// Returns `None` if there's no such entry with a valid `updated_at`
+pub fn now_watching(list: &[AnimeListNode]) -> Option<Presence> {
+    timeline(list)
+        .into_iter()
+        .find(|entry| {
+            entry
+                .list_status
+                .as_ref()
+                .is_some_and(|s| s.is_rewatching || s.status == Some(UserAnimeListStatus::Watching))
+        })
+        .map(Presence::from_entry)
+}
+
+// Cuts the text to what Discord shows, ending with an ellipsis if it was cut
+fn fit(text: &str) -> String {
+    if text.chars().count() <= PRESENCE_TEXT_CHARS {
+        return text.to_string();
+    }
+    let mut fitted: String = text.chars().take(PRESENCE_TEXT_CHARS - 1).collect();
+    fitted.push('…');
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, status: &str, num_episodes: u32, updated_at: &str) -> AnimeListNode {
+        anime_entry(id)
+            .status(status)
+            .watched(3)
+            .updated_at(updated_at)
+            .node(serde_json::json!({
+                "num_episodes": num_episodes,
+                "main_picture": { "medium": "https://cdn.myanimelist.net/m.jpg", "large": "" }
+            }))
+            .build()
+    }
+
+    #[test]
+    fn test_now_watching() {
+        let list = vec![
+            entry(1, "watching", 12, "2023-01-01T00:00:00+00:00"),
+            entry(2, "completed", 12, "2023-03-01T00:00:00+00:00"),
+            entry(3, "watching", 0, "2023-02-01T00:00:00+00:00"),
+        ];
+
+        let presence = now_watching(&list).unwrap();
+        assert_eq!(
+            presence,
+            Presence {
+                anime_id: 3,
+                details: "Anime 3".to_string(),
+                state: "Episode 3".to_string(),
+                large_image: Some("https://cdn.myanimelist.net/m.jpg".to_string()),
+                url: "https://myanimelist.net/anime/3".to_string(),
+            }
+        );
+        assert_eq!(Presence::from_entry(&list[0]).state, "Episode 3 of 12");
+        assert!(now_watching(&list[1..2]).is_none());
+
+        let long = fit(&"a".repeat(200));
+        assert_eq!(long.chars().count(), PRESENCE_TEXT_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
@@ -1,7 +1,16 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
 use super::error::AnimeApiError;
+use crate::common::{
+    impl_field_set, impl_to_query_string, FieldError, FieldSet, Priority, QueryField, RewatchValue,
+    ValidationError,
+};
+use crate::limits::{
+    ANIME_LIST_MAX, ANIME_RANKING_MAX, DEFAULT_LIMIT, MIN_LIMIT, SEASONAL_ANIME_MAX,
+    SUGGESTED_ANIME_MAX, USER_LIST_MAX,
+};
+use crate::sort::{Order, SortKey};
 
 /// Corresponds to the [Get anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_get) endpoint
 #[derive(Debug, Serialize)]
@@ -25,17 +34,17 @@ impl GetAnimeList {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Result<Self, AnimeApiError> {
-        let limit = limit.map(|l| l.clamp(1, 100));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, ANIME_LIST_MAX));
         let q: String = q.into();
 
         if q.is_empty() {
-            return Err(AnimeApiError::new("Query cannot be empty".to_string()));
+            return Err(ValidationError::EmptyQuery.into());
         }
 
         Ok(Self {
             q,
             nsfw,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         })
@@ -78,7 +87,7 @@ impl<'a> GetAnimeListBuilder<'a> {
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 100));
+        self.limit = Some(value.clamp(MIN_LIMIT, ANIME_LIST_MAX));
         self
     }
 
@@ -103,16 +112,14 @@ pub struct GetAnimeDetails {
     #[serde(skip_serializing)]
     pub(crate) anime_id: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    fields: Option<String>,
+    pub(crate) fields: Option<String>,
 }
 
 impl GetAnimeDetails {
     /// Create new `Get anime details` query
     pub fn new(anime_id: u32, fields: Option<&AnimeDetailFields>) -> Result<Self, AnimeApiError> {
         if anime_id == 0 {
-            return Err(AnimeApiError::new(
-                "anime_id must be greater than 0".to_string(),
-            ));
+            return Err(ValidationError::InvalidId("anime_id").into());
         }
 
         Ok(Self {
@@ -191,12 +198,12 @@ impl GetAnimeRanking {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Self {
-        let limit = limit.map(|l| l.clamp(1, 500));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, ANIME_RANKING_MAX));
 
         Self {
             ranking_type,
             nsfw,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         }
@@ -238,7 +245,7 @@ impl<'a> GetAnimeRankingBuilder<'a> {
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 500));
+        self.limit = Some(value.clamp(MIN_LIMIT, ANIME_RANKING_MAX));
         self
     }
 
@@ -248,7 +255,7 @@ impl<'a> GetAnimeRankingBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a AnimeCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
@@ -299,7 +306,7 @@ pub enum SeasonalAnimeSort {
 }
 
 /// Corresponds to the [Get seasonal anime](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_season_year_season_get) endpoint
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GetSeasonalAnime {
     #[serde(skip_serializing)]
     pub(crate) year: u16,
@@ -326,14 +333,14 @@ impl GetSeasonalAnime {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Self {
-        let limit = limit.map(|l| l.clamp(1, 500));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, SEASONAL_ANIME_MAX));
 
         Self {
             year,
             season,
             nsfw,
             sort,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         }
@@ -343,6 +350,14 @@ impl GetSeasonalAnime {
     pub fn builder(year: u16, season: Season) -> GetSeasonalAnimeBuilder<'static> {
         GetSeasonalAnimeBuilder::new(year, season)
     }
+
+    // The same query with the largest page size the endpoint allows
+    pub(crate) fn with_max_limit(&self) -> Self {
+        Self {
+            limit: SEASONAL_ANIME_MAX,
+            ..self.clone()
+        }
+    }
 }
 
 pub struct GetSeasonalAnimeBuilder<'a> {
@@ -399,7 +414,7 @@ impl<'a> GetSeasonalAnimeBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a AnimeCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
@@ -436,11 +451,11 @@ impl GetSuggestedAnime {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Self {
-        let limit = limit.map(|l| l.clamp(1, 100));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, SUGGESTED_ANIME_MAX));
 
         Self {
             nsfw,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         }
@@ -475,12 +490,12 @@ impl<'a> GetSuggestedAnimeBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a AnimeCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 100));
+        self.limit = Some(value.clamp(MIN_LIMIT, SUGGESTED_ANIME_MAX));
         self
     }
 
@@ -494,7 +509,7 @@ impl<'a> GetSuggestedAnimeBuilder<'a> {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum UserAnimeListStatus {
     Watching,
@@ -504,7 +519,7 @@ pub enum UserAnimeListStatus {
     PlanToWatch,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum UserAnimeListSort {
     ListScore,
@@ -516,13 +531,15 @@ pub enum UserAnimeListSort {
 }
 
 /// Corresponds to the [Get user anime list](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_animelist_get) endpoint
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GetUserAnimeList {
     #[serde(skip_serializing)]
     pub(crate) user_name: String,
     nsfw: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     status: Option<UserAnimeListStatus>,
+    #[serde(skip_serializing)]
+    pub(crate) statuses: Vec<UserAnimeListStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     sort: Option<UserAnimeListSort>,
     limit: u16,
@@ -548,18 +565,19 @@ impl GetUserAnimeList {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Result<Self, AnimeApiError> {
-        let limit = limit.map(|l| l.clamp(1, 1000));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, USER_LIST_MAX));
 
         if user_name.is_empty() {
-            return Err(AnimeApiError::new("user_name cannot be empty".to_string()));
+            return Err(ValidationError::EmptyUserName.into());
         }
 
         Ok(Self {
             user_name,
             nsfw,
             status,
+            statuses: vec![],
             sort,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         })
@@ -569,6 +587,37 @@ impl GetUserAnimeList {
     pub fn builder(user_name: &str) -> GetUserAnimeListBuilder<'static> {
         GetUserAnimeListBuilder::new(user_name.to_string())
     }
+
+    // The same query with the largest page size the endpoint allows
+    pub(crate) fn with_max_limit(&self) -> Self {
+        Self {
+            limit: USER_LIST_MAX,
+            ..self.clone()
+        }
+    }
+
+    // One query per status of a multi-status query
+    pub(crate) fn split_statuses(&self) -> Vec<Self> {
+        self.statuses
+            .iter()
+            .map(|status| Self {
+                status: Some(*status),
+                statuses: vec![],
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    // The order MAL returns the entries in for the `sort` of the query
+    pub(crate) fn sort_order(&self) -> Option<(SortKey, Order)> {
+        let sort = match self.sort? {
+            UserAnimeListSort::ListScore => (SortKey::Score, Order::Descending),
+            UserAnimeListSort::ListUpdatedAt => (SortKey::UpdatedAt, Order::Descending),
+            UserAnimeListSort::AnimeTitle => (SortKey::Title, Order::Ascending),
+            UserAnimeListSort::AnimeStartDate => (SortKey::StartDate, Order::Descending),
+        };
+        Some(sort)
+    }
 }
 
 pub struct GetUserAnimeListBuilder<'a> {
@@ -576,6 +625,7 @@ pub struct GetUserAnimeListBuilder<'a> {
     nsfw: bool,
     fields: Option<&'a AnimeCommonFields>,
     status: Option<UserAnimeListStatus>,
+    statuses: Vec<UserAnimeListStatus>,
     sort: Option<UserAnimeListSort>,
     limit: Option<u16>,
     offset: Option<u32>,
@@ -589,6 +639,7 @@ impl<'a> GetUserAnimeListBuilder<'a> {
             nsfw: false,
             fields: None,
             status: None,
+            statuses: vec![],
             sort: None,
             limit: None,
             offset: None,
@@ -606,12 +657,42 @@ impl<'a> GetUserAnimeListBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a AnimeCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
     pub fn status(mut self, value: UserAnimeListStatus) -> Self {
         self.status = Some(value);
+        self.statuses.clear();
+        self
+    }
+
+    /// Get the entries with any of the given statuses
+    ///
+    /// MAL only filters by one status per request, so the client sends one request
+    /// per status at the same time and merges the responses into one list. The
+    /// merged list is re-sorted on the client by the `sort`, with the
+    /// [sort](crate::sort) helpers, so entries MAL considers equal can end up in
+    /// a different order than in a single response. Without a `sort`, the
+    /// entries are in the order of the statuses. The `limit` and `offset` apply
+    /// to each status, and the merged list has no pages to follow. Use
+    /// [get_full_user_anime_list](super::api::AnimeApi::get_full_user_anime_list)
+    /// to get every entry.
+    ///
+    /// Only the anime list methods of the clients merge statuses. Sending the
+    /// query with `get_raw` or as a stream fails, and `to_url` only shows the
+    /// first status.
+    pub fn statuses(mut self, values: &[UserAnimeListStatus]) -> Self {
+        self.statuses.clear();
+        for value in values {
+            if !self.statuses.contains(value) {
+                self.statuses.push(*value);
+            }
+        }
+        self.status = self.statuses.first().copied();
+        if self.statuses.len() == 1 {
+            self.statuses.clear();
+        }
         self
     }
 
@@ -621,7 +702,7 @@ impl<'a> GetUserAnimeListBuilder<'a> {
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 1000));
+        self.limit = Some(value.clamp(MIN_LIMIT, USER_LIST_MAX));
         self
     }
 
@@ -631,7 +712,7 @@ impl<'a> GetUserAnimeListBuilder<'a> {
     }
 
     pub fn build(self) -> Result<GetUserAnimeList, AnimeApiError> {
-        GetUserAnimeList::new(
+        let mut query = GetUserAnimeList::new(
             self.user_name,
             self.nsfw,
             self.fields,
@@ -639,7 +720,9 @@ impl<'a> GetUserAnimeListBuilder<'a> {
             self.sort,
             self.limit,
             self.offset,
-        )
+        )?;
+        query.statuses = self.statuses;
+        Ok(query)
     }
 }
 
@@ -657,11 +740,11 @@ pub struct UpdateMyAnimeListStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
     num_watched_episodes: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    priority: Option<u8>,
+    priority: Option<Priority>,
     #[serde(skip_serializing_if = "Option::is_none")]
     num_times_rewatched: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    rewatch_value: Option<u8>,
+    rewatch_value: Option<RewatchValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tags: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -672,49 +755,27 @@ impl UpdateMyAnimeListStatus {
     /// Create new `Update my anime list status` query
     ///
     /// Score must be within `[0, 10]`
-    ///
-    /// Priority must be within `[0, 2]`
-    ///
-    /// Rewatch_value must be within `[0, 5]`
     pub fn new(
         anime_id: u32,
         status: Option<UserAnimeListStatus>,
         is_rewatching: Option<bool>,
         score: Option<u8>,
         num_watched_episodes: Option<u32>,
-        priority: Option<u8>,
+        priority: Option<Priority>,
         num_times_rewatched: Option<u32>,
-        rewatch_value: Option<u8>,
+        rewatch_value: Option<RewatchValue>,
         tags: Option<String>,
         comments: Option<String>,
     ) -> Result<Self, AnimeApiError> {
         // Instead of clamping, be more verbose with errors so the user is more aware of the values
         if let Some(score) = score {
             if score > 10 {
-                return Err(AnimeApiError::new(
-                    "Score must be between 0 and 10 inclusive".to_string(),
-                ));
-            }
-        }
-        if let Some(priority) = priority {
-            if priority > 2 {
-                return Err(AnimeApiError::new(
-                    "Priority must be between 0 and 2 inclusive".to_string(),
-                ));
-            }
-        }
-        if let Some(rewatch_value) = rewatch_value {
-            if rewatch_value > 5 {
-                return Err(AnimeApiError::new(
-                    "Rewatch value must be between 0 and 5 inclusive".to_string(),
-                ));
+                return Err(ValidationError::InvalidScore(score).into());
             }
         }
 
         if anime_id == 0 {
-            return Err(AnimeApiError::new(
-                "anime_id must be greater than 0".to_string(),
-            ));
+            return Err(ValidationError::InvalidId("anime_id").into());
         }
 
         // TODO: Abstract this logic to make it re-useable
@@ -728,9 +789,7 @@ impl UpdateMyAnimeListStatus {
             || tags.is_some()
             || comments.is_some())
         {
-            return Err(AnimeApiError::new(
-                "At least one of the optional arguments must be Some".to_string(),
-            ));
+            return Err(ValidationError::MissingUpdateField.into());
         }
 
         Ok(Self {
@@ -759,9 +818,9 @@ pub struct UpdateMyAnimeListStatusBuilder {
     is_rewatching: Option<bool>,
     score: Option<u8>,
     num_watched_episodes: Option<u32>,
-    priority: Option<u8>,
+    priority: Option<Priority>,
     num_times_rewatched: Option<u32>,
-    rewatch_value: Option<u8>,
+    rewatch_value: Option<RewatchValue>,
     tags: Option<String>,
     comments: Option<String>,
 }
@@ -807,7 +866,7 @@ impl UpdateMyAnimeListStatusBuilder {
         self
     }
 
-    pub fn priority(mut self, value: u8) -> Self {
+    pub fn priority(mut self, value: Priority) -> Self {
         self.priority = Some(value);
         self
     }
@@ -817,7 +876,7 @@ impl UpdateMyAnimeListStatusBuilder {
         self
     }
 
-    pub fn rewatch_value(mut self, value: u8) -> Self {
+    pub fn rewatch_value(mut self, value: RewatchValue) -> Self {
         self.rewatch_value = Some(value);
         self
     }
@@ -849,8 +908,9 @@ impl UpdateMyAnimeListStatusBuilder {
 }
 
 /// Corresponds to the [Delete my anime list item](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_anime_id_my_list_status_delete) endpoint
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct DeleteMyAnimeListItem {
+    #[serde(skip_serializing)]
     pub(crate) anime_id: u32,
 }
 
@@ -861,7 +921,18 @@ impl DeleteMyAnimeListItem {
     }
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
+impl_to_query_string!(
+    GetAnimeList,
+    GetAnimeDetails,
+    GetAnimeRanking,
+    GetSeasonalAnime,
+    GetSuggestedAnime,
+    GetUserAnimeList,
+    UpdateMyAnimeListStatus,
+    DeleteMyAnimeListItem,
+);
+
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, IntoStaticStr, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum AnimeField {
     id,
@@ -892,7 +963,7 @@ pub enum AnimeField {
     studios,
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, IntoStaticStr, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum AnimeDetail {
     // Common fields
@@ -930,50 +1001,69 @@ pub enum AnimeDetail {
     related_manga,
     recommendations,
     statistics,
+    opening_themes,
+    ending_themes,
 }
 
-/// Wrapper for a vector of valid Anime Common Fields
-#[derive(Debug)]
-pub struct AnimeCommonFields(pub Vec<AnimeField>);
+/// Set of valid Anime Common Fields
+#[derive(Debug, Clone)]
+pub struct AnimeCommonFields(pub FieldSet<AnimeField>);
 
-/// Wrapper for a vector of valid Anime Detail Fields
-#[derive(Debug)]
-pub struct AnimeDetailFields(pub Vec<AnimeDetail>);
+impl_field_set!(AnimeCommonFields, AnimeField);
 
-impl<'a> Into<String> for &'a AnimeCommonFields {
-    fn into(self) -> String {
-        let result = self
-            .0
-            .iter()
-            .map(|e| format!("{:?}", e))
-            .collect::<Vec<String>>()
-            .join(",");
-        result
+/// Set of valid Anime Detail Fields
+#[derive(Debug, Clone)]
+pub struct AnimeDetailFields(pub FieldSet<AnimeDetail>);
+
+impl_field_set!(AnimeDetailFields, AnimeDetail);
+
+impl QueryField for AnimeField {
+    fn parse(name: &str) -> Result<Self, FieldError> {
+        name.parse().map_err(|_| match name.parse::<AnimeDetail>() {
+            Ok(_) => FieldError::DetailOnly(name.to_string()),
+            Err(_) => FieldError::Unknown(name.to_string()),
+        })
     }
 }
 
-impl<'a> Into<String> for &'a AnimeDetailFields {
-    fn into(self) -> String {
-        let result = self
-            .0
+impl QueryField for AnimeDetail {
+    fn parse(name: &str) -> Result<Self, FieldError> {
+        name.parse()
+            .map_err(|_| FieldError::Unknown(name.to_string()))
+    }
+}
+
+impl TryFrom<&AnimeDetailFields> for AnimeCommonFields {
+    type Error = FieldError;
+
+    /// Keep the fields of a detail field set, failing on the detail-only ones
+    fn try_from(value: &AnimeDetailFields) -> Result<Self, Self::Error> {
+        value
+            .fields()
             .iter()
-            .map(|e| format!("{:?}", e))
-            .collect::<Vec<String>>()
-            .join(",");
-        result
+            .map(|field| AnimeField::parse(field.into()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::anime::all_common_fields;
+    use crate::anime::{
+        all_common_fields, ANIME_COMMON_FIELDS, ANIME_COMMON_FIELDS_STR, ANIME_DETAIL_FIELDS,
+        ANIME_DETAIL_FIELDS_STR,
+    };
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_get_anime_list() {
         let fields = all_common_fields();
         let query = GetAnimeList::new("".to_string(), false, Some(&fields), Some(100), None);
-        assert!(query.is_err());
+        assert_eq!(
+            query.unwrap_err().validation().copied(),
+            Some(ValidationError::EmptyQuery)
+        );
 
         let query = GetAnimeList::new("one".to_string(), false, Some(&fields), Some(999), None);
         assert!(query.is_ok());
@@ -1105,7 +1195,45 @@ mod tests {
             None,
         );
         assert!(query.is_ok());
-        assert_eq!(query.unwrap().limit, 100);
+        let query = query.unwrap();
+        assert_eq!(query.limit, 100);
+
+        let adapted = query.with_max_limit();
+        assert_eq!(adapted.limit, 1000);
+        assert_eq!(adapted.user_name, "hello");
+        assert_eq!(adapted.status, Some(UserAnimeListStatus::Completed));
+    }
+
+    #[test]
+    fn test_statuses() {
+        let query = GetUserAnimeList::builder("hello")
+            .statuses(&[
+                UserAnimeListStatus::Watching,
+                UserAnimeListStatus::OnHold,
+                UserAnimeListStatus::Watching,
+            ])
+            .sort(UserAnimeListSort::ListScore)
+            .build()
+            .unwrap();
+        let split: Vec<_> = query.split_statuses().iter().map(|q| q.status).collect();
+        assert_eq!(
+            split,
+            vec![
+                Some(UserAnimeListStatus::Watching),
+                Some(UserAnimeListStatus::OnHold)
+            ]
+        );
+        assert_eq!(
+            query.sort_order(),
+            Some((SortKey::Score, Order::Descending))
+        );
+
+        let query = GetUserAnimeList::builder("hello")
+            .statuses(&[UserAnimeListStatus::Completed])
+            .build()
+            .unwrap();
+        assert_eq!(query.status, Some(UserAnimeListStatus::Completed));
+        assert!(query.statuses.is_empty());
     }
 
     #[test]
@@ -1113,7 +1241,10 @@ mod tests {
         let query = UpdateMyAnimeListStatus::new(
             1234, None, None, None, None, None, None, None, None, None,
         );
-        assert!(query.is_err());
+        assert_eq!(
+            query.unwrap_err().validation().copied(),
+            Some(ValidationError::MissingUpdateField)
+        );
 
         let query = UpdateMyAnimeListStatus::new(
             1234,
@@ -1127,35 +1258,19 @@ mod tests {
             None,
             None,
         );
-        assert!(query.is_err());
-
-        let query = UpdateMyAnimeListStatus::new(
-            1234,
-            Some(UserAnimeListStatus::Dropped),
-            None,
-            None,
-            None,
-            Some(3),
-            None,
-            None,
-            None,
-            None,
+        let err = query.unwrap_err();
+        assert_eq!(
+            err.validation().copied(),
+            Some(ValidationError::InvalidScore(11))
         );
-        assert!(query.is_err());
-
-        let query = UpdateMyAnimeListStatus::new(
-            1234,
-            Some(UserAnimeListStatus::Dropped),
-            None,
-            None,
-            None,
-            None,
-            None,
-            Some(6),
-            None,
-            None,
+        assert_eq!(
+            err.to_string(),
+            "Score must be between 0 and 10 inclusive, got 11"
         );
-        assert!(query.is_err());
+
+        assert!(Priority::try_from(3).is_err());
+
+        assert!(RewatchValue::try_from(6).is_err());
 
         let query = UpdateMyAnimeListStatus::new(
             1234,
@@ -1163,12 +1278,86 @@ mod tests {
             None,
             Some(10),
             None,
-            Some(2),
+            Some(Priority::High),
             None,
-            Some(5),
+            Some(RewatchValue::VeryHigh),
             None,
             None,
         );
         assert!(query.is_ok());
     }
+
+    #[test]
+    fn test_to_query_string() {
+        let query = GetAnimeList::builder("one piece")
+            .limit(10)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.to_query_string().unwrap(),
+            "q=one+piece&nsfw=false&limit=10&offset=0"
+        );
+
+        let query = GetUserAnimeList::builder("hello")
+            .status(UserAnimeListStatus::Watching)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.to_query_string().unwrap(),
+            "nsfw=false&status=watching&limit=100&offset=0"
+        );
+
+        let query = UpdateMyAnimeListStatus::builder(1)
+            .priority(Priority::High)
+            .rewatch_value(RewatchValue::Low)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.to_query_string().unwrap(),
+            "priority=2&rewatch_value=2"
+        );
+
+        assert_eq!(DeleteMyAnimeListItem::new(1).to_query_string().unwrap(), "");
+    }
+
+    #[test]
+    fn test_field_set_formats_once() {
+        let fields = AnimeCommonFields::new(vec![AnimeField::id, AnimeField::title]);
+        let formatted = fields.as_str();
+        assert_eq!(formatted, "id,title");
+        assert!(std::ptr::eq(formatted, fields.as_str()));
+
+        let query = GetAnimeList::new("one".to_string(), false, Some(&fields), None, None).unwrap();
+        assert_eq!(query.fields.as_deref(), Some("id,title"));
+    }
+
+    #[test]
+    fn test_all_fields_consts() {
+        assert_eq!(ANIME_COMMON_FIELDS, AnimeField::iter().collect::<Vec<_>>());
+        let fields = AnimeCommonFields::new(ANIME_COMMON_FIELDS.to_vec());
+        assert_eq!(fields.as_str(), ANIME_COMMON_FIELDS_STR);
+        assert_eq!(ANIME_DETAIL_FIELDS, AnimeDetail::iter().collect::<Vec<_>>());
+        let fields = AnimeDetailFields::new(ANIME_DETAIL_FIELDS.to_vec());
+        assert_eq!(fields.as_str(), ANIME_DETAIL_FIELDS_STR);
+    }
+
+    #[test]
+    fn test_field_validation() {
+        let fields: AnimeCommonFields = "id, title,mean".parse().unwrap();
+        assert_eq!(fields.as_str(), "id,title,mean");
+        assert_eq!(
+            "id,statistics".parse::<AnimeCommonFields>().unwrap_err(),
+            FieldError::DetailOnly("statistics".to_string())
+        );
+        assert_eq!(
+            "id,episodes".parse::<AnimeDetailFields>().unwrap_err(),
+            FieldError::Unknown("episodes".to_string())
+        );
+
+        let details: AnimeDetailFields = "id,title,statistics".parse().unwrap();
+        assert!(AnimeCommonFields::try_from(&details).is_err());
+        let details = AnimeDetailFields::new(vec![AnimeDetail::id, AnimeDetail::mean]);
+        let fields = AnimeCommonFields::try_from(&details).unwrap();
+        assert_eq!(fields.fields(), &[AnimeField::id, AnimeField::mean]);
+    }
 }
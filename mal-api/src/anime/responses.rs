@@ -1,7 +1,15 @@
-use std::fmt::Display;
-
-use crate::common::{
-    AlternativeTitles, Genre, MainPicture, Paging, PagingIter, Ranking, RelationType, NSFW,
+use std::{borrow::Borrow, collections::HashMap};
+
+use super::themes::ThemeSong;
+use crate::{
+    common::{
+        impl_to_json, AlternativeTitles, EntrySummary, Genre, MainPicture, Paging, PagingIter,
+        Priority, Ranking, RelationType, RewatchValue, SharedList, Truncated, NSFW,
+        SUMMARY_TEXT_CHARS,
+    },
+    diff::{self, ListDiff},
+    titles::{self, TitlePreference},
+    urls,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Value};
@@ -15,22 +23,27 @@ pub struct AnimeList {
 impl PagingIter for AnimeList {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
+}
 
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+impl_to_json!(AnimeList);
+
+impl AnimeList {
+    /// Move the entries into a [SharedList] that tasks can read concurrently
+    /// without cloning them
+    pub fn into_shared(self) -> SharedList<AnimeListNode> {
+        SharedList::new(self.data, self.paging)
     }
-}
 
-impl Display for AnimeList {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+    /// Patch the entries in place with a diff of them, see [apply_diff](crate::diff::apply_diff)
+    pub fn apply_diff<E: Borrow<AnimeListNode>>(&mut self, diff: &ListDiff<E>) {
+        diff::apply_diff(&mut self.data, diff)
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnimeListNode {
     pub node: AnimeFields,
 
@@ -38,25 +51,39 @@ pub struct AnimeListNode {
     pub list_status: Option<AnimeListStatus>,
 }
 
-impl Display for AnimeListNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl AnimeListNode {
+    /// Fraction of the episodes watched, within `[0, 1]`
+    ///
+    /// Returns `None` if the entry has no list status, or if the number of episodes
+    /// is unknown, which MAL reports as 0 for ongoing anime. Request the
+    /// `num_episodes` field for it to be available.
+    pub fn progress_fraction(&self) -> Option<f32> {
+        let watched = self.list_status.as_ref()?.num_episodes_watched;
+        let total = self.node.num_episodes.filter(|n| *n > 0)?;
+        Some((watched as f32 / total as f32).min(1.0))
+    }
+
+    /// Number of episodes left to watch
+    ///
+    /// Returns `None` in the same cases as [AnimeListNode::progress_fraction]
+    pub fn episodes_remaining(&self) -> Option<u32> {
+        let watched = self.list_status.as_ref()?.num_episodes_watched;
+        let total = self.node.num_episodes.filter(|n| *n > 0)?;
+        Some(total.saturating_sub(watched))
     }
 }
 
+impl_to_json!(AnimeListNode);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AnimePicture {
     pub medium: String,
     pub large: String,
 }
 
-impl Display for AnimePicture {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AnimePicture);
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum AnimeMediaType {
     Unknown,
@@ -70,7 +97,7 @@ pub enum AnimeMediaType {
     Music,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum AnimeStatus {
     FinishedAiring,
@@ -78,7 +105,13 @@ pub enum AnimeStatus {
     NotYetAired,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The status of an anime in a user's list
+///
+/// Used for the `list_status` of user anime list entries, the `my_list_status`
+/// of anime, and the response of the update endpoint. MAL only returns the
+/// priority, rewatch, tags, and comments fields in some of these, so they
+/// default to their empty values when missing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnimeListStatus {
     pub status: Option<super::requests::UserAnimeListStatus>,
     pub score: u8,
@@ -86,45 +119,38 @@ pub struct AnimeListStatus {
     pub is_rewatching: bool,
     pub start_date: Option<String>,
     pub finish_date: Option<String>,
-    pub priority: u8,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
     pub num_times_rewatched: u32,
-    pub rewatch_value: u8,
+    #[serde(default)]
+    pub rewatch_value: RewatchValue,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub comments: String,
     pub updated_at: String,
 }
 
-impl Display for AnimeListStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AnimeListStatus);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StartSeason {
     pub year: u32,
     pub season: super::requests::Season,
 }
 
-impl Display for StartSeason {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(StartSeason);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Broadcast {
     pub day_of_the_week: String,
     pub start_time: Option<String>,
 }
 
-impl Display for Broadcast {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Broadcast);
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Source {
     Other,
@@ -146,7 +172,7 @@ pub enum Source {
     Music,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Rating {
     G,
@@ -159,28 +185,26 @@ pub enum Rating {
     RX,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Studio {
     pub id: u32,
     pub name: String,
 }
 
-impl Display for Studio {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Studio);
 
-// Wrap everything in Options since user controls what fields should be returned
-#[derive(Debug, Deserialize, Serialize)]
+// Wrap everything in Options since user controls what fields should be returned.
+// The large fields that are often not requested are boxed, so that a `None`
+// only takes a pointer; this keeps long lists of entries small
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnimeFields {
     pub id: u32,
     pub title: String,
-    pub main_picture: Option<MainPicture>,
-    pub alternative_titles: Option<AlternativeTitles>,
+    pub main_picture: Option<Box<MainPicture>>,
+    pub alternative_titles: Option<Box<AlternativeTitles>>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
-    pub synopsis: Option<String>,
+    pub synopsis: Option<Box<str>>,
     pub mean: Option<f32>,
     pub rank: Option<u32>,
     pub popularity: Option<u32>,
@@ -192,22 +216,65 @@ pub struct AnimeFields {
     pub updated_at: Option<String>,
     pub media_type: Option<AnimeMediaType>,
     pub status: Option<AnimeStatus>,
-    pub my_list_status: Option<AnimeListStatus>,
+    pub my_list_status: Option<Box<AnimeListStatus>>,
     pub num_episodes: Option<u32>,
     pub start_season: Option<StartSeason>,
-    pub broadcast: Option<Broadcast>,
+    pub broadcast: Option<Box<Broadcast>>,
     pub source: Option<Source>,
     pub average_episode_duration: Option<u32>,
     pub rating: Option<Rating>,
     pub studios: Option<Vec<Studio>>,
+    /// Fields returned by MAL that this crate doesn't know about yet
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-impl Display for AnimeFields {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl AnimeFields {
+    /// URL of the anime's myanimelist.net page
+    pub fn url(&self) -> String {
+        urls::anime_url(self.id)
+    }
+
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub fn display_title(&self) -> &str {
+        self.title_in(titles::title_preference())
+    }
+
+    /// Compact view of the anime for logging, with the synopsis truncated
+    pub fn summary(&self) -> EntrySummary<'_> {
+        EntrySummary {
+            id: Some(self.id),
+            title: &self.title,
+            mean: self.mean,
+            rank: self.rank,
+            synopsis: self
+                .synopsis
+                .as_deref()
+                .map(|s| Truncated::new(s, SUMMARY_TEXT_CHARS)),
+            background: None,
+        }
+    }
+
+    /// Check if the main title or any of the alternative titles matches the given title
+    ///
+    /// Titles are compared after [normalizing](titles::normalize_title) them
+    pub fn has_title(&self, title: &str) -> bool {
+        titles::same_title(&self.title, title)
+            || self
+                .alternative_titles
+                .as_ref()
+                .is_some_and(|t| t.has_title(title))
+    }
+
+    /// Title in the given language, falling back to the main title
+    pub fn title_in(&self, preference: TitlePreference) -> &str {
+        titles::select_title(&self.title, self.alternative_titles.as_deref(), preference)
     }
 }
 
+impl_to_json!(AnimeFields);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RelatedAnime {
     pub node: AnimeFields,
@@ -215,11 +282,7 @@ pub struct RelatedAnime {
     pub relation_type_formatted: String,
 }
 
-impl Display for RelatedAnime {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(RelatedAnime);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Recommendations {
@@ -227,28 +290,21 @@ pub struct Recommendations {
     pub num_recommendations: u32,
 }
 
-impl Display for Recommendations {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Recommendations);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Statistics {
     pub num_list_users: u32,
     pub status: StatisticsStatus,
 }
 
-impl Display for Statistics {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Statistics);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StatisticsStatus {
     // MAL returns these as strings, even though docs say they are supposed to be integers
-    // Use custom serializer for these fields to turn the strings into u32
+    // Use custom deserializer for these fields to turn the strings into u32. Integers
+    // are accepted as well, so serialized responses can be deserialized again
     #[serde(deserialize_with = "deserialize_string_to_u32")]
     pub watching: u32,
     #[serde(deserialize_with = "deserialize_string_to_u32")]
@@ -261,11 +317,7 @@ pub struct StatisticsStatus {
     pub plan_to_watch: u32,
 }
 
-impl Display for StatisticsStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(StatisticsStatus);
 
 fn deserialize_string_to_u32<'de, D>(deserializer: D) -> Result<u32, D::Error>
 where
@@ -274,11 +326,37 @@ where
     let value: Value = Deserialize::deserialize(deserializer)?;
     if let Some(number) = value.as_str().and_then(|s| s.parse().ok()) {
         Ok(number)
+    } else if let Some(number) = value.as_u64().and_then(|n| u32::try_from(n).ok()) {
+        Ok(number)
     } else {
         Err(serde::de::Error::custom("Invalid value for u32"))
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AnimeTheme {
+    pub id: u32,
+    pub anime_id: u32,
+
+    /// e.g. `#1: "Yuusha (勇者)" by YOASOBI (eps 1-16)`. See [ThemeSong::parse]
+    pub text: String,
+}
+
+impl AnimeTheme {
+    /// Parse the theme text into a [ThemeSong]
+    pub fn song(&self) -> Option<ThemeSong> {
+        ThemeSong::parse(&self.text)
+    }
+}
+
+fn parse_themes(themes: &Option<Vec<AnimeTheme>>) -> Vec<ThemeSong> {
+    themes
+        .iter()
+        .flatten()
+        .filter_map(AnimeTheme::song)
+        .collect()
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AnimeDetails {
     #[serde(flatten)]
@@ -287,38 +365,114 @@ pub struct AnimeDetails {
     pub pictures: Option<Vec<AnimePicture>>,
     pub background: Option<String>,
     pub related_anime: Option<Vec<RelatedAnime>>,
+    #[cfg(feature = "manga")]
     pub related_manga: Option<Vec<crate::manga::responses::RelatedManga>>,
     pub recommendations: Option<Vec<Recommendations>>,
     pub statistics: Option<Statistics>,
+    pub opening_themes: Option<Vec<AnimeTheme>>,
+    pub ending_themes: Option<Vec<AnimeTheme>>,
 }
 
-impl Display for AnimeDetails {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl AnimeDetails {
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub fn display_title(&self) -> &str {
+        self.shared_fields.display_title()
+    }
+
+    /// Compact view of the anime for logging, with the synopsis and background truncated
+    pub fn summary(&self) -> EntrySummary<'_> {
+        EntrySummary {
+            background: self
+                .background
+                .as_deref()
+                .map(|s| Truncated::new(s, SUMMARY_TEXT_CHARS)),
+            ..self.shared_fields.summary()
+        }
+    }
+
+    /// Related anime with the given relation type
+    pub fn related_anime_of(&self, relation_type: RelationType) -> Vec<&RelatedAnime> {
+        self.related_anime
+            .iter()
+            .flatten()
+            .filter(|r| r.relation_type == relation_type)
+            .collect()
+    }
+
+    /// Related anime grouped by their relation type
+    pub fn related_anime_by_type(&self) -> HashMap<RelationType, Vec<&RelatedAnime>> {
+        let mut groups: HashMap<RelationType, Vec<&RelatedAnime>> = HashMap::new();
+        for related in self.related_anime.iter().flatten() {
+            groups
+                .entry(related.relation_type)
+                .or_default()
+                .push(related);
+        }
+        groups
+    }
+
+    pub fn sequels(&self) -> Vec<&RelatedAnime> {
+        self.related_anime_of(RelationType::Sequel)
+    }
+
+    pub fn prequels(&self) -> Vec<&RelatedAnime> {
+        self.related_anime_of(RelationType::Prequel)
+    }
+
+    pub fn side_stories(&self) -> Vec<&RelatedAnime> {
+        self.related_anime_of(RelationType::SideStory)
+    }
+
+    /// Parsed opening themes, skipping the ones that couldn't be parsed
+    ///
+    /// Requires the `opening_themes` field
+    pub fn openings(&self) -> Vec<ThemeSong> {
+        parse_themes(&self.opening_themes)
+    }
+
+    /// Parsed ending themes, skipping the ones that couldn't be parsed
+    ///
+    /// Requires the `ending_themes` field
+    pub fn endings(&self) -> Vec<ThemeSong> {
+        parse_themes(&self.ending_themes)
+    }
+
+    /// Manga related to this anime as an [adaptation](RelationType::Adaptation)
+    ///
+    /// Requires the `related_manga` field
+    #[cfg(feature = "manga")]
+    pub fn adaptations(&self) -> Vec<&crate::manga::responses::RelatedManga> {
+        self.related_manga
+            .iter()
+            .flatten()
+            .filter(|r| r.relation_type == RelationType::Adaptation)
+            .collect()
+    }
+
+    /// The manga the anime is adapted from, e.g. for "read the source" links
+    ///
+    /// Same as [adaptations](AnimeDetails::adaptations). Requires the `related_manga` field
+    #[cfg(feature = "manga")]
+    pub fn source_manga(&self) -> Vec<&crate::manga::responses::RelatedManga> {
+        self.adaptations()
     }
 }
 
+impl_to_json!(AnimeDetails);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AnimeRanking {
     pub data: Vec<AnimeRankingNode>,
     pub paging: Paging,
 }
 
-impl Display for AnimeRanking {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AnimeRanking);
 
 impl PagingIter for AnimeRanking {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -328,11 +482,7 @@ pub struct AnimeRankingNode {
     pub ranking: Ranking,
 }
 
-impl Display for AnimeRankingNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AnimeRankingNode);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SeasonalAnime {
@@ -340,21 +490,13 @@ pub struct SeasonalAnime {
     pub paging: Paging,
 }
 
-impl Display for SeasonalAnime {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(SeasonalAnime);
 
 impl PagingIter for SeasonalAnime {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -363,11 +505,7 @@ pub struct SeasonalAnimeNode {
     pub node: AnimeFields,
 }
 
-impl Display for SeasonalAnimeNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(SeasonalAnimeNode);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SuggestedAnime {
@@ -375,21 +513,13 @@ pub struct SuggestedAnime {
     pub paging: Paging,
 }
 
-impl Display for SuggestedAnime {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(SuggestedAnime);
 
 impl PagingIter for SuggestedAnime {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -398,8 +528,208 @@ pub struct SuggestedAnimeNode {
     pub node: AnimeFields,
 }
 
-impl Display for SuggestedAnimeNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl_to_json!(SuggestedAnimeNode);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anime::requests::UserAnimeListStatus;
+    use crate::fixtures::{anime_details, anime_entry, related};
+
+    #[test]
+    fn test_into_shared() {
+        let list: AnimeList = serde_json::from_value(serde_json::json!({
+            "data": [
+                { "node": { "id": 1, "title": "Cowboy Bebop" } },
+                { "node": { "id": 5, "title": "Cowboy Bebop: Tengoku no Tobira" } }
+            ],
+            "paging": { "next": "https://api.myanimelist.net/v2/anime?offset=2&limit=2" }
+        }))
+        .unwrap();
+        let shared = list.into_shared();
+        let other = shared.clone();
+        assert!(std::sync::Arc::ptr_eq(&shared.entries(), &other.entries()));
+        assert_eq!(other.len(), 2);
+        assert_eq!(other[1].node.id, 5);
+        assert!(other.next_page().is_some());
+    }
+
+    #[test]
+    #[cfg(not(feature = "unknown-fields"))]
+    fn test_anime_fields_size() {
+        // Was 576 bytes before the large fields were boxed
+        assert!(std::mem::size_of::<AnimeFields>() <= 320);
+    }
+
+    #[test]
+    fn test_progress() {
+        let entry = |num_episodes: u32, watched: u32| -> AnimeListNode {
+            anime_entry(1)
+                .status("watching")
+                .watched(watched)
+                .node(serde_json::json!({ "num_episodes": num_episodes }))
+                .build()
+        };
+
+        assert_eq!(entry(12, 3).progress_fraction(), Some(0.25));
+        assert_eq!(entry(12, 3).episodes_remaining(), Some(9));
+        // Ongoing anime have 0 episodes
+        assert_eq!(entry(0, 3).progress_fraction(), None);
+        assert_eq!(entry(0, 3).episodes_remaining(), None);
+        assert_eq!(entry(12, 13).progress_fraction(), Some(1.0));
+        assert_eq!(entry(12, 13).episodes_remaining(), Some(0));
+    }
+
+    #[test]
+    fn test_anime_list_status_shapes() {
+        // `list_status` of a user anime list entry
+        let status: AnimeListStatus = serde_json::from_value(serde_json::json!({
+            "status": "watching",
+            "score": 8,
+            "num_episodes_watched": 5,
+            "is_rewatching": false,
+            "updated_at": "2023-01-01T00:00:00+00:00"
+        }))
+        .unwrap();
+        assert!(matches!(status.status, Some(UserAnimeListStatus::Watching)));
+        assert_eq!(status.priority, Priority::Low);
+        assert_eq!(status.rewatch_value, RewatchValue::NotSet);
+        assert!(status.tags.is_empty());
+
+        // Response of the update endpoint
+        let status: AnimeListStatus = serde_json::from_value(serde_json::json!({
+            "status": "completed",
+            "score": 10,
+            "num_episodes_watched": 12,
+            "is_rewatching": false,
+            "updated_at": "2023-01-01T00:00:00+00:00",
+            "priority": 2,
+            "num_times_rewatched": 1,
+            "rewatch_value": 5,
+            "tags": ["favorite"],
+            "comments": "great"
+        }))
+        .unwrap();
+        assert_eq!(status.priority, Priority::High);
+        assert_eq!(status.rewatch_value, RewatchValue::VeryHigh);
+        assert_eq!(status.tags, vec!["favorite".to_string()]);
+        assert_eq!(status.comments, "great");
+
+        let value = serde_json::to_value(&status).unwrap();
+        assert_eq!(value["priority"], 2);
+        assert_eq!(value["rewatch_value"], 5);
+
+        let result = serde_json::from_value::<AnimeListStatus>(serde_json::json!({
+            "status": "completed",
+            "score": 10,
+            "num_episodes_watched": 12,
+            "is_rewatching": false,
+            "updated_at": "2023-01-01T00:00:00+00:00",
+            "priority": 3
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_related_anime() {
+        let details = anime_details(
+            1,
+            serde_json::json!({
+                "related_anime": [
+                    related(2, "sequel"),
+                    related(3, "side_story"),
+                    related(4, "sequel")
+                ]
+            }),
+        );
+
+        let ids =
+            |related: Vec<&RelatedAnime>| related.iter().map(|r| r.node.id).collect::<Vec<_>>();
+        assert_eq!(ids(details.sequels()), vec![2, 4]);
+        assert_eq!(ids(details.side_stories()), vec![3]);
+        assert!(details.prequels().is_empty());
+        #[cfg(feature = "manga")]
+        assert!(details.adaptations().is_empty());
+        assert_eq!(
+            details.related_anime_by_type()[&RelationType::Sequel].len(),
+            2
+        );
+    }
+
+    #[cfg(feature = "manga")]
+    #[test]
+    fn test_source_manga() {
+        use crate::well_known::{ONE_PIECE_ANIME_ID, ONE_PIECE_MANGA_ID};
+
+        let anime = anime_details(
+            ONE_PIECE_ANIME_ID,
+            serde_json::json!({
+                "title": "One Piece",
+                "related_manga": [
+                    related(ONE_PIECE_MANGA_ID, "adaptation"),
+                    related(25146, "spin_off")
+                ]
+            }),
+        );
+        let source: Vec<_> = anime.source_manga().iter().map(|r| r.node.id).collect();
+        assert_eq!(source, vec![Some(ONE_PIECE_MANGA_ID)]);
+        assert_eq!(anime.adaptations().len(), 1);
+
+        let manga: crate::manga::responses::MangaDetails =
+            crate::fixtures::parse(serde_json::json!({
+                "id": ONE_PIECE_MANGA_ID,
+                "title": "One Piece",
+                "related_anime": [
+                    related(ONE_PIECE_ANIME_ID, "adaptation"),
+                    related(459, "other")
+                ]
+            }));
+        let adaptations: Vec<_> = manga
+            .anime_adaptations()
+            .iter()
+            .map(|r| r.node.id)
+            .collect();
+        assert_eq!(adaptations, vec![ONE_PIECE_ANIME_ID]);
+    }
+
+    #[test]
+    fn test_summary() {
+        let synopsis = "a".repeat(100);
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "Anime 1",
+            "mean": 8.5,
+            "synopsis": synopsis,
+            "background": "Short background"
+        }))
+        .unwrap();
+
+        let summary = details.summary();
+        assert_eq!(summary.id, Some(1));
+        assert_eq!(summary.mean, Some(8.5));
+        assert!(summary.synopsis.unwrap().is_truncated());
+        assert_eq!(summary.background.unwrap().to_string(), "Short background");
+        assert!(format!("{:?}", summary).contains("(20 more chars)"));
+        assert!(details.shared_fields.summary().background.is_none());
+    }
+
+    #[cfg(feature = "unknown-fields")]
+    #[test]
+    fn test_unknown_fields() {
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "Anime 1",
+            "background": "",
+            "new_field": { "value": 1 }
+        }))
+        .unwrap();
+        assert_eq!(
+            details.shared_fields.extra["new_field"],
+            serde_json::json!({ "value": 1 })
+        );
+        assert!(!details.shared_fields.extra.contains_key("background"));
+
+        let value = serde_json::to_value(&details).unwrap();
+        assert_eq!(value["new_field"]["value"], 1);
     }
 }
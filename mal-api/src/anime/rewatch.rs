@@ -0,0 +1,160 @@
+//! Suggest completed anime worth rewatching
+//!
+//! [rewatch_candidates] ranks the completed entries of a list by a weighted sum
+//! of the user's score, their rewatch value, and the time since they finished
+//! the anime. Each part is scaled between `0.0` and `1.0` before weighting, and
+//! parts the user didn't fill in count as `0.5`. Use [RewatchWeights] to change
+//! how much each part counts.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let list = api_client.get_full_user_anime_list(&query).await.unwrap();
+//!
+//! for candidate in rewatch::rewatch_candidates(&list, &PartialDate::today()).iter().take(5) {
+//!     println!("{} ({:.2})", candidate.entry.node.title, candidate.weight);
+//! }
+//! ```
+
+use super::{requests::UserAnimeListStatus, responses::AnimeListNode};
+use crate::{common::Score, dates::PartialDate};
+
+// Value of the parts the user didn't fill in
+const UNKNOWN: f32 = 0.5;
+
+/// How much the score, rewatch value, and time since finishing count towards
+/// the weight of a [RewatchCandidate]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewatchWeights {
+    pub score: f32,
+    pub rewatch_value: f32,
+    pub time_since_finish: f32,
+
+    /// Days after finishing at which the time part reaches its maximum
+    pub full_after_days: u32,
+}
+
+impl Default for RewatchWeights {
+    fn default() -> Self {
+        Self {
+            score: 0.5,
+            rewatch_value: 0.3,
+            time_since_finish: 0.2,
+            full_after_days: 730,
+        }
+    }
+}
+
+impl RewatchWeights {
+    /// Rank the completed entries of `list`, highest weight first
+    ///
+    /// Entries that are being rewatched are left out
+    pub fn rank<'a>(
+        &self,
+        list: &'a [AnimeListNode],
+        today: &PartialDate,
+    ) -> Vec<RewatchCandidate<'a>> {
+        let mut candidates: Vec<RewatchCandidate> = list
+            .iter()
+            .filter_map(|entry| {
+                let status = entry.list_status.as_ref()?;
+                if status.status != Some(UserAnimeListStatus::Completed) || status.is_rewatching {
+                    return None;
+                }
+
+                let days_since_finish = status
+                    .finish_date
+                    .as_deref()
+                    .and_then(|date| date.parse::<PartialDate>().ok())
+                    .map(|date| date.days_until(today));
+                let elapsed = days_since_finish
+                    .map(|days| (days.max(0) as f32 / self.full_after_days.max(1) as f32).min(1.0));
+                let score = Score::try_from(status.score)
+                    .ok()
+                    .and_then(|s| s.fraction());
+
+                let weight = self.score * score.unwrap_or(UNKNOWN)
+                    + self.rewatch_value * status.rewatch_value.fraction().unwrap_or(UNKNOWN)
+                    + self.time_since_finish * elapsed.unwrap_or(UNKNOWN);
+                Some(RewatchCandidate {
+                    entry,
+                    days_since_finish,
+                    weight,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        candidates
+    }
+}
+
+/// A completed anime suggested for a rewatch
+#[derive(Debug, Clone, Copy)]
+pub struct RewatchCandidate<'a> {
+    pub entry: &'a AnimeListNode,
+
+    /// Days since the `finish_date`, if it was set
+    pub days_since_finish: Option<i64>,
+
+    /// Between `0.0` and the sum of the [RewatchWeights]
+    pub weight: f32,
+}
+
+/// Rank the completed entries of `list` with the default [RewatchWeights]
+pub fn rewatch_candidates<'a>(
+    list: &'a [AnimeListNode],
+    today: &PartialDate,
+) -> Vec<RewatchCandidate<'a>> {
+    RewatchWeights::default().rank(list, today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(
+        id: u32,
+        status: &str,
+        score: u8,
+        finish_date: &str,
+        rewatch_value: u8,
+    ) -> AnimeListNode {
+        anime_entry(id)
+            .status(status)
+            .score(score)
+            .list_status(serde_json::json!({
+                "finish_date": finish_date,
+                "rewatch_value": rewatch_value
+            }))
+            .build()
+    }
+
+    #[test]
+    fn test_rewatch_candidates() {
+        let list = vec![
+            entry(1, "completed", 10, "2023-06-01", 5),
+            entry(2, "completed", 10, "2021-01", 5),
+            entry(3, "completed", 6, "2020-01-01", 1),
+            entry(4, "completed", 0, "not a date", 0),
+            entry(5, "watching", 10, "2020-01-01", 5),
+        ];
+        let today = PartialDate::new(2024, 1, 1).unwrap();
+
+        let candidates = rewatch_candidates(&list, &today);
+        let ids: Vec<u32> = candidates.iter().map(|c| c.entry.node.id).collect();
+        assert_eq!(ids, vec![2, 1, 3, 4]);
+        assert!((candidates[0].weight - 1.0).abs() < 1e-6);
+        assert_eq!(candidates[1].days_since_finish, Some(214));
+        assert_eq!(candidates[3].days_since_finish, None);
+        assert!((candidates[3].weight - 0.5).abs() < 1e-6);
+
+        let by_score = RewatchWeights {
+            score: 1.0,
+            rewatch_value: 0.0,
+            time_since_finish: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(by_score.rank(&list, &today)[3].entry.node.id, 4);
+    }
+}
@@ -0,0 +1,222 @@
+//! Anime search with better recall than a single MAL search
+//!
+//! MAL only matches the query against the titles it indexes, so a search for
+//! the English title of a show, or one with a typo or a season suffix, often
+//! misses it. [search_thorough] sends a few variants of the query at the same
+//! time, merges their results, and ranks them by how similar their titles are
+//! to the query with [title_similarity].
+//!
+//! Each variant is a separate request, so this costs up to [MAX_VARIANTS] times
+//! the rate limit of a plain search.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let fields = anime_common_fields!(AnimeField::id, AnimeField::title, AnimeField::alternative_titles);
+//! let matches = search_thorough(&api_client, "Frieren: Beyond Journey's End season 2", &fields, 10)
+//!     .await
+//!     .unwrap();
+//! for m in matches {
+//!     println!("{:.2} {}", m.score, m.entry.node.title);
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use futures::future::join_all;
+
+use super::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeCommonFields, GetAnimeList},
+    responses::{AnimeFields, AnimeListNode},
+};
+use crate::titles::{normalize_title, title_similarity};
+
+/// The most query variants [search_thorough] sends
+pub const MAX_VARIANTS: usize = 4;
+
+// MAL rejects shorter queries
+const MIN_QUERY_LENGTH: usize = 3;
+
+/// A search result with its similarity to the query
+#[derive(Debug)]
+pub struct SearchMatch {
+    pub entry: AnimeListNode,
+
+    /// Similarity of the closest title of the anime to the query, from 0 to 1
+    pub score: f64,
+}
+
+/// The queries [search_thorough] sends for `q`, starting with `q` itself
+///
+/// Besides the query as typed, it tries the [normalized](normalize_title) query,
+/// the query without a subtitle after `:` or ` - `, and the query without a
+/// season or part suffix such as `season 2` or `2nd season`, which English titles
+/// often have and MAL titles often don't.
+pub fn query_variants(q: &str) -> Vec<String> {
+    let q = q.trim();
+    let normalized = normalize_title(q);
+    let candidates = [
+        q.to_string(),
+        normalized.clone(),
+        without_subtitle(q).to_string(),
+        without_season(&normalized),
+    ];
+
+    let mut seen = HashSet::new();
+    let mut variants = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.trim().to_string();
+        if candidate.chars().count() < MIN_QUERY_LENGTH {
+            continue;
+        }
+        if seen.insert(candidate.to_lowercase()) {
+            variants.push(candidate);
+        }
+    }
+    variants.truncate(MAX_VARIANTS);
+    variants
+}
+
+/// Search for `q` with several [query_variants], returning the merged results
+/// ranked by similarity to `q`
+///
+/// Results found by several variants are only returned once, and ties keep the
+/// order MAL returned them in. Request the `alternative_titles` field so that
+/// English and Japanese titles are compared too. Fails only if every variant
+/// fails, with the error of the first one.
+pub async fn search_thorough<A>(
+    api: &A,
+    q: &str,
+    fields: &AnimeCommonFields,
+    limit: u16,
+) -> Result<Vec<SearchMatch>, AnimeApiError>
+where
+    A: AnimeApi + Sync,
+{
+    let mut queries = Vec::new();
+    for variant in query_variants(q) {
+        queries.push(GetAnimeList::new(
+            variant,
+            false,
+            Some(fields),
+            Some(limit),
+            None,
+        )?);
+    }
+    if queries.is_empty() {
+        return Ok(vec![]);
+    }
+    let pages = join_all(queries.iter().map(|query| api.get_anime_list(query))).await;
+
+    let mut seen = HashSet::new();
+    let mut matches = Vec::new();
+    let mut first_error = None;
+    for page in pages {
+        let page = match page {
+            Ok(page) => page,
+            Err(err) => {
+                first_error.get_or_insert(err);
+                continue;
+            }
+        };
+        for entry in page.data {
+            if seen.insert(entry.node.id) {
+                let score = best_similarity(q, &entry.node);
+                matches.push(SearchMatch { entry, score });
+            }
+        }
+    }
+    if seen.is_empty() {
+        if let Some(err) = first_error {
+            return Err(err);
+        }
+    }
+
+    // Stable, so ties keep MAL's order
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(matches)
+}
+
+fn best_similarity(q: &str, anime: &AnimeFields) -> f64 {
+    let alternatives = anime.alternative_titles.iter().flat_map(|t| t.titles());
+    std::iter::once(anime.title.as_str())
+        .chain(alternatives)
+        .map(|title| title_similarity(q, title))
+        .fold(0.0, f64::max)
+}
+
+fn without_subtitle(q: &str) -> &str {
+    let end = [":", " - "]
+        .iter()
+        .filter_map(|separator| q.find(separator))
+        .min()
+        .unwrap_or(q.len());
+    &q[..end]
+}
+
+// Drops a trailing `season 2`, `2nd season`, `part 2`, or `s2` from a normalized query
+fn without_season(normalized: &str) -> String {
+    let mut words: Vec<&str> = normalized.split(' ').collect();
+    let is_number = |word: &str| {
+        let digits = word.trim_end_matches(|c: char| c.is_alphabetic());
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    };
+    let n = words.len();
+    if n < 2 {
+        return normalized.to_string();
+    }
+    let (before, last) = (words[n - 2], words[n - 1]);
+    let numbered = matches!(before, "season" | "part" | "cour") && is_number(last);
+    let ordinal = last == "season" && is_number(before);
+    let short =
+        last.len() > 1 && last.starts_with('s') && last[1..].chars().all(|c| c.is_ascii_digit());
+    if numbered || ordinal {
+        words.truncate(n - 2);
+    } else if short {
+        words.truncate(n - 1);
+    }
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::FRIEREN_ANIME_ID;
+
+    #[test]
+    fn test_query_variants() {
+        assert_eq!(
+            query_variants("Frieren: Beyond Journey's End Season 2"),
+            vec![
+                "Frieren: Beyond Journey's End Season 2",
+                "frieren beyond journeys end season 2",
+                "Frieren",
+                "frieren beyond journeys end",
+            ]
+        );
+        assert_eq!(
+            query_variants("Attack on Titan 2nd Season"),
+            vec!["Attack on Titan 2nd Season", "attack on titan"]
+        );
+        assert_eq!(query_variants("  bocchi  "), vec!["bocchi"]);
+        assert!(query_variants("k").is_empty());
+    }
+
+    #[test]
+    fn test_best_similarity() {
+        let anime: AnimeFields = serde_json::from_value(serde_json::json!({
+            "id": FRIEREN_ANIME_ID,
+            "title": "Sousou no Frieren",
+            "alternative_titles": {
+                "synonyms": ["Frieren at the Funeral"],
+                "en": "Frieren: Beyond Journey's End",
+                "ja": "葬送のフリーレン"
+            }
+        }))
+        .unwrap();
+        assert_eq!(best_similarity("frieren beyond journeys end", &anime), 1.0);
+        assert!(best_similarity("Frieren", &anime) > best_similarity("Fire Force", &anime));
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::OnceCell, task::JoinHandle};
+
+use super::{
+    api::AnimeApi, error::AnimeApiError, requests::GetSeasonalAnime, responses::SeasonalAnime,
+};
+
+// (year, season, query string)
+type SeasonalKey = (u16, String, String);
+
+// Fetches of seasons that aren't cached yet, shared by concurrent callers
+type InFlight = HashMap<SeasonalKey, Arc<OnceCell<Arc<SeasonalAnime>>>>;
+
+#[derive(Debug)]
+struct Entry {
+    fetched_at: Instant,
+    value: Arc<SeasonalAnime>,
+}
+
+/// Stale-while-revalidate cache for the [Get seasonal anime](https://myanimelist.net/apiconfig/references/api/v2#operation/anime_season_year_season_get) endpoint
+///
+/// Seasonal pages rarely change, so once a season has been fetched it is always
+/// returned from the cache. When a cached season is older than `stale_after`, the
+/// cached result is still returned immediately and a refresh is started in the
+/// background. Only the first request for a season waits on MAL, and concurrent
+/// first requests for the same season share a single fetch.
+///
+/// Background refreshes are spawned onto the current Tokio runtime.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api_client = AnimeApiClient::from(&client_id);
+/// let cache = SeasonalCache::new(api_client, Duration::from_secs(3600));
+///
+/// let query = GetSeasonalAnime::builder(2023, Season::Fall).build();
+/// let response = cache.get_seasonal_anime_cached(&query).await.unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeasonalCache<C> {
+    client: C,
+    stale_after: Duration,
+    entries: Arc<Mutex<HashMap<SeasonalKey, Entry>>>,
+    loading: Arc<Mutex<InFlight>>,
+    refreshes: Arc<Mutex<HashMap<SeasonalKey, JoinHandle<()>>>>,
+}
+
+impl<C> SeasonalCache<C>
+where
+    C: AnimeApi + Clone + Send + Sync + 'static,
+{
+    /// Create a new [SeasonalCache] around the given AnimeApiClient, refreshing
+    /// cached seasons in the background once they are older than `stale_after`
+    pub fn new(client: C, stale_after: Duration) -> Self {
+        Self {
+            client,
+            stale_after,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            loading: Arc::new(Mutex::new(HashMap::new())),
+            refreshes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the seasonal anime for the given query, returning the cached result if it exists
+    pub async fn get_seasonal_anime_cached(
+        &self,
+        query: &GetSeasonalAnime,
+    ) -> Result<Arc<SeasonalAnime>, AnimeApiError> {
+        let client = self.client.clone();
+        let query = query.clone();
+        self.get_with(Self::key(&query), move || async move {
+            client.get_seasonal_anime(&query).await
+        })
+        .await
+    }
+
+    /// Remove the cached result for the given query
+    pub fn invalidate(&self, query: &GetSeasonalAnime) {
+        self.entries.lock().unwrap().remove(&Self::key(query));
+    }
+
+    /// Remove all of the cached results
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Get a reference to the underlying AnimeApiClient
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    fn key(query: &GetSeasonalAnime) -> SeasonalKey {
+        (
+            query.year,
+            query.season.to_string(),
+            query.to_query_string().unwrap(),
+        )
+    }
+}
+
+impl<C> SeasonalCache<C> {
+    async fn get_with<F, Fut>(
+        &self,
+        key: SeasonalKey,
+        fetch: F,
+    ) -> Result<Arc<SeasonalAnime>, AnimeApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SeasonalAnime, AnimeApiError>> + Send + 'static,
+    {
+        let stale = {
+            let entries = self.entries.lock().unwrap();
+            match entries.get(&key) {
+                Some(entry) if entry.fetched_at.elapsed() < self.stale_after => {
+                    return Ok(entry.value.clone());
+                }
+                Some(entry) => Some(entry.value.clone()),
+                None => None,
+            }
+        };
+
+        match stale {
+            Some(value) => {
+                self.refresh(key, fetch);
+                Ok(value)
+            }
+            None => self.load(key, fetch).await,
+        }
+    }
+
+    // Starts a background refresh, unless one is already running for the key.
+    // Failed refreshes keep serving the stale result and retry on the next call
+    fn refresh<F, Fut>(&self, key: SeasonalKey, fetch: F)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SeasonalAnime, AnimeApiError>> + Send + 'static,
+    {
+        let mut refreshes = self.refreshes.lock().unwrap();
+        if refreshes
+            .get(&key)
+            .is_some_and(|refresh| !refresh.is_finished())
+        {
+            return;
+        }
+
+        let entries = self.entries.clone();
+        let refresh = fetch();
+        let task_key = key.clone();
+        let handle = tokio::spawn(async move {
+            if let Ok(value) = refresh.await {
+                entries.lock().unwrap().insert(task_key, Entry::new(value));
+            }
+        });
+        refreshes.insert(key, handle);
+    }
+
+    // Fetches a season that isn't cached yet. Concurrent callers wait for the
+    // same fetch, and a failed fetch isn't cached so the next caller retries
+    async fn load<F, Fut>(
+        &self,
+        key: SeasonalKey,
+        fetch: F,
+    ) -> Result<Arc<SeasonalAnime>, AnimeApiError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SeasonalAnime, AnimeApiError>>,
+    {
+        let cell = self
+            .loading
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = cell
+            .get_or_try_init(|| async {
+                let entry = Entry::new(fetch().await?);
+                let value = entry.value.clone();
+                self.entries.lock().unwrap().insert(key.clone(), entry);
+                Ok(value)
+            })
+            .await
+            .cloned();
+
+        let mut loading = self.loading.lock().unwrap();
+        if loading
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            loading.remove(&key);
+        }
+
+        result
+    }
+}
+
+impl Entry {
+    fn new(value: SeasonalAnime) -> Self {
+        Self {
+            fetched_at: Instant::now(),
+            value: Arc::new(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::sync::Notify;
+
+    fn seasonal(id: u32) -> SeasonalAnime {
+        serde_json::from_value(serde_json::json!({
+            "data": [{ "node": { "id": id, "title": format!("Anime {}", id) } }],
+            "paging": {}
+        }))
+        .unwrap()
+    }
+
+    fn first_id(value: &SeasonalAnime) -> u32 {
+        value.data[0].node.id
+    }
+
+    fn key() -> SeasonalKey {
+        (2023, "fall".to_string(), String::new())
+    }
+
+    fn cache(stale_after: Duration) -> SeasonalCache<()> {
+        SeasonalCache {
+            client: (),
+            stale_after,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            loading: Arc::new(Mutex::new(HashMap::new())),
+            refreshes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Wait for the background refresh of the key to finish
+    async fn refreshed(cache: &SeasonalCache<()>) {
+        let refresh = cache.refreshes.lock().unwrap().remove(&key()).unwrap();
+        refresh.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let mut cache = cache(Duration::from_secs(3600));
+
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(1)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+
+        // Fresh results don't fetch
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(2)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+
+        // Stale results are returned while refreshing in the background
+        cache.stale_after = Duration::ZERO;
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(3)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+
+        refreshed(&cache).await;
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(4)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_stale_result() {
+        let cache = cache(Duration::ZERO);
+
+        cache
+            .get_with(key(), || async { Ok(seasonal(1)) })
+            .await
+            .unwrap();
+        let value = cache
+            .get_with(key(), || async {
+                Err(AnimeApiError::new("failed".to_string()))
+            })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+
+        refreshed(&cache).await;
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(2)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+
+        // The failed refresh doesn't stop the next one
+        refreshed(&cache).await;
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(3)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cold_loads_are_coalesced() {
+        let cache = cache(Duration::from_secs(3600));
+        let calls = Arc::new(AtomicU32::new(0));
+        let release = Arc::new(Notify::new());
+        let fetch = || {
+            let calls = calls.clone();
+            let release = release.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                release.notified().await;
+                Ok(seasonal(1))
+            }
+        };
+
+        // The fetch waits to be released, so the second load finds it in flight
+        let (a, b, _) = tokio::join!(
+            cache.get_with(key(), fetch),
+            cache.get_with(key(), fetch),
+            async { release.notify_one() }
+        );
+        assert_eq!(first_id(&a.unwrap()), 1);
+        assert_eq!(first_id(&b.unwrap()), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(cache.loading.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_cold_load_is_not_cached() {
+        let cache = cache(Duration::from_secs(3600));
+
+        let result = cache
+            .get_with(key(), || async {
+                Err(AnimeApiError::new("failed".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+
+        let value = cache
+            .get_with(key(), || async { Ok(seasonal(1)) })
+            .await
+            .unwrap();
+        assert_eq!(first_id(&value), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! Find sequels of completed anime that are missing from a user's list
+//!
+//! [sequels_not_in_list] fetches the `related_anime` of every completed entry of
+//! a list and returns the sequels that aren't on the list in any status, grouped
+//! by franchise. Entries are in the same franchise when they are related to each
+//! other, e.g. the first and second season of a show.
+//!
+//! One details request is sent per completed entry. Fetch the list with
+//! [get_full_user_anime_list](super::api::AnimeApi::get_full_user_anime_list)
+//! so that sequels in other statuses, such as plan to watch, aren't reported.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetUserAnimeList::builder("@me").limit(1000).build().unwrap();
+//! let list = api_client.get_full_user_anime_list(&query).await.unwrap();
+//!
+//! for franchise in sequels::sequels_not_in_list(&api_client, &list).await.unwrap() {
+//!     println!("{}:", franchise.title);
+//!     for sequel in &franchise.sequels {
+//!         println!("  {}", sequel.anime.title);
+//!     }
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::codes::ErrorCode;
+
+use super::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeDetail, AnimeDetailFields, GetAnimeDetails, UserAnimeListStatus},
+    responses::{AnimeDetails, AnimeFields, AnimeListNode},
+};
+use crate::common::RelationType;
+
+/// A sequel that isn't on the user's list
+#[derive(Debug)]
+pub struct MissingSequel {
+    /// The sequel, with the `id`, `title`, and `main_picture` fields
+    pub anime: AnimeFields,
+
+    /// Id of the completed anime it continues
+    pub sequel_of: u32,
+}
+
+/// The missing sequels of one franchise
+#[derive(Debug)]
+pub struct FranchiseSequels {
+    /// Title of the first completed entry of the franchise in the list
+    pub title: String,
+
+    /// Ids of the completed entries of the franchise, in list order
+    pub completed: Vec<u32>,
+
+    pub sequels: Vec<MissingSequel>,
+}
+
+/// Find the sequels of the completed entries of `list` that aren't on the list
+///
+/// Franchises are returned in the order of their first completed entry in the list
+pub async fn sequels_not_in_list<C>(
+    client: &C,
+    list: &[AnimeListNode],
+) -> Result<Vec<FranchiseSequels>, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    let fields = AnimeDetailFields::new(vec![
+        AnimeDetail::id,
+        AnimeDetail::title,
+        AnimeDetail::related_anime,
+    ]);
+    let mut details = vec![];
+    for entry in list.iter().filter(|e| is_completed(e)) {
+        let query = GetAnimeDetails::new(entry.node.id, Some(&fields))?;
+        match client.get_anime_details(&query).await {
+            Ok(result) => details.push((entry.node.id, result)),
+            // Entries MAL removed since they were added to the list
+            Err(err) if err.code() == ErrorCode::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(group_sequels(list, details))
+}
+
+fn is_completed(entry: &AnimeListNode) -> bool {
+    entry
+        .list_status
+        .as_ref()
+        .is_some_and(|s| s.status == Some(UserAnimeListStatus::Completed))
+}
+
+// Groups the missing sequels from the details of the completed entries by franchise
+fn group_sequels(
+    list: &[AnimeListNode],
+    details: Vec<(u32, AnimeDetails)>,
+) -> Vec<FranchiseSequels> {
+    let in_list: HashSet<u32> = list.iter().map(|e| e.node.id).collect();
+    let mut franchises = Franchises::default();
+    let mut missing: Vec<MissingSequel> = vec![];
+
+    for (id, details) in details {
+        for related in details.related_anime.into_iter().flatten() {
+            if related.relation_type == RelationType::Character {
+                continue;
+            }
+            franchises.join(id, related.node.id);
+            let is_missing = related.relation_type == RelationType::Sequel
+                && !in_list.contains(&related.node.id)
+                && !missing.iter().any(|m| m.anime.id == related.node.id);
+            if is_missing {
+                missing.push(MissingSequel {
+                    anime: related.node,
+                    sequel_of: id,
+                });
+            }
+        }
+    }
+
+    let mut groups: Vec<FranchiseSequels> = vec![];
+    let mut group_of_root: HashMap<u32, usize> = HashMap::new();
+    for entry in list.iter().filter(|e| is_completed(e)) {
+        let root = franchises.root(entry.node.id);
+        match group_of_root.get(&root) {
+            Some(&index) => groups[index].completed.push(entry.node.id),
+            None => {
+                group_of_root.insert(root, groups.len());
+                groups.push(FranchiseSequels {
+                    title: entry.node.display_title().to_string(),
+                    completed: vec![entry.node.id],
+                    sequels: vec![],
+                });
+            }
+        }
+    }
+    for sequel in missing {
+        let root = franchises.root(sequel.sequel_of);
+        if let Some(&index) = group_of_root.get(&root) {
+            groups[index].sequels.push(sequel);
+        }
+    }
+
+    groups.retain(|g| !g.sequels.is_empty());
+    groups
+}
+
+// Union-find over anime ids, joining related anime into franchises
+#[derive(Default)]
+struct Franchises {
+    parent: HashMap<u32, u32>,
+}
+
+impl Franchises {
+    fn root(&mut self, id: u32) -> u32 {
+        let parent = *self.parent.get(&id).unwrap_or(&id);
+        if parent == id {
+            return id;
+        }
+        let root = self.root(parent);
+        self.parent.insert(id, root);
+        root
+    }
+
+    fn join(&mut self, a: u32, b: u32) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a != b {
+            self.parent.insert(a, b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{anime_details, anime_entry, related};
+
+    fn entry(id: u32, status: &str) -> AnimeListNode {
+        anime_entry(id).status(status).build()
+    }
+
+    fn details(id: u32, related_anime: &[(u32, &str)]) -> (u32, AnimeDetails) {
+        let related_anime: Vec<_> = related_anime
+            .iter()
+            .map(|(id, relation_type)| related(*id, relation_type))
+            .collect();
+        let details = anime_details(id, serde_json::json!({ "related_anime": related_anime }));
+        (id, details)
+    }
+
+    #[test]
+    fn test_group_sequels() {
+        let list = vec![
+            entry(1, "completed"),
+            entry(2, "completed"),
+            entry(3, "plan_to_watch"),
+            entry(10, "completed"),
+        ];
+        let details = vec![
+            details(1, &[(2, "sequel"), (5, "side_story")]),
+            details(2, &[(1, "prequel"), (4, "sequel"), (6, "character")]),
+            details(10, &[(3, "sequel"), (11, "alternative_version")]),
+        ];
+
+        let groups = group_sequels(&list, details);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].title, "Anime 1");
+        assert_eq!(groups[0].completed, vec![1, 2]);
+        let sequels: Vec<_> = groups[0]
+            .sequels
+            .iter()
+            .map(|s| (s.anime.id, s.sequel_of))
+            .collect();
+        assert_eq!(sequels, vec![(4, 2)]);
+    }
+}
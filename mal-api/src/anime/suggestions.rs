@@ -0,0 +1,206 @@
+//! Filter the anime MAL suggests against the user's own list
+//!
+//! MAL's suggestions often include sequels and spin-offs of shows the user
+//! dropped. [SuggestionFilter] removes every suggestion that shares a franchise
+//! with a title in the excluded statuses, found by following the `related_anime`
+//! of the suggestions.
+//!
+//! Each suggestion costs up to one details request per related title within
+//! the filter's depth. Relations are fetched once per call, so suggestions from
+//! the same franchise share requests.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::codes::ErrorCode;
+
+use super::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeDetail, AnimeDetailFields, GetAnimeDetails, UserAnimeListStatus},
+    responses::AnimeDetails,
+};
+use crate::common::RelationType;
+
+/// Options for [get_filtered_suggested_anime](super::api::AnimeApiClient::get_filtered_suggested_anime)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionFilter {
+    excluded_statuses: Vec<UserAnimeListStatus>,
+    pub(crate) depth: u8,
+}
+
+impl Default for SuggestionFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuggestionFilter {
+    /// Exclude suggestions from the franchises of dropped anime, following
+    /// relations up to 3 titles away
+    pub fn new() -> Self {
+        Self {
+            excluded_statuses: vec![UserAnimeListStatus::Dropped],
+            depth: 3,
+        }
+    }
+
+    /// Also exclude suggestions from the franchises of anime put on hold
+    pub fn exclude_on_hold(mut self) -> Self {
+        if !self
+            .excluded_statuses
+            .contains(&UserAnimeListStatus::OnHold)
+        {
+            self.excluded_statuses.push(UserAnimeListStatus::OnHold);
+        }
+        self
+    }
+
+    /// How many relations to follow from each suggestion
+    ///
+    /// A depth of 0 only excludes suggestions that are on the user's list
+    /// themselves. Higher depths catch more distant entries of long franchises,
+    /// at the cost of more requests
+    pub fn depth(mut self, value: u8) -> Self {
+        self.depth = value;
+        self
+    }
+
+    /// Statuses whose franchises are excluded
+    pub fn excluded_statuses(&self) -> &[UserAnimeListStatus] {
+        &self.excluded_statuses
+    }
+}
+
+// Character relations link unrelated shows that share a cast member
+fn is_franchise_relation(relation_type: RelationType) -> bool {
+    relation_type != RelationType::Character
+}
+
+fn franchise_relations(details: &AnimeDetails, follow: fn(RelationType) -> bool) -> Vec<u32> {
+    details
+        .related_anime
+        .iter()
+        .flatten()
+        .filter(|r| follow(r.relation_type))
+        .map(|r| r.node.id)
+        .collect()
+}
+
+/// Follows `related_anime` from an anime, caching the relations it fetches
+pub(crate) struct FranchiseWalker<'a, C> {
+    client: &'a C,
+    depth: u8,
+    follow: fn(RelationType) -> bool,
+    relations: HashMap<u32, Vec<u32>>,
+}
+
+impl<'a, C> FranchiseWalker<'a, C>
+where
+    C: AnimeApi + Sync,
+{
+    /// Create a walker that follows every relation but [RelationType::Character]
+    pub(crate) fn new(client: &'a C, depth: u8) -> Self {
+        Self {
+            client,
+            depth,
+            follow: is_franchise_relation,
+            relations: HashMap::new(),
+        }
+    }
+
+    /// Only follow the relations the function returns true for
+    pub(crate) fn following(mut self, follow: fn(RelationType) -> bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Returns true if any of the `targets` is within the walker's depth of `anime_id`
+    pub(crate) async fn reaches(
+        &mut self,
+        anime_id: u32,
+        targets: &HashSet<u32>,
+    ) -> Result<bool, AnimeApiError> {
+        Ok(self.find(anime_id, targets).await?.is_some())
+    }
+
+    /// Find the closest of the `targets` within the walker's depth of `anime_id`
+    pub(crate) async fn find(
+        &mut self,
+        anime_id: u32,
+        targets: &HashSet<u32>,
+    ) -> Result<Option<u32>, AnimeApiError> {
+        let mut visited = HashSet::from([anime_id]);
+        let mut queue = VecDeque::from([(anime_id, 0)]);
+        while let Some((id, distance)) = queue.pop_front() {
+            if targets.contains(&id) {
+                return Ok(Some(id));
+            }
+            if distance == self.depth {
+                continue;
+            }
+            for related in self.relations(id).await? {
+                if visited.insert(related) {
+                    queue.push_back((related, distance + 1));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn relations(&mut self, anime_id: u32) -> Result<Vec<u32>, AnimeApiError> {
+        if let Some(relations) = self.relations.get(&anime_id) {
+            return Ok(relations.clone());
+        }
+        let fields = AnimeDetailFields::new(vec![AnimeDetail::id, AnimeDetail::related_anime]);
+        let query = GetAnimeDetails::new(anime_id, Some(&fields))?;
+        let relations = match self.client.get_anime_details(&query).await {
+            Ok(details) => franchise_relations(&details, self.follow),
+            // Relations can point at entries that were removed since
+            Err(err) if err.code() == ErrorCode::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+        self.relations.insert(anime_id, relations.clone());
+        Ok(relations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_franchise_relations() {
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "Anime 1",
+            "related_anime": [
+                {
+                    "node": { "id": 2, "title": "Anime 2" },
+                    "relation_type": "sequel",
+                    "relation_type_formatted": "Sequel"
+                },
+                {
+                    "node": { "id": 3, "title": "Anime 3" },
+                    "relation_type": "character",
+                    "relation_type_formatted": "Character"
+                },
+                {
+                    "node": { "id": 4, "title": "Anime 4" },
+                    "relation_type": "side_story",
+                    "relation_type_formatted": "Side story"
+                }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            franchise_relations(&details, is_franchise_relation),
+            vec![2, 4]
+        );
+
+        let filter = SuggestionFilter::new().exclude_on_hold().exclude_on_hold();
+        assert_eq!(
+            filter.excluded_statuses(),
+            &[UserAnimeListStatus::Dropped, UserAnimeListStatus::OnHold]
+        );
+    }
+}
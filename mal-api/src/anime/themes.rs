@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// An opening or ending theme, parsed from MAL's theme text
+///
+/// MAL formats themes like `#1: "Yuusha (勇者)" by YOASOBI (eps 1-16)`. The index,
+/// artist, and episodes are left out for some anime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeSong {
+    pub index: Option<u32>,
+    pub title: String,
+    pub artist: Option<String>,
+
+    /// Episodes the theme is used in, as written by MAL, e.g. `1-12, 14`
+    pub episodes: Option<String>,
+}
+
+impl ThemeSong {
+    /// Parse MAL's theme text
+    ///
+    /// Returns `None` if the text has no quoted title
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut rest = text.trim();
+
+        let mut index = None;
+        let digits = rest.strip_prefix('#').unwrap_or(rest);
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        if let Ok(number) = digits[..end].parse() {
+            index = Some(number);
+            rest = digits[end..].trim_start_matches(':').trim_start();
+        }
+
+        let rest = rest.strip_prefix('"')?;
+        let (title, rest) = match rest.find("\" by ") {
+            Some(end) => (&rest[..end], &rest[end + 5..]),
+            None => {
+                let end = rest.rfind('"')?;
+                (&rest[..end], &rest[end + 1..])
+            }
+        };
+
+        let mut artist = rest.trim();
+        let mut episodes = None;
+        if let Some(start) = artist.rfind("(ep") {
+            if artist.ends_with(')') {
+                let inner = &artist[start + 1..artist.len() - 1];
+                let inner = inner
+                    .trim_start_matches("eps")
+                    .trim_start_matches("ep")
+                    .trim();
+                episodes = Some(inner.to_string());
+                artist = artist[..start].trim();
+            }
+        }
+
+        Some(Self {
+            index,
+            title: title.trim().to_string(),
+            artist: (!artist.is_empty()).then(|| artist.to_string()),
+            episodes,
+        })
+    }
+}
+
+impl Display for ThemeSong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"", self.title)?;
+        if let Some(artist) = &self.artist {
+            write!(f, " by {}", artist)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme_song() {
+        assert_eq!(
+            ThemeSong::parse("#1: \"Yuusha (勇者)\" by YOASOBI (eps 1-16)"),
+            Some(ThemeSong {
+                index: Some(1),
+                title: "Yuusha (勇者)".to_string(),
+                artist: Some("YOASOBI".to_string()),
+                episodes: Some("1-16".to_string()),
+            })
+        );
+        assert_eq!(
+            ThemeSong::parse("\"Anytime Anywhere\" by milet"),
+            Some(ThemeSong {
+                index: None,
+                title: "Anytime Anywhere".to_string(),
+                artist: Some("milet".to_string()),
+                episodes: None,
+            })
+        );
+        assert_eq!(
+            ThemeSong::parse("2: \"Song\" by Artist (ep 28)")
+                .unwrap()
+                .episodes,
+            Some("28".to_string())
+        );
+        assert_eq!(ThemeSong::parse("No theme song"), None);
+    }
+}
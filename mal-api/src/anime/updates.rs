@@ -0,0 +1,473 @@
+//! Detect episode count and airing status changes between two snapshots of anime
+//!
+//! Useful for "new episode announced" style alerts: store the [AnimeFields] you
+//! fetched previously, fetch them again later, and compare the two.
+//!
+//! Both snapshots need to be fetched with the `num_episodes` and `status` fields.
+//! Changes are only reported when the field is present in both snapshots.
+//!
+//! A [RankWatch] does the same for the `rank` and `popularity` of a set of anime,
+//! reporting when they enter or leave the top N, or move by many places.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let watch = RankWatch::new([52991, 5114]).top(10).moved(Some(20));
+//! let previous = watch.fetch(&api_client).await.unwrap();
+//!
+//! // Later, e.g. once a day
+//! let current = watch.fetch(&api_client).await.unwrap();
+//! for update in watch.detect(&previous, &current) {
+//!     println!("{}: {:?}", update.anime.title, update.events);
+//! }
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeDetail, AnimeDetailFields, GetAnimeDetails},
+    responses::{AnimeFields, AnimeStatus},
+};
+
+/// A single change to an anime
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimeChange {
+    /// The number of episodes changed. MAL reports `0` when the count is unknown
+    EpisodeCount { previous: u32, current: u32 },
+
+    /// The airing status changed, e.g. from [AnimeStatus::NotYetAired] to [AnimeStatus::CurrentlyAiring]
+    Status {
+        previous: AnimeStatus,
+        current: AnimeStatus,
+    },
+}
+
+/// The changes detected for an anime
+#[derive(Debug)]
+pub struct AnimeUpdate<'a> {
+    /// The freshly fetched anime
+    pub anime: &'a AnimeFields,
+    pub changes: Vec<AnimeChange>,
+}
+
+impl<'a> AnimeUpdate<'a> {
+    /// Number of episodes added since the previous snapshot, if the count went up
+    pub fn new_episodes(&self) -> Option<u32> {
+        self.changes.iter().find_map(|change| match change {
+            AnimeChange::EpisodeCount { previous, current } if current > previous => {
+                Some(current - previous)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns true if the anime started airing since the previous snapshot
+    pub fn started_airing(&self) -> bool {
+        self.changes.iter().any(|change| {
+            matches!(
+                change,
+                AnimeChange::Status {
+                    previous: AnimeStatus::NotYetAired,
+                    current: AnimeStatus::CurrentlyAiring | AnimeStatus::FinishedAiring,
+                }
+            )
+        })
+    }
+
+    /// Returns true if the anime finished airing since the previous snapshot
+    pub fn finished_airing(&self) -> bool {
+        self.changes.iter().any(|change| {
+            matches!(
+                change,
+                AnimeChange::Status {
+                    current: AnimeStatus::FinishedAiring,
+                    ..
+                }
+            )
+        })
+    }
+}
+
+/// Compare two snapshots of the same anime
+pub fn compare(previous: &AnimeFields, current: &AnimeFields) -> Vec<AnimeChange> {
+    let mut changes = vec![];
+
+    if let (Some(previous), Some(current)) = (previous.num_episodes, current.num_episodes) {
+        if previous != current {
+            changes.push(AnimeChange::EpisodeCount { previous, current });
+        }
+    }
+
+    if let (Some(previous), Some(current)) = (previous.status, current.status) {
+        if previous != current {
+            changes.push(AnimeChange::Status { previous, current });
+        }
+    }
+
+    changes
+}
+
+/// Detect the changes between previously stored and freshly fetched anime
+///
+/// Anime are matched by id. Anime that are only in one of the snapshots are ignored.
+pub fn detect_updates<'a>(
+    previous: &[AnimeFields],
+    current: &'a [AnimeFields],
+) -> Vec<AnimeUpdate<'a>> {
+    let previous: HashMap<u32, &AnimeFields> =
+        previous.iter().map(|anime| (anime.id, anime)).collect();
+
+    current
+        .iter()
+        .filter_map(|anime| {
+            let changes = compare(previous.get(&anime.id)?, anime);
+            if changes.is_empty() {
+                return None;
+            }
+            Some(AnimeUpdate { anime, changes })
+        })
+        .collect()
+}
+
+/// A ranking of anime tracked by a [RankWatch]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RankMetric {
+    /// The `rank` field, i.e. the ranking by score
+    Rank,
+
+    /// The `popularity` field, i.e. the ranking by number of members
+    Popularity,
+}
+
+impl RankMetric {
+    fn value(&self, anime: &AnimeFields) -> Option<u32> {
+        match self {
+            RankMetric::Rank => anime.rank,
+            RankMetric::Popularity => anime.popularity,
+        }
+    }
+}
+
+/// A change of the rank of an anime that crossed a threshold of a [RankWatch]
+///
+/// Ranks start at 1, so dropping means the rank went up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankEvent {
+    /// The rank is now within the top `top`
+    EnteredTop {
+        metric: RankMetric,
+        top: u32,
+        previous: u32,
+        current: u32,
+    },
+
+    /// The rank is no longer within the top `top`
+    LeftTop {
+        metric: RankMetric,
+        top: u32,
+        previous: u32,
+        current: u32,
+    },
+
+    /// The anime climbed by at least the number of places of [RankWatch::moved]
+    Rose {
+        metric: RankMetric,
+        previous: u32,
+        current: u32,
+    },
+
+    /// The anime fell by at least the number of places of [RankWatch::moved]
+    Dropped {
+        metric: RankMetric,
+        previous: u32,
+        current: u32,
+    },
+}
+
+/// The rank events of an anime
+#[derive(Debug)]
+pub struct RankUpdate<'a> {
+    /// The freshly fetched anime
+    pub anime: &'a AnimeFields,
+    pub events: Vec<RankEvent>,
+}
+
+/// Watch the rank and popularity of a set of anime for large changes
+///
+/// By default, reports anime entering or leaving the top 100 and moving by 50
+/// places or more, for both [metrics](RankMetric).
+#[derive(Debug, Clone)]
+pub struct RankWatch {
+    anime_ids: BTreeSet<u32>,
+    metrics: Vec<RankMetric>,
+    tops: Vec<u32>,
+    moved: Option<u32>,
+}
+
+impl RankWatch {
+    pub fn new(anime_ids: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            anime_ids: anime_ids.into_iter().collect(),
+            metrics: vec![RankMetric::Rank, RankMetric::Popularity],
+            tops: vec![],
+            moved: Some(50),
+        }
+    }
+
+    /// Add an anime to watch
+    pub fn watch(&mut self, anime_id: u32) {
+        self.anime_ids.insert(anime_id);
+    }
+
+    /// Stop watching an anime
+    pub fn unwatch(&mut self, anime_id: u32) {
+        self.anime_ids.remove(&anime_id);
+    }
+
+    /// Get the ids of the watched anime, in ascending order
+    pub fn anime_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.anime_ids.iter().copied()
+    }
+
+    /// Only track these metrics
+    pub fn metrics(mut self, metrics: &[RankMetric]) -> Self {
+        self.metrics = metrics.to_vec();
+        self
+    }
+
+    /// Report anime entering or leaving the top `n`, instead of the top 100. Can
+    /// be set several times, e.g. for the top 10 and the top 100
+    pub fn top(mut self, n: u32) -> Self {
+        if !self.tops.contains(&n) {
+            self.tops.push(n);
+        }
+        self
+    }
+
+    /// Report anime moving by at least this many places, or `None` to not report moves
+    pub fn moved(mut self, places: Option<u32>) -> Self {
+        self.moved = places.filter(|p| *p > 0);
+        self
+    }
+
+    fn tops(&self) -> &[u32] {
+        if self.tops.is_empty() {
+            &[100]
+        } else {
+            &self.tops
+        }
+    }
+
+    /// The fields requested by [fetch](RankWatch::fetch)
+    pub fn fields() -> AnimeDetailFields {
+        AnimeDetailFields::new(vec![
+            AnimeDetail::id,
+            AnimeDetail::title,
+            AnimeDetail::rank,
+            AnimeDetail::popularity,
+        ])
+    }
+
+    /// Fetch the current ranks of the watched anime
+    ///
+    /// Sends one details request per anime, one after the other
+    pub async fn fetch<A>(&self, api: &A) -> Result<Vec<AnimeFields>, AnimeApiError>
+    where
+        A: AnimeApi + Sync,
+    {
+        let fields = Self::fields();
+        let mut anime = Vec::with_capacity(self.anime_ids.len());
+        for anime_id in &self.anime_ids {
+            let query = GetAnimeDetails::new(*anime_id, Some(&fields))?;
+            anime.push(api.get_anime_details(&query).await?.shared_fields);
+        }
+        Ok(anime)
+    }
+
+    /// Compare the ranks of a watched anime in two snapshots
+    ///
+    /// Metrics missing from either snapshot are skipped, e.g. for unranked anime
+    pub fn compare(&self, previous: &AnimeFields, current: &AnimeFields) -> Vec<RankEvent> {
+        let mut events = vec![];
+        for metric in &self.metrics {
+            let (Some(previous), Some(current)) = (metric.value(previous), metric.value(current))
+            else {
+                continue;
+            };
+            for top in self.tops() {
+                if previous > *top && current <= *top {
+                    events.push(RankEvent::EnteredTop {
+                        metric: *metric,
+                        top: *top,
+                        previous,
+                        current,
+                    });
+                } else if previous <= *top && current > *top {
+                    events.push(RankEvent::LeftTop {
+                        metric: *metric,
+                        top: *top,
+                        previous,
+                        current,
+                    });
+                }
+            }
+            match self.moved {
+                Some(places) if previous >= current + places => events.push(RankEvent::Rose {
+                    metric: *metric,
+                    previous,
+                    current,
+                }),
+                Some(places) if current >= previous + places => events.push(RankEvent::Dropped {
+                    metric: *metric,
+                    previous,
+                    current,
+                }),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Detect the rank events of the watched anime between two snapshots
+    ///
+    /// Anime are matched by id. Anime that aren't watched or are only in one of
+    /// the snapshots are ignored.
+    pub fn detect<'a>(
+        &self,
+        previous: &[AnimeFields],
+        current: &'a [AnimeFields],
+    ) -> Vec<RankUpdate<'a>> {
+        let previous: HashMap<u32, &AnimeFields> =
+            previous.iter().map(|anime| (anime.id, anime)).collect();
+
+        current
+            .iter()
+            .filter(|anime| self.anime_ids.contains(&anime.id))
+            .filter_map(|anime| {
+                let events = self.compare(previous.get(&anime.id)?, anime);
+                if events.is_empty() {
+                    return None;
+                }
+                Some(RankUpdate { anime, events })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_fields;
+
+    fn anime(id: u32, num_episodes: u32, status: &str) -> AnimeFields {
+        anime_fields(
+            id,
+            serde_json::json!({ "num_episodes": num_episodes, "status": status }),
+        )
+    }
+
+    #[test]
+    fn test_detect_updates() {
+        let previous = vec![
+            anime(1, 0, "not_yet_aired"),
+            anime(2, 12, "currently_airing"),
+            anime(3, 24, "currently_airing"),
+        ];
+        let current = vec![
+            anime(1, 12, "currently_airing"),
+            anime(2, 12, "currently_airing"),
+            anime(3, 24, "finished_airing"),
+            anime(4, 1, "currently_airing"),
+        ];
+
+        let updates = detect_updates(&previous, &current);
+        assert_eq!(updates.len(), 2);
+
+        assert_eq!(updates[0].anime.id, 1);
+        assert_eq!(updates[0].new_episodes(), Some(12));
+        assert!(updates[0].started_airing());
+        assert!(!updates[0].finished_airing());
+
+        assert_eq!(updates[1].anime.id, 3);
+        assert_eq!(updates[1].new_episodes(), None);
+        assert!(updates[1].finished_airing());
+        assert_eq!(
+            updates[1].changes,
+            vec![AnimeChange::Status {
+                previous: AnimeStatus::CurrentlyAiring,
+                current: AnimeStatus::FinishedAiring,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compare_missing_fields() {
+        let previous = anime_fields(1, serde_json::json!({}));
+        assert!(compare(&previous, &anime(1, 12, "currently_airing")).is_empty());
+    }
+
+    fn ranked(id: u32, rank: u32, popularity: u32) -> AnimeFields {
+        anime_fields(
+            id,
+            serde_json::json!({ "rank": rank, "popularity": popularity }),
+        )
+    }
+
+    #[test]
+    fn test_rank_watch() {
+        let watch = RankWatch::new([1, 2, 3]).top(10).top(100);
+        let previous = vec![
+            ranked(1, 120, 500),
+            ranked(2, 8, 40),
+            ranked(3, 300, 300),
+            ranked(4, 500, 500),
+        ];
+        let current = vec![
+            ranked(1, 90, 480),
+            ranked(2, 12, 41),
+            ranked(3, 300, 300),
+            ranked(4, 1, 1),
+        ];
+
+        let updates = watch.detect(&previous, &current);
+        assert_eq!(updates.len(), 2);
+
+        assert_eq!(updates[0].anime.id, 1);
+        assert_eq!(
+            updates[0].events,
+            vec![RankEvent::EnteredTop {
+                metric: RankMetric::Rank,
+                top: 100,
+                previous: 120,
+                current: 90,
+            }]
+        );
+        assert_eq!(
+            updates[1].events,
+            vec![RankEvent::LeftTop {
+                metric: RankMetric::Rank,
+                top: 10,
+                previous: 8,
+                current: 12,
+            }]
+        );
+
+        let popularity = RankWatch::new([1])
+            .metrics(&[RankMetric::Popularity])
+            .moved(Some(20));
+        assert_eq!(
+            popularity.compare(&ranked(1, 50, 400), &ranked(1, 50, 450)),
+            vec![RankEvent::Dropped {
+                metric: RankMetric::Popularity,
+                previous: 400,
+                current: 450,
+            }]
+        );
+        assert!(popularity
+            .moved(None)
+            .compare(&ranked(1, 50, 400), &ranked(1, 50, 450))
+            .is_empty());
+    }
+}
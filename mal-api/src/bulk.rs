@@ -0,0 +1,154 @@
+//! Module for sending many list updates in one call
+//!
+//! MAL has no batch endpoint, so bulk updates are sent one at a time, paced by a
+//! [RateLimiter]. Importers can choose whether a failed update aborts the rest
+//! of the batch or is collected alongside the successful ones.
+
+use std::time::Duration;
+
+use crate::rate_limit::RateLimiter;
+
+/// What to do when one of the updates fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BulkErrorPolicy {
+    /// Stop at the first failed update. The remaining updates are not sent
+    AbortOnError,
+
+    /// Keep sending the remaining updates
+    #[default]
+    ContinueOnError,
+}
+
+/// Options for a bulk update
+#[derive(Debug, Clone, Default)]
+pub struct BulkOptions {
+    pub policy: BulkErrorPolicy,
+
+    /// Paces the requests, to stay within MAL's rate limits
+    pub limiter: RateLimiter,
+}
+
+impl BulkOptions {
+    /// Create options with the given error policy and the default rate limiter
+    pub fn new(policy: BulkErrorPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    /// Pace the requests with a new limiter of the given interval
+    pub fn interval(mut self, value: Duration) -> Self {
+        self.limiter = RateLimiter::new(value);
+        self
+    }
+
+    /// Pace the requests with a limiter shared with other requests
+    pub fn limiter(mut self, value: RateLimiter) -> Self {
+        self.limiter = value;
+        self
+    }
+}
+
+/// Result of a single update in a bulk update
+#[derive(Debug)]
+pub struct BulkItem<T, E> {
+    /// Id of the anime or manga that was updated
+    pub id: u32,
+    pub result: Result<T, E>,
+}
+
+/// Results of a bulk update, in the order the updates were given
+#[derive(Debug)]
+pub struct BulkResult<T, E> {
+    /// Results of the updates that were sent
+    pub items: Vec<BulkItem<T, E>>,
+
+    /// Ids of the updates that were not sent because an earlier update failed
+    /// with [BulkErrorPolicy::AbortOnError]
+    pub skipped: Vec<u32>,
+}
+
+impl<T, E> BulkResult<T, E> {
+    /// Returns true if every update was sent and succeeded
+    pub fn is_success(&self) -> bool {
+        self.skipped.is_empty() && self.items.iter().all(|item| item.result.is_ok())
+    }
+
+    /// Iterate over the failed updates
+    pub fn failures(&self) -> impl Iterator<Item = (u32, &E)> {
+        self.items
+            .iter()
+            .filter_map(|item| item.result.as_ref().err().map(|err| (item.id, err)))
+    }
+}
+
+#[cfg(lists)]
+pub(crate) async fn run_bulk<Q, T, E, I, F, Fut>(
+    queries: Vec<Q>,
+    options: &BulkOptions,
+    id: I,
+    mut send: F,
+) -> BulkResult<T, E>
+where
+    I: Fn(&Q) -> u32,
+    F: FnMut(Q) -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    let mut result = BulkResult {
+        items: Vec::with_capacity(queries.len()),
+        skipped: vec![],
+    };
+    let mut queries = queries.into_iter();
+
+    for query in queries.by_ref() {
+        options.limiter.wait().await;
+        let item = BulkItem {
+            id: id(&query),
+            result: send(query).await,
+        };
+        let failed = item.result.is_err();
+        result.items.push(item);
+
+        if failed && options.policy == BulkErrorPolicy::AbortOnError {
+            break;
+        }
+    }
+
+    result.skipped = queries.map(|query| id(&query)).collect();
+    result
+}
+
+#[cfg(all(test, lists))]
+mod tests {
+    use super::*;
+
+    async fn send(id: u32) -> Result<u32, String> {
+        if id % 2 == 0 {
+            Err(format!("failed {}", id))
+        } else {
+            Ok(id)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_bulk() {
+        let options = BulkOptions::new(BulkErrorPolicy::ContinueOnError).interval(Duration::ZERO);
+        let result = run_bulk(vec![1, 2, 3, 4, 5], &options, |id| *id, send).await;
+        assert_eq!(result.items.len(), 5);
+        assert!(result.skipped.is_empty());
+        assert!(!result.is_success());
+        assert_eq!(
+            result.failures().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+
+        let options = BulkOptions::new(BulkErrorPolicy::AbortOnError).interval(Duration::ZERO);
+        let result = run_bulk(vec![1, 2, 3, 4, 5], &options, |id| *id, send).await;
+        assert_eq!(result.items.len(), 2);
+        assert_eq!(result.skipped, vec![3, 4, 5]);
+
+        let result = run_bulk(vec![1, 3], &options, |id| *id, send).await;
+        assert!(result.is_success());
+    }
+}
@@ -0,0 +1,238 @@
+//! Module containing in-memory caching utilities shared by the API clients
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::OnceCell;
+
+/// A thread-safe key/value cache whose entries expire after a fixed time-to-live
+///
+/// Expired entries are dropped when they're read, and all at once whenever an
+/// insert would have to grow the cache, so entries that are never read again
+/// don't pile up.
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash,
+{
+    /// Create a new, empty [TtlCache] whose entries live for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the time-to-live of the cache entries
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Store `value` for `key`, replacing any previous value and resetting its expiration
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+        // Only purge before growing, so inserts stay cheap on average
+        if entries.len() == entries.capacity() && !entries.contains_key(&key) {
+            let ttl = self.ttl;
+            entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < ttl);
+        }
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Remove the value stored for `key`, returning it if it had not expired
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(key)
+            .filter(|(inserted_at, _)| inserted_at.elapsed() < self.ttl)
+            .map(|(_, value)| value)
+    }
+
+    /// Remove all of the entries from the cache
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Remove all of the expired entries from the cache
+    pub fn purge_expired(&self) {
+        let ttl = self.ttl;
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, (inserted_at, _)| inserted_at.elapsed() < ttl);
+    }
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    /// Get a clone of the value stored for `key`, if it exists and has not expired
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((inserted_at, value)) if inserted_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// Memoizing loader that coalesces concurrent loads of the same key
+///
+/// While a key is being loaded, any other callers asking for the same key
+/// wait for the in-flight load instead of starting their own. Successful
+/// results are cached for the loader's time-to-live. Failed loads are not
+/// cached, so the next caller will retry.
+#[derive(Debug)]
+pub struct Loader<K, V> {
+    cache: TtlCache<K, Arc<V>>,
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<Arc<V>>>>>,
+}
+
+impl<K, V> Loader<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Create a new [Loader] whose results are cached for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: TtlCache::new(ttl),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return the cached value for `key`, or load it with `fetch`
+    ///
+    /// `fetch` is only awaited if no valid cached value exists and no other
+    /// load of `key` is in flight.
+    pub async fn load_with<F, Fut, E>(&self, key: K, fetch: F) -> Result<Arc<V>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            return Ok(value);
+        }
+
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = cell
+            .get_or_try_init(|| async { fetch().await.map(Arc::new) })
+            .await
+            .cloned();
+
+        if let Ok(value) = &result {
+            self.cache.insert(key.clone(), value.clone());
+        }
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+
+    /// Remove the cached value for `key`, forcing the next load to fetch it again
+    pub fn invalidate(&self, key: &K) {
+        self.cache.remove(key);
+    }
+
+    /// Remove all of the cached values
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_ttl_cache() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), Some("one"));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.remove(&1), Some("one"));
+        assert_eq!(cache.get(&1), None);
+
+        let cache = TtlCache::new(Duration::ZERO);
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn test_ttl_cache_purges_on_insert() {
+        let cache = TtlCache::new(Duration::ZERO);
+        for key in 0..1000 {
+            cache.insert(key, key);
+        }
+        // Only the entries inserted since the last purge are left
+        let entries = cache.entries.lock().unwrap();
+        assert!(entries.len() < 1000);
+        assert!(entries.capacity() < 1000);
+    }
+
+    #[tokio::test]
+    async fn test_loader_coalesces_requests() {
+        let loader: Loader<u32, u32> = Loader::new(Duration::from_secs(60));
+        let calls = AtomicU32::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok::<u32, ()>(42)
+        };
+
+        let (a, b) = tokio::join!(loader.load_with(1, fetch), loader.load_with(1, fetch));
+        assert_eq!(*a.unwrap(), 42);
+        assert_eq!(*b.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let c = loader.load_with(1, fetch).await;
+        assert_eq!(*c.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        loader.invalidate(&1);
+        let _ = loader.load_with(1, fetch).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_loader_does_not_cache_errors() {
+        let loader: Loader<u32, u32> = Loader::new(Duration::from_secs(60));
+        let result = loader
+            .load_with(1, || async { Err::<u32, &str>("failed") })
+            .await;
+        assert!(result.is_err());
+
+        let result = loader.load_with(1, || async { Ok::<u32, &str>(7) }).await;
+        assert_eq!(*result.unwrap(), 7);
+    }
+}
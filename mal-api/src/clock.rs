@@ -0,0 +1,113 @@
+//! Sources of the current time
+//!
+//! Everything in this crate that records or compares times, such as the
+//! [OauthClient](crate::oauth::OauthClient) checking whether its token expired,
+//! reads them from a [Clock]. The [SystemClock] is used by default. Pass a
+//! [ManualClock] to move time by hand in tests, or your own [Clock] where
+//! `SystemTime` isn't available, such as on WASM.
+//!
+//! Reading the time can fail, e.g. if the system clock is set before the Unix
+//! Epoch. The [ClockError] is returned instead of a made up time, so nothing
+//! is stored as issued or expiring at `0`.
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use crate::codes::ErrorCode;
+
+/// The current time couldn't be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("unable to fetch system time")]
+pub struct ClockError;
+
+impl ClockError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::NoSystemTime
+    }
+}
+
+/// A source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Number of seconds since the Unix Epoch
+    fn now(&self) -> Result<u64, ClockError>;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Result<u64, ClockError> {
+        (**self).now()
+    }
+}
+
+/// The [Clock] of the host, through [SystemTime]
+///
+/// Fails if the system clock is set before the Unix Epoch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Result<u64, ClockError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .map_err(|_| ClockError)
+    }
+}
+
+/// A [Clock] that only moves when told to
+///
+/// Share it through an [Arc] to move the time of the clients using it
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    /// Create a clock stopped at `now` seconds since the Unix Epoch
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: AtomicU64::new(now),
+        }
+    }
+
+    /// Set the time to `now` seconds since the Unix Epoch
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Move the time forward
+    pub fn advance(&self, duration: Duration) {
+        self.now.fetch_add(duration.as_secs(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Result<u64, ClockError> {
+        Ok(self.now.load(Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let shared: Arc<dyn Clock> = clock.clone();
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(shared.now(), Ok(1_060));
+        clock.set(5);
+        assert_eq!(shared.now(), Ok(5));
+        assert!(SystemClock.now().unwrap() > 1_600_000_000);
+        assert_eq!(ClockError.code(), ErrorCode::NoSystemTime);
+    }
+}
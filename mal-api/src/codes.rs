@@ -0,0 +1,237 @@
+//! Module for the stable codes of the errors returned by this crate
+//!
+//! Every error type has a `code()` method returning an [ErrorCode]. Codes
+//! don't change between versions, unlike the English error messages, so
+//! applications can map them to their own, e.g. translated, messages. The
+//! string form, e.g. `MAL_E_RATE_LIMIT`, can be used as a translation key.
+//! New codes can be added in minor versions, so matches need a fallback arm.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! match api_client.get_anime_list(&query).await {
+//!     Ok(list) => show(list),
+//!     Err(err) => match err.code() {
+//!         ErrorCode::RateLimit => show_message(t!("try-again-later")),
+//!         code => show_message(t!(code.as_str())),
+//!     },
+//! }
+//! ```
+
+use std::fmt;
+
+#[cfg(api)]
+use crate::common::MalError;
+
+/// A stable, machine-readable error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The request couldn't be sent, or the response couldn't be read
+    Request,
+
+    /// The response couldn't be parsed
+    Parse,
+
+    /// There's no next or previous page
+    NoPage,
+
+    /// MAL rejected the request as invalid (`400`)
+    BadRequest,
+
+    /// The access token is missing, invalid, or expired (`401`)
+    Unauthorized,
+
+    /// The request isn't allowed with these credentials (`403`)
+    Forbidden,
+
+    /// The entry doesn't exist (`404`)
+    NotFound,
+
+    /// Too many requests were sent (`429`)
+    RateLimit,
+
+    /// MAL failed to handle the request (`5xx`)
+    Server,
+
+    /// MAL responded with any other status
+    UnexpectedResponse,
+
+    /// MAL doesn't have the endpoint, see `UnsupportedEndpoint`
+    UnsupportedEndpoint,
+
+    // Validation errors, see `ValidationError`
+    EmptyQuery,
+    EmptyUserName,
+    InvalidId,
+    LimitOutOfRange,
+    MissingUpdateField,
+    MissingSearchFilter,
+    InvalidScore,
+
+    /// A value is out of range, e.g. a priority of `3`
+    InvalidValue,
+
+    /// A field name isn't valid
+    InvalidField,
+
+    /// A field is only available on the details endpoints
+    DetailOnlyField,
+
+    // OAuth errors, see `OauthError`
+    OauthMissingEnvVar,
+    OauthMissingClientId,
+    OauthMissingClientSecret,
+    OauthMissingRedirectUrl,
+    OauthStateMismatch,
+    OauthBadTokenResponse,
+    OauthInvalidRedirectUrl,
+    OauthInvalidRedirectResponse,
+    OauthMissingAccessToken,
+    OauthMissingRefreshToken,
+    OauthMissingTokenExpiration,
+    OauthMissingConfig,
+    OauthInvalidConfigFormat,
+    OauthConfigCreationFailure,
+    OauthNoSystemTime,
+    OauthInvalidExpirationTime,
+    OauthRefreshFailed,
+    OauthMissingCodeOrState,
+    OauthLoopbackFailure,
+    OauthBrowserFailure,
+    OauthInvalidEndpointUrl,
+    OauthPendingAuthStoreFailure,
+
+    /// An [AuthProvider](crate::oauth::provider::AuthProvider) couldn't get the credentials
+    AuthProvider,
+
+    /// A local store failed to read or write
+    Store,
+
+    /// A stored value couldn't be serialized or deserialized
+    StoreData,
+
+    /// The store was created by a newer version of this crate
+    StoreSchema,
+
+    /// A template couldn't be parsed or rendered
+    Template,
+
+    /// The current time couldn't be read, e.g. the system clock is set before
+    /// the Unix Epoch
+    NoSystemTime,
+
+    /// Any other error
+    Other,
+}
+
+impl ErrorCode {
+    /// The code as a string, e.g. `MAL_E_RATE_LIMIT`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Request => "MAL_E_REQUEST",
+            ErrorCode::Parse => "MAL_E_PARSE",
+            ErrorCode::NoPage => "MAL_E_NO_PAGE",
+            ErrorCode::BadRequest => "MAL_E_BAD_REQUEST",
+            ErrorCode::Unauthorized => "MAL_E_UNAUTHORIZED",
+            ErrorCode::Forbidden => "MAL_E_FORBIDDEN",
+            ErrorCode::NotFound => "MAL_E_NOT_FOUND",
+            ErrorCode::RateLimit => "MAL_E_RATE_LIMIT",
+            ErrorCode::Server => "MAL_E_SERVER",
+            ErrorCode::UnexpectedResponse => "MAL_E_UNEXPECTED_RESPONSE",
+            ErrorCode::UnsupportedEndpoint => "MAL_E_UNSUPPORTED_ENDPOINT",
+            ErrorCode::EmptyQuery => "MAL_E_EMPTY_QUERY",
+            ErrorCode::EmptyUserName => "MAL_E_EMPTY_USER_NAME",
+            ErrorCode::InvalidId => "MAL_E_INVALID_ID",
+            ErrorCode::LimitOutOfRange => "MAL_E_LIMIT_OUT_OF_RANGE",
+            ErrorCode::MissingUpdateField => "MAL_E_MISSING_UPDATE_FIELD",
+            ErrorCode::MissingSearchFilter => "MAL_E_MISSING_SEARCH_FILTER",
+            ErrorCode::InvalidScore => "MAL_E_INVALID_SCORE",
+            ErrorCode::InvalidValue => "MAL_E_INVALID_VALUE",
+            ErrorCode::InvalidField => "MAL_E_INVALID_FIELD",
+            ErrorCode::DetailOnlyField => "MAL_E_DETAIL_ONLY_FIELD",
+            ErrorCode::OauthMissingEnvVar => "MAL_E_OAUTH_MISSING_ENV_VAR",
+            ErrorCode::OauthMissingClientId => "MAL_E_OAUTH_MISSING_CLIENT_ID",
+            ErrorCode::OauthMissingClientSecret => "MAL_E_OAUTH_MISSING_CLIENT_SECRET",
+            ErrorCode::OauthMissingRedirectUrl => "MAL_E_OAUTH_MISSING_REDIRECT_URL",
+            ErrorCode::OauthStateMismatch => "MAL_E_OAUTH_STATE_MISMATCH",
+            ErrorCode::OauthBadTokenResponse => "MAL_E_OAUTH_BAD_TOKEN_RESPONSE",
+            ErrorCode::OauthInvalidRedirectUrl => "MAL_E_OAUTH_INVALID_REDIRECT_URL",
+            ErrorCode::OauthInvalidRedirectResponse => "MAL_E_OAUTH_INVALID_REDIRECT_RESPONSE",
+            ErrorCode::OauthMissingAccessToken => "MAL_E_OAUTH_MISSING_ACCESS_TOKEN",
+            ErrorCode::OauthMissingRefreshToken => "MAL_E_OAUTH_MISSING_REFRESH_TOKEN",
+            ErrorCode::OauthMissingTokenExpiration => "MAL_E_OAUTH_MISSING_TOKEN_EXPIRATION",
+            ErrorCode::OauthMissingConfig => "MAL_E_OAUTH_MISSING_CONFIG",
+            ErrorCode::OauthInvalidConfigFormat => "MAL_E_OAUTH_INVALID_CONFIG_FORMAT",
+            ErrorCode::OauthConfigCreationFailure => "MAL_E_OAUTH_CONFIG_CREATION_FAILURE",
+            ErrorCode::OauthNoSystemTime => "MAL_E_OAUTH_NO_SYSTEM_TIME",
+            ErrorCode::OauthInvalidExpirationTime => "MAL_E_OAUTH_INVALID_EXPIRATION_TIME",
+            ErrorCode::OauthRefreshFailed => "MAL_E_OAUTH_REFRESH_FAILED",
+            ErrorCode::OauthMissingCodeOrState => "MAL_E_OAUTH_MISSING_CODE_OR_STATE",
+            ErrorCode::OauthLoopbackFailure => "MAL_E_OAUTH_LOOPBACK_FAILURE",
+            ErrorCode::OauthBrowserFailure => "MAL_E_OAUTH_BROWSER_FAILURE",
+            ErrorCode::OauthInvalidEndpointUrl => "MAL_E_OAUTH_INVALID_ENDPOINT_URL",
+            ErrorCode::OauthPendingAuthStoreFailure => "MAL_E_OAUTH_PENDING_AUTH_STORE_FAILURE",
+            ErrorCode::AuthProvider => "MAL_E_AUTH_PROVIDER",
+            ErrorCode::Store => "MAL_E_STORE",
+            ErrorCode::StoreData => "MAL_E_STORE_DATA",
+            ErrorCode::StoreSchema => "MAL_E_STORE_SCHEMA",
+            ErrorCode::Template => "MAL_E_TEMPLATE",
+            ErrorCode::NoSystemTime => "MAL_E_NO_SYSTEM_TIME",
+            ErrorCode::Other => "MAL_E_OTHER",
+        }
+    }
+
+    // The code of a non-OK response
+    #[cfg(api)]
+    pub(crate) fn from_response(status: reqwest::StatusCode, mal_error: Option<&MalError>) -> Self {
+        match status.as_u16() {
+            400 => ErrorCode::BadRequest,
+            401 => ErrorCode::Unauthorized,
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            429 => ErrorCode::RateLimit,
+            500..=599 => ErrorCode::Server,
+            _ => match mal_error.map(|e| e.error.as_str()) {
+                Some("invalid_token") => ErrorCode::Unauthorized,
+                Some("not_found") => ErrorCode::NotFound,
+                _ => ErrorCode::UnexpectedResponse,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{FieldError, ValidationError};
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(ErrorCode::RateLimit.to_string(), "MAL_E_RATE_LIMIT");
+        assert_eq!(
+            ValidationError::InvalidScore(11).code().as_str(),
+            "MAL_E_INVALID_SCORE"
+        );
+        assert_eq!(
+            FieldError::DetailOnly("statistics".to_string()).code(),
+            ErrorCode::DetailOnlyField
+        );
+
+        #[cfg(feature = "anime")]
+        {
+            let err = crate::anime::error::AnimeApiError::from(ValidationError::EmptyQuery);
+            assert_eq!(err.code(), ErrorCode::EmptyQuery);
+            assert_eq!(
+                crate::anime::error::AnimeApiError::new("timeout".to_string()).code(),
+                ErrorCode::Other
+            );
+        }
+    }
+}
@@ -1,12 +1,17 @@
 //! Module containing common request/response fields, traits, and functions
 
 use std::{
-    collections::HashMap,
     error::Error,
-    fmt::{self, Display},
+    fmt::{self, Debug, Display},
+    ops::Deref,
+    str::FromStr,
+    sync::{Arc, OnceLock},
 };
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::codes::ErrorCode;
 
 #[derive(Debug)]
 pub struct CommonError {
@@ -25,46 +30,439 @@ impl CommonError {
     pub fn new(message: String) -> Self {
         Self { message }
     }
+
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidValue
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+// Implements `to_json` and the other serializers for the given response types
+macro_rules! impl_to_json {
+    ($($response:ty),* $(,)?) => {
+        $(
+            impl $response {
+                /// Format the response as JSON, in the same structure MAL sends
+                pub fn to_json(&self) -> String {
+                    serde_json::to_string(self).unwrap_or_default()
+                }
+
+                /// Format the response as indented JSON
+                pub fn to_pretty_json(&self) -> String {
+                    serde_json::to_string_pretty(self).unwrap_or_default()
+                }
+
+                /// Format the response as TOML
+                ///
+                /// Fails for responses TOML can't represent, such as lists of lists
+                #[cfg(feature = "toml")]
+                pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+                    toml::to_string(self)
+                }
+
+                /// Format the response as YAML
+                #[cfg(feature = "yaml")]
+                pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+                    serde_yaml::to_string(self)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(api)]
+pub(crate) use impl_to_json;
+
+/// A field that can be requested through the `fields` query parameter
+pub trait QueryField: Sized {
+    /// Parse the field from its MAL name, e.g. `num_episodes`
+    fn parse(name: &str) -> Result<Self, FieldError>;
+}
+
+/// Error from parsing or converting a set of fields
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FieldError {
+    /// The field is only returned by the details endpoints, e.g. `statistics`
+    #[error("`{0}` is only available on the details endpoints")]
+    DetailOnly(String),
+
+    #[error("`{0}` is not a valid field")]
+    Unknown(String),
+}
+
+impl FieldError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            FieldError::DetailOnly(_) => ErrorCode::DetailOnlyField,
+            FieldError::Unknown(_) => ErrorCode::InvalidField,
+        }
+    }
+}
+
+/// Why a query was rejected before being sent
+///
+/// Returned by the `validation()` accessor of the API errors, so applications can
+/// show their own, e.g. localized, messages instead of the English ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    /// The search query `q` is empty
+    #[error("Query cannot be empty")]
+    EmptyQuery,
+
+    #[error("user_name cannot be empty")]
+    EmptyUserName,
+
+    /// An id is `0`, e.g. `anime_id`
+    #[error("{0} must be greater than 0")]
+    InvalidId(&'static str),
+
+    /// A `limit` is outside of what the endpoint allows, see [limits](crate::limits)
+    #[error("Limit must be between {min} and {max} inclusive, got {got}")]
+    LimitOutOfRange { min: u16, max: u16, got: u16 },
+
+    /// A list update doesn't set any field
+    #[error("At least one of the optional arguments must be Some")]
+    MissingUpdateField,
+
+    /// A forum topic search doesn't set any filter besides `limit` and `offset`
+    #[error("At least one of the optional arguments must be Some, excluding limit and offset")]
+    MissingSearchFilter,
+
+    #[error("Score must be between 0 and 10 inclusive, got {0}")]
+    InvalidScore(u8),
+}
+
+impl ValidationError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ValidationError::EmptyQuery => ErrorCode::EmptyQuery,
+            ValidationError::EmptyUserName => ErrorCode::EmptyUserName,
+            ValidationError::InvalidId(_) => ErrorCode::InvalidId,
+            ValidationError::LimitOutOfRange { .. } => ErrorCode::LimitOutOfRange,
+            ValidationError::MissingUpdateField => ErrorCode::MissingUpdateField,
+            ValidationError::MissingSearchFilter => ErrorCode::MissingSearchFilter,
+            ValidationError::InvalidScore(_) => ErrorCode::InvalidScore,
+        }
+    }
+}
+
+/// An endpoint MAL doesn't have, which the API clients still offer so that the
+/// anime and manga clients have the same methods
+///
+/// Converted into an API error whose code is [ErrorCode::UnsupportedEndpoint]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("MAL does not have the `{endpoint}` endpoint")]
+pub struct UnsupportedEndpoint {
+    /// Name of the endpoint, e.g. `Get suggested manga`
+    pub endpoint: &'static str,
+}
+
+impl UnsupportedEndpoint {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::UnsupportedEndpoint
+    }
+}
+
+/// Error body MAL sends when it rejects a request
+///
+/// e.g. `{"error": "invalid_parameter", "message": "invalid q"}`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MalError {
+    pub error: String,
+
+    /// Often empty
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Display for MalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "{}: {}", self.error, self.message)
+        }
+    }
+}
+
+// A non-OK response, converted into the API errors
+#[cfg(api)]
+#[derive(Debug)]
+pub(crate) struct ResponseError {
+    pub(crate) message: String,
+    pub(crate) code: ErrorCode,
+    pub(crate) mal_error: Option<MalError>,
+}
+
+// Defines the error type of an API client. Every client's error has the same
+// fields and conversions, and is classified only by its `code()`
+#[cfg(api)]
+macro_rules! api_error {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name {
+            message: String,
+            code: $crate::codes::ErrorCode,
+            mal_error: Option<$crate::common::MalError>,
+            validation: Option<$crate::common::ValidationError>,
+        }
+
+        impl std::error::Error for $name {}
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.message)
+            }
+        }
+
+        impl From<$crate::common::ValidationError> for $name {
+            fn from(value: $crate::common::ValidationError) -> Self {
+                Self {
+                    validation: Some(value),
+                    ..Self::with_code(value.code(), value.to_string())
+                }
+            }
+        }
+
+        impl From<$crate::common::ResponseError> for $name {
+            fn from(value: $crate::common::ResponseError) -> Self {
+                Self {
+                    mal_error: value.mal_error,
+                    ..Self::with_code(value.code, value.message)
+                }
+            }
+        }
+
+        impl From<$crate::oauth::provider::AuthProviderError> for $name {
+            fn from(value: $crate::oauth::provider::AuthProviderError) -> Self {
+                Self::with_code(
+                    value.code(),
+                    format!("Failed to get credentials: {}", value.message),
+                )
+            }
+        }
+
+        impl $name {
+            pub fn new(message: String) -> Self {
+                Self::with_code($crate::codes::ErrorCode::Other, message)
+            }
+
+            pub(crate) fn with_code(code: $crate::codes::ErrorCode, message: String) -> Self {
+                Self {
+                    message,
+                    code,
+                    mal_error: None,
+                    validation: None,
+                }
+            }
+
+            // Prefixes the message, keeping the rest of the error
+            #[allow(dead_code)]
+            pub(crate) fn context(self, context: &str) -> Self {
+                Self {
+                    message: format!("{}: {}", context, self.message),
+                    ..self
+                }
+            }
+
+            /// The error message, in English
+            pub fn message(&self) -> &str {
+                &self.message
+            }
+
+            /// The stable code of the error, see [codes](crate::codes)
+            pub fn code(&self) -> $crate::codes::ErrorCode {
+                self.code
+            }
+
+            /// Error body MAL sent with the response, if it rejected the request
+            pub fn mal_error(&self) -> Option<&$crate::common::MalError> {
+                self.mal_error.as_ref()
+            }
+
+            /// Why the query was rejected before being sent, if it was
+            pub fn validation(&self) -> Option<&$crate::common::ValidationError> {
+                self.validation.as_ref()
+            }
+        }
+    };
+}
+
+#[cfg(api)]
+pub(crate) use api_error;
+
+// Deserializes the body of an OK response, so every endpoint reports parse
+// failures the same way
+#[cfg(api)]
+pub(crate) fn parse_response<T: serde::de::DeserializeOwned>(
+    what: &str,
+    body: &[u8],
+) -> Result<T, ResponseError> {
+    serde_json::from_slice(body).map_err(|err| ResponseError {
+        message: format!("Failed to parse {} result: {}", what, err),
+        code: ErrorCode::Parse,
+        mal_error: None,
+    })
+}
+
+// Builds the error for a non-OK response, along with MAL's error body
+#[cfg(api)]
+pub(crate) async fn unexpected_response(
+    context: &str,
+    response: reqwest::Response,
+) -> ResponseError {
+    let status = response.status();
+    let body = response.bytes().await.unwrap_or_default();
+    error_message(context, status, &body)
+}
+
+#[cfg(api)]
+fn error_message(context: &str, status: reqwest::StatusCode, body: &[u8]) -> ResponseError {
+    let mal_error = serde_json::from_slice::<MalError>(body).ok();
+    let message = match &mal_error {
+        Some(mal_error) => format!("{}: {} ({})", context, status, mal_error),
+        None => format!("{}: {}", context, status),
+    };
+    ResponseError {
+        message,
+        code: ErrorCode::from_response(status, mal_error.as_ref()),
+        mal_error,
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct Paging {
     pub previous: Option<String>,
     pub next: Option<String>,
+
+    /// Total number of entries, only sent by some endpoints
+    #[serde(default, alias = "count", skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
 }
 
-impl Display for Paging {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl_to_json!(Paging);
+
+impl Paging {
+    /// The offset of the page, read from the URLs of the next and previous pages
+    ///
+    /// Returns `None` if neither URL is present, i.e. for a single page, or if
+    /// the offset is too large for a `u32`
+    pub fn offset(&self) -> Option<u32> {
+        if let Some((offset, limit)) = self.next.as_deref().and_then(page_window) {
+            return Some(offset.saturating_sub(limit));
+        }
+        let (offset, limit) = self.previous.as_deref().and_then(page_window)?;
+        offset.checked_add(limit)
+    }
+
+    /// The `limit` of the page, read from the URLs of the next and previous pages
+    pub fn limit(&self) -> Option<u32> {
+        self.next
+            .as_deref()
+            .or(self.previous.as_deref())
+            .and_then(page_window)
+            .map(|(_, limit)| limit)
+    }
+
+    /// The total number of entries, if it's known
+    ///
+    /// Uses the total MAL sent if there is one. Otherwise, the total is only
+    /// known on the last page, from its offset and the number of entries on it,
+    /// `page_len`. Returns `None` if the total is too large for a `u32`
+    pub fn estimated_total(&self, page_len: usize) -> Option<u32> {
+        if self.total.is_some() {
+            return self.total;
+        }
+        if self.next.is_some() {
+            return None;
+        }
+        let offset = if self.previous.is_some() {
+            self.offset()?
+        } else {
+            0
+        };
+        offset.checked_add(u32::try_from(page_len).ok()?)
+    }
+
+    /// The number of the page, starting at 1
+    pub fn page_number(&self) -> Option<u32> {
+        if self.next.is_none() && self.previous.is_none() {
+            return Some(1);
+        }
+        match (self.offset()?, self.limit()?) {
+            (_, 0) => None,
+            (offset, limit) => (offset / limit).checked_add(1),
+        }
+    }
+
+    /// The number of pages, if the total number of entries is known
+    ///
+    /// e.g. to show "page 3 of 12" with [page_number](Paging::page_number)
+    pub fn page_count(&self, page_len: usize) -> Option<u32> {
+        let total = self.estimated_total(page_len)?;
+        match self.limit() {
+            Some(limit) if limit > 0 => Some(total.div_ceil(limit).max(1)),
+            _ => Some(1),
+        }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+// The offset and limit query parameters of a page URL
+fn page_window(url: &str) -> Option<(u32, u32)> {
+    let (_, query) = url.split_once('?')?;
+    let params: Vec<(String, String)> = serde_urlencoded::from_str(query).ok()?;
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.parse::<u32>().ok())
+    };
+    Some((param("offset").unwrap_or(0), param("limit")?))
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MainPicture {
     pub medium: String,
     pub large: String,
 }
 
-impl Display for MainPicture {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(MainPicture);
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct AlternativeTitles {
     pub synonyms: Option<Vec<String>>,
     pub en: Option<String>,
     pub ja: Option<String>,
 }
 
-impl Display for AlternativeTitles {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl AlternativeTitles {
+    /// All of the alternative titles, skipping empty ones
+    pub fn titles(&self) -> impl Iterator<Item = &str> {
+        self.en
+            .iter()
+            .chain(self.ja.iter())
+            .chain(self.synonyms.iter().flatten())
+            .map(String::as_str)
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Check if any of the alternative titles matches the given title
+    ///
+    /// Titles are compared after [normalizing](crate::titles::normalize_title) them,
+    /// so case, punctuation, and spacing are ignored
+    pub fn has_title(&self, title: &str) -> bool {
+        self.titles().any(|t| crate::titles::same_title(t, title))
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+impl_to_json!(AlternativeTitles);
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub enum NSFW {
     #[serde(rename = "white")]
     SFW,
@@ -74,17 +472,13 @@ pub enum NSFW {
     NSFW,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Genre {
     pub id: u32,
     pub name: String,
 }
 
-impl Display for Genre {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Genre);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Ranking {
@@ -92,14 +486,15 @@ pub struct Ranking {
     pub previous_rank: Option<u32>,
 }
 
-impl Display for Ranking {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Ranking);
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+/// How two related anime or manga are related
+///
+/// MAL adds relation types from time to time, so new variants can be added in
+/// minor versions
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum RelationType {
     Sequel,
     Prequel,
@@ -110,9 +505,374 @@ pub enum RelationType {
     Summary,
     FullStory,
     Character, // this type is not documented in the MAL API reference...
+
+    /// Between an anime and its source manga, in `related_manga` and `related_anime`
+    Adaptation,
+    SpinOff,
+    Other,
+}
+
+/// Priority of an entry in a user's anime or manga list
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Priority {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl From<Priority> for u8 {
+    fn from(value: Priority) -> Self {
+        match value {
+            Priority::Low => 0,
+            Priority::Medium => 1,
+            Priority::High => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Priority {
+    type Error = CommonError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Priority::Low),
+            1 => Ok(Priority::Medium),
+            2 => Ok(Priority::High),
+            _ => Err(CommonError::new(
+                "Priority must be between 0 and 2 inclusive".to_string(),
+            )),
+        }
+    }
+}
+
+/// Rewatch value of an anime, or reread value of a manga, in a user's list
+///
+/// `NotSet` corresponds to `0`, which MAL uses for entries without a value
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum RewatchValue {
+    #[default]
+    NotSet,
+    VeryLow,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+}
+
+impl From<RewatchValue> for u8 {
+    fn from(value: RewatchValue) -> Self {
+        match value {
+            RewatchValue::NotSet => 0,
+            RewatchValue::VeryLow => 1,
+            RewatchValue::Low => 2,
+            RewatchValue::Medium => 3,
+            RewatchValue::High => 4,
+            RewatchValue::VeryHigh => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for RewatchValue {
+    type Error = CommonError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RewatchValue::NotSet),
+            1 => Ok(RewatchValue::VeryLow),
+            2 => Ok(RewatchValue::Low),
+            3 => Ok(RewatchValue::Medium),
+            4 => Ok(RewatchValue::High),
+            5 => Ok(RewatchValue::VeryHigh),
+            _ => Err(CommonError::new(
+                "Rewatch value must be between 0 and 5 inclusive".to_string(),
+            )),
+        }
+    }
+}
+
+impl RewatchValue {
+    /// The value as a share of [VeryHigh](RewatchValue::VeryHigh), or `None` if
+    /// it's not set
+    pub fn fraction(&self) -> Option<f32> {
+        match self {
+            RewatchValue::NotSet => None,
+            value => Some(f32::from(u8::from(*value)) / 5.0),
+        }
+    }
+}
+
+/// Score of an anime or manga in a user's list
+///
+/// MAL uses `0` for entries that weren't scored, and `1` to `10` otherwise
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(u8);
+
+impl Score {
+    /// Returns true if the entry was scored
+    pub fn is_set(&self) -> bool {
+        self.0 != 0
+    }
+
+    /// The score as a share of `10`, or `None` if it's not set
+    pub fn fraction(&self) -> Option<f32> {
+        self.is_set().then(|| f32::from(self.0) / 10.0)
+    }
+}
+
+impl From<Score> for u8 {
+    fn from(value: Score) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u8> for Score {
+    type Error = ValidationError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value > 10 {
+            return Err(ValidationError::InvalidScore(value));
+        }
+        Ok(Score(value))
+    }
+}
+
+/// Outcome of deleting an entry from a user's anime or manga list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteOutcome {
+    /// The entry was removed from the list
+    Deleted,
+
+    /// The entry was not in the list to begin with
+    NotInList,
+}
+
+/// Outcome of looking up an anime or manga by an id that may be stale
+///
+/// MAL merges duplicate entries from time to time, and deletes others. Ids kept
+/// from older exports or imports can point to either.
+#[derive(Debug)]
+pub enum EntryLookup<T> {
+    /// The entry exists under the requested id
+    Found(T),
+
+    /// MAL redirected the request to an entry with a different id, e.g. because the
+    /// requested entry was merged into it
+    Moved { new_id: u32, entry: T },
+
+    /// MAL doesn't know the requested id, e.g. because the entry was deleted
+    Gone,
+}
+
+impl<T> EntryLookup<T> {
+    /// Get the entry, whether it moved or not
+    pub fn entry(self) -> Option<T> {
+        match self {
+            EntryLookup::Found(entry) | EntryLookup::Moved { entry, .. } => Some(entry),
+            EntryLookup::Gone => None,
+        }
+    }
+}
+
+/// Text that is cut off after a number of characters when formatted
+///
+/// Used by the `summary` methods so that logging a response doesn't write out
+/// long synopses in full. The number of characters left out is appended.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Truncated<'a> {
+    text: &'a str,
+    max_chars: usize,
+}
+
+impl<'a> Truncated<'a> {
+    pub fn new(text: &'a str, max_chars: usize) -> Self {
+        Self { text, max_chars }
+    }
+
+    /// The full text
+    pub fn full_text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns true if the text is longer than the maximum number of characters
+    pub fn is_truncated(&self) -> bool {
+        self.text.chars().nth(self.max_chars).is_some()
+    }
+}
+
+impl Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.text.char_indices().nth(self.max_chars) {
+            Some((end, _)) => {
+                let omitted = self.text[end..].chars().count();
+                write!(f, "{}… ({} more chars)", &self.text[..end], omitted)
+            }
+            None => write!(f, "{}", self.text),
+        }
+    }
+}
+
+impl Debug for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+/// Compact view of an anime or manga, for logging
+///
+/// Long text fields are [truncated](Truncated) and everything else but a few key
+/// stats is left out, so `{:?}` and `{:#?}` stay short
+#[cfg(lists)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntrySummary<'a> {
+    pub id: Option<u32>,
+    pub title: &'a str,
+    pub mean: Option<f32>,
+    pub rank: Option<u32>,
+    pub synopsis: Option<Truncated<'a>>,
+
+    /// Only returned by the details endpoints
+    pub background: Option<Truncated<'a>>,
+}
+
+/// Number of characters of the text fields kept by the `summary` methods
+#[cfg(lists)]
+pub const SUMMARY_TEXT_CHARS: usize = 80;
+
+/// A set of fields to request from MAL
+///
+/// The fields are joined into the `fields` query parameter the first time the set
+/// is used and the result is kept, so one set can be reused for many queries
+/// without formatting it again.
+#[derive(Debug, Clone)]
+pub struct FieldSet<T> {
+    fields: Vec<T>,
+    formatted: OnceLock<String>,
+}
+
+impl<T> FieldSet<T> {
+    pub fn new(fields: Vec<T>) -> Self {
+        Self {
+            fields,
+            formatted: OnceLock::new(),
+        }
+    }
+
+    // Used for the all-fields sets, whose formatted strings are precomputed
+    #[cfg(users)]
+    pub(crate) fn with_formatted(fields: Vec<T>, formatted: &str) -> Self {
+        Self {
+            fields,
+            formatted: OnceLock::from(formatted.to_string()),
+        }
+    }
+
+    /// Get the fields in this set
+    pub fn fields(&self) -> &[T] {
+        &self.fields
+    }
+}
+
+impl<T: Display> FieldSet<T> {
+    /// Get the fields formatted for the `fields` query parameter, e.g. `id,title,mean`
+    pub fn as_str(&self) -> &str {
+        self.formatted.get_or_init(|| {
+            self.fields
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+    }
+}
+
+impl<T: QueryField> FromStr for FieldSet<T> {
+    type Err = FieldError;
+
+    /// Parse a comma separated list of field names, e.g. `id,title,mean`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(T::parse)
+            .collect::<Result<Vec<T>, FieldError>>()
+            .map(Self::new)
+    }
+}
+
+impl<T> From<Vec<T>> for FieldSet<T> {
+    fn from(fields: Vec<T>) -> Self {
+        Self::new(fields)
+    }
+}
+
+impl<T: Display> From<&FieldSet<T>> for String {
+    fn from(value: &FieldSet<T>) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+// Implements the constructors and conversions of a field set newtype, e.g.
+// `pub struct AnimeCommonFields(pub FieldSet<AnimeField>)`
+#[cfg(users)]
+macro_rules! impl_field_set {
+    ($name:ident, $field:ty) => {
+        impl $name {
+            pub fn new(fields: Vec<$field>) -> Self {
+                Self($crate::common::FieldSet::new(fields))
+            }
+
+            pub(crate) fn with_formatted(fields: Vec<$field>, formatted: &str) -> Self {
+                Self($crate::common::FieldSet::with_formatted(fields, formatted))
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = $crate::common::FieldSet<$field>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl From<Vec<$field>> for $name {
+            fn from(fields: Vec<$field>) -> Self {
+                Self::new(fields)
+            }
+        }
+
+        impl From<$crate::common::FieldSet<$field>> for $name {
+            fn from(fields: $crate::common::FieldSet<$field>) -> Self {
+                Self(fields)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::common::FieldError;
+
+            /// Parse a comma separated list of field names, e.g. `id,title,mean`
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl From<&$name> for String {
+            fn from(value: &$name) -> Self {
+                value.as_str().to_string()
+            }
+        }
+    };
 }
 
-pub(crate) fn struct_to_form_data<T>(query: &T) -> Result<HashMap<String, String>, Box<dyn Error>>
+#[cfg(users)]
+pub(crate) use impl_field_set;
+
+#[cfg(lists)]
+pub(crate) fn struct_to_form_data<T>(
+    query: &T,
+) -> Result<std::collections::HashMap<String, String>, Box<dyn Error>>
 where
     T: Serialize,
 {
@@ -128,10 +888,281 @@ where
     Ok(form)
 }
 
+// Implements `to_query_string` for the given request types
+#[cfg(api)]
+macro_rules! impl_to_query_string {
+    ($($query:ty),* $(,)?) => {
+        $(
+            impl $query {
+                /// Get the URL-encoded parameters that will be sent for this request
+                ///
+                /// Update requests send these as their form body. Useful for logging
+                /// requests or reproducing them with other tools, such as curl.
+                pub fn to_query_string(&self) -> Result<String, serde_urlencoded::ser::Error> {
+                    serde_urlencoded::to_string(self)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(api)]
+pub(crate) use impl_to_query_string;
+
+/// A paged response whose next and previous pages can be requested
+///
+/// Implemented by every response with a [Paging], so generic code can walk the
+/// pages of any of them
 pub trait PagingIter {
     type Item;
 
-    fn next_page(&self) -> Option<&String>;
+    /// The paging of the response
+    fn paging(&self) -> &Paging;
+
+    /// The URL of the next page, if there is one
+    fn next_page(&self) -> Option<&str> {
+        self.paging().next.as_deref()
+    }
+
+    /// The URL of the previous page, if there is one
+    fn prev_page(&self) -> Option<&str> {
+        self.paging().previous.as_deref()
+    }
+}
+
+impl PagingIter for Paging {
+    type Item = Self;
+
+    fn paging(&self) -> &Paging {
+        self
+    }
+}
+
+/// An immutable list of entries that can be shared between tasks
+///
+/// Cloning only clones the [Arc]s, so several tasks, e.g. computing stats and
+/// rendering, can read the same fetched list without copying its entries. It
+/// derefs to a slice, so it can be passed to the functions taking `&[T]`.
+///
+/// Created with `into_shared` on the list responses, or from the `Vec` of a
+/// full list.
+#[derive(Debug)]
+pub struct SharedList<T> {
+    entries: Arc<[T]>,
+    paging: Arc<Paging>,
+}
+
+impl<T> SharedList<T> {
+    pub fn new(entries: Vec<T>, paging: Paging) -> Self {
+        Self {
+            entries: entries.into(),
+            paging: Arc::new(paging),
+        }
+    }
+
+    /// The entries, e.g. to keep them without the paging
+    pub fn entries(&self) -> Arc<[T]> {
+        Arc::clone(&self.entries)
+    }
+}
+
+// Not derived, which would require `T: Clone`
+impl<T> Clone for SharedList<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            paging: Arc::clone(&self.paging),
+        }
+    }
+}
+
+impl<T> Deref for SharedList<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.entries
+    }
+}
+
+impl<T> From<Vec<T>> for SharedList<T> {
+    fn from(entries: Vec<T>) -> Self {
+        Self::new(entries, Paging::default())
+    }
+}
+
+impl<T> PagingIter for SharedList<T> {
+    type Item = Self;
+
+    fn paging(&self) -> &Paging {
+        &self.paging
+    }
+}
+
+#[cfg(all(test, api))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_message() {
+        let err = error_message(
+            "Did not recieve OK response",
+            reqwest::StatusCode::BAD_REQUEST,
+            br#"{"message":"invalid q","error":"invalid_parameter"}"#,
+        );
+        assert_eq!(
+            err.message,
+            "Did not recieve OK response: 400 Bad Request (invalid_parameter: invalid q)"
+        );
+        assert_eq!(err.mal_error.unwrap().error, "invalid_parameter");
+        assert_eq!(err.code, ErrorCode::BadRequest);
+
+        let err = error_message(
+            "Did not recieve OK response",
+            reqwest::StatusCode::BAD_GATEWAY,
+            b"<html></html>",
+        );
+        assert_eq!(err.message, "Did not recieve OK response: 502 Bad Gateway");
+        assert!(err.mal_error.is_none());
+        assert_eq!(err.code, ErrorCode::Server);
+
+        let err = error_message(
+            "Did not recieve OK response",
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            b"",
+        );
+        assert_eq!(err.code.as_str(), "MAL_E_RATE_LIMIT");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let value: Vec<u32> = parse_response("raw", b"[1, 2]").unwrap();
+        assert_eq!(value, vec![1, 2]);
+
+        let err = parse_response::<Vec<u32>>("raw", b"{}").unwrap_err();
+        assert!(err.message.starts_with("Failed to parse raw result: "));
+        assert_eq!(err.code, ErrorCode::Parse);
+        assert!(err.mal_error.is_none());
+    }
 
-    fn prev_page(&self) -> Option<&String>;
+    #[test]
+    fn test_score() {
+        assert_eq!(Score::try_from(0).map(|s| s.fraction()), Ok(None));
+        assert_eq!(Score::try_from(8).map(|s| s.fraction()), Ok(Some(0.8)));
+        assert_eq!(Score::try_from(11), Err(ValidationError::InvalidScore(11)));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let text = Truncated::new("Frieren is an elf", 9);
+        assert!(text.is_truncated());
+        assert_eq!(text.to_string(), "Frieren i… (8 more chars)");
+        assert_eq!(format!("{:?}", text), "\"Frieren i… (8 more chars)\"");
+
+        let text = Truncated::new("葬送のフリーレン", 8);
+        assert!(!text.is_truncated());
+        assert_eq!(text.to_string(), "葬送のフリーレン");
+        assert_eq!(
+            Truncated::new("葬送のフリーレン", 2).to_string(),
+            "葬送… (6 more chars)"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let paging = Paging {
+            previous: None,
+            next: Some("next".to_string()),
+            total: None,
+        };
+        assert_eq!(paging.to_json(), r#"{"previous":null,"next":"next"}"#);
+        assert_eq!(
+            paging.to_pretty_json(),
+            "{\n  \"previous\": null,\n  \"next\": \"next\"\n}"
+        );
+        #[cfg(feature = "toml")]
+        assert_eq!(paging.to_toml().unwrap(), "next = \"next\"\n");
+        #[cfg(feature = "yaml")]
+        assert_eq!(paging.to_yaml().unwrap(), "previous: null\nnext: next\n");
+    }
+
+    #[test]
+    fn test_paging_totals() {
+        let paging: Paging = serde_json::from_str(
+            r#"{"previous": "https://api.myanimelist.net/v2/anime/ranking?offset=10&limit=10",
+                "next": "https://api.myanimelist.net/v2/anime/ranking?offset=30&limit=10"}"#,
+        )
+        .unwrap();
+        assert_eq!(paging.offset(), Some(20));
+        assert_eq!(paging.page_number(), Some(3));
+        assert_eq!(paging.estimated_total(10), None);
+        assert_eq!(paging.page_count(10), None);
+
+        let paging: Paging = serde_json::from_str(
+            r#"{"next": "https://api.myanimelist.net/v2/anime/ranking?offset=30&limit=10", "count": 115}"#,
+        )
+        .unwrap();
+        assert_eq!(paging.estimated_total(10), Some(115));
+        assert_eq!(paging.page_count(10), Some(12));
+
+        // The last page knows the total from its offset
+        let paging: Paging = serde_json::from_str(
+            r#"{"previous": "https://api.myanimelist.net/v2/users/@me/animelist?offset=100&limit=100"}"#,
+        )
+        .unwrap();
+        assert_eq!(paging.offset(), Some(200));
+        assert_eq!(paging.estimated_total(42), Some(242));
+        assert_eq!(paging.page_count(42), Some(3));
+        assert_eq!(paging.page_number(), Some(3));
+        assert_eq!(paging.next_page(), None);
+        assert_eq!(
+            paging.prev_page(),
+            Some("https://api.myanimelist.net/v2/users/@me/animelist?offset=100&limit=100")
+        );
+
+        let paging = Paging::default();
+        assert_eq!(paging.page_number(), Some(1));
+        assert_eq!(paging.estimated_total(7), Some(7));
+        assert_eq!(paging.page_count(7), Some(1));
+
+        // Offsets and totals past u32::MAX are unknown, instead of overflowing
+        let paging: Paging = serde_json::from_str(
+            r#"{"previous": "https://api.myanimelist.net/v2/anime/ranking?offset=4294967295&limit=10"}"#,
+        )
+        .unwrap();
+        assert_eq!(paging.offset(), None);
+        assert_eq!(paging.estimated_total(10), None);
+        assert_eq!(paging.page_number(), None);
+
+        let paging: Paging = serde_json::from_str(
+            r#"{"previous": "https://api.myanimelist.net/v2/anime/ranking?offset=4294967290&limit=1"}"#,
+        )
+        .unwrap();
+        assert_eq!(paging.offset(), Some(u32::MAX - 4));
+        assert_eq!(paging.estimated_total(10), None);
+        assert_eq!(paging.page_count(10), None);
+    }
+
+    #[test]
+    #[cfg(feature = "anime")]
+    fn test_not_found() {
+        let err = error_message(
+            "Did not recieve OK response",
+            reqwest::StatusCode::NOT_FOUND,
+            br#"{"message":"","error":"not_found"}"#,
+        );
+        assert_eq!(
+            err.message,
+            "Did not recieve OK response: 404 Not Found (not_found)"
+        );
+        let err =
+            crate::anime::error::AnimeApiError::from(err).context("Failed to get anime details");
+        assert_eq!(err.code(), ErrorCode::NotFound);
+        assert_eq!(err.mal_error().unwrap().error, "not_found");
+        assert_eq!(
+            err.message(),
+            "Failed to get anime details: Did not recieve OK response: 404 Not Found (not_found)"
+        );
+        let err = crate::anime::error::AnimeApiError::new("timeout".to_string());
+        assert_eq!(err.code(), ErrorCode::Other);
+    }
 }
@@ -0,0 +1,489 @@
+//! Module for fetching the anime lists of many users, e.g. for community stats
+//!
+//! [fetch_many_user_lists] fetches the lists a page at a time, taking turns
+//! between users, so a user with a huge list doesn't hold up everyone else. A
+//! few pages are fetched at the same time, and every request waits for a
+//! [RateLimiter] to stay within MAL's rate limits. A user whose list
+//! can't be fetched, e.g. because it's private, doesn't fail the others.
+//!
+//! A [Leaderboard] ranks the users by their mean score, number of scored anime,
+//! or affinity to a reference user, like the leaderboards of server bots.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let limiter = RateLimiter::default();
+//! let lists =
+//!     community::fetch_many_user_lists(&api_client, &["user_a", "user_b"], 4, &limiter).await;
+//! for (user_name, err) in lists.failures() {
+//!     println!("Couldn't fetch {}'s list: {}", user_name, err);
+//! }
+//! for (user_name, list) in lists.lists() {
+//!     println!("{}: {} anime", user_name, list.len());
+//! }
+//!
+//! let mut leaderboard = Leaderboard::from_lists(&lists, Some("user_a"));
+//! leaderboard.sort_by(LeaderboardSort::Affinity);
+//! for (place, entry) in leaderboard.entries.iter().enumerate() {
+//!     println!("#{} {}: {:?}", place + 1, entry.user_name, entry.affinity);
+//! }
+//! ```
+
+use std::{cmp::Ordering, collections::VecDeque, future::Future};
+
+use futures::future::join_all;
+
+use crate::{
+    anime::{
+        api::AnimeApi,
+        error::AnimeApiError,
+        responses::{AnimeList, AnimeListNode},
+    },
+    common::PagingIter,
+    compare::{self, list_query},
+    histogram::ScoreHistogram,
+    rate_limit::RateLimiter,
+};
+
+/// The list of a single user fetched by [fetch_many_user_lists]
+#[derive(Debug)]
+pub struct UserList<T, E> {
+    pub user_name: String,
+
+    /// Every entry of the list, or the error that stopped it from being fetched
+    pub result: Result<Vec<T>, E>,
+}
+
+/// The lists fetched by [fetch_many_user_lists], in the order the users were given
+#[derive(Debug)]
+pub struct ManyUserLists<T, E> {
+    pub users: Vec<UserList<T, E>>,
+}
+
+impl<T, E> ManyUserLists<T, E> {
+    /// Returns true if every list was fetched
+    pub fn is_success(&self) -> bool {
+        self.users.iter().all(|user| user.result.is_ok())
+    }
+
+    /// Iterate over the lists that were fetched, with their user names
+    pub fn lists(&self) -> impl Iterator<Item = (&str, &[T])> {
+        self.users.iter().filter_map(|user| {
+            let list = user.result.as_ref().ok()?;
+            Some((user.user_name.as_str(), list.as_slice()))
+        })
+    }
+
+    /// Iterate over the users whose lists couldn't be fetched
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &E)> {
+        self.users.iter().filter_map(|user| {
+            let err = user.result.as_ref().err()?;
+            Some((user.user_name.as_str(), err))
+        })
+    }
+}
+
+/// Fetch the whole anime lists of many users, interleaving their pages
+///
+/// Up to `concurrency` pages are fetched at the same time, at least 1. Pages are
+/// requested in turns: after a page of a user, the next page of every other
+/// user that is waiting is requested before the user's own next page. Every
+/// request waits for the `limiter`, so the rate doesn't grow with `concurrency`.
+pub async fn fetch_many_user_lists<'a, A>(
+    api: &A,
+    user_names: &[&'a str],
+    concurrency: usize,
+    limiter: &RateLimiter,
+) -> ManyUserLists<AnimeListNode, AnimeApiError>
+where
+    A: AnimeApi + Sync,
+{
+    let fetch = |request: PageRequest<'a, AnimeList>| async move {
+        let mut page: AnimeList = match request {
+            PageRequest::First(user_name) => {
+                api.get_user_anime_list(&list_query(user_name)?).await?
+            }
+            PageRequest::Next(page) => api.next(&page).await?,
+        };
+        let entries = std::mem::take(&mut page.data);
+        let next = page.next_page().is_some().then_some(page);
+        Ok::<_, AnimeApiError>((entries, next))
+    };
+    fetch_interleaved(user_names, concurrency, limiter, fetch).await
+}
+
+// The page of a user to fetch next
+enum PageRequest<'a, P> {
+    First(&'a str),
+
+    /// The next page after this one, whose entries were already taken
+    Next(P),
+}
+
+// Fetches the pages round-robin, `concurrency` at a time, each request paced
+// by the limiter
+async fn fetch_interleaved<'a, P, T, E, F, Fut>(
+    user_names: &[&'a str],
+    concurrency: usize,
+    limiter: &RateLimiter,
+    fetch: F,
+) -> ManyUserLists<T, E>
+where
+    F: Fn(PageRequest<'a, P>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<P>), E>>,
+{
+    let mut users: Vec<UserList<T, E>> = user_names
+        .iter()
+        .map(|user_name| UserList {
+            user_name: user_name.to_string(),
+            result: Ok(vec![]),
+        })
+        .collect();
+    let mut queue: VecDeque<(usize, PageRequest<'a, P>)> = user_names
+        .iter()
+        .enumerate()
+        .map(|(index, user_name)| (index, PageRequest::First(user_name)))
+        .collect();
+
+    while !queue.is_empty() {
+        let round: Vec<_> = queue.drain(..concurrency.clamp(1, queue.len())).collect();
+        let (indices, requests): (Vec<_>, Vec<_>) = round.into_iter().unzip();
+        let pages = join_all(requests.into_iter().map(|request| async {
+            limiter.wait().await;
+            fetch(request).await
+        }))
+        .await;
+
+        for (index, page) in indices.into_iter().zip(pages) {
+            match (page, &mut users[index].result) {
+                (Ok((entries, next)), Ok(list)) => {
+                    list.extend(entries);
+                    if let Some(next) = next {
+                        queue.push_back((index, PageRequest::Next(next)));
+                    }
+                }
+                (Err(err), result) => *result = Err(err),
+                (Ok(_), Err(_)) => {}
+            }
+        }
+    }
+    ManyUserLists { users }
+}
+
+/// A user of a [Leaderboard]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub user_name: String,
+
+    /// Mean of the scores given, or `None` if nothing was scored
+    pub mean_score: Option<f64>,
+
+    /// Number of anime scored
+    pub num_scored: u64,
+
+    /// Number of anime in the list
+    pub num_entries: usize,
+
+    /// [Affinity](compare::UserComparison::affinity) to the reference user, as a
+    /// percentage within `[-100, 100]`. `None` for the reference user, and if it
+    /// can't be computed
+    pub affinity: Option<f64>,
+
+    /// Number of anime also in the reference user's list
+    pub num_shared: usize,
+
+    pub scores: ScoreHistogram,
+}
+
+/// What a [Leaderboard] is ordered by, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeaderboardSort {
+    #[default]
+    MeanScore,
+    Scored,
+    Entries,
+    Affinity,
+}
+
+/// Users ranked by their anime lists
+///
+/// Users without a value for the sort, e.g. without an affinity, are last
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    /// The user the affinities are computed against
+    pub reference: Option<String>,
+
+    pub entries: Vec<LeaderboardEntry>,
+
+    /// Users whose lists couldn't be fetched, so aren't on the leaderboard. Has
+    /// the reference user if their list couldn't be fetched, so there are no
+    /// affinities
+    pub missing: Vec<String>,
+}
+
+impl Leaderboard {
+    /// Build a leaderboard from lists that were already fetched, sorted by mean score
+    ///
+    /// Affinities are only computed if the reference user's list was fetched
+    pub fn from_lists<E>(lists: &ManyUserLists<AnimeListNode, E>, reference: Option<&str>) -> Self {
+        let reference_list = reference.and_then(|reference| {
+            lists
+                .lists()
+                .find(|(user_name, _)| *user_name == reference)
+                .map(|(_, list)| list)
+        });
+
+        let entries = lists
+            .lists()
+            .map(|(user_name, list)| {
+                let scores = ScoreHistogram::from_anime_list(list);
+                let comparison = reference_list
+                    .filter(|_| reference != Some(user_name))
+                    .map(|reference_list| compare::lists(reference_list, list));
+                LeaderboardEntry {
+                    user_name: user_name.to_string(),
+                    mean_score: scores.mean(),
+                    num_scored: scores.total(),
+                    num_entries: list.len(),
+                    affinity: comparison.as_ref().and_then(|c| c.affinity()),
+                    num_shared: comparison.map_or(0, |c| c.shared.len()),
+                    scores,
+                }
+            })
+            .collect();
+
+        let mut leaderboard = Self {
+            reference: reference.map(String::from),
+            entries,
+            missing: lists
+                .failures()
+                .map(|(user_name, _)| user_name.to_string())
+                .collect(),
+        };
+        leaderboard.sort_by(LeaderboardSort::default());
+        leaderboard
+    }
+
+    /// Reorder the users. Ties keep their current order
+    pub fn sort_by(&mut self, sort: LeaderboardSort) {
+        let value = |entry: &LeaderboardEntry| match sort {
+            LeaderboardSort::MeanScore => entry.mean_score,
+            LeaderboardSort::Scored => Some(entry.num_scored as f64),
+            LeaderboardSort::Entries => Some(entry.num_entries as f64),
+            LeaderboardSort::Affinity => entry.affinity,
+        };
+        self.entries.sort_by(|a, b| match (value(a), value(b)) {
+            (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+
+    /// Get the place of a user, starting at 1
+    pub fn place(&self, user_name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.user_name == user_name)
+            .map(|index| index + 1)
+    }
+
+    /// Get the first `n` users
+    pub fn top(&self, n: usize) -> &[LeaderboardEntry] {
+        &self.entries[..n.min(self.entries.len())]
+    }
+}
+
+/// Fetch the lists of the users and build a [Leaderboard] of them
+///
+/// The reference user's list is fetched too if they aren't one of the users, but
+/// they are only on the leaderboard if they are. They are in
+/// [missing](Leaderboard::missing) if their list couldn't be fetched either way.
+/// See [fetch_many_user_lists].
+pub async fn leaderboard<A>(
+    api: &A,
+    user_names: &[&str],
+    reference: Option<&str>,
+    concurrency: usize,
+    limiter: &RateLimiter,
+) -> Leaderboard
+where
+    A: AnimeApi + Sync,
+{
+    let mut names = user_names.to_vec();
+    let extra = reference.filter(|reference| !user_names.contains(reference));
+    names.extend(extra);
+
+    let lists = fetch_many_user_lists(api, &names, concurrency, limiter).await;
+    let mut leaderboard = Leaderboard::from_lists(&lists, reference);
+    if let Some(extra) = extra {
+        leaderboard.entries.retain(|entry| entry.user_name != extra);
+    }
+    leaderboard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_fetch_interleaved() {
+        // Pages are numbered, and each user has as many as the length of their name
+        let log = Mutex::new(vec![]);
+        let fetch = |request: PageRequest<'static, (&'static str, u32)>| {
+            let (user_name, page) = match request {
+                PageRequest::First(user_name) => (user_name, 1),
+                PageRequest::Next((user_name, page)) => (user_name, page + 1),
+            };
+            log.lock().unwrap().push(format!("{}{}", user_name, page));
+            let result = if user_name == "err" {
+                Err(format!("{} is private", user_name))
+            } else {
+                let next = (page < user_name.len() as u32).then_some((user_name, page));
+                Ok((vec![page], next))
+            };
+            std::future::ready(result)
+        };
+
+        let lists = fetch_interleaved(
+            &["abc", "a", "err", "ab"],
+            2,
+            &RateLimiter::unlimited(),
+            fetch,
+        )
+        .await;
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["abc1", "a1", "err1", "ab1", "abc2", "ab2", "abc3"]
+        );
+        assert!(!lists.is_success());
+        assert_eq!(
+            lists.lists().collect::<Vec<_>>(),
+            vec![
+                ("abc", [1, 2, 3].as_slice()),
+                ("a", [1].as_slice()),
+                ("ab", [1, 2].as_slice())
+            ]
+        );
+        assert_eq!(
+            lists.failures().collect::<Vec<_>>(),
+            vec![("err", &"err is private".to_string())]
+        );
+    }
+
+    fn list(scores: &[(u32, u8)]) -> Vec<AnimeListNode> {
+        scores
+            .iter()
+            .map(|(id, score)| anime_entry(*id).score(*score).build())
+            .collect()
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let user = |user_name: &str, result: Result<Vec<AnimeListNode>, ()>| UserList {
+            user_name: user_name.to_string(),
+            result,
+        };
+        let lists = ManyUserLists {
+            users: vec![
+                user("me", Ok(list(&[(1, 10), (2, 6), (3, 8)]))),
+                user("alike", Ok(list(&[(1, 9), (2, 5), (3, 7), (4, 0)]))),
+                user("opposite", Ok(list(&[(1, 5), (2, 10), (3, 9), (5, 10)]))),
+                user("new", Ok(vec![])),
+                user("private", Err(())),
+            ],
+        };
+
+        let mut leaderboard = Leaderboard::from_lists(&lists, Some("me"));
+        let names = |leaderboard: &Leaderboard| {
+            leaderboard
+                .entries
+                .iter()
+                .map(|e| e.user_name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&leaderboard), vec!["opposite", "me", "alike", "new"]);
+        assert_eq!(leaderboard.missing, vec!["private"]);
+        assert_eq!(leaderboard.entries[0].mean_score, Some(8.5));
+        assert_eq!(leaderboard.place("alike"), Some(3));
+
+        leaderboard.sort_by(LeaderboardSort::Affinity);
+        assert_eq!(leaderboard.top(2)[0].user_name, "alike");
+        assert_eq!(leaderboard.top(2)[0].affinity, Some(100.0));
+        assert_eq!(leaderboard.top(2)[0].num_shared, 3);
+        assert!(leaderboard.top(2)[1].affinity.unwrap() < 0.0);
+        assert_eq!(leaderboard.place("me"), Some(3));
+        assert_eq!(leaderboard.top(10).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_missing_reference() {
+        use crate::anime::{
+            api::Request,
+            requests::{GetAnimeDetails, GetAnimeRanking, GetSeasonalAnime, GetUserAnimeList},
+        };
+        use crate::{codes::ErrorCode, fixtures::anime_list};
+        use async_trait::async_trait;
+        use bytes::Bytes;
+        use serde::Serialize;
+
+        // Serves a one page list of one anime for every user, but "private"
+        struct Lists;
+
+        #[async_trait]
+        impl Request for Lists {
+            async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+            where
+                T: Serialize + Send + Sync,
+            {
+                unimplemented!()
+            }
+
+            async fn get_details(&self, _query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_seasonal(
+                &self,
+                _query: &GetSeasonalAnime,
+            ) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_user(&self, query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+                if query.user_name == "private" {
+                    return Err(AnimeApiError::with_code(
+                        ErrorCode::Forbidden,
+                        "private list".to_string(),
+                    ));
+                }
+                let list = anime_list(vec![anime_entry(1).score(8).build()]);
+                Ok(Bytes::from(list.to_json()))
+            }
+
+            async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+        }
+
+        impl AnimeApi for Lists {
+            type State = Lists;
+
+            fn get_self(&self) -> &Self::State {
+                self
+            }
+        }
+
+        let limiter = RateLimiter::unlimited();
+        let leaderboard = leaderboard(&Lists, &["a", "b"], Some("private"), 2, &limiter).await;
+        assert_eq!(leaderboard.entries.len(), 2);
+        assert!(leaderboard.entries.iter().all(|e| e.affinity.is_none()));
+        assert_eq!(leaderboard.missing, vec!["private"]);
+    }
+}
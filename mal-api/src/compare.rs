@@ -0,0 +1,444 @@
+//! Module for comparing the anime lists of users
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let api_client = AnimeApiClient::from(&client_id);
+//! let limiter = RateLimiter::default();
+//! let comparison = mal_api::compare::users(&api_client, "user_a", "user_b", &limiter)
+//!     .await
+//!     .unwrap();
+//!
+//! println!("Shared anime: {}", comparison.shared.len());
+//! if let Some(affinity) = comparison.affinity() {
+//!     println!("Affinity: {:.1}%", affinity);
+//! }
+//!
+//! // Plan a group watch
+//! let group = mal_api::compare::group(&api_client, &["user_a", "user_b", "user_c"], &limiter)
+//!     .await
+//!     .unwrap();
+//! for anime in group.unseen_by_all() {
+//!     println!("Nobody has seen {} yet", anime.title);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    anime::{
+        api::AnimeApi,
+        error::AnimeApiError,
+        requests::{GetUserAnimeList, UserAnimeListStatus},
+        responses::AnimeListNode,
+    },
+    common::PagingIter,
+    rate_limit::RateLimiter,
+};
+
+/// An anime that is in both users' lists
+#[derive(Debug, Clone)]
+pub struct SharedAnime {
+    pub anime_id: u32,
+    pub title: String,
+
+    /// Score given by the first user. `0` means the anime wasn't scored
+    pub score_a: u8,
+
+    /// Score given by the second user. `0` means the anime wasn't scored
+    pub score_b: u8,
+}
+
+impl SharedAnime {
+    /// Difference between the first and second user's scores, if both scored the anime
+    pub fn score_delta(&self) -> Option<i16> {
+        if self.score_a == 0 || self.score_b == 0 {
+            return None;
+        }
+        Some(self.score_a as i16 - self.score_b as i16)
+    }
+}
+
+/// Comparison of two users' anime lists
+#[derive(Debug, Clone)]
+pub struct UserComparison {
+    /// Anime in both lists, in the order of the first user's list
+    pub shared: Vec<SharedAnime>,
+}
+
+impl UserComparison {
+    /// Anime that both users scored
+    pub fn scored(&self) -> impl Iterator<Item = &SharedAnime> {
+        self.shared.iter().filter(|s| s.score_delta().is_some())
+    }
+
+    /// Mean of the score deltas of the anime both users scored
+    pub fn mean_score_delta(&self) -> Option<f64> {
+        let deltas: Vec<f64> = self
+            .scored()
+            .filter_map(|s| s.score_delta())
+            .map(f64::from)
+            .collect();
+        if deltas.is_empty() {
+            return None;
+        }
+        Some(deltas.iter().sum::<f64>() / deltas.len() as f64)
+    }
+
+    /// Pearson correlation of the scores of the anime both users scored, within `[-1, 1]`
+    ///
+    /// Returns `None` if fewer than two anime were scored by both users, or if either
+    /// user gave all of them the same score
+    pub fn correlation(&self) -> Option<f64> {
+        let scores: Vec<(f64, f64)> = self
+            .scored()
+            .map(|s| (f64::from(s.score_a), f64::from(s.score_b)))
+            .collect();
+        if scores.len() < 2 {
+            return None;
+        }
+
+        let count = scores.len() as f64;
+        let mean_a = scores.iter().map(|(a, _)| a).sum::<f64>() / count;
+        let mean_b = scores.iter().map(|(_, b)| b).sum::<f64>() / count;
+
+        let mut covariance = 0.0;
+        let mut variance_a = 0.0;
+        let mut variance_b = 0.0;
+        for (a, b) in &scores {
+            covariance += (a - mean_a) * (b - mean_b);
+            variance_a += (a - mean_a).powi(2);
+            variance_b += (b - mean_b).powi(2);
+        }
+        if variance_a == 0.0 || variance_b == 0.0 {
+            return None;
+        }
+        Some(covariance / (variance_a * variance_b).sqrt())
+    }
+
+    /// The [correlation](UserComparison::correlation) as a percentage, like MAL's affinity
+    pub fn affinity(&self) -> Option<f64> {
+        self.correlation().map(|c| c * 100.0)
+    }
+}
+
+/// Fetch the anime lists of both users and compare them
+///
+/// Every page request waits for the `limiter` to stay within MAL's rate limits
+pub async fn users<C>(
+    api: &C,
+    user_a: &str,
+    user_b: &str,
+    limiter: &RateLimiter,
+) -> Result<UserComparison, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    let list_a = full_list(api, user_a, limiter).await?;
+    let list_b = full_list(api, user_b, limiter).await?;
+    Ok(lists(&list_a, &list_b))
+}
+
+/// Compare two anime lists that were already fetched
+///
+/// The entries need their `list_status`, which MAL only returns for user lists
+pub fn lists(list_a: &[AnimeListNode], list_b: &[AnimeListNode]) -> UserComparison {
+    let scores_b: HashMap<u32, u8> = list_b
+        .iter()
+        .filter_map(|e| Some((e.node.id, e.list_status.as_ref()?.score)))
+        .collect();
+
+    let shared = list_a
+        .iter()
+        .filter_map(|e| {
+            let score_b = *scores_b.get(&e.node.id)?;
+            Some(SharedAnime {
+                anime_id: e.node.id,
+                title: e.node.title.clone(),
+                score_a: e.list_status.as_ref().map(|s| s.score).unwrap_or_default(),
+                score_b,
+            })
+        })
+        .collect();
+
+    UserComparison { shared }
+}
+
+/// An anime from the lists of a group of users
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupAnime {
+    pub anime_id: u32,
+    pub title: String,
+}
+
+/// The anime lists of a group of users
+#[derive(Debug, Clone)]
+pub struct GroupLists {
+    users: Vec<String>,
+    titles: HashMap<u32, String>,
+    // anime id -> (user index -> status)
+    statuses: HashMap<u32, HashMap<usize, Option<UserAnimeListStatus>>>,
+}
+
+impl GroupLists {
+    /// Build the group from lists that were already fetched, given as `(user name, list)`
+    pub fn new(lists: Vec<(String, Vec<AnimeListNode>)>) -> Self {
+        let mut group = Self {
+            users: Vec::with_capacity(lists.len()),
+            titles: HashMap::new(),
+            statuses: HashMap::new(),
+        };
+        for (index, (user_name, list)) in lists.into_iter().enumerate() {
+            group.users.push(user_name);
+            for entry in list {
+                let status = entry.list_status.as_ref().and_then(|s| s.status);
+                group
+                    .statuses
+                    .entry(entry.node.id)
+                    .or_default()
+                    .insert(index, status);
+                group.titles.insert(entry.node.id, entry.node.title);
+            }
+        }
+        group
+    }
+
+    /// Get the user names of the group
+    pub fn users(&self) -> &[String] {
+        &self.users
+    }
+
+    /// Anime that every user has in their list with the given status
+    ///
+    /// e.g. `PlanToWatch` finds the shows everyone wants to watch
+    pub fn with_status_for_all(&self, status: UserAnimeListStatus) -> Vec<GroupAnime> {
+        self.filter(|statuses| {
+            statuses.len() == self.users.len() && statuses.values().all(|s| *s == Some(status))
+        })
+    }
+
+    /// Anime that everyone has completed
+    pub fn seen_by_all(&self) -> Vec<GroupAnime> {
+        self.with_status_for_all(UserAnimeListStatus::Completed)
+    }
+
+    /// Anime in at least one list that nobody has started watching
+    pub fn unseen_by_all(&self) -> Vec<GroupAnime> {
+        self.filter(|statuses| {
+            statuses
+                .values()
+                .all(|s| *s == Some(UserAnimeListStatus::PlanToWatch))
+        })
+    }
+
+    fn filter<F>(&self, keep: F) -> Vec<GroupAnime>
+    where
+        F: Fn(&HashMap<usize, Option<UserAnimeListStatus>>) -> bool,
+    {
+        let mut anime: Vec<GroupAnime> = self
+            .statuses
+            .iter()
+            .filter(|(_, statuses)| keep(statuses))
+            .map(|(id, _)| GroupAnime {
+                anime_id: *id,
+                title: self.titles.get(id).cloned().unwrap_or_default(),
+            })
+            .collect();
+        anime.sort_by_key(|a| a.anime_id);
+        anime
+    }
+}
+
+/// Fetch the anime lists of a group of users
+///
+/// The lists are fetched one at a time, and every page request waits for the
+/// `limiter` to stay within MAL's rate limits
+pub async fn group<C>(
+    api: &C,
+    user_names: &[&str],
+    limiter: &RateLimiter,
+) -> Result<GroupLists, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    let mut lists = Vec::with_capacity(user_names.len());
+    for user_name in user_names {
+        let list = full_list(api, user_name, limiter).await?;
+        lists.push((user_name.to_string(), list));
+    }
+    Ok(GroupLists::new(lists))
+}
+
+// Like get_full_user_anime_list, but waits for the limiter before every page
+async fn full_list<C>(
+    api: &C,
+    user_name: &str,
+    limiter: &RateLimiter,
+) -> Result<Vec<AnimeListNode>, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    limiter.wait().await;
+    let mut page = api.get_user_anime_list(&list_query(user_name)?).await?;
+    let mut entries = std::mem::take(&mut page.data);
+    while page.next_page().is_some() {
+        limiter.wait().await;
+        page = api.next(&page).await?;
+        entries.append(&mut page.data);
+    }
+    Ok(entries)
+}
+
+pub(crate) fn list_query(user_name: &str) -> Result<GetUserAnimeList, AnimeApiError> {
+    GetUserAnimeList::builder(user_name)
+        .enable_nsfw()
+        .limit(1000)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, score: u8) -> AnimeListNode {
+        anime_entry(id).score(score).build()
+    }
+
+    #[test]
+    fn test_compare_lists() {
+        let list_a = vec![entry(1, 8), entry(2, 6), entry(3, 10), entry(4, 0)];
+        let list_b = vec![
+            entry(1, 7),
+            entry(2, 5),
+            entry(3, 9),
+            entry(4, 5),
+            entry(5, 9),
+        ];
+
+        let comparison = lists(&list_a, &list_b);
+        assert_eq!(comparison.shared.len(), 4);
+        assert_eq!(comparison.shared[0].score_delta(), Some(1));
+        assert_eq!(comparison.shared[3].score_delta(), None);
+        assert_eq!(comparison.mean_score_delta(), Some(1.0));
+        assert!((comparison.correlation().unwrap() - 1.0).abs() < 1e-9);
+
+        let comparison = lists(&list_a, &[entry(1, 7)]);
+        assert_eq!(comparison.correlation(), None);
+    }
+
+    #[test]
+    fn test_group_lists() {
+        let ptw = |id| anime_entry(id).status("plan_to_watch").build();
+        let group = GroupLists::new(vec![
+            ("a".to_string(), vec![entry(1, 8), ptw(2), ptw(3)]),
+            ("b".to_string(), vec![entry(1, 7), ptw(2), entry(3, 6)]),
+            ("c".to_string(), vec![entry(1, 9), ptw(2), ptw(4)]),
+        ]);
+
+        let ids = |anime: Vec<GroupAnime>| anime.iter().map(|a| a.anime_id).collect::<Vec<_>>();
+        assert_eq!(ids(group.seen_by_all()), vec![1]);
+        assert_eq!(
+            ids(group.with_status_for_all(UserAnimeListStatus::PlanToWatch)),
+            vec![2]
+        );
+        assert_eq!(ids(group.unseen_by_all()), vec![2, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_group_paces_pages() {
+        use crate::{
+            anime::{
+                api::Request,
+                requests::{GetAnimeDetails, GetAnimeRanking, GetSeasonalAnime},
+            },
+            fixtures::anime_list,
+        };
+        use async_trait::async_trait;
+        use bytes::Bytes;
+        use serde::Serialize;
+        use std::{sync::Mutex, time::Duration};
+        use tokio::time::Instant;
+
+        // Serves two pages of one anime for every user, logging when each page
+        // was requested
+        struct Pages {
+            requested: Mutex<Vec<Instant>>,
+        }
+
+        impl Pages {
+            fn page(&self, id: u32, next: Option<&str>) -> Result<Bytes, AnimeApiError> {
+                self.requested.lock().unwrap().push(Instant::now());
+                let mut page: serde_json::Value =
+                    serde_json::from_str(&anime_list(vec![entry(id, 8)]).to_json()).unwrap();
+                page["paging"] = serde_json::json!({ "next": next });
+                Ok(Bytes::from(page.to_string()))
+            }
+        }
+
+        #[async_trait]
+        impl Request for Pages {
+            async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+            where
+                T: Serialize + Send + Sync,
+            {
+                unimplemented!()
+            }
+
+            async fn get_details(&self, _query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_seasonal(
+                &self,
+                _query: &GetSeasonalAnime,
+            ) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_user(&self, _query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+                self.page(1, Some("page2"))
+            }
+
+            async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+                self.page(2, None)
+            }
+        }
+
+        impl AnimeApi for Pages {
+            type State = Pages;
+
+            fn get_self(&self) -> &Self::State {
+                self
+            }
+        }
+
+        let api = Pages {
+            requested: Mutex::new(vec![]),
+        };
+        let interval = Duration::from_millis(20);
+        let limiter = RateLimiter::new(interval);
+        let start = Instant::now();
+        let group = group(&api, &["a", "b"], &limiter).await.unwrap();
+        let ids = group
+            .seen_by_all()
+            .iter()
+            .map(|a| a.anime_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+
+        let comparison = users(&api, "a", "b", &limiter).await.unwrap();
+        assert_eq!(comparison.shared.len(), 2);
+
+        // Every page waited for the limiter, not only the first of each list
+        let requested = api.requested.lock().unwrap();
+        assert_eq!(requested.len(), 8);
+        for (i, requested) in requested.iter().enumerate() {
+            assert!(*requested - start >= interval * i as u32);
+        }
+    }
+}
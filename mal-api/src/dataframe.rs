@@ -0,0 +1,304 @@
+//! Module for converting anime and manga lists into polars [DataFrame]s
+//!
+//! Every entry becomes a row. Fields that weren't requested, or that MAL didn't
+//! return for an entry, are null. Enum fields such as `media_type` are stored as
+//! the strings MAL uses for them, e.g. `tv`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let ranking = api_client.get_anime_ranking(&query).await.unwrap();
+//! let df = ranking.to_dataframe().unwrap();
+//!
+//! println!("{}", df.head(Some(10)));
+//! ```
+
+use polars::prelude::*;
+#[cfg(lists)]
+use serde::Serialize;
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::{AnimeFields, AnimeList, AnimeRanking, SeasonalAnime};
+#[cfg(feature = "manga")]
+use crate::manga::responses::{MangaFields, MangaList, MangaRanking};
+
+/// Convert a response into a [DataFrame] with one row per entry
+pub trait ToDataFrame {
+    fn to_dataframe(&self) -> PolarsResult<DataFrame>;
+}
+
+#[cfg(feature = "anime")]
+impl ToDataFrame for AnimeList {
+    /// Includes the `list_status`, `score`, `num_episodes_watched`, and `list_updated_at`
+    /// columns, which are only set for user lists
+    fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = anime_columns(self.data.iter().map(|e| &e.node));
+        let list_status = || self.data.iter().map(|e| e.list_status.as_ref());
+        columns.extend([
+            Column::new(
+                "list_status".into(),
+                list_status()
+                    .map(|s| variant(&s?.status))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "score".into(),
+                list_status()
+                    .map(|s| Some(u32::from(s?.score)))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "num_episodes_watched".into(),
+                list_status()
+                    .map(|s| Some(s?.num_episodes_watched))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "list_updated_at".into(),
+                list_status()
+                    .map(|s| Some(s?.updated_at.clone()))
+                    .collect::<Vec<_>>(),
+            ),
+        ]);
+        DataFrame::new(columns)
+    }
+}
+
+#[cfg(feature = "anime")]
+impl ToDataFrame for AnimeRanking {
+    /// Includes the `ranking` and `previous_ranking` columns
+    fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = anime_columns(self.data.iter().map(|e| &e.node));
+        columns.extend(ranking_columns(self.data.iter().map(|e| &e.ranking)));
+        DataFrame::new(columns)
+    }
+}
+
+#[cfg(feature = "anime")]
+impl ToDataFrame for SeasonalAnime {
+    fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        DataFrame::new(anime_columns(self.data.iter().map(|e| &e.node)))
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ToDataFrame for MangaList {
+    /// Includes the `list_status`, `score`, `num_volumes_read`, `num_chapters_read`,
+    /// and `list_updated_at` columns, which are only set for user lists
+    fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = manga_columns(self.data.iter().map(|e| &e.node));
+        let list_status = || self.data.iter().map(|e| e.list_status.as_ref());
+        columns.extend([
+            Column::new(
+                "list_status".into(),
+                list_status()
+                    .map(|s| variant(&s?.status))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "score".into(),
+                list_status()
+                    .map(|s| Some(u32::from(s?.score)))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "num_volumes_read".into(),
+                list_status()
+                    .map(|s| Some(s?.num_volumes_read))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "num_chapters_read".into(),
+                list_status()
+                    .map(|s| Some(s?.num_chapters_read))
+                    .collect::<Vec<_>>(),
+            ),
+            Column::new(
+                "list_updated_at".into(),
+                list_status()
+                    .map(|s| Some(s?.updated_at.clone()))
+                    .collect::<Vec<_>>(),
+            ),
+        ]);
+        DataFrame::new(columns)
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ToDataFrame for MangaRanking {
+    /// Includes the `ranking` and `previous_ranking` columns
+    fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = manga_columns(self.data.iter().map(|e| &e.node));
+        columns.extend(ranking_columns(self.data.iter().map(|e| &e.ranking)));
+        DataFrame::new(columns)
+    }
+}
+
+#[cfg(feature = "anime")]
+fn anime_columns<'a, I>(nodes: I) -> Vec<Column>
+where
+    I: Iterator<Item = &'a AnimeFields>,
+{
+    let nodes: Vec<&AnimeFields> = nodes.collect();
+    vec![
+        Column::new("id".into(), nodes.iter().map(|n| n.id).collect::<Vec<_>>()),
+        Column::new(
+            "title".into(),
+            nodes.iter().map(|n| n.title.clone()).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "media_type".into(),
+            nodes
+                .iter()
+                .map(|n| variant(&n.media_type))
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "status".into(),
+            nodes.iter().map(|n| variant(&n.status)).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_episodes".into(),
+            nodes.iter().map(|n| n.num_episodes).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "start_date".into(),
+            nodes
+                .iter()
+                .map(|n| n.start_date.clone())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "mean".into(),
+            nodes.iter().map(|n| n.mean).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "rank".into(),
+            nodes.iter().map(|n| n.rank).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "popularity".into(),
+            nodes.iter().map(|n| n.popularity).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_list_users".into(),
+            nodes.iter().map(|n| n.num_list_users).collect::<Vec<_>>(),
+        ),
+    ]
+}
+
+#[cfg(feature = "manga")]
+fn manga_columns<'a, I>(nodes: I) -> Vec<Column>
+where
+    I: Iterator<Item = &'a MangaFields>,
+{
+    let nodes: Vec<&MangaFields> = nodes.collect();
+    vec![
+        Column::new("id".into(), nodes.iter().map(|n| n.id).collect::<Vec<_>>()),
+        Column::new(
+            "title".into(),
+            nodes.iter().map(|n| n.title.clone()).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "media_type".into(),
+            nodes
+                .iter()
+                .map(|n| variant(&n.media_type))
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "status".into(),
+            nodes.iter().map(|n| variant(&n.status)).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_volumes".into(),
+            nodes.iter().map(|n| n.num_volumes).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_chapters".into(),
+            nodes.iter().map(|n| n.num_chapters).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "start_date".into(),
+            nodes
+                .iter()
+                .map(|n| n.start_date.clone())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "mean".into(),
+            nodes.iter().map(|n| n.mean).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "rank".into(),
+            nodes.iter().map(|n| n.rank).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "popularity".into(),
+            nodes.iter().map(|n| n.popularity).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_list_users".into(),
+            nodes.iter().map(|n| n.num_list_users).collect::<Vec<_>>(),
+        ),
+    ]
+}
+
+#[cfg(lists)]
+fn ranking_columns<'a, I>(rankings: I) -> [Column; 2]
+where
+    I: Iterator<Item = &'a crate::common::Ranking>,
+{
+    let rankings: Vec<_> = rankings.collect();
+    [
+        Column::new(
+            "ranking".into(),
+            rankings.iter().map(|r| r.rank).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "previous_ranking".into(),
+            rankings.iter().map(|r| r.previous_rank).collect::<Vec<_>>(),
+        ),
+    ]
+}
+
+// The name MAL uses for an enum variant, e.g. `plan_to_watch`
+#[cfg(lists)]
+fn variant<T: Serialize>(value: &Option<T>) -> Option<String> {
+    match serde_json::to_value(value.as_ref()?).ok()? {
+        serde_json::Value::String(name) => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{anime_entry, anime_list, parse};
+
+    #[test]
+    fn test_anime_list_dataframe() {
+        let list = anime_list(vec![
+            anime_entry(1)
+                .status("plan_to_watch")
+                .node(serde_json::json!({ "media_type": "tv", "mean": 8.5 }))
+                .build(),
+            // Not on the user's list
+            parse(serde_json::json!({ "node": { "id": 2, "title": "Anime 2" } })),
+        ]);
+
+        let df = list.to_dataframe().unwrap();
+        assert_eq!(df.shape(), (2, 14));
+        assert_eq!(df.column("id").unwrap().dtype(), &DataType::UInt32);
+        assert_eq!(
+            df.column("media_type").unwrap().str().unwrap().get(0),
+            Some("tv")
+        );
+        assert_eq!(df.column("mean").unwrap().f32().unwrap().get(1), None);
+        assert_eq!(
+            df.column("list_status").unwrap().str().unwrap().get(0),
+            Some("plan_to_watch")
+        );
+        assert_eq!(df.column("score").unwrap().null_count(), 1);
+    }
+}
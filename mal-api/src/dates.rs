@@ -0,0 +1,251 @@
+//! Module for working with the dates and timestamps MAL returns
+//!
+//! List entries have `start_date` and `finish_date` fields formatted as
+//! `YYYY-MM-DD`, where the month and the day can be missing when the user only
+//! entered part of the date. [PartialDate] parses them and counts the days
+//! between dates without pulling in a date library.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let finished: PartialDate = "2021-03".parse().unwrap();
+//! println!("Finished {} days ago", finished.days_until(&PartialDate::today()));
+//! ```
+
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::common::CommonError;
+
+/// A date with an optional month and day, e.g. `2021`, `2021-03`, or `2021-03-14`
+///
+/// A day is only set if the month is set too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartialDate {
+    pub year: u16,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl PartialDate {
+    /// Create a full date
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, CommonError> {
+        let date = Self {
+            year,
+            month: Some(month),
+            day: Some(day),
+        };
+        date.validate()?;
+        Ok(date)
+    }
+
+    /// The current date in UTC
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        Self::from_days(seconds.div_euclid(86400))
+    }
+
+    /// Returns true if the month and the day are set
+    pub fn is_complete(&self) -> bool {
+        self.month.is_some() && self.day.is_some()
+    }
+
+    /// Number of days from this date to `other`, negative if `other` is earlier
+    ///
+    /// Missing parts count as the start of the period, so `2021` is treated as
+    /// `2021-01-01`
+    pub fn days_until(&self, other: &PartialDate) -> i64 {
+        other.days_since_epoch() - self.days_since_epoch()
+    }
+
+    // Day number since the Unix Epoch of the first day of the date
+    fn days_since_epoch(&self) -> i64 {
+        days_from_civil(
+            i64::from(self.year),
+            i64::from(self.month.unwrap_or(1)),
+            i64::from(self.day.unwrap_or(1)),
+        )
+    }
+
+    fn from_days(days: i64) -> Self {
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year: year as u16,
+            month: Some(month as u8),
+            day: Some(day as u8),
+        }
+    }
+
+    fn validate(&self) -> Result<(), CommonError> {
+        if let Some(month) = self.month {
+            if !(1..=12).contains(&month) {
+                return Err(CommonError::new(format!("Invalid month: {}", month)));
+            }
+        }
+        if let Some(day) = self.day {
+            let month = match self.month {
+                Some(month) => month,
+                None => return Err(CommonError::new("Day set without a month".to_string())),
+            };
+            let year = i64::from(self.year);
+            let days_in_month =
+                days_from_civil(year + i64::from(month / 12), i64::from(month % 12 + 1), 1)
+                    - days_from_civil(year, i64::from(month), 1);
+            if day == 0 || i64::from(day) > days_in_month {
+                return Err(CommonError::new(format!("Invalid day: {}", day)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PartialDate {
+    type Err = CommonError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CommonError::new(format!("Invalid date: {}", value));
+        let mut parts = value.split('-');
+        let mut number = |len: usize| -> Result<Option<u16>, CommonError> {
+            match parts.next() {
+                None => Ok(None),
+                Some(part) if part.len() == len && part.bytes().all(|b| b.is_ascii_digit()) => {
+                    Ok(part.parse().ok())
+                }
+                Some(_) => Err(invalid()),
+            }
+        };
+
+        let year = number(4)?.ok_or_else(invalid)?;
+        let month = number(2)?.map(|m| m as u8);
+        let day = number(2)?.map(|d| d as u8);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let date = Self { year, month, day };
+        date.validate()?;
+        Ok(date)
+    }
+}
+
+impl fmt::Display for PartialDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{:02}", month)?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "-{:02}", day)?;
+        }
+        Ok(())
+    }
+}
+
+// Parses the RFC 3339 timestamps MAL returns, e.g. `2023-01-01T12:30:00+00:00`,
+// into seconds since the Unix Epoch
+pub(crate) fn parse_timestamp(value: &str) -> Option<i64> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = value.get(range)?;
+        if !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+
+    let days = days_from_civil(number(0..4)?, number(5..7)?, number(8..10)?);
+    let seconds = number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?;
+
+    // Skip fractional seconds
+    let rest = value.get(19..)?;
+    let offset = rest.trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match offset.as_bytes().first()? {
+        b'Z' => 0,
+        b'+' | b'-' => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let hours: i64 = offset.get(1..3)?.parse().ok()?;
+            let minutes: i64 = offset.get(4..6)?.parse().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return None,
+    };
+
+    Some(days * 86400 + seconds - offset)
+}
+
+// Howard Hinnant's `days_from_civil` and `civil_from_days` algorithms
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_date() {
+        let date: PartialDate = "2021-03".parse().unwrap();
+        assert_eq!(date.month, Some(3));
+        assert_eq!(date.day, None);
+        assert!(!date.is_complete());
+        assert_eq!(date.to_string(), "2021-03");
+
+        let full = PartialDate::new(2021, 3, 14).unwrap();
+        assert_eq!("2021-03-14".parse::<PartialDate>().unwrap(), full);
+        assert_eq!(date.days_until(&full), 13);
+        assert_eq!(full.days_until(&date), -13);
+        assert_eq!(
+            "2020".parse::<PartialDate>().unwrap().days_until(&date),
+            425
+        );
+
+        assert!("2024-02-29".parse::<PartialDate>().is_ok());
+        assert!("2023-02-29".parse::<PartialDate>().is_err());
+        assert!("2021-13".parse::<PartialDate>().is_err());
+        assert!("21-03-14".parse::<PartialDate>().is_err());
+        assert!("".parse::<PartialDate>().is_err());
+
+        let today = PartialDate::today();
+        assert!(today.is_complete() && today.year >= 2023);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00+00:00"), Some(0));
+        assert_eq!(parse_timestamp("1970-01-01T09:00:00+09:00"), Some(0));
+        assert_eq!(
+            parse_timestamp("2023-10-16T12:30:15.250Z"),
+            Some(1697459415)
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Module for computing the differences between two snapshots of a user's list
+
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+};
+
+use serde::Serialize;
+
+#[cfg(feature = "anime")]
+use crate::anime::{
+    requests::UserAnimeListStatus,
+    responses::{AnimeListNode, AnimeRankingNode},
+};
+#[cfg(feature = "manga")]
+use crate::manga::{
+    requests::UserMangaListStatus,
+    responses::{MangaListNode, MangaRankingNode},
+};
+
+/// An anime or manga entry of a list or ranking
+///
+/// Used by the helpers that work on fetched entries, such as [diff_lists],
+/// [sorting](crate::sort) and [filtering](crate::filter).
+pub trait ListEntry {
+    /// The anime or manga list status type
+    type Status: PartialEq;
+
+    /// Get the MAL id of the anime or manga, if it was returned
+    fn entry_id(&self) -> Option<u32>;
+
+    /// Get the main title of the anime or manga, or an empty string if it wasn't returned
+    fn title(&self) -> &str;
+
+    /// Get the mean score of the anime or manga on MAL, if it was returned
+    fn mean(&self) -> Option<f32>;
+
+    /// Get when the anime started airing, or the manga started publishing
+    fn start_date(&self) -> Option<&str>;
+
+    /// Get the status of the entry on the user's list, if it was returned
+    fn list_status(&self) -> Option<&Self::Status>;
+
+    /// Get the score the user gave the entry, or `None` if it wasn't scored
+    fn list_score(&self) -> Option<u8>;
+
+    /// Get when the user last updated the entry, if it was returned
+    fn list_updated_at(&self) -> Option<&str>;
+}
+
+#[cfg(feature = "anime")]
+impl ListEntry for AnimeListNode {
+    type Status = UserAnimeListStatus;
+
+    fn entry_id(&self) -> Option<u32> {
+        Some(self.node.id)
+    }
+
+    fn title(&self) -> &str {
+        &self.node.title
+    }
+
+    fn mean(&self) -> Option<f32> {
+        self.node.mean
+    }
+
+    fn start_date(&self) -> Option<&str> {
+        self.node.start_date.as_deref()
+    }
+
+    fn list_status(&self) -> Option<&Self::Status> {
+        self.list_status.as_ref()?.status.as_ref()
+    }
+
+    fn list_score(&self) -> Option<u8> {
+        self.list_status
+            .as_ref()
+            .map(|s| s.score)
+            .filter(|s| *s > 0)
+    }
+
+    fn list_updated_at(&self) -> Option<&str> {
+        self.list_status.as_ref().map(|s| s.updated_at.as_str())
+    }
+}
+
+#[cfg(feature = "anime")]
+impl ListEntry for AnimeRankingNode {
+    type Status = UserAnimeListStatus;
+
+    fn entry_id(&self) -> Option<u32> {
+        Some(self.node.id)
+    }
+
+    fn title(&self) -> &str {
+        &self.node.title
+    }
+
+    fn mean(&self) -> Option<f32> {
+        self.node.mean
+    }
+
+    fn start_date(&self) -> Option<&str> {
+        self.node.start_date.as_deref()
+    }
+
+    fn list_status(&self) -> Option<&Self::Status> {
+        self.node.my_list_status.as_deref()?.status.as_ref()
+    }
+
+    fn list_score(&self) -> Option<u8> {
+        self.node
+            .my_list_status
+            .as_deref()
+            .map(|s| s.score)
+            .filter(|s| *s > 0)
+    }
+
+    fn list_updated_at(&self) -> Option<&str> {
+        self.node
+            .my_list_status
+            .as_deref()
+            .map(|s| s.updated_at.as_str())
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ListEntry for MangaListNode {
+    type Status = UserMangaListStatus;
+
+    fn entry_id(&self) -> Option<u32> {
+        self.node.id
+    }
+
+    fn title(&self) -> &str {
+        self.node.title.as_deref().unwrap_or_default()
+    }
+
+    fn mean(&self) -> Option<f32> {
+        self.node.mean
+    }
+
+    fn start_date(&self) -> Option<&str> {
+        self.node.start_date.as_deref()
+    }
+
+    fn list_status(&self) -> Option<&Self::Status> {
+        self.list_status.as_ref()?.status.as_ref()
+    }
+
+    fn list_score(&self) -> Option<u8> {
+        self.list_status
+            .as_ref()
+            .map(|s| s.score)
+            .filter(|s| *s > 0)
+    }
+
+    fn list_updated_at(&self) -> Option<&str> {
+        self.list_status.as_ref().map(|s| s.updated_at.as_str())
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ListEntry for MangaRankingNode {
+    type Status = UserMangaListStatus;
+
+    fn entry_id(&self) -> Option<u32> {
+        self.node.id
+    }
+
+    fn title(&self) -> &str {
+        self.node.title.as_deref().unwrap_or_default()
+    }
+
+    fn mean(&self) -> Option<f32> {
+        self.node.mean
+    }
+
+    fn start_date(&self) -> Option<&str> {
+        self.node.start_date.as_deref()
+    }
+
+    fn list_status(&self) -> Option<&Self::Status> {
+        self.node.my_list_status.as_ref()?.status.as_ref()
+    }
+
+    fn list_score(&self) -> Option<u8> {
+        self.node
+            .my_list_status
+            .as_ref()
+            .map(|s| s.score)
+            .filter(|s| *s > 0)
+    }
+
+    fn list_updated_at(&self) -> Option<&str> {
+        self.node
+            .my_list_status
+            .as_ref()
+            .map(|s| s.updated_at.as_str())
+    }
+}
+
+/// The differences between an old and new snapshot of a list
+#[derive(Debug)]
+pub struct ListDiff<T> {
+    /// Entries in the new snapshot that were not in the old one
+    pub added: Vec<T>,
+
+    /// Entries in both snapshots whose contents changed
+    pub updated: Vec<T>,
+
+    /// Ids of the entries in the old snapshot that are not in the new one
+    pub removed: Vec<u32>,
+}
+
+impl<T> ListDiff<T> {
+    /// Returns true if the snapshots were identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compute the differences between the `old` and `new` snapshots of a list
+///
+/// Entries are matched by id and compared by their serialized contents.
+/// Entries without an id are ignored.
+pub fn diff_lists<'a, T: ListEntry + Serialize>(old: &[T], new: &'a [T]) -> ListDiff<&'a T> {
+    let old: HashMap<u32, serde_json::Value> = old
+        .iter()
+        .filter_map(|entry| {
+            let id = entry.entry_id()?;
+            Some((id, serde_json::to_value(entry).unwrap_or_default()))
+        })
+        .collect();
+
+    let mut diff = ListDiff {
+        added: vec![],
+        updated: vec![],
+        removed: vec![],
+    };
+    let mut seen = HashSet::with_capacity(new.len());
+
+    for entry in new {
+        let id = match entry.entry_id() {
+            Some(id) => id,
+            None => continue,
+        };
+        seen.insert(id);
+
+        match old.get(&id) {
+            None => diff.added.push(entry),
+            Some(previous) => {
+                if *previous != serde_json::to_value(entry).unwrap_or_default() {
+                    diff.updated.push(entry);
+                }
+            }
+        }
+    }
+
+    diff.removed = old.into_keys().filter(|id| !seen.contains(id)).collect();
+    diff.removed.sort_unstable();
+    diff
+}
+
+/// Patch `list` in place with a diff of it, e.g. from [diff_lists]
+///
+/// Updated entries replace the entries with the same id at their position,
+/// added ones are appended in the order of the diff, and removed ones are
+/// dropped without moving the others. This keeps the order of the list, and
+/// with it e.g. the scroll position and selection of a UI showing it.
+pub fn apply_diff<T, E>(list: &mut Vec<T>, diff: &ListDiff<E>)
+where
+    T: ListEntry + Clone,
+    E: Borrow<T>,
+{
+    let removed: HashSet<u32> = diff.removed.iter().copied().collect();
+    list.retain(|entry| !entry.entry_id().is_some_and(|id| removed.contains(&id)));
+
+    let mut positions: HashMap<u32, usize> = list
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| Some((entry.entry_id()?, i)))
+        .collect();
+    for entry in diff.updated.iter().chain(&diff.added) {
+        let entry = entry.borrow();
+        let id = match entry.entry_id() {
+            Some(id) => id,
+            None => continue,
+        };
+        match positions.get(&id) {
+            Some(&i) => list[i] = entry.clone(),
+            None => {
+                positions.insert(id, list.len());
+                list.push(entry.clone());
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, score: u8) -> AnimeListNode {
+        anime_entry(id).status("watching").score(score).build()
+    }
+
+    #[test]
+    fn test_diff_lists() {
+        let old = vec![entry(1, 5), entry(2, 6), entry(3, 7)];
+        let new = vec![entry(1, 5), entry(2, 8), entry(4, 9)];
+
+        let diff = diff_lists(&old, &new);
+        assert_eq!(
+            diff.added.iter().map(|e| e.node.id).collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(
+            diff.updated.iter().map(|e| e.node.id).collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(diff.removed, vec![3]);
+
+        let diff = diff_lists(&old, &old);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let old = vec![entry(1, 5), entry(2, 6), entry(3, 7), entry(5, 4)];
+        let new = vec![entry(5, 4), entry(2, 8), entry(1, 5), entry(4, 9)];
+
+        let mut list = old.clone();
+        apply_diff(&mut list, &diff_lists(&old, &new));
+        assert_eq!(
+            list.iter().map(|e| e.node.id).collect::<Vec<_>>(),
+            vec![1, 2, 5, 4]
+        );
+        assert_eq!(list[1].list_status.as_ref().unwrap().score, 8);
+        assert!(diff_lists(&list, &new).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Module for filtering fetched lists
+//!
+//! MAL's list endpoints can only filter by status. [ListFilter]s are small
+//! predicates that can be combined, giving a consistent way to select entries
+//! by anything else.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let filter = ListFilter::status(UserAnimeListStatus::Watching)
+//!     .and(ListFilter::min_score(8))
+//!     .or(ListFilter::new(|e: &AnimeListNode| e.node.num_episodes == Some(12)));
+//!
+//! let list = api_client.get_user_anime_list(&query).await.unwrap();
+//! for entry in list.filter(&filter) {
+//!     println!("{}", entry.node.title);
+//! }
+//! ```
+
+use std::ops::Not;
+
+use crate::diff::ListEntry;
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::{AnimeList, AnimeListNode};
+#[cfg(feature = "manga")]
+use crate::manga::responses::{MangaList, MangaListNode};
+
+/// A predicate over list entries, which can be combined with other filters
+pub struct ListFilter<T> {
+    predicate: Box<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<T> ListFilter<T> {
+    /// Create a filter from your own predicate
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Returns true if the entry passes the filter
+    pub fn matches(&self, entry: &T) -> bool {
+        (self.predicate)(entry)
+    }
+}
+
+impl<T: 'static> ListFilter<T> {
+    /// Only keep entries that pass both filters
+    pub fn and(self, other: ListFilter<T>) -> Self {
+        Self::new(move |e| self.matches(e) && other.matches(e))
+    }
+
+    /// Keep entries that pass either filter
+    pub fn or(self, other: ListFilter<T>) -> Self {
+        Self::new(move |e| self.matches(e) || other.matches(e))
+    }
+}
+
+impl<T: ListEntry + 'static> ListFilter<T> {
+    /// Only keep entries with the given list status
+    pub fn status(status: T::Status) -> Self
+    where
+        T::Status: Send + Sync + 'static,
+    {
+        Self::new(move |e: &T| e.list_status() == Some(&status))
+    }
+
+    /// Only keep entries the user scored at least `score`
+    pub fn min_score(score: u8) -> Self {
+        Self::new(move |e: &T| e.list_score().is_some_and(|s| s >= score))
+    }
+
+    /// Only keep entries the user scored at most `score`. Unscored entries are excluded
+    pub fn max_score(score: u8) -> Self {
+        Self::new(move |e: &T| e.list_score().is_some_and(|s| s <= score))
+    }
+
+    /// Only keep entries the user hasn't scored
+    pub fn unscored() -> Self {
+        Self::new(|e: &T| e.list_score().is_none())
+    }
+}
+
+impl<T: 'static> Not for ListFilter<T> {
+    type Output = Self;
+
+    /// Keep the entries that don't pass the filter
+    fn not(self) -> Self {
+        Self::new(move |e| !self.matches(e))
+    }
+}
+
+impl<T> std::fmt::Debug for ListFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ListFilter")
+    }
+}
+
+// Implements `filter` and `retain` for the given lists
+#[cfg(lists)]
+macro_rules! impl_filter {
+    ($($list:ty => $entry:ty),* $(,)?) => {
+        $(
+            impl $list {
+                /// Get the entries that pass the filter
+                pub fn filter(&self, filter: &ListFilter<$entry>) -> Vec<&$entry> {
+                    self.data.iter().filter(|e| filter.matches(e)).collect()
+                }
+
+                /// Remove the entries that don't pass the filter
+                pub fn retain(&mut self, filter: &ListFilter<$entry>) {
+                    self.data.retain(|e| filter.matches(e));
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "anime")]
+impl_filter!(AnimeList => AnimeListNode);
+
+#[cfg(feature = "manga")]
+impl_filter!(MangaList => MangaListNode);
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::anime::requests::UserAnimeListStatus;
+    use crate::fixtures::{anime_entry, anime_list};
+
+    fn entry(id: u32, status: &str, score: u8) -> AnimeListNode {
+        anime_entry(id).status(status).score(score).build()
+    }
+
+    #[test]
+    fn test_list_filter() {
+        let mut list = anime_list(vec![
+            entry(1, "watching", 9),
+            entry(2, "watching", 6),
+            entry(3, "completed", 8),
+            entry(4, "watching", 0),
+        ]);
+        let ids =
+            |entries: Vec<&AnimeListNode>| entries.iter().map(|e| e.node.id).collect::<Vec<_>>();
+
+        let filter =
+            ListFilter::status(UserAnimeListStatus::Watching).and(ListFilter::min_score(8));
+        assert_eq!(ids(list.filter(&filter)), vec![1]);
+
+        let filter = ListFilter::status(UserAnimeListStatus::Completed).or(ListFilter::unscored());
+        assert_eq!(ids(list.filter(&filter)), vec![3, 4]);
+
+        let filter = !ListFilter::max_score(8);
+        assert_eq!(ids(list.filter(&filter)), vec![1, 4]);
+
+        list.retain(&ListFilter::new(|e: &AnimeListNode| e.node.id > 2));
+        assert_eq!(list.data.len(), 2);
+    }
+}
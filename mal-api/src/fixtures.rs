@@ -0,0 +1,297 @@
+//! Responses shared by the unit tests
+//!
+//! The fixtures are deserialized from JSON shaped like MAL's responses, so the
+//! tests go through the same parsing as real responses. Entries are titled
+//! after their id, e.g. "Anime 1", unless a test sets the title.
+
+// Not every fixture is used with every set of features
+#![allow(dead_code)]
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::{AnimeDetails, AnimeFields, AnimeList, AnimeListNode};
+#[cfg(feature = "forum")]
+use crate::forum::responses::{ForumTopic, ForumTopicDetail};
+#[cfg(feature = "manga")]
+use crate::manga::responses::MangaListNode;
+
+// The time every fixture was last updated or created at, unless it's set
+const UPDATED_AT: &str = "2023-01-01T00:00:00+00:00";
+
+/// Deserialize `value`, panicking if it doesn't match the response type
+pub(crate) fn parse<T: DeserializeOwned>(value: Value) -> T {
+    serde_json::from_value(value).unwrap()
+}
+
+// Adds the fields of `extra` to `value`, replacing the fields it already has
+fn merge(value: &mut Value, extra: Value) {
+    if let (Value::Object(value), Value::Object(extra)) = (value, extra) {
+        value.extend(extra);
+    }
+}
+
+/// An entry of a user's anime list
+///
+/// Completed with 0 episodes watched and no score, until changed
+#[cfg(feature = "anime")]
+pub(crate) struct AnimeEntry {
+    node: Value,
+    list_status: Value,
+}
+
+#[cfg(feature = "anime")]
+pub(crate) fn anime_entry(id: u32) -> AnimeEntry {
+    AnimeEntry {
+        node: json!({ "id": id, "title": format!("Anime {}", id) }),
+        list_status: json!({
+            "status": "completed",
+            "score": 0,
+            "num_episodes_watched": 0,
+            "is_rewatching": false,
+            "updated_at": UPDATED_AT
+        }),
+    }
+}
+
+#[cfg(feature = "anime")]
+impl AnimeEntry {
+    pub(crate) fn status(self, status: &str) -> Self {
+        self.list_status(json!({ "status": status }))
+    }
+
+    pub(crate) fn score(self, score: u8) -> Self {
+        self.list_status(json!({ "score": score }))
+    }
+
+    pub(crate) fn watched(self, num_episodes_watched: u32) -> Self {
+        self.list_status(json!({ "num_episodes_watched": num_episodes_watched }))
+    }
+
+    pub(crate) fn updated_at(self, updated_at: &str) -> Self {
+        self.list_status(json!({ "updated_at": updated_at }))
+    }
+
+    /// Set fields of the anime, e.g. `json!({ "num_episodes": 12 })`
+    pub(crate) fn node(mut self, fields: Value) -> Self {
+        merge(&mut self.node, fields);
+        self
+    }
+
+    /// Set fields of the list status, e.g. `json!({ "finish_date": "2023-06-01" })`
+    pub(crate) fn list_status(mut self, fields: Value) -> Self {
+        merge(&mut self.list_status, fields);
+        self
+    }
+
+    pub(crate) fn build(self) -> AnimeListNode {
+        parse(json!({ "node": self.node, "list_status": self.list_status }))
+    }
+}
+
+/// A page of an anime list with the given entries and no other pages
+#[cfg(feature = "anime")]
+pub(crate) fn anime_list(data: Vec<AnimeListNode>) -> AnimeList {
+    let mut list: AnimeList = parse(json!({ "data": [], "paging": {} }));
+    list.data = data;
+    list
+}
+
+/// The fields of an anime, with the given `fields` added to its id and title
+#[cfg(feature = "anime")]
+pub(crate) fn anime_fields(id: u32, fields: Value) -> AnimeFields {
+    let mut anime = json!({ "id": id, "title": format!("Anime {}", id) });
+    merge(&mut anime, fields);
+    parse(anime)
+}
+
+/// The details of an anime, with the given `fields` added to its id and title
+#[cfg(feature = "anime")]
+pub(crate) fn anime_details(id: u32, fields: Value) -> AnimeDetails {
+    let mut details = json!({ "id": id, "title": format!("Anime {}", id) });
+    merge(&mut details, fields);
+    parse(details)
+}
+
+/// An entry of `related_anime` or `related_manga`
+pub(crate) fn related(id: u32, relation_type: &str) -> Value {
+    json!({
+        "node": { "id": id, "title": format!("Title {}", id) },
+        "relation_type": relation_type,
+        "relation_type_formatted": relation_type
+    })
+}
+
+/// An entry of a user's manga list
+///
+/// Completed with nothing read and no score, until changed
+#[cfg(feature = "manga")]
+pub(crate) struct MangaEntry {
+    node: Value,
+    list_status: Value,
+}
+
+#[cfg(feature = "manga")]
+pub(crate) fn manga_entry(id: u32) -> MangaEntry {
+    MangaEntry {
+        node: json!({ "id": id, "title": format!("Manga {}", id) }),
+        list_status: json!({
+            "status": "completed",
+            "score": 0,
+            "num_volumes_read": 0,
+            "num_chapters_read": 0,
+            "is_rereading": false,
+            "updated_at": UPDATED_AT
+        }),
+    }
+}
+
+#[cfg(feature = "manga")]
+impl MangaEntry {
+    pub(crate) fn status(self, status: &str) -> Self {
+        self.list_status(json!({ "status": status }))
+    }
+
+    pub(crate) fn score(self, score: u8) -> Self {
+        self.list_status(json!({ "score": score }))
+    }
+
+    pub(crate) fn read(self, num_chapters_read: u32, num_volumes_read: u32) -> Self {
+        self.list_status(json!({
+            "num_chapters_read": num_chapters_read,
+            "num_volumes_read": num_volumes_read
+        }))
+    }
+
+    /// Set fields of the manga, e.g. `json!({ "num_chapters": 30 })`
+    pub(crate) fn node(mut self, fields: Value) -> Self {
+        merge(&mut self.node, fields);
+        self
+    }
+
+    /// Set fields of the list status
+    pub(crate) fn list_status(mut self, fields: Value) -> Self {
+        merge(&mut self.list_status, fields);
+        self
+    }
+
+    pub(crate) fn build(self) -> MangaListNode {
+        parse(json!({ "node": self.node, "list_status": self.list_status }))
+    }
+}
+
+/// A forum topic with one post, with the given `fields` added
+///
+/// The topic and its last post are by "user", at the same time
+#[cfg(feature = "forum")]
+pub(crate) fn forum_topic(id: u32, fields: Value) -> ForumTopic {
+    let user = json!({ "id": 1, "name": "user" });
+    let mut topic = json!({
+        "id": id,
+        "title": format!("Topic {}", id),
+        "created_at": UPDATED_AT,
+        "created_by": user,
+        "number_of_posts": 1,
+        "last_post_created_at": UPDATED_AT,
+        "last_post_created_by": user,
+        "is_locked": false
+    });
+    merge(&mut topic, fields);
+    parse(topic)
+}
+
+/// A page of a forum topic with the posts of the given numbers
+///
+/// The id of each post is its number plus 100
+#[cfg(feature = "forum")]
+pub(crate) fn forum_topic_detail(numbers: &[u32]) -> ForumTopicDetail {
+    let posts: Vec<_> = numbers
+        .iter()
+        .map(|number| {
+            json!({
+                "id": number + 100,
+                "number": number,
+                "created_at": UPDATED_AT,
+                "created_by": { "id": 1, "name": "user", "forum_avator": "" },
+                "body": format!("Post {}", number),
+                "signature": ""
+            })
+        })
+        .collect();
+    parse(json!({
+        "data": { "title": "Episode 12 Discussion", "posts": posts, "poll": null },
+        "paging": {}
+    }))
+}
+
+/// Serve `body` as the JSON response to every request, like MAL's token endpoint
+///
+/// Returns the URL of the endpoint, which is served until the test's runtime
+/// shuts down
+#[cfg(feature = "oauth")]
+pub(crate) async fn token_endpoint(body: Value) -> String {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/token", listener.local_addr().unwrap());
+    let body = body.to_string();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            // Read the whole request, so the client isn't reset mid-write
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            loop {
+                let Ok(n @ 1..) = stream.read(&mut buf).await else {
+                    break;
+                };
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, form)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            line.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                        })
+                        .unwrap_or(0);
+                    if form.len() >= length {
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    url
+}
+
+/// A [MalOauthState](crate::oauth::MalOauthState) exchanging codes through a
+/// mocked token endpoint, and the CSRF state of a login started with it
+#[cfg(feature = "oauth")]
+pub(crate) async fn pending_login() -> (crate::oauth::MalOauthState, String) {
+    let token_url = token_endpoint(json!({
+        "access_token": "access",
+        "refresh_token": "refresh",
+        "token_type": "Bearer",
+        "expires_in": 3600
+    }))
+    .await;
+    let state = crate::oauth::MalOauthState::new("id", None, "http://127.0.0.1/callback")
+        .with_token_url(token_url);
+    let url = url::Url::parse(&state.authorize_url().await.unwrap()).unwrap();
+    let csrf = url
+        .query_pairs()
+        .find_map(|(name, value)| (name == "state").then(|| value.into_owned()))
+        .unwrap();
+    (state, csrf)
+}
@@ -1,8 +1,14 @@
 //! Module for interacting with the `forum` endpoints
 
+/// Summaries of a user's forum activity
+pub mod activity;
+
 /// Forum API client
 pub mod api;
 
+/// Forum board ids and boards cache
+pub mod boards;
+
 /// Forum API errors
 pub mod error;
 
@@ -11,3 +17,9 @@ pub mod requests;
 
 /// Forum API responses
 pub mod responses;
+
+/// Paging through forum topic search results
+pub mod search;
+
+/// Polling for new forum topics
+pub mod watch;
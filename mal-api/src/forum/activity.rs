@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use super::responses::ForumTopic;
+
+/// Summary of a user's forum activity
+///
+/// Created by [get_user_forum_activity](super::api::ForumApi::get_user_forum_activity)
+#[derive(Debug)]
+pub struct ForumActivity {
+    pub user_name: String,
+
+    /// Topics created by the user, most recently active first
+    pub topics_started: Vec<ForumTopic>,
+
+    /// Topics the user posted in, including the ones they started
+    pub topics_posted_in: Vec<ForumTopic>,
+}
+
+impl ForumActivity {
+    /// Topics the user posted in that were started by someone else
+    pub fn topics_replied_to(&self) -> impl Iterator<Item = &ForumTopic> {
+        let started: HashSet<u32> = self.topics_started.iter().map(|t| t.id).collect();
+        self.topics_posted_in
+            .iter()
+            .filter(move |t| !started.contains(&t.id))
+    }
+
+    /// Number of distinct topics the user started or posted in
+    pub fn topic_count(&self) -> usize {
+        let mut ids: HashSet<u32> = self.topics_posted_in.iter().map(|t| t.id).collect();
+        ids.extend(self.topics_started.iter().map(|t| t.id));
+        ids.len()
+    }
+
+    /// Topics where the user wrote the most recent post
+    pub fn topics_with_last_post(&self) -> impl Iterator<Item = &ForumTopic> {
+        self.topics_posted_in.iter().filter(|t| {
+            t.last_post_created_by
+                .name
+                .eq_ignore_ascii_case(&self.user_name)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::forum_topic;
+
+    fn topic(id: u32, created_by: &str, last_post_by: &str) -> ForumTopic {
+        forum_topic(
+            id,
+            serde_json::json!({
+                "created_by": { "id": 1, "name": created_by },
+                "number_of_posts": 2,
+                "last_post_created_at": "2023-01-02T00:00:00+00:00",
+                "last_post_created_by": { "id": 2, "name": last_post_by }
+            }),
+        )
+    }
+
+    #[test]
+    fn test_forum_activity() {
+        let activity = ForumActivity {
+            user_name: "Xinil".to_string(),
+            topics_started: vec![topic(1, "Xinil", "other"), topic(4, "Xinil", "other")],
+            topics_posted_in: vec![
+                topic(1, "Xinil", "other"),
+                topic(2, "other", "xinil"),
+                topic(3, "other", "other"),
+            ],
+        };
+
+        let replied: Vec<u32> = activity.topics_replied_to().map(|t| t.id).collect();
+        assert_eq!(replied, vec![2, 3]);
+        assert_eq!(activity.topic_count(), 4);
+        let last: Vec<u32> = activity.topics_with_last_post().map(|t| t.id).collect();
+        assert_eq!(last, vec![2]);
+    }
+}
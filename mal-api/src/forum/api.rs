@@ -1,28 +1,36 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use oauth2::{AccessToken, ClientId};
+use bytes::Bytes;
+use reqwest::RequestBuilder;
 use serde::de::DeserializeOwned;
 
 use crate::{
-    common::PagingIter,
-    oauth::{Authenticated, MalClientId, OauthClient},
+    codes::ErrorCode,
+    common::{parse_response, unexpected_response, PagingIter},
+    http::{self, SendThrough, Transport},
+    oauth::{
+        provider::{AuthProvider, AuthState},
+        MalAccessToken, MalClientId,
+    },
     FORUM_URL,
 };
 
+#[cfg(feature = "oauth")]
+use crate::oauth::{Authenticated, OauthClient};
+
 use super::{
+    activity::ForumActivity,
     error::ForumApiError,
     requests::{GetForumTopicDetail, GetForumTopics},
-    responses::{ForumBoards, ForumTopicDetail, ForumTopics},
+    responses::{ForumBoards, ForumTopic, ForumTopicDetail, ForumTopics},
+    search::TopicStream,
+    watch::{NewTopics, TopicWatermark},
 };
 
 #[doc(hidden)]
-#[derive(Debug)]
-pub struct Client {}
-
-#[doc(hidden)]
-#[derive(Debug)]
-pub struct Oauth {}
+pub use crate::oauth::provider::{Client, Oauth};
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -48,13 +56,13 @@ pub struct None {}
 ///
 ///     let topics = api_client.get_forum_boards().await;
 ///     if let Ok(topics) = topics {
-///         println!("Topics: {}\n", topics);
+///         println!("Topics: {}\n", topics.to_json());
 ///     }
 ///
 ///     let query = GetForumTopicDetail::new(481, limit, None).unwrap();
 ///     let response = api_client.get_forum_topic_detail(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Forum topic detail: {}\n", response);
+///         println!("Forum topic detail: {}\n", response.to_json());
 ///     }
 ///
 ///     let query = GetForumTopics::builder()
@@ -65,7 +73,7 @@ pub struct None {}
 ///         .unwrap();
 ///     let response = api_client.get_forum_topics(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Forum topics: {}", response)
+///         println!("Forum topics: {}", response.to_json())
 ///     }
 /// }
 /// ```
@@ -73,66 +81,196 @@ pub struct None {}
 #[derive(Debug, Clone)]
 pub struct ForumApiClient<State = None> {
     client: reqwest::Client,
-    client_id: Option<String>,
-    access_token: Option<String>,
+    transport: Transport,
+    auth: Arc<dyn AuthProvider<State = State>>,
+    adaptive_limit: bool,
     state: PhantomData<State>,
 }
 
-impl From<&AccessToken> for ForumApiClient<Oauth> {
-    fn from(value: &AccessToken) -> Self {
-        ForumApiClient::<Oauth> {
-            client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.secret().clone()),
-            state: PhantomData::<Oauth>,
-        }
-    }
-}
-
-impl From<&ClientId> for ForumApiClient<Client> {
-    fn from(value: &ClientId) -> Self {
-        ForumApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.clone().to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+impl From<&MalAccessToken> for ForumApiClient<Oauth> {
+    fn from(value: &MalAccessToken) -> Self {
+        ForumApiClient::from_auth_provider(value.clone())
     }
 }
 
 impl From<&MalClientId> for ForumApiClient<Client> {
     fn from(value: &MalClientId) -> Self {
-        ForumApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.0.to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+        ForumApiClient::from_auth_provider(value.clone())
     }
 }
 
+#[cfg(feature = "oauth")]
 impl From<&OauthClient<Authenticated>> for ForumApiClient<Oauth> {
     fn from(value: &OauthClient<Authenticated>) -> Self {
+        ForumApiClient::from_auth_provider(value.access_token())
+    }
+}
+
+impl<State: AuthState> ForumApiClient<State> {
+    /// Create a ForumApiClient that gets its credentials from the provider before
+    /// every request
+    ///
+    /// The client gets the [State](AuthProvider::State) of the provider, which
+    /// decides the available endpoints. See the [provider](crate::oauth::provider) module
+    pub fn from_auth_provider<P: AuthProvider<State = State> + 'static>(provider: P) -> Self {
         ForumApiClient {
             client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.get_access_token().secret().clone()),
+            transport: Transport::default(),
+            auth: Arc::new(provider),
+            adaptive_limit: false,
+            state: PhantomData::<State>,
+        }
+    }
+
+    /// Send requests through the given HTTP client instead of the default one
+    ///
+    /// Use [HttpClientBuilder](crate::http::HttpClientBuilder) to configure it
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send requests through the given tower service instead of the HTTP client
+    ///
+    /// Wrap a [MalService](crate::service::MalService) in layers to apply them
+    /// to every request of this client. The HTTP client of the service sends
+    /// the requests then, so configure that one instead of
+    /// [with_http_client](Self::with_http_client). See the [service](crate::service) module
+    #[cfg(feature = "tower")]
+    pub fn with_service<S>(mut self, service: S) -> Self
+    where
+        S: tower_service::Service<
+                crate::service::MalRequest,
+                Response = crate::service::MalResponse,
+            > + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<crate::service::BoxError>,
+        S::Future: Send,
+    {
+        self.transport = Transport::with_service(crate::service::SharedService::new(service));
+        self
+    }
+
+    /// Fetch all topics with the largest page size the endpoint allows
+    ///
+    /// With the adaptive limit, [get_full_forum_topics](ForumApi::get_full_forum_topics)
+    /// requests pages of 100 topics whatever the `limit` of the query. The `limit`
+    /// of the query is still used for single page requests.
+    pub fn with_adaptive_limit(mut self) -> Self {
+        self.adaptive_limit = true;
+        self
+    }
+
+    /// Open a connection to the MAL API ahead of the first request
+    ///
+    /// Resolves DNS and completes the TLS handshake so that the first request your
+    /// users wait on doesn't pay for them. The connection stays in the client's pool.
+    pub async fn warm_up(&self) -> Result<(), ForumApiError> {
+        http::warm_up(&self.client, &self.transport)
+            .await
+            .map_err(|err| {
+                ForumApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to warm up connection: {}", err),
+                )
+            })
+    }
+
+    // Adds the credentials of the auth provider to the request
+    async fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, ForumApiError> {
+        let header = self.auth.auth_header().await?;
+        Ok(header.apply(request))
+    }
+}
+
+impl ForumApiClient<Client> {
+    /// Create an Oauth ForumApiClient after the user logs in
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    #[cfg(feature = "oauth")]
+    pub fn upgrade(&self, oauth_client: &OauthClient<Authenticated>) -> ForumApiClient<Oauth> {
+        self.upgrade_with_token(&oauth_client.access_token())
+    }
+
+    /// Create an Oauth ForumApiClient from an access token, sharing the HTTP client
+    /// and the adaptive limit setting
+    pub fn upgrade_with_token(&self, access_token: &MalAccessToken) -> ForumApiClient<Oauth> {
+        ForumApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(access_token.clone()),
+            adaptive_limit: self.adaptive_limit,
             state: PhantomData::<Oauth>,
         }
     }
 }
 
+impl ForumApiClient<Oauth> {
+    /// Create a Client ForumApiClient, e.g. after the user logs out
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    pub fn downgrade(&self, client_id: &MalClientId) -> ForumApiClient<Client> {
+        ForumApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(client_id.clone()),
+            adaptive_limit: self.adaptive_limit,
+            state: PhantomData::<Client>,
+        }
+    }
+}
+
 /// This trait defines the common request methods available to both
 /// Client and Oauth ForumApiClients
 #[async_trait]
 pub trait Request {
-    async fn get(&self) -> Result<String, ForumApiError>;
+    async fn get(&self) -> Result<Bytes, ForumApiError>;
 
-    async fn get_detail(&self, query: &GetForumTopicDetail) -> Result<String, ForumApiError>;
+    async fn get_detail(&self, query: &GetForumTopicDetail) -> Result<Bytes, ForumApiError>;
 
-    async fn get_topics(&self, query: &GetForumTopics) -> Result<String, ForumApiError>;
+    async fn get_topics(&self, query: &GetForumTopics) -> Result<Bytes, ForumApiError>;
+
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, ForumApiError>;
+
+    /// Returns true if all topics should be fetched with the largest page size
+    /// allowed. See [ForumApiClient::with_adaptive_limit]
+    fn adaptive_limit(&self) -> bool {
+        false
+    }
+}
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, ForumApiError>;
+/// A query for one of the shared forum endpoints that can be sent
+/// with [ForumApi::get_raw]
+#[async_trait]
+pub trait ForumQuery: Send + Sync {
+    #[doc(hidden)]
+    async fn send<R>(&self, request: &R) -> Result<Bytes, ForumApiError>
+    where
+        R: Request + Send + Sync;
+}
+
+#[async_trait]
+impl ForumQuery for GetForumTopicDetail {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, ForumApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_detail(self).await
+    }
+}
+
+#[async_trait]
+impl ForumQuery for GetForumTopics {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, ForumApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_topics(self).await
+    }
 }
 
 /// This trait defines the shared endpoints for Client and Oauth
@@ -147,9 +285,7 @@ pub trait ForumApi {
     /// Corresponds to the [Get forum boards](https://myanimelist.net/apiconfig/references/api/v2#operation/forum_boards_get) endpoint
     async fn get_forum_boards(&self) -> Result<ForumBoards, ForumApiError> {
         let response = self.get_self().get().await?;
-        let result: ForumBoards = serde_json::from_str(response.as_str()).map_err(|err| {
-            ForumApiError::new(format!("Failed to parse Forum Boards result: {}", err))
-        })?;
+        let result: ForumBoards = parse_response("Forum Boards", &response)?;
         Ok(result)
     }
 
@@ -161,12 +297,7 @@ pub trait ForumApi {
         query: &GetForumTopicDetail,
     ) -> Result<ForumTopicDetail, ForumApiError> {
         let response = self.get_self().get_detail(query).await?;
-        let result: ForumTopicDetail = serde_json::from_str(response.as_str()).map_err(|err| {
-            ForumApiError::new(format!(
-                "Failed to parse Forum Topic Details result: {}",
-                err
-            ))
-        })?;
+        let result: ForumTopicDetail = parse_response("Forum Topic Details", &response)?;
         Ok(result)
     }
 
@@ -175,9 +306,100 @@ pub trait ForumApi {
     /// Corresponds to the [Get forum topics](https://myanimelist.net/apiconfig/references/api/v2#operation/forum_topics_get) endpoint
     async fn get_forum_topics(&self, query: &GetForumTopics) -> Result<ForumTopics, ForumApiError> {
         let response = self.get_self().get_topics(query).await?;
-        let result: ForumTopics = serde_json::from_str(response.as_str()).map_err(|err| {
-            ForumApiError::new(format!("Failed to parse Forum Topics result: {}", err))
-        })?;
+        let result: ForumTopics = parse_response("Forum Topics", &response)?;
+        Ok(result)
+    }
+
+    /// Get every topic matching the query by following the pages of the response
+    ///
+    /// Use a large `limit` in the query to keep the number of requests down, or
+    /// enable [with_adaptive_limit](ForumApiClient::with_adaptive_limit) on the client
+    async fn get_full_forum_topics(
+        &self,
+        query: &GetForumTopics,
+    ) -> Result<Vec<ForumTopic>, ForumApiError> {
+        let adapted;
+        let query = if self.get_self().adaptive_limit() {
+            adapted = query.with_max_limit();
+            &adapted
+        } else {
+            query
+        };
+        let mut page = self.get_forum_topics(query).await?;
+        let mut topics = std::mem::take(&mut page.data);
+        while page.next_page().is_some() {
+            page = self.next(&page).await?;
+            topics.append(&mut page.data);
+        }
+        Ok(topics)
+    }
+
+    /// Get the topics matching the query one at a time, fetching the pages as needed
+    ///
+    /// Useful to stop at the first topic of interest, see [TopicStream::find]
+    fn search_topics_stream(&self, query: &GetForumTopics) -> TopicStream<'_, Self>
+    where
+        Self: Sized,
+    {
+        TopicStream::new(self, query.clone())
+    }
+
+    /// Summarize the forum activity of a user
+    ///
+    /// Fetches every topic the user started and every topic they posted in, so
+    /// this can take many requests for active users
+    async fn get_user_forum_activity(
+        &self,
+        user_name: &str,
+    ) -> Result<ForumActivity, ForumApiError> {
+        let started = GetForumTopics::builder()
+            .topic_user_name(user_name)
+            .build()?;
+        let posted_in = GetForumTopics::builder().user_name(user_name).build()?;
+        Ok(ForumActivity {
+            user_name: user_name.to_string(),
+            topics_started: self.get_full_forum_topics(&started).await?,
+            topics_posted_in: self.get_full_forum_topics(&posted_in).await?,
+        })
+    }
+
+    /// Get the topics matching the query that are newer than the watermark
+    ///
+    /// Pages are followed until a topic older than the watermark is reached. Store
+    /// the returned watermark for the next poll. Without a timestamp, only the first
+    /// page is fetched and all of its topics are returned.
+    async fn get_forum_topics_since(
+        &self,
+        query: &GetForumTopics,
+        watermark: &TopicWatermark,
+    ) -> Result<NewTopics, ForumApiError> {
+        let mut page = self.get_forum_topics(query).await?;
+        let mut topics = vec![];
+        loop {
+            let reached_end = page.data.iter().any(|t| watermark.is_past(t));
+            topics.extend(page.data.drain(..).filter(|t| watermark.is_new(t)));
+            if reached_end || watermark.timestamp.is_none() || page.next_page().is_none() {
+                break;
+            }
+            page = self.next(&page).await?;
+        }
+
+        let mut watermark = watermark.clone();
+        watermark.advance(&topics);
+        Ok(NewTopics { topics, watermark })
+    }
+
+    /// Send the query and deserialize the response into your own type
+    ///
+    /// Use this if your application has its own models for the MAL responses
+    /// instead of the ones provided by this crate
+    async fn get_raw<Q, T>(&self, query: &Q) -> Result<T, ForumApiError>
+    where
+        Q: ForumQuery,
+        T: DeserializeOwned,
+    {
+        let response = query.send(self.get_self()).await?;
+        let result: T = parse_response("raw", &response)?;
         Ok(result)
     }
 
@@ -190,8 +412,7 @@ pub trait ForumApi {
             .get_self()
             .get_next_or_prev(response.next_page())
             .await?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| ForumApiError::new(format!("Failed to fetch next page: {}", err)))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -204,8 +425,7 @@ pub trait ForumApi {
             .get_self()
             .get_next_or_prev(response.prev_page())
             .await?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| ForumApiError::new(format!("Failed to fetch next page: {}", err)))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -214,114 +434,75 @@ pub trait ForumApi {
 }
 
 #[async_trait]
-impl Request for ForumApiClient<Client> {
-    async fn get(&self) -> Result<String, ForumApiError> {
+impl<State: AuthState> Request for ForumApiClient<State> {
+    async fn get(&self) -> Result<Bytes, ForumApiError> {
         let response = self
-            .client
-            .get(format!("{}/boards", FORUM_URL))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .send()
+            .authorize(self.client.get(format!("{}/boards", FORUM_URL)))
+            .await?
+            .send_through(&self.transport)
             .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                ForumApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_detail(&self, query: &GetForumTopicDetail) -> Result<String, ForumApiError> {
+    async fn get_detail(&self, query: &GetForumTopicDetail) -> Result<Bytes, ForumApiError> {
         let response = self
-            .client
-            .get(format!("{}/topic/{}", FORUM_URL, query.topic_id))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-            .send()
+            .authorize(
+                self.client
+                    .get(format!("{}/topic/{}", FORUM_URL, query.topic_id)),
+            )
+            .await?
+            .send_through(&self.transport)
             .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                ForumApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_topics(&self, query: &GetForumTopics) -> Result<String, ForumApiError> {
+    async fn get_topics(&self, query: &GetForumTopics) -> Result<Bytes, ForumApiError> {
         let response = self
-            .client
-            .get(format!("{}/topics", FORUM_URL))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/topics", FORUM_URL)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                ForumApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, ForumApiError> {
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, ForumApiError> {
         if let Some(itr) = query {
             let response = self
-                .client
-                .get(itr)
-                .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-                .send()
+                .authorize(self.client.get(itr))
+                .await?
+                .send_through(&self.transport)
                 .await
-                .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
+                .map_err(|err| {
+                    ForumApiError::with_code(
+                        ErrorCode::Request,
+                        format!("Failed get request: {}", err),
+                    )
+                })?;
 
             handle_response(response).await
         } else {
-            Err(ForumApiError::new("Page does not exist".to_string()))
+            Err(ForumApiError::with_code(
+                ErrorCode::NoPage,
+                "Page does not exist".to_string(),
+            ))
         }
     }
-}
-
-#[async_trait]
-impl Request for ForumApiClient<Oauth> {
-    async fn get(&self) -> Result<String, ForumApiError> {
-        let response = self
-            .client
-            .get(format!("{}/boards", FORUM_URL))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .send()
-            .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
 
-        handle_response(response).await
-    }
-
-    async fn get_detail(&self, query: &GetForumTopicDetail) -> Result<String, ForumApiError> {
-        let response = self
-            .client
-            .get(format!("{}/topic/{}", FORUM_URL, query.topic_id))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .send()
-            .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
-
-        handle_response(response).await
-    }
-
-    async fn get_topics(&self, query: &GetForumTopics) -> Result<String, ForumApiError> {
-        let response = self
-            .client
-            .get(format!("{}/topics", FORUM_URL))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
-
-        handle_response(response).await
-    }
-
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, ForumApiError> {
-        if let Some(itr) = query {
-            let response = self
-                .client
-                .get(itr)
-                .bearer_auth(self.access_token.as_ref().unwrap())
-                .send()
-                .await
-                .map_err(|err| ForumApiError::new(format!("Failed get request: {}", err)))?;
-
-            handle_response(response).await
-        } else {
-            Err(ForumApiError::new("Page does not exist".to_string()))
-        }
+    fn adaptive_limit(&self) -> bool {
+        self.adaptive_limit
     }
 }
 
@@ -341,17 +522,19 @@ impl ForumApi for ForumApiClient<Oauth> {
     }
 }
 
-async fn handle_response(response: reqwest::Response) -> Result<String, ForumApiError> {
+async fn handle_response(response: reqwest::Response) -> Result<Bytes, ForumApiError> {
     match response.status() {
         reqwest::StatusCode::OK => {
-            let content = response.text().await.map_err(|err| {
-                ForumApiError::new(format!("Failed to get content from response: {}", err))
+            let content = response.bytes().await.map_err(|err| {
+                ForumApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to get content from response: {}", err),
+                )
             })?;
             Ok(content)
         }
-        _ => Err(ForumApiError::new(format!(
-            "Did not recieve OK response: {}",
-            response.status()
-        ))),
+        _ => Err(unexpected_response("Did not recieve OK response", response)
+            .await
+            .into()),
     }
 }
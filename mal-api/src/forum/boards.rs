@@ -0,0 +1,166 @@
+use tokio::sync::OnceCell;
+
+use super::{
+    api::ForumApi,
+    error::ForumApiError,
+    responses::{Board, ForumBoards, Subboard},
+};
+
+/// Id of a forum board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoardId(pub u32);
+
+/// Id of a forum subboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubboardId(pub u32);
+
+impl From<BoardId> for u32 {
+    fn from(value: BoardId) -> Self {
+        value.0
+    }
+}
+
+impl From<SubboardId> for u32 {
+    fn from(value: SubboardId) -> Self {
+        value.0
+    }
+}
+
+// MyAnimeList
+pub const UPDATES_AND_ANNOUNCEMENTS: BoardId = BoardId(5);
+pub const MAL_GUIDELINES_AND_FAQ: BoardId = BoardId(14);
+pub const DB_MODIFICATION_REQUESTS: BoardId = BoardId(17);
+pub const SUPPORT: BoardId = BoardId(3);
+pub const SUGGESTIONS: BoardId = BoardId(4);
+pub const MAL_CONTESTS: BoardId = BoardId(13);
+
+// Anime & Manga
+pub const NEWS_DISCUSSION: BoardId = BoardId(15);
+pub const ANIME_AND_MANGA_RECOMMENDATIONS: BoardId = BoardId(16);
+pub const ANIME_DISCUSSION: BoardId = BoardId(1);
+pub const MANGA_DISCUSSION: BoardId = BoardId(2);
+
+// General
+pub const INTRODUCTIONS: BoardId = BoardId(8);
+pub const GAMES_COMPUTERS_AND_TECH_SUPPORT: BoardId = BoardId(7);
+pub const MUSIC_AND_ENTERTAINMENT: BoardId = BoardId(10);
+pub const CURRENT_EVENTS: BoardId = BoardId(6);
+pub const CASUAL_DISCUSSION: BoardId = BoardId(11);
+pub const CREATIVE_CORNER: BoardId = BoardId(12);
+pub const FORUM_GAMES: BoardId = BoardId(9);
+
+// Subboards
+pub const ANIME_SERIES: SubboardId = SubboardId(1);
+pub const MANGA_SERIES: SubboardId = SubboardId(4);
+pub const ANIME_DB: SubboardId = SubboardId(2);
+pub const CHARACTER_AND_PEOPLE_DB: SubboardId = SubboardId(3);
+pub const MANGA_DB: SubboardId = SubboardId(5);
+
+impl ForumBoards {
+    /// Get the board with the given id
+    pub fn board(&self, id: BoardId) -> Option<&Board> {
+        self.boards().find(|b| b.id == id.0)
+    }
+
+    /// Get the board with the given title, ignoring case
+    pub fn board_by_title(&self, title: &str) -> Option<&Board> {
+        self.boards().find(|b| b.title.eq_ignore_ascii_case(title))
+    }
+
+    /// Get the subboard with the given id
+    pub fn subboard(&self, id: SubboardId) -> Option<&Subboard> {
+        self.subboards().find(|s| s.id == id.0)
+    }
+
+    /// Get the subboard with the given title, ignoring case
+    pub fn subboard_by_title(&self, title: &str) -> Option<&Subboard> {
+        self.subboards()
+            .find(|s| s.title.eq_ignore_ascii_case(title))
+    }
+
+    fn boards(&self) -> impl Iterator<Item = &Board> {
+        self.categories.iter().flat_map(|c| c.boards.iter())
+    }
+
+    fn subboards(&self) -> impl Iterator<Item = &Subboard> {
+        self.boards().flat_map(|b| b.subboards.iter())
+    }
+}
+
+/// Client-side cache for the [Get forum boards](https://myanimelist.net/apiconfig/references/api/v2#operation/forum_boards_get) endpoint
+///
+/// Forum boards practically never change, so they are fetched once and kept for the
+/// lifetime of the cache. Lookups by id or title after that need no network call.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let api_client = ForumApiClient::from(&client_id);
+/// let cache = BoardsCache::new(api_client);
+///
+/// let boards = cache.get_forum_boards_cached().await.unwrap();
+/// let board = boards.board(boards::ANIME_DISCUSSION).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct BoardsCache<C> {
+    client: C,
+    boards: OnceCell<ForumBoards>,
+}
+
+impl<C> BoardsCache<C>
+where
+    C: ForumApi + Sync,
+{
+    /// Create a new [BoardsCache] around the given ForumApiClient
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            boards: OnceCell::new(),
+        }
+    }
+
+    /// Get the forum boards, fetching them on the first call
+    pub async fn get_forum_boards_cached(&self) -> Result<&ForumBoards, ForumApiError> {
+        self.boards
+            .get_or_try_init(|| self.client.get_forum_boards())
+            .await
+    }
+
+    /// Get a reference to the underlying ForumApiClient
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boards() -> ForumBoards {
+        serde_json::from_value(serde_json::json!({
+            "categories": [{
+                "title": "Anime & Manga",
+                "boards": [{
+                    "id": 1,
+                    "title": "Anime Discussion",
+                    "description": "General anime discussion",
+                    "subboards": [{ "id": 1, "title": "Anime Series" }]
+                }]
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lookups() {
+        let boards = boards();
+        assert_eq!(
+            boards.board(ANIME_DISCUSSION).unwrap().title,
+            "Anime Discussion"
+        );
+        assert_eq!(boards.board_by_title("anime discussion").unwrap().id, 1);
+        assert!(boards.board(MANGA_DISCUSSION).is_none());
+        assert_eq!(boards.subboard(ANIME_SERIES).unwrap().title, "Anime Series");
+        assert_eq!(boards.subboard_by_title("Anime Series").unwrap().id, 1);
+    }
+}
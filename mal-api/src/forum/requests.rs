@@ -1,6 +1,9 @@
 use serde::Serialize;
 
+use super::boards::{BoardId, SubboardId};
 use super::error::ForumApiError;
+use crate::common::{impl_to_query_string, ValidationError};
+use crate::limits::{DEFAULT_LIMIT, FORUM_TOPICS_MAX, FORUM_TOPIC_DETAIL_MAX, MIN_LIMIT};
 
 /// Corresponds to the [Get forum topic detail](https://myanimelist.net/apiconfig/references/api/v2#operation/forum_topic_get) endpoint
 #[derive(Debug, Serialize)]
@@ -20,11 +23,11 @@ impl GetForumTopicDetail {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Result<Self, ForumApiError> {
-        let limit = limit.map(|l| l.clamp(1, 100));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, FORUM_TOPIC_DETAIL_MAX));
 
         Ok(Self {
             topic_id,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
         })
     }
@@ -71,7 +74,7 @@ impl GetForumTopicDetailBuilder {
 }
 
 /// Corresponds to the [Get forum topics](https://myanimelist.net/apiconfig/references/api/v2#operation/forum_topics_get) endpoint
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GetForumTopics {
     nsfw: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -93,6 +96,10 @@ pub struct GetForumTopics {
 impl GetForumTopics {
     /// Create new `Get forum topics` query
     ///
+    /// At least one filter is required. A `board_id` or `subboard_id` alone lists
+    /// the topics of that board, without a search term. Empty strings are treated
+    /// as missing filters.
+    ///
     /// Limit must be within `[1, 100]`
     pub fn new(
         nsfw: bool,
@@ -104,7 +111,10 @@ impl GetForumTopics {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Result<Self, ForumApiError> {
-        let limit = limit.map(|l| l.clamp(1, 100));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, FORUM_TOPICS_MAX));
+        let q = q.filter(|q| !q.is_empty());
+        let topic_user_name = topic_user_name.filter(|n| !n.is_empty());
+        let user_name = user_name.filter(|n| !n.is_empty());
 
         if !(q.is_some()
             || board_id.is_some()
@@ -112,17 +122,14 @@ impl GetForumTopics {
             || topic_user_name.is_some()
             || user_name.is_some())
         {
-            return Err(ForumApiError::new(
-                "At least one of the optional arguments must be Some, excluding limit and offset"
-                    .to_string(),
-            ));
+            return Err(ValidationError::MissingSearchFilter.into());
         }
 
         Ok(Self {
             nsfw,
             board_id,
             subboard_id,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             q,
             topic_user_name,
@@ -135,6 +142,14 @@ impl GetForumTopics {
     pub fn builder() -> GetForumTopicsBuilder {
         GetForumTopicsBuilder::new()
     }
+
+    // The same query with the largest page size the endpoint allows
+    pub(crate) fn with_max_limit(&self) -> Self {
+        Self {
+            limit: FORUM_TOPICS_MAX,
+            ..self.clone()
+        }
+    }
 }
 
 pub struct GetForumTopicsBuilder {
@@ -177,11 +192,21 @@ impl GetForumTopicsBuilder {
         self
     }
 
+    /// Filter by one of the [boards](crate::forum::boards) constants
+    pub fn board(self, value: BoardId) -> Self {
+        self.board_id(value.0)
+    }
+
     pub fn subboard_id(mut self, value: u32) -> Self {
         self.subboard_id = Some(value);
         self
     }
 
+    /// Filter by one of the [boards](crate::forum::boards) subboard constants
+    pub fn subboard(self, value: SubboardId) -> Self {
+        self.subboard_id(value.0)
+    }
+
     pub fn topic_user_name(mut self, value: &str) -> Self {
         self.topic_user_name = Some(value.to_string());
         self
@@ -216,6 +241,8 @@ impl GetForumTopicsBuilder {
     }
 }
 
+impl_to_query_string!(GetForumTopicDetail, GetForumTopics);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,4 +315,31 @@ mod tests {
         );
         assert_eq!(query.unwrap().limit, 100);
     }
+
+    #[test]
+    fn test_get_forum_topics_filters() {
+        let query = GetForumTopics::builder()
+            .board(crate::forum::boards::ANIME_DISCUSSION)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.to_query_string().unwrap(),
+            "nsfw=false&board_id=1&limit=100&offset=0&sort=recent"
+        );
+
+        let query = GetForumTopics::builder().subboard_id(4).build();
+        assert!(query.is_ok());
+
+        let query = GetForumTopics::builder()
+            .subboard(crate::forum::boards::MANGA_SERIES)
+            .build()
+            .unwrap();
+        assert_eq!(query.subboard_id, Some(4));
+
+        let query = GetForumTopics::builder().q("").user_name("").build();
+        assert!(query.is_err());
+
+        let query = GetForumTopics::builder().q("").board_id(1).build().unwrap();
+        assert!(query.q.is_none());
+    }
 }
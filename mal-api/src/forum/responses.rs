@@ -1,19 +1,21 @@
-use std::fmt::Display;
+#[cfg(feature = "unknown-fields")]
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "unknown-fields")]
+use serde_json::Value;
 
-use crate::common::{Paging, PagingIter};
+use crate::{
+    common::{impl_to_json, Paging, PagingIter},
+    urls,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ForumBoards {
     pub categories: Vec<Category>,
 }
 
-impl Display for ForumBoards {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(ForumBoards);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Category {
@@ -21,11 +23,7 @@ pub struct Category {
     pub boards: Vec<Board>,
 }
 
-impl Display for Category {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Category);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Board {
@@ -35,24 +33,30 @@ pub struct Board {
     pub subboards: Vec<Subboard>,
 }
 
-impl Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl Board {
+    /// URL of the board's myanimelist.net page
+    pub fn url(&self) -> String {
+        urls::forum_board_url(self.id)
     }
 }
 
+impl_to_json!(Board);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Subboard {
     pub id: u32,
     pub title: String,
 }
 
-impl Display for Subboard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl Subboard {
+    /// URL of the subboard's myanimelist.net page
+    pub fn url(&self) -> String {
+        urls::forum_subboard_url(self.id)
     }
 }
 
+impl_to_json!(Subboard);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ForumTopicDetail {
     // According to the MAL API reference, this is supposed to be an array.
@@ -61,21 +65,30 @@ pub struct ForumTopicDetail {
     pub paging: Paging,
 }
 
-impl Display for ForumTopicDetail {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl_to_json!(ForumTopicDetail);
+
+impl ForumTopicDetail {
+    /// The post that opened the topic
+    ///
+    /// Only on the first page of the topic, i.e. with no `offset` in the query
+    pub fn first_post(&self) -> Option<&Post> {
+        self.data.posts.iter().find(|post| post.number == 1)
+    }
+
+    /// The latest post of the page
+    ///
+    /// This is the latest post of the topic on its last page, i.e. when there
+    /// is no next page
+    pub fn last_post(&self) -> Option<&Post> {
+        self.data.posts.iter().max_by_key(|post| post.number)
     }
 }
 
 impl PagingIter for ForumTopicDetail {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -86,11 +99,7 @@ pub struct TopicDetail {
     pub poll: Option<Poll>,
 }
 
-impl Display for TopicDetail {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(TopicDetail);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Post {
@@ -104,13 +113,13 @@ pub struct Post {
 
     /// `Warning`: This field can contain raw HTML
     pub signature: String,
+    /// Fields returned by MAL that this crate doesn't know about yet
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-impl Display for Post {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Post);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ForumTopicPostCreatedBy {
@@ -120,11 +129,7 @@ pub struct ForumTopicPostCreatedBy {
     pub forum_avator: String,
 }
 
-impl Display for ForumTopicPostCreatedBy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(ForumTopicPostCreatedBy);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Poll {
@@ -134,11 +139,7 @@ pub struct Poll {
     pub options: Vec<PollOptions>,
 }
 
-impl Display for Poll {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Poll);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PollOptions {
@@ -147,11 +148,7 @@ pub struct PollOptions {
     pub votes: u32,
 }
 
-impl Display for PollOptions {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(PollOptions);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ForumTopics {
@@ -159,21 +156,13 @@ pub struct ForumTopics {
     pub paging: Paging,
 }
 
-impl Display for ForumTopics {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(ForumTopics);
 
 impl PagingIter for ForumTopics {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -187,22 +176,49 @@ pub struct ForumTopic {
     pub last_post_created_at: String,
     pub last_post_created_by: ForumTopicUser,
     pub is_locked: bool,
+    /// Fields returned by MAL that this crate doesn't know about yet
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-impl Display for ForumTopic {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl ForumTopic {
+    /// URL of the topic's myanimelist.net page
+    pub fn url(&self) -> String {
+        urls::forum_topic_url(self.id)
     }
 }
 
+impl_to_json!(ForumTopic);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ForumTopicUser {
     pub id: u32,
     pub name: String,
 }
 
-impl Display for ForumTopicUser {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl ForumTopicUser {
+    /// URL of the user's myanimelist.net profile page
+    pub fn profile_url(&self) -> String {
+        urls::profile_url(&self.name)
+    }
+}
+
+impl_to_json!(ForumTopicUser);
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::forum_topic_detail as detail;
+
+    #[test]
+    fn test_first_and_last_post() {
+        let first_page = detail(&[1, 2, 3]);
+        assert_eq!(first_page.first_post().map(|p| p.id), Some(101));
+        assert_eq!(first_page.last_post().map(|p| p.number), Some(3));
+
+        let second_page = detail(&[4, 5]);
+        assert!(second_page.first_post().is_none());
+        assert_eq!(second_page.last_post().map(|p| p.number), Some(5));
+        assert!(detail(&[]).last_post().is_none());
     }
 }
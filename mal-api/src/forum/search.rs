@@ -0,0 +1,224 @@
+//! Module for paging through the topics of a forum search
+//!
+//! A search can match far more topics than fit in a page, while bots usually
+//! only look for one of them, e.g. the discussion thread of an episode. A
+//! [TopicStream] hands out the topics one at a time and only fetches the next
+//! page once the previous one is used up, so it stops sending requests as soon
+//! as the topic is found. It's a [Stream], so the [StreamExt] combinators work
+//! on it too.
+//!
+//! [StreamExt]: futures::StreamExt
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures::{future::BoxFuture, Stream, StreamExt};
+
+use super::{
+    api::ForumApi,
+    error::ForumApiError,
+    requests::GetForumTopics,
+    responses::{ForumTopic, ForumTopics},
+};
+use crate::common::PagingIter;
+
+/// Topics of a search, fetched a page at a time
+///
+/// Created with [search_topics_stream](ForumApi::search_topics_stream).
+///
+/// ```rust,ignore
+/// let query = GetForumTopics::builder()
+///     .q("Sousou no Frieren Episode 12 Discussion")
+///     .build()
+///     .unwrap();
+/// let mut topics = api_client.search_topics_stream(&query);
+/// let thread = topics
+///     .find(|topic| topic.title.ends_with("Episode 12 Discussion"))
+///     .await
+///     .unwrap();
+/// ```
+pub struct TopicStream<'a, F> {
+    api: &'a F,
+    query: Option<GetForumTopics>,
+    page: Option<ForumTopics>,
+    fetch: Option<BoxFuture<'a, Result<ForumTopics, ForumApiError>>>,
+    topics: VecDeque<ForumTopic>,
+}
+
+impl<'a, F> TopicStream<'a, F> {
+    pub(crate) fn new(api: &'a F, query: GetForumTopics) -> Self {
+        Self {
+            api,
+            query: Some(query),
+            page: None,
+            fetch: None,
+            topics: VecDeque::new(),
+        }
+    }
+}
+
+impl<F: ForumApi + Sync> TopicStream<'_, F> {
+    /// Get the next topic, or `None` once every page has been read
+    ///
+    /// After an error, the stream ends
+    pub async fn next_topic(&mut self) -> Option<Result<ForumTopic, ForumApiError>> {
+        self.next().await
+    }
+
+    /// Get the first of the remaining topics matching the predicate
+    ///
+    /// Stops fetching pages once one is found
+    pub async fn find<P>(&mut self, mut predicate: P) -> Result<Option<ForumTopic>, ForumApiError>
+    where
+        P: FnMut(&ForumTopic) -> bool,
+    {
+        while let Some(topic) = self.next_topic().await {
+            let topic = topic?;
+            if predicate(&topic) {
+                return Ok(Some(topic));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, F: ForumApi + Sync> Stream for TopicStream<'a, F> {
+    type Item = Result<ForumTopic, ForumApiError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(topic) = this.topics.pop_front() {
+                return Poll::Ready(Some(Ok(topic)));
+            }
+
+            let api: &'a F = this.api;
+            let fetch = match this.fetch.as_mut() {
+                Some(fetch) => fetch,
+                None => {
+                    let fetch: BoxFuture<'a, _> = match (this.query.take(), this.page.take()) {
+                        (Some(query), _) => {
+                            Box::pin(async move { api.get_forum_topics(&query).await })
+                        }
+                        (None, Some(page)) if page.next_page().is_some() => {
+                            Box::pin(async move { api.next(&page).await })
+                        }
+                        (None, _) => return Poll::Ready(None),
+                    };
+                    this.fetch.insert(fetch)
+                }
+            };
+            let page = ready!(fetch.as_mut().poll(cx));
+            this.fetch = None;
+            match page {
+                Ok(mut page) => {
+                    this.topics.extend(page.data.drain(..));
+                    this.page = Some(page);
+                }
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::forum_topic,
+        forum::{api::Request, requests::GetForumTopicDetail},
+    };
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    // Serves three pages of two topics each, logging the pages requested
+    struct Search {
+        requested: Mutex<Vec<u32>>,
+    }
+
+    impl Search {
+        fn page(&self, number: u32) -> Result<Bytes, ForumApiError> {
+            self.requested.lock().unwrap().push(number);
+            let data: Vec<_> = [number * 2 - 1, number * 2]
+                .into_iter()
+                .map(|id| forum_topic(id, json!({})))
+                .collect();
+            let next = (number < 3).then(|| format!("page{}", number + 1));
+            let page = json!({ "data": data, "paging": { "next": next } });
+            Ok(Bytes::from(page.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl Request for Search {
+        async fn get(&self) -> Result<Bytes, ForumApiError> {
+            unimplemented!()
+        }
+
+        async fn get_detail(&self, _query: &GetForumTopicDetail) -> Result<Bytes, ForumApiError> {
+            unimplemented!()
+        }
+
+        async fn get_topics(&self, _query: &GetForumTopics) -> Result<Bytes, ForumApiError> {
+            self.page(1)
+        }
+
+        async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, ForumApiError> {
+            let number = query.unwrap().trim_start_matches("page").parse().unwrap();
+            self.page(number)
+        }
+    }
+
+    impl ForumApi for Search {
+        type State = Search;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    fn search() -> Search {
+        Search {
+            requested: Mutex::new(vec![]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_topic_stream() {
+        let query = GetForumTopics::builder().q("Discussion").build().unwrap();
+
+        let api = search();
+        let mut topics = api.search_topics_stream(&query);
+        let mut ids = vec![];
+        while let Some(topic) = topics.next_topic().await {
+            ids.push(topic.unwrap().id);
+        }
+        assert_eq!(ids, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(*api.requested.lock().unwrap(), vec![1, 2, 3]);
+        assert!(topics.next_topic().await.is_none());
+
+        // Pages after the one with the topic aren't fetched
+        let api = search();
+        let topic = api
+            .search_topics_stream(&query)
+            .find(|topic| topic.id == 3)
+            .await
+            .unwrap();
+        assert_eq!(topic.map(|topic| topic.title), Some("Topic 3".to_string()));
+        assert_eq!(*api.requested.lock().unwrap(), vec![1, 2]);
+
+        let api = search();
+        let titles: Vec<String> = api
+            .search_topics_stream(&query)
+            .filter_map(|topic| async move { topic.ok().filter(|t| t.id % 2 == 0) })
+            .map(|topic| topic.title)
+            .collect()
+            .await;
+        assert_eq!(titles, vec!["Topic 2", "Topic 4", "Topic 6"]);
+    }
+}
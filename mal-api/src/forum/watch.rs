@@ -0,0 +1,132 @@
+use super::responses::ForumTopic;
+
+/// Which timestamp of a topic a [TopicWatermark] follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatermarkField {
+    /// Only topics created after the watermark are new
+    #[default]
+    CreatedAt,
+
+    /// Topics with a post after the watermark are new, including older topics
+    LastPostCreatedAt,
+}
+
+/// The newest topic timestamp seen by a subscription
+///
+/// Store the watermark returned by
+/// [get_forum_topics_since](super::api::ForumApi::get_forum_topics_since) and pass it
+/// back on the next poll to only receive the topics that are new since then.
+/// Timestamps are compared as the ISO 8601 strings MAL returns.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TopicWatermark {
+    pub field: WatermarkField,
+
+    /// The newest timestamp seen, or `None` if nothing was polled yet
+    pub timestamp: Option<String>,
+}
+
+impl TopicWatermark {
+    /// Create a watermark that hasn't seen any topics yet
+    pub fn new(field: WatermarkField) -> Self {
+        Self {
+            field,
+            timestamp: None,
+        }
+    }
+
+    /// Create a watermark from a timestamp stored by a previous poll
+    pub fn from_timestamp<T: Into<String>>(field: WatermarkField, timestamp: T) -> Self {
+        Self {
+            field,
+            timestamp: Some(timestamp.into()),
+        }
+    }
+
+    /// Returns true if the topic is newer than the watermark
+    pub fn is_new(&self, topic: &ForumTopic) -> bool {
+        match &self.timestamp {
+            Some(timestamp) => self.value(topic) > timestamp.as_str(),
+            None => true,
+        }
+    }
+
+    // Topics are sorted by their last post, so nothing after an old one can be new
+    pub(crate) fn is_past(&self, topic: &ForumTopic) -> bool {
+        match &self.timestamp {
+            Some(timestamp) => topic.last_post_created_at.as_str() <= timestamp.as_str(),
+            None => false,
+        }
+    }
+
+    pub(crate) fn advance(&mut self, topics: &[ForumTopic]) {
+        let newest = topics.iter().map(|t| self.value(t)).max();
+        match (newest, &self.timestamp) {
+            (Some(newest), Some(timestamp)) if newest <= timestamp.as_str() => {}
+            (Some(newest), _) => self.timestamp = Some(newest.to_string()),
+            (None, _) => {}
+        }
+    }
+
+    fn value<'a>(&self, topic: &'a ForumTopic) -> &'a str {
+        match self.field {
+            WatermarkField::CreatedAt => &topic.created_at,
+            WatermarkField::LastPostCreatedAt => &topic.last_post_created_at,
+        }
+    }
+}
+
+/// Topics found by a poll, along with the watermark to store for the next one
+#[derive(Debug)]
+pub struct NewTopics {
+    /// New topics, most recently active first
+    pub topics: Vec<ForumTopic>,
+    pub watermark: TopicWatermark,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::forum_topic;
+
+    fn topic(id: u32, created_at: &str, last_post_created_at: &str) -> ForumTopic {
+        forum_topic(
+            id,
+            serde_json::json!({
+                "created_at": created_at,
+                "last_post_created_at": last_post_created_at
+            }),
+        )
+    }
+
+    #[test]
+    fn test_watermark() {
+        let topics = vec![
+            topic(3, "2023-01-03T00:00:00+00:00", "2023-01-05T00:00:00+00:00"),
+            topic(1, "2023-01-01T00:00:00+00:00", "2023-01-04T00:00:00+00:00"),
+            topic(2, "2023-01-02T00:00:00+00:00", "2023-01-02T00:00:00+00:00"),
+        ];
+
+        let created =
+            TopicWatermark::from_timestamp(WatermarkField::CreatedAt, "2023-01-01T00:00:00+00:00");
+        let new: Vec<u32> = topics
+            .iter()
+            .filter(|t| created.is_new(t))
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(new, vec![3, 2]);
+
+        let mut last_post = TopicWatermark::from_timestamp(
+            WatermarkField::LastPostCreatedAt,
+            "2023-01-03T00:00:00+00:00",
+        );
+        assert!(last_post.is_new(&topics[1]));
+        assert!(last_post.is_past(&topics[2]));
+
+        last_post.advance(&topics);
+        assert_eq!(
+            last_post.timestamp.as_deref(),
+            Some("2023-01-05T00:00:00+00:00")
+        );
+        assert!(!TopicWatermark::new(WatermarkField::CreatedAt).is_past(&topics[2]));
+    }
+}
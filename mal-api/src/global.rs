@@ -0,0 +1,121 @@
+//! Module for storing configured API clients in a process-wide registry
+//!
+//! Applications such as bots often need the API clients deep inside command
+//! handlers. Instead of passing them through every function, register them once
+//! at startup with [Registry::init], and fetch them anywhere with [global].
+//!
+//! Clients are looked up by their type, so an `AnimeApiClient<Client>` and an
+//! `AnimeApiClient<Oauth>` can be registered side by side. The registry can only
+//! be initialized once, and can't be changed afterwards.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let client_id = MalClientId::try_from_env().unwrap();
+//! Registry::new()
+//!     .with(AnimeApiClient::from(&client_id))
+//!     .with(MangaApiClient::from(&client_id))
+//!     .init()
+//!     .expect("Registry was already initialized");
+//!
+//! // Later, anywhere in the application
+//! let api_client = global().get::<AnimeApiClient<Client>>().unwrap();
+//! let response = api_client.get_anime_list(&query).await.unwrap();
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Set of API clients, keyed by their type
+#[derive(Debug, Default)]
+pub struct Registry {
+    clients: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a client to the registry, replacing any client of the same type
+    pub fn with<T>(mut self, client: T) -> Self
+    where
+        T: Any + Send + Sync,
+    {
+        self.clients.insert(TypeId::of::<T>(), Box::new(client));
+        self
+    }
+
+    /// Get the client of type `T`, if one was registered
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.clients
+            .get(&TypeId::of::<T>())
+            .and_then(|client| client.downcast_ref())
+    }
+
+    /// Returns true if a client of type `T` was registered
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.clients.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Make this the global registry
+    ///
+    /// Returns the registry back if the global registry was already initialized
+    pub fn init(self) -> Result<(), Registry> {
+        REGISTRY.set(self)
+    }
+}
+
+/// Get the global registry
+///
+/// # Panics
+///
+/// Panics if [Registry::init] wasn't called yet. Use [try_global] to check first
+pub fn global() -> &'static Registry {
+    try_global().expect("mal_api global registry was not initialized")
+}
+
+/// Get the global registry, or `None` if [Registry::init] wasn't called yet
+pub fn try_global() -> Option<&'static Registry> {
+    REGISTRY.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct FirstClient(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct SecondClient(&'static str);
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::new()
+            .with(FirstClient(1))
+            .with(FirstClient(2))
+            .with(SecondClient("client"));
+        assert_eq!(registry.get::<FirstClient>(), Some(&FirstClient(2)));
+        assert_eq!(
+            registry.get::<SecondClient>(),
+            Some(&SecondClient("client"))
+        );
+        assert!(!registry.contains::<u32>());
+
+        // The global registry is shared by the whole test binary, so it's only set here
+        assert!(registry.init().is_ok());
+        assert_eq!(global().get::<FirstClient>(), Some(&FirstClient(2)));
+        assert!(Registry::new().init().is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Module for tracking weekly watching and reading goals
+//!
+//! MAL doesn't record when episodes were watched, so progress is measured between
+//! two snapshots of a user's list: one taken at the start of the week, and the
+//! current one. The episodes or chapters gained on each entry are added up with
+//! [diff_lists](crate::diff::diff_lists).
+//!
+//! Entries added during the week count with all of their progress. Progress that
+//! went down, e.g. because a rewatch was started, counts as nothing.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! // Stored when the week started
+//! let start_of_week: Vec<AnimeListNode> = load_snapshot();
+//! let current = api_client.get_full_user_anime_list(&query).await.unwrap();
+//!
+//! let progress = WeeklyGoal::new(20).progress(&start_of_week, &current);
+//! println!("{} of {} episodes this week", progress.done, progress.target);
+//! if !progress.is_on_pace(3.0) {
+//!     println!("{} episodes left to catch up", progress.remaining());
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::diff::{diff_lists, ListEntry};
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::AnimeListNode;
+#[cfg(feature = "manga")]
+use crate::manga::responses::MangaListNode;
+
+/// A list entry with countable progress
+pub trait ProgressEntry: ListEntry {
+    /// Episodes watched or chapters read, if the list status was returned
+    fn progress(&self) -> Option<u32>;
+}
+
+#[cfg(feature = "anime")]
+impl ProgressEntry for AnimeListNode {
+    fn progress(&self) -> Option<u32> {
+        self.list_status.as_ref().map(|s| s.num_episodes_watched)
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ProgressEntry for MangaListNode {
+    fn progress(&self) -> Option<u32> {
+        self.list_status.as_ref().map(|s| s.num_chapters_read)
+    }
+}
+
+/// A number of episodes or chapters to get through each week
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeeklyGoal {
+    pub target: u32,
+}
+
+impl WeeklyGoal {
+    pub fn new(target: u32) -> Self {
+        Self { target }
+    }
+
+    /// Measure the progress made between the snapshot from the start of the week
+    /// and the current one
+    pub fn progress<T: ProgressEntry + Serialize>(
+        &self,
+        start: &[T],
+        current: &[T],
+    ) -> GoalProgress {
+        let before: HashMap<u32, u32> = start
+            .iter()
+            .filter_map(|e| Some((e.entry_id()?, e.progress()?)))
+            .collect();
+
+        let diff = diff_lists(start, current);
+        let added = diff.added.iter().filter_map(|e| e.progress());
+        let updated = diff.updated.iter().filter_map(|e| {
+            let previous = e.entry_id().and_then(|id| before.get(&id)).copied();
+            Some(e.progress()?.saturating_sub(previous.unwrap_or_default()))
+        });
+
+        GoalProgress {
+            target: self.target,
+            done: added.chain(updated).sum(),
+        }
+    }
+}
+
+/// Progress towards a [WeeklyGoal]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoalProgress {
+    pub target: u32,
+
+    /// Episodes or chapters gained since the start of the week
+    pub done: u32,
+}
+
+impl GoalProgress {
+    /// Returns true if the target was reached
+    pub fn is_met(&self) -> bool {
+        self.done >= self.target
+    }
+
+    /// Episodes or chapters left to reach the target
+    pub fn remaining(&self) -> u32 {
+        self.target.saturating_sub(self.done)
+    }
+
+    /// Share of the target that was reached, capped at `1.0`
+    ///
+    /// A target of 0 is always complete
+    pub fn fraction(&self) -> f32 {
+        if self.target == 0 {
+            return 1.0;
+        }
+        (self.done as f32 / self.target as f32).min(1.0)
+    }
+
+    /// Returns true if the progress keeps up with an even pace through the week,
+    /// `days_elapsed` days after it started
+    pub fn is_on_pace(&self, days_elapsed: f32) -> bool {
+        let expected = self.target as f32 * (days_elapsed / 7.0).clamp(0.0, 1.0);
+        self.done as f32 >= expected
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, watched: u32) -> AnimeListNode {
+        anime_entry(id).status("watching").watched(watched).build()
+    }
+
+    #[test]
+    fn test_weekly_goal() {
+        let start = vec![entry(1, 3), entry(2, 10), entry(3, 5)];
+        // 1 gained 4, 2 restarted for a rewatch, 3 is unchanged, 4 was added
+        let current = vec![entry(1, 7), entry(2, 1), entry(3, 5), entry(4, 2)];
+
+        let progress = WeeklyGoal::new(10).progress(&start, &current);
+        assert_eq!(progress.done, 6);
+        assert_eq!(progress.remaining(), 4);
+        assert!(!progress.is_met());
+        assert_eq!(progress.fraction(), 0.6);
+        assert!(progress.is_on_pace(3.5));
+        assert!(!progress.is_on_pace(7.0));
+
+        assert!(WeeklyGoal::new(0).progress(&start, &start).is_met());
+    }
+}
@@ -0,0 +1,251 @@
+//! Module for the distribution of the scores of anime and manga lists
+//!
+//! A [ScoreHistogram] counts how many entries were given each score from 1 to
+//! 10. Entries that weren't scored, i.e. with a score of `0`, are not counted.
+//! Histograms of several users can be [merged](ScoreHistogram::merge), e.g. for
+//! the score distribution of a community.
+//!
+//! The `statistics` field of the anime details endpoint only has the number of
+//! users per list status, so MAL's own score distribution of an anime can't be
+//! fetched. Counts from another source can be used with
+//! [from_counts](ScoreHistogram::from_counts).
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let histogram = mal_api::histogram::fetch_user_anime(&api_client, "user").await.unwrap();
+//!
+//! for (score, share) in (1..=10).zip(histogram.normalized()) {
+//!     println!("{:>2}: {:.1}%", score, share * 100.0);
+//! }
+//! println!("median: {:?}", histogram.median());
+//! println!("90th percentile: {:?}", histogram.percentile(90.0));
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "anime")]
+use crate::anime::{
+    api::AnimeApi, error::AnimeApiError, requests::GetUserAnimeList, responses::AnimeListNode,
+};
+#[cfg(feature = "manga")]
+use crate::manga::responses::MangaListNode;
+
+/// Number of entries given each score from 1 to 10
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScoreHistogram {
+    counts: [u32; 10],
+}
+
+impl ScoreHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a histogram from the counts of the scores 1 to 10, in that order
+    pub fn from_counts(counts: [u32; 10]) -> Self {
+        Self { counts }
+    }
+
+    /// Histogram of the scores of a user's anime list
+    ///
+    /// Entries without a `list_status` are skipped
+    #[cfg(feature = "anime")]
+    pub fn from_anime_list(entries: &[AnimeListNode]) -> Self {
+        entries
+            .iter()
+            .filter_map(|e| Some(e.list_status.as_ref()?.score))
+            .collect()
+    }
+
+    /// Histogram of the scores of a user's manga list
+    ///
+    /// Entries without a `list_status` are skipped
+    #[cfg(feature = "manga")]
+    pub fn from_manga_list(entries: &[MangaListNode]) -> Self {
+        entries
+            .iter()
+            .filter_map(|e| Some(e.list_status.as_ref()?.score))
+            .collect()
+    }
+
+    /// Count a score. Scores outside of `1..=10` are ignored
+    pub fn add(&mut self, score: u8) {
+        if (1..=10).contains(&score) {
+            self.counts[score as usize - 1] += 1;
+        }
+    }
+
+    /// Add the counts of another histogram to this one
+    pub fn merge(&mut self, other: &ScoreHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+
+    /// Number of entries given the score, `0` for scores outside of `1..=10`
+    pub fn count(&self, score: u8) -> u32 {
+        match score {
+            1..=10 => self.counts[score as usize - 1],
+            _ => 0,
+        }
+    }
+
+    /// Counts of the scores 1 to 10, in that order
+    pub fn counts(&self) -> &[u32; 10] {
+        &self.counts
+    }
+
+    /// Number of scored entries
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|c| u64::from(*c)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    /// Share of the entries given each score from 1 to 10, within `[0, 1]`
+    ///
+    /// All zeros if the histogram is empty
+    pub fn normalized(&self) -> [f64; 10] {
+        let total = self.total();
+        if total == 0 {
+            return [0.0; 10];
+        }
+        self.counts.map(|c| f64::from(c) / total as f64)
+    }
+
+    /// Mean score, or `None` if the histogram is empty
+    pub fn mean(&self) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let sum: u64 = self.iter().map(|(s, c)| u64::from(s) * u64::from(c)).sum();
+        Some(sum as f64 / total as f64)
+    }
+
+    /// Score at the `p`th percentile, with `p` within `[0, 100]`
+    ///
+    /// Uses the nearest-rank method, so the result is always one of the scores
+    /// given. Returns `None` if the histogram is empty.
+    pub fn percentile(&self, p: f64) -> Option<u8> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((p.clamp(0.0, 100.0) / 100.0 * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (score, count) in self.iter() {
+            seen += u64::from(count);
+            if seen >= rank {
+                return Some(score);
+            }
+        }
+        None
+    }
+
+    /// Score at the 50th percentile
+    pub fn median(&self) -> Option<u8> {
+        self.percentile(50.0)
+    }
+
+    /// Percentage of the entries given the score or lower, within `[0, 100]`
+    ///
+    /// Returns `None` if the histogram is empty
+    pub fn percentile_rank(&self, score: u8) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let at_or_below: u64 = self
+            .iter()
+            .take_while(|(s, _)| *s <= score)
+            .map(|(_, c)| u64::from(c))
+            .sum();
+        Some(at_or_below as f64 / total as f64 * 100.0)
+    }
+
+    /// Iterate over `(score, count)` from 1 to 10
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u32)> + '_ {
+        (1..=10).zip(self.counts.iter().copied())
+    }
+}
+
+impl FromIterator<u8> for ScoreHistogram {
+    fn from_iter<I: IntoIterator<Item = u8>>(scores: I) -> Self {
+        let mut histogram = Self::new();
+        for score in scores {
+            histogram.add(score);
+        }
+        histogram
+    }
+}
+
+/// Fetch the whole anime list of a user and build the histogram of its scores
+#[cfg(feature = "anime")]
+pub async fn fetch_user_anime<A>(api: &A, user_name: &str) -> Result<ScoreHistogram, AnimeApiError>
+where
+    A: AnimeApi + Sync,
+{
+    let query = GetUserAnimeList::builder(user_name)
+        .enable_nsfw()
+        .limit(1000)
+        .build()?;
+    let list = api.get_full_user_anime_list(&query).await?;
+    Ok(ScoreHistogram::from_anime_list(&list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_histogram() {
+        let histogram: ScoreHistogram = [7, 8, 8, 9, 10, 0, 11].into_iter().collect();
+        assert_eq!(histogram.total(), 5);
+        assert_eq!(histogram.count(8), 2);
+        assert_eq!(histogram.count(0), 0);
+        assert_eq!(histogram.mean(), Some(8.4));
+        assert_eq!(histogram.median(), Some(8));
+        assert_eq!(histogram.percentile(0.0), Some(7));
+        assert_eq!(histogram.percentile(80.0), Some(9));
+        assert_eq!(histogram.percentile(100.0), Some(10));
+        assert_eq!(histogram.percentile_rank(8), Some(60.0));
+        assert_eq!(histogram.normalized()[7], 0.4);
+
+        let mut merged = histogram;
+        merged.merge(&ScoreHistogram::from_counts([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(merged.total(), 6);
+        assert_eq!(merged.percentile(0.0), Some(1));
+
+        let empty = ScoreHistogram::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.median(), None);
+        assert_eq!(empty.percentile_rank(5), None);
+        assert_eq!(empty.normalized(), [0.0; 10]);
+    }
+
+    #[cfg(feature = "anime")]
+    #[test]
+    fn test_from_anime_list() {
+        use crate::fixtures::{anime_entry, parse};
+        use crate::well_known::{COWBOY_BEBOP_ANIME_ID, ONE_PIECE_ANIME_ID};
+
+        let entries = vec![
+            // Not on the user's list
+            parse(
+                serde_json::json!({ "node": { "id": COWBOY_BEBOP_ANIME_ID, "title": "Cowboy Bebop" } }),
+            ),
+            anime_entry(ONE_PIECE_ANIME_ID)
+                .status("watching")
+                .score(9)
+                .watched(100)
+                .build(),
+        ];
+        let histogram = ScoreHistogram::from_anime_list(&entries);
+        assert_eq!(histogram.total(), 1);
+        assert_eq!(histogram.count(9), 1);
+    }
+}
@@ -0,0 +1,194 @@
+//! Module for configuring the HTTP client used by the API clients
+//!
+//! Every API client sends its requests through a `reqwest::Client`. By default
+//! a client with reqwest's defaults is used. Build one with [HttpClientBuilder]
+//! and pass it to the API client's `with_http_client` to change how requests are sent.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let http_client = HttpClientBuilder::new()
+//!     .pool_max_idle_per_host(32)
+//!     .tcp_keepalive(Some(Duration::from_secs(60)))
+//!     .build()
+//!     .unwrap();
+//! let api_client = AnimeApiClient::from(&client_id).with_http_client(http_client);
+//! ```
+
+use std::time::Duration;
+
+#[cfg(api)]
+const API_URL: &str = "https://api.myanimelist.net";
+
+/// Builder for the `reqwest::Client` used by the API clients
+///
+/// Settings that are not set keep reqwest's defaults
+#[derive(Debug, Clone)]
+pub struct HttpClientBuilder {
+    compression: bool,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Option<Duration>>,
+    tcp_keepalive: Option<Duration>,
+    http1_only: bool,
+    http2_prior_knowledge: bool,
+}
+
+impl HttpClientBuilder {
+    pub fn new() -> Self {
+        Self {
+            compression: true,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            http1_only: false,
+            http2_prior_knowledge: false,
+        }
+    }
+
+    /// Send `Accept-Encoding: gzip, br` and decode compressed responses
+    ///
+    /// Enabled by default. Full-field list responses are several times smaller
+    /// when compressed, so only disable this if you are debugging raw responses.
+    pub fn compression(mut self, value: bool) -> Self {
+        self.compression = value;
+        self
+    }
+
+    /// Maximum number of idle connections to keep open to MAL
+    ///
+    /// Raise this for services that send many requests concurrently
+    pub fn pool_max_idle_per_host(mut self, value: usize) -> Self {
+        self.pool_max_idle_per_host = Some(value);
+        self
+    }
+
+    /// How long idle connections are kept open, or `None` to never close them
+    pub fn pool_idle_timeout(mut self, value: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(value);
+        self
+    }
+
+    /// Send TCP keep-alive probes on open connections at the given interval
+    pub fn tcp_keepalive(mut self, value: Option<Duration>) -> Self {
+        self.tcp_keepalive = value;
+        self
+    }
+
+    /// Only use HTTP/1.1
+    pub fn http1_only(mut self, value: bool) -> Self {
+        self.http1_only = value;
+        self
+    }
+
+    /// Use HTTP/2 without negotiating it first
+    pub fn http2_prior_knowledge(mut self, value: bool) -> Self {
+        self.http2_prior_knowledge = value;
+        self
+    }
+
+    pub fn build(self) -> Result<reqwest::Client, reqwest::Error> {
+        let mut builder = reqwest::Client::builder()
+            .gzip(self.compression)
+            .brotli(self.compression)
+            .tcp_keepalive(self.tcp_keepalive);
+
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder.build()
+    }
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error of sending a request, either from the HTTP client or from the
+/// service the requests are sent through
+#[cfg(api)]
+pub(crate) type SendError = Box<dyn std::error::Error + Send + Sync>;
+
+// How an API client sends its requests: with the HTTP client of the request,
+// or through the tower service set with `with_service`
+#[cfg(api)]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Transport {
+    #[cfg(feature = "tower")]
+    service: Option<crate::service::SharedService>,
+}
+
+#[cfg(api)]
+impl Transport {
+    #[cfg(feature = "tower")]
+    pub(crate) fn with_service(service: crate::service::SharedService) -> Self {
+        Self {
+            service: Some(service),
+        }
+    }
+
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SendError> {
+        #[cfg(feature = "tower")]
+        if let Some(service) = &self.service {
+            return service.send(request).await;
+        }
+        Ok(request.send().await?)
+    }
+}
+
+// Lets the API clients send a request with `.send_through(&self.transport)`
+// in place of `.send()`
+#[cfg(api)]
+#[async_trait::async_trait]
+pub(crate) trait SendThrough {
+    async fn send_through(self, transport: &Transport) -> Result<reqwest::Response, SendError>;
+}
+
+#[cfg(api)]
+#[async_trait::async_trait]
+impl SendThrough for reqwest::RequestBuilder {
+    async fn send_through(self, transport: &Transport) -> Result<reqwest::Response, SendError> {
+        transport.send(self).await
+    }
+}
+
+// Any response means the connection is open, so the status is ignored
+#[cfg(api)]
+pub(crate) async fn warm_up(
+    client: &reqwest::Client,
+    transport: &Transport,
+) -> Result<(), SendError> {
+    client.head(API_URL).send_through(transport).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        assert!(HttpClientBuilder::new().build().is_ok());
+        assert!(HttpClientBuilder::new().compression(false).build().is_ok());
+        assert!(HttpClientBuilder::new()
+            .pool_max_idle_per_host(32)
+            .pool_idle_timeout(None)
+            .tcp_keepalive(Some(Duration::from_secs(60)))
+            .http2_prior_knowledge(true)
+            .build()
+            .is_ok());
+    }
+}
@@ -53,6 +53,9 @@
 //!     - Implements all of the [user](https://myanimelist.net/apiconfig/references/api/v2#tag/user) MAL API endpoints
 //!     - Can be created from a MAL Oauth access token
 //!
+//! Every client can also be created from an [AuthProvider](oauth::provider::AuthProvider)
+//! with `from_auth_provider`, e.g. to refresh tokens or to read them from a secrets service.
+//!
 //! # Anime and Manga Fields
 //!
 //! `mal-api` provides utilities to ensure that the fields you want returned from the
@@ -109,14 +112,14 @@
 //!         .build()
 //!         .unwrap();
 //!     let result = api_client.get_anime_list(&query).await.unwrap();
-//!     println!("Result: {}", &result);
+//!     println!("Result: {}", result.to_json());
 //!
 //!     // Example iterating through pages
 //!     let result = api_client.next(&result).await.unwrap();
-//!     println!("Next result: {}", &result);
+//!     println!("Next result: {}", result.to_json());
 //!
 //!     let result = api_client.prev(&result).await.unwrap();
-//!     println!("Prev result: {}", &result);
+//!     println!("Prev result: {}", result.to_json());
 //!
 //!     // Manga API example
 //!     let api_client = MangaApiClient::from(&client_id);
@@ -127,7 +130,7 @@
 //!     let limit = Some(5);
 //!     let query = GetMangaList::new("one".to_string(), nsfw, Some(&fields), limit, None).unwrap();
 //!     let result = api_client.get_manga_list(&query).await.unwrap();
-//!     println!("Result: {}", result);
+//!     println!("Result: {}", result.to_json());
 //! }
 //! ```
 //!
@@ -199,7 +202,15 @@
 //! }
 //! ```
 
+// Lets the code generated by `#[derive(MalProjection)]` use `::mal_api` paths
+// in this crate's own tests
+#[cfg(feature = "derive")]
+extern crate self as mal_api;
+
+#[cfg(feature = "anime")]
 pub mod anime;
+
+#[cfg(feature = "manga")]
 pub mod manga;
 
 #[cfg(feature = "forum")]
@@ -208,14 +219,93 @@ pub mod forum;
 #[cfg(feature = "user")]
 pub mod user;
 
+pub mod activity;
+pub mod bulk;
+pub mod cache;
+pub mod clock;
+pub mod codes;
 pub mod common;
+
+#[cfg(feature = "anime")]
+pub mod community;
+
+#[cfg(feature = "anime")]
+pub mod compare;
+
+#[cfg(feature = "polars")]
+pub mod dataframe;
+
+pub mod dates;
+pub mod diff;
+pub mod filter;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+#[cfg(lists)]
+pub mod goals;
+
+#[cfg(feature = "global")]
+pub mod global;
+
+#[cfg(lists)]
+pub mod histogram;
+
+pub mod http;
+pub mod limits;
 pub mod macros;
 pub mod oauth;
+pub mod poll;
+pub mod projection;
+
+#[cfg(lists)]
+pub mod random;
+
+pub mod ranking;
+pub mod rate_limit;
 
+#[cfg(feature = "render")]
+pub mod render;
+
+#[cfg(lists)]
+pub mod reports;
+
+pub mod schema;
+
+#[cfg(feature = "tower")]
+pub mod service;
+
+pub mod sort;
+
+#[cfg(feature = "anime")]
+pub mod stats;
+
+#[cfg(feature = "stream-parse")]
+pub mod stream;
+
+#[cfg(feature = "anime")]
+pub mod taste;
+
+pub mod title_index;
+pub mod titles;
+pub mod urls;
+pub mod well_known;
+
+#[cfg(feature = "wiki")]
+pub mod wiki;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(feature = "oauth")]
 const OAUTH_URL: &'static str = "https://myanimelist.net/v1/oauth2/authorize";
+#[cfg(feature = "oauth")]
 const OAUTH_TOKEN_URL: &'static str = "https://myanimelist.net/v1/oauth2/token";
+#[cfg(feature = "anime")]
 const ANIME_URL: &'static str = "https://api.myanimelist.net/v2/anime";
+#[cfg(feature = "manga")]
 const MANGA_URL: &'static str = "https://api.myanimelist.net/v2/manga";
+#[cfg(users)]
 const USER_URL: &'static str = "https://api.myanimelist.net/v2/users";
 
 #[cfg(feature = "forum")]
@@ -223,14 +313,24 @@ const FORUM_URL: &'static str = "https://api.myanimelist.net/v2/forum";
 
 /// Module re-exports
 pub mod prelude {
-    pub use crate::oauth::{MalClientId, OauthClient};
+    pub use crate::common::{
+        DeleteOutcome, EntryLookup, FieldError, FieldSet, MalError, Priority, RewatchValue,
+    };
+    pub use crate::http::HttpClientBuilder;
+    pub use crate::oauth::{MalAccessToken, MalClientId};
+    pub use crate::titles::TitlePreference;
+
+    #[cfg(feature = "oauth")]
+    pub use crate::oauth::OauthClient;
 
+    #[cfg(feature = "anime")]
     pub use crate::anime::{
         api::{AnimeApi, AnimeApiClient},
         requests::*,
         responses::*,
     };
 
+    #[cfg(feature = "manga")]
     pub use crate::manga::{
         api::{MangaApi, MangaApiClient},
         requests::*,
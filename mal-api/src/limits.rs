@@ -0,0 +1,92 @@
+//! Module for the page size limits of the MAL API endpoints
+//!
+//! Every query with a `limit` clamps it to the range allowed by its endpoint,
+//! and uses [DEFAULT_LIMIT] when it isn't set. Use these constants to build
+//! page size pickers or validate input without hardcoding the values, and
+//! [check_limit] to reject a page size instead of clamping it.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let page_sizes: Vec<u16> = [25, 50, 100, 250, 500, 1000]
+//!     .into_iter()
+//!     .filter(|size| *size <= limits::USER_LIST_MAX)
+//!     .collect();
+//! ```
+
+use crate::common::ValidationError;
+
+/// Smallest `limit` of every endpoint
+pub const MIN_LIMIT: u16 = 1;
+
+/// `limit` used by every query when it isn't set
+pub const DEFAULT_LIMIT: u16 = 100;
+
+/// Largest `limit` of the `Get anime list` endpoint
+pub const ANIME_LIST_MAX: u16 = 100;
+
+/// Largest `limit` of the `Get anime ranking` endpoint
+pub const ANIME_RANKING_MAX: u16 = 500;
+
+/// Largest `limit` of the `Get seasonal anime` endpoint
+pub const SEASONAL_ANIME_MAX: u16 = 500;
+
+/// Largest `limit` of the `Get suggested anime` endpoint
+pub const SUGGESTED_ANIME_MAX: u16 = 100;
+
+/// Largest `limit` of the `Get user anime list` and `Get user manga list` endpoints
+pub const USER_LIST_MAX: u16 = 1000;
+
+/// Largest `limit` of the `Get manga list` endpoint
+pub const MANGA_LIST_MAX: u16 = 100;
+
+/// Largest `limit` of the `Get manga ranking` endpoint
+pub const MANGA_RANKING_MAX: u16 = 500;
+
+/// Largest `limit` of the `Get suggested manga` endpoint, which MAL doesn't have
+/// yet. The same as for anime
+pub const SUGGESTED_MANGA_MAX: u16 = 100;
+
+/// Largest `limit` of the `Get forum topic detail` endpoint
+pub const FORUM_TOPIC_DETAIL_MAX: u16 = 100;
+
+/// Largest `limit` of the `Get forum topics` endpoint
+pub const FORUM_TOPICS_MAX: u16 = 100;
+
+/// Check a `limit`, e.g. from user input, against the maximum of an endpoint
+///
+/// Returns [LimitOutOfRange](ValidationError::LimitOutOfRange) if the query
+/// would have clamped it
+pub fn check_limit(limit: u16, max: u16) -> Result<u16, ValidationError> {
+    if (MIN_LIMIT..=max).contains(&limit) {
+        Ok(limit)
+    } else {
+        Err(ValidationError::LimitOutOfRange {
+            min: MIN_LIMIT,
+            max,
+            got: limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_limit() {
+        assert_eq!(check_limit(100, ANIME_LIST_MAX), Ok(100));
+        assert_eq!(
+            check_limit(0, ANIME_LIST_MAX),
+            Err(ValidationError::LimitOutOfRange {
+                min: 1,
+                max: 100,
+                got: 0
+            })
+        );
+        assert_eq!(
+            check_limit(1001, USER_LIST_MAX).unwrap_err().to_string(),
+            "Limit must be between 1 and 1000 inclusive, got 1001"
+        );
+    }
+}
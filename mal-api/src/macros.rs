@@ -1,6 +1,7 @@
 //! Module for ease-of-use macros
 
 /// Macro for creating a vector of valid AnimeCommonFields
+#[cfg(feature = "anime")]
 #[macro_export]
 macro_rules! anime_common_fields {
     ($($variant:path),* $(,)?) => {
@@ -9,12 +10,13 @@ macro_rules! anime_common_fields {
             $(
                 v.push($variant);
             )*
-            AnimeCommonFields(v)
+            $crate::anime::requests::AnimeCommonFields::new(v)
         }
     };
 }
 
 /// Macro for creating a vector of valid AnimeDetailFields
+#[cfg(feature = "anime")]
 #[macro_export]
 macro_rules! anime_detail_fields {
     ($($variant:path),* $(,)?) => {
@@ -23,12 +25,13 @@ macro_rules! anime_detail_fields {
             $(
                 v.push($variant);
             )*
-            AnimeDetailFields(v)
+            $crate::anime::requests::AnimeDetailFields::new(v)
         }
     };
 }
 
 /// Macro for creating a vector of valid MangaCommonFields
+#[cfg(feature = "manga")]
 #[macro_export]
 macro_rules! manga_common_fields {
     ($($variant:path),* $(,)?) => {
@@ -37,12 +40,13 @@ macro_rules! manga_common_fields {
             $(
                 v.push($variant);
             )*
-            MangaFields(v)
+            $crate::manga::requests::MangaCommonFields::new(v)
         }
     };
 }
 
 /// Macro for creating a vector of valid MangaDetailFields
+#[cfg(feature = "manga")]
 #[macro_export]
 macro_rules! manga_detail_fields {
     ($($variant:path),* $(,)?) => {
@@ -51,7 +55,7 @@ macro_rules! manga_detail_fields {
             $(
                 v.push($variant);
             )*
-            MangaDetailFields(v)
+            $crate::manga::requests::MangaDetailFields::new(v)
         }
     };
 }
@@ -66,7 +70,7 @@ macro_rules! user_fields {
             $(
                 v.push($variant);
             )*
-            UserFields(v)
+            $crate::user::requests::UserFields::new(v)
         }
     };
 }
@@ -1,34 +1,101 @@
 //! Module for interacting with the `manga` and `user mangalist` endpoints
 
 use self::requests::{MangaCommonFields, MangaDetail, MangaDetailFields, MangaField};
-use strum::IntoEnumIterator;
 
 /// Manga API client
 pub mod api;
 
+/// Finding the manga of an author
+pub mod authors;
+
 /// Manga API errors
 pub mod error;
 
+/// Manga details loader
+pub mod loader;
+
+/// Manga reading time estimates
+pub mod reading_time;
+
 /// Manga API request structs
 pub mod requests;
 
 /// Manga API responses
 pub mod responses;
 
+/// Grouping manga by serialization magazine
+pub mod serialization;
+
+/// All of the possible [MangaField] fields
+pub const MANGA_COMMON_FIELDS: &[MangaField] = &[
+    MangaField::id,
+    MangaField::title,
+    MangaField::main_picture,
+    MangaField::alternative_titles,
+    MangaField::start_date,
+    MangaField::end_date,
+    MangaField::synopsis,
+    MangaField::mean,
+    MangaField::rank,
+    MangaField::popularity,
+    MangaField::num_list_users,
+    MangaField::num_scoring_users,
+    MangaField::nsfw,
+    MangaField::genres,
+    MangaField::created_at,
+    MangaField::updated_at,
+    MangaField::media_type,
+    MangaField::status,
+    MangaField::my_list_status,
+    MangaField::num_volumes,
+    MangaField::num_chapters,
+    MangaField::authors,
+];
+
+/// All of the possible [MangaField] fields, formatted for the `fields` query parameter
+pub const MANGA_COMMON_FIELDS_STR: &str = "id,title,main_picture,alternative_titles,start_date,end_date,synopsis,mean,rank,popularity,num_list_users,num_scoring_users,nsfw,genres,created_at,updated_at,media_type,status,my_list_status,num_volumes,num_chapters,authors";
+
+/// All of the possible [MangaDetail] fields
+pub const MANGA_DETAIL_FIELDS: &[MangaDetail] = &[
+    MangaDetail::id,
+    MangaDetail::title,
+    MangaDetail::main_picture,
+    MangaDetail::alternative_titles,
+    MangaDetail::start_date,
+    MangaDetail::end_date,
+    MangaDetail::synopsis,
+    MangaDetail::mean,
+    MangaDetail::rank,
+    MangaDetail::popularity,
+    MangaDetail::num_list_users,
+    MangaDetail::num_scoring_users,
+    MangaDetail::nsfw,
+    MangaDetail::genres,
+    MangaDetail::created_at,
+    MangaDetail::updated_at,
+    MangaDetail::media_type,
+    MangaDetail::status,
+    MangaDetail::my_list_status,
+    MangaDetail::num_volumes,
+    MangaDetail::num_chapters,
+    MangaDetail::authors,
+    MangaDetail::pictures,
+    MangaDetail::background,
+    MangaDetail::related_anime,
+    MangaDetail::related_manga,
+    MangaDetail::recommendations,
+    MangaDetail::serialization,
+];
+
+/// All of the possible [MangaDetail] fields, formatted for the `fields` query parameter
+pub const MANGA_DETAIL_FIELDS_STR: &str = "id,title,main_picture,alternative_titles,start_date,end_date,synopsis,mean,rank,popularity,num_list_users,num_scoring_users,nsfw,genres,created_at,updated_at,media_type,status,my_list_status,num_volumes,num_chapters,authors,pictures,background,related_anime,related_manga,recommendations,serialization";
+
 /// Return all of the possible [MangaField] values
 pub fn all_common_fields() -> MangaCommonFields {
-    let mut vec = Vec::with_capacity(MangaField::iter().len());
-    for variant in MangaField::iter() {
-        vec.push(variant);
-    }
-    MangaCommonFields(vec)
+    MangaCommonFields::with_formatted(MANGA_COMMON_FIELDS.to_vec(), MANGA_COMMON_FIELDS_STR)
 }
 
 /// Return all of the possible [MangaDetail] fields
 pub fn all_detail_fields() -> MangaDetailFields {
-    let mut vec = Vec::with_capacity(MangaDetail::iter().len());
-    for variant in MangaDetail::iter() {
-        vec.push(variant);
-    }
-    MangaDetailFields(vec)
+    MangaDetailFields::with_formatted(MANGA_DETAIL_FIELDS.to_vec(), MANGA_DETAIL_FIELDS_STR)
 }
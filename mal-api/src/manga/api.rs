@@ -1,29 +1,43 @@
 use super::{error::MangaApiError, requests::GetUserMangaList, responses::MangaListStatus};
 use async_trait::async_trait;
-use oauth2::{AccessToken, ClientId};
+use bytes::Bytes;
 use serde::{de::DeserializeOwned, Serialize};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use crate::{
-    common::{struct_to_form_data, PagingIter},
+    bulk::{run_bulk, BulkOptions, BulkResult},
+    codes::ErrorCode,
+    common::{
+        parse_response, struct_to_form_data, unexpected_response, DeleteOutcome, EntryLookup,
+        PagingIter, UnsupportedEndpoint,
+    },
+    http::{self, SendThrough, Transport},
     manga::requests::{DeleteMyMangaListItem, UpdateMyMangaListStatus},
-    oauth::{Authenticated, MalClientId, OauthClient},
+    oauth::{
+        provider::{AuthProvider, AuthState},
+        MalAccessToken, MalClientId,
+    },
     MANGA_URL, USER_URL,
 };
 
+#[cfg(feature = "oauth")]
+use crate::oauth::{Authenticated, OauthClient};
+
 use super::{
-    requests::{GetMangaDetails, GetMangaList, GetMangaRanking},
-    responses::{MangaDetails, MangaList, MangaRanking},
+    requests::{
+        GetMangaDetails, GetMangaList, GetMangaRanking, GetSuggestedManga, MangaDetail,
+        MangaDetailFields,
+    },
+    responses::{MangaDetails, MangaList, MangaListNode, MangaRanking, SuggestedManga},
 };
-use reqwest;
+use reqwest::{self, RequestBuilder};
 
-#[doc(hidden)]
-#[derive(Debug)]
-pub struct Client {}
+#[cfg(feature = "stream-parse")]
+use crate::stream::ListStream;
 
 #[doc(hidden)]
-#[derive(Debug)]
-pub struct Oauth {}
+pub use crate::oauth::provider::{Client, Oauth};
 
 #[doc(hidden)]
 #[derive(Debug)]
@@ -45,6 +59,7 @@ pub struct None {}
 /// use dotenvy;
 /// use mal_api::oauth::MalClientId;
 /// use mal_api::prelude::*;
+/// use mal_api::well_known::ONE_PUNCH_MAN_MANGA_ID;
 ///
 /// #[tokio::main]
 /// async fn main() {
@@ -62,16 +77,16 @@ pub struct None {}
 ///         .unwrap();
 ///     let response = api_client.get_manga_list(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Response: {}\n", response);
+///         println!("Response: {}\n", response.to_json());
 ///     }
 ///
-///     let query = GetMangaDetails::builder(44347)
+///     let query = GetMangaDetails::builder(ONE_PUNCH_MAN_MANGA_ID)
 ///         .fields(&detail_fields)
 ///         .build()
 ///         .unwrap();
 ///     let response = api_client.get_manga_details(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Response: {}\n", response);
+///         println!("Response: {}\n", response.to_json());
 ///     }
 ///
 ///     let query = GetMangaRanking::builder(MangaRankingType::All)
@@ -82,7 +97,7 @@ pub struct None {}
 ///         .unwrap();
 ///     let response = api_client.get_manga_ranking(&query).await;
 ///     if let Ok(response) = response {
-///         println!("Response: {}\n", response);
+///         println!("Response: {}\n", response.to_json());
 ///     }
 /// }
 /// ```
@@ -90,217 +105,328 @@ pub struct None {}
 #[derive(Debug, Clone)]
 pub struct MangaApiClient<State = None> {
     client: reqwest::Client,
-    client_id: Option<String>,
-    access_token: Option<String>,
+    transport: Transport,
+    auth: Arc<dyn AuthProvider<State = State>>,
+    suggestions_path: Option<String>,
+    adaptive_limit: bool,
     state: PhantomData<State>,
 }
 
-impl From<&AccessToken> for MangaApiClient<Oauth> {
-    fn from(value: &AccessToken) -> Self {
-        MangaApiClient::<Oauth> {
-            client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.secret().clone()),
-            state: PhantomData::<Oauth>,
-        }
-    }
-}
-
-impl From<&ClientId> for MangaApiClient<Client> {
-    fn from(value: &ClientId) -> Self {
-        MangaApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.clone().to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+impl From<&MalAccessToken> for MangaApiClient<Oauth> {
+    fn from(value: &MalAccessToken) -> Self {
+        MangaApiClient::from_auth_provider(value.clone())
     }
 }
 
 impl From<&MalClientId> for MangaApiClient<Client> {
     fn from(value: &MalClientId) -> Self {
-        MangaApiClient::<Client> {
-            client: reqwest::Client::new(),
-            client_id: Some(value.0.to_string()),
-            access_token: None,
-            state: PhantomData::<Client>,
-        }
+        MangaApiClient::from_auth_provider(value.clone())
     }
 }
 
+#[cfg(feature = "oauth")]
 impl From<&OauthClient<Authenticated>> for MangaApiClient<Oauth> {
     fn from(value: &OauthClient<Authenticated>) -> Self {
+        MangaApiClient::from_auth_provider(value.access_token())
+    }
+}
+
+impl<State: AuthState> MangaApiClient<State> {
+    /// Create a MangaApiClient that gets its credentials from the provider before
+    /// every request
+    ///
+    /// The client gets the [State](AuthProvider::State) of the provider, which
+    /// decides the available endpoints. See the [provider](crate::oauth::provider) module
+    pub fn from_auth_provider<P: AuthProvider<State = State> + 'static>(provider: P) -> Self {
         MangaApiClient {
             client: reqwest::Client::new(),
-            client_id: None,
-            access_token: Some(value.get_access_token().secret().clone()),
+            transport: Transport::default(),
+            auth: Arc::new(provider),
+            suggestions_path: None,
+            adaptive_limit: false,
+            state: PhantomData::<State>,
+        }
+    }
+
+    /// Send requests through the given HTTP client instead of the default one
+    ///
+    /// Use [HttpClientBuilder](crate::http::HttpClientBuilder) to configure it
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send requests through the given tower service instead of the HTTP client
+    ///
+    /// Wrap a [MalService](crate::service::MalService) in layers to apply them
+    /// to every request of this client. The HTTP client of the service sends
+    /// the requests then, so configure that one instead of
+    /// [with_http_client](Self::with_http_client). See the [service](crate::service) module
+    #[cfg(feature = "tower")]
+    pub fn with_service<S>(mut self, service: S) -> Self
+    where
+        S: tower_service::Service<
+                crate::service::MalRequest,
+                Response = crate::service::MalResponse,
+            > + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<crate::service::BoxError>,
+        S::Future: Send,
+    {
+        self.transport = Transport::with_service(crate::service::SharedService::new(service));
+        self
+    }
+
+    /// Send [get_suggested_manga](MangaApiClient::get_suggested_manga) requests to
+    /// the given path under the manga endpoints, e.g. `suggestions`
+    ///
+    /// MAL doesn't have a suggested manga endpoint yet. Set this if it ships one
+    /// to use it without waiting for a new version of this crate
+    pub fn with_suggestions_path(mut self, path: impl Into<String>) -> Self {
+        self.suggestions_path = Some(path.into());
+        self
+    }
+
+    /// Fetch full lists with the largest page size the endpoint allows
+    ///
+    /// By default, [get_full_user_manga_list](MangaApi::get_full_user_manga_list)
+    /// follows the pages with the `limit` of the query, which is 100 unless set.
+    /// With the adaptive limit, it requests pages of
+    /// [USER_LIST_MAX](crate::limits::USER_LIST_MAX) entries instead, so a full
+    /// list takes a tenth of the requests. The `limit` of the query is
+    /// still used for single page requests.
+    pub fn with_adaptive_limit(mut self) -> Self {
+        self.adaptive_limit = true;
+        self
+    }
+
+    /// Open a connection to the MAL API ahead of the first request
+    ///
+    /// Resolves DNS and completes the TLS handshake so that the first request your
+    /// users wait on doesn't pay for them. The connection stays in the client's pool.
+    pub async fn warm_up(&self) -> Result<(), MangaApiError> {
+        http::warm_up(&self.client, &self.transport)
+            .await
+            .map_err(|err| {
+                MangaApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to warm up connection: {}", err),
+                )
+            })
+    }
+
+    // Adds the credentials of the auth provider to the request
+    async fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, MangaApiError> {
+        let header = self.auth.auth_header().await?;
+        Ok(header.apply(request))
+    }
+}
+
+impl MangaApiClient<Client> {
+    /// Create an Oauth MangaApiClient after the user logs in
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    #[cfg(feature = "oauth")]
+    pub fn upgrade(&self, oauth_client: &OauthClient<Authenticated>) -> MangaApiClient<Oauth> {
+        self.upgrade_with_token(&oauth_client.access_token())
+    }
+
+    /// Create an Oauth MangaApiClient from an access token, sharing the HTTP client
+    /// and the adaptive limit setting
+    pub fn upgrade_with_token(&self, access_token: &MalAccessToken) -> MangaApiClient<Oauth> {
+        MangaApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(access_token.clone()),
+            suggestions_path: self.suggestions_path.clone(),
+            adaptive_limit: self.adaptive_limit,
             state: PhantomData::<Oauth>,
         }
     }
 }
 
+impl MangaApiClient<Oauth> {
+    /// Create a Client MangaApiClient, e.g. after the user logs out
+    ///
+    /// The HTTP client and the adaptive limit setting are shared with this client,
+    /// so its configuration and open connections are kept
+    pub fn downgrade(&self, client_id: &MalClientId) -> MangaApiClient<Client> {
+        MangaApiClient {
+            client: self.client.clone(),
+            transport: self.transport.clone(),
+            auth: Arc::new(client_id.clone()),
+            suggestions_path: self.suggestions_path.clone(),
+            adaptive_limit: self.adaptive_limit,
+            state: PhantomData::<Client>,
+        }
+    }
+}
+
 /// This trait defines the common request methods available to both
 /// Client and Oauth MangaApiClients
 #[async_trait]
 pub trait Request {
-    async fn get<T>(&self, query: &T) -> Result<String, MangaApiError>
+    async fn get<T>(&self, query: &T) -> Result<Bytes, MangaApiError>
     where
         T: Serialize + Send + Sync;
 
-    async fn get_details(&self, query: &GetMangaDetails) -> Result<String, MangaApiError>;
+    async fn get_details(&self, query: &GetMangaDetails) -> Result<Bytes, MangaApiError>;
+
+    async fn get_ranking(&self, query: &GetMangaRanking) -> Result<Bytes, MangaApiError>;
 
-    async fn get_ranking(&self, query: &GetMangaRanking) -> Result<String, MangaApiError>;
+    async fn get_user(&self, query: &GetUserMangaList) -> Result<Bytes, MangaApiError>;
 
-    async fn get_user(&self, query: &GetUserMangaList) -> Result<String, MangaApiError>;
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, MangaApiError>;
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, MangaApiError>;
+    /// Returns true if full lists should be fetched with the largest page size
+    /// allowed. See [MangaApiClient::with_adaptive_limit]
+    fn adaptive_limit(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
-impl Request for MangaApiClient<Client> {
-    async fn get<T>(&self, query: &T) -> Result<String, MangaApiError>
+impl<State: AuthState> Request for MangaApiClient<State> {
+    async fn get<T>(&self, query: &T) -> Result<Bytes, MangaApiError>
     where
         T: Serialize + Send + Sync,
     {
         let response = self
-            .client
-            .get(MANGA_URL)
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
+            .authorize(self.client.get(MANGA_URL))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_details(&self, query: &GetMangaDetails) -> Result<String, MangaApiError> {
+    async fn get_details(&self, query: &GetMangaDetails) -> Result<Bytes, MangaApiError> {
         let response = self
-            .client
-            .get(format!("{}/{}", MANGA_URL, query.manga_id))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/{}", MANGA_URL, query.manga_id)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_ranking(&self, query: &GetMangaRanking) -> Result<String, MangaApiError> {
+    async fn get_ranking(&self, query: &GetMangaRanking) -> Result<Bytes, MangaApiError> {
         let response = self
-            .client
-            .get(format!("{}/ranking", MANGA_URL))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
+            .authorize(self.client.get(format!("{}/ranking", MANGA_URL)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_user(&self, query: &GetUserMangaList) -> Result<String, MangaApiError> {
+    async fn get_user(&self, query: &GetUserMangaList) -> Result<Bytes, MangaApiError> {
         let response = self
-            .client
-            .get(format!("{}/{}/mangalist", USER_URL, query.user_name))
-            .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/mangalist", USER_URL, query.user_name)),
+            )
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, MangaApiError> {
+    async fn get_next_or_prev(&self, query: Option<&str>) -> Result<Bytes, MangaApiError> {
         if let Some(itr) = query {
             let response = self
-                .client
-                .get(itr)
-                .header("X-MAL-CLIENT-ID", self.client_id.as_ref().unwrap())
-                .send()
+                .authorize(self.client.get(itr))
+                .await?
+                .send_through(&self.transport)
                 .await
-                .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+                .map_err(|err| {
+                    MangaApiError::with_code(
+                        ErrorCode::Request,
+                        format!("Failed get request: {}", err),
+                    )
+                })?;
 
             handle_response(response).await
         } else {
-            Err(MangaApiError::new("Page does not exist".to_string()))
+            Err(MangaApiError::with_code(
+                ErrorCode::NoPage,
+                "Page does not exist".to_string(),
+            ))
         }
     }
+
+    fn adaptive_limit(&self) -> bool {
+        self.adaptive_limit
+    }
 }
 
+/// A query for one of the shared manga endpoints that can be sent
+/// with [MangaApi::get_raw]
 #[async_trait]
-impl Request for MangaApiClient<Oauth> {
-    async fn get<T>(&self, query: &T) -> Result<String, MangaApiError>
+pub trait MangaQuery: Send + Sync {
+    #[doc(hidden)]
+    async fn send<R>(&self, request: &R) -> Result<Bytes, MangaApiError>
     where
-        T: Serialize + std::marker::Send + std::marker::Sync,
-    {
-        let response = self
-            .client
-            .get(MANGA_URL)
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
-
-        handle_response(response).await
-    }
-
-    async fn get_details(&self, query: &GetMangaDetails) -> Result<String, MangaApiError> {
-        let response = self
-            .client
-            .get(format!("{}/{}", MANGA_URL, query.manga_id))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
+        R: Request + Send + Sync;
+}
 
-        handle_response(response).await
+#[async_trait]
+impl MangaQuery for GetMangaList {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, MangaApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get(self).await
     }
+}
 
-    async fn get_ranking(&self, query: &GetMangaRanking) -> Result<String, MangaApiError> {
-        let response = self
-            .client
-            .get(format!("{}/ranking", MANGA_URL))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
-
-        handle_response(response).await
+#[async_trait]
+impl MangaQuery for GetMangaDetails {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, MangaApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_details(self).await
     }
+}
 
-    async fn get_user(&self, query: &GetUserMangaList) -> Result<String, MangaApiError> {
-        let response = self
-            .client
-            .get(format!("{}/{}/mangalist", USER_URL, query.user_name))
-            .bearer_auth(self.access_token.as_ref().unwrap())
-            .query(&query)
-            .send()
-            .await
-            .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
-
-        handle_response(response).await
+#[async_trait]
+impl MangaQuery for GetMangaRanking {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, MangaApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_ranking(self).await
     }
+}
 
-    async fn get_next_or_prev(&self, query: Option<&String>) -> Result<String, MangaApiError> {
-        if let Some(itr) = query {
-            let response = self
-                .client
-                .get(itr)
-                .bearer_auth(self.access_token.as_ref().unwrap())
-                .send()
-                .await
-                .map_err(|err| MangaApiError::new(format!("Failed get request: {}", err)))?;
-
-            handle_response(response).await
-        } else {
-            Err(MangaApiError::new("Page does not exist".to_string()))
-        }
+#[async_trait]
+impl MangaQuery for GetUserMangaList {
+    async fn send<R>(&self, request: &R) -> Result<Bytes, MangaApiError>
+    where
+        R: Request + Send + Sync,
+    {
+        request.get_user(self).await
     }
 }
 
@@ -316,9 +442,7 @@ pub trait MangaApi {
     /// Corresponds to the [Get manga list](https://myanimelist.net/apiconfig/references/api/v2#operation/manga_get) endpoint
     async fn get_manga_list(&self, query: &GetMangaList) -> Result<MangaList, MangaApiError> {
         let response = self.get_self().get(query).await?;
-        let result: MangaList = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse MangaList result: {}", err))
-        })?;
+        let result: MangaList = parse_response("Manga List", &response)?;
         Ok(result)
     }
 
@@ -330,12 +454,45 @@ pub trait MangaApi {
         query: &GetMangaDetails,
     ) -> Result<MangaDetails, MangaApiError> {
         let response = self.get_self().get_details(query).await?;
-        let result: MangaDetails = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse MangaList result: {}", err))
-        })?;
+        let result: MangaDetails = parse_response("Manga Details", &response)?;
         Ok(result)
     }
 
+    /// Get the details of a manga, telling apart entries that moved or are gone
+    ///
+    /// Use this when the id may be stale, e.g. when importing old lists
+    async fn resolve_manga_details(
+        &self,
+        query: &GetMangaDetails,
+    ) -> Result<EntryLookup<MangaDetails>, MangaApiError> {
+        match self.get_manga_details(query).await {
+            Ok(details) => match details.shared_fields.id {
+                Some(id) if id != query.manga_id => Ok(EntryLookup::Moved {
+                    new_id: id,
+                    entry: details,
+                }),
+                _ => Ok(EntryLookup::Found(details)),
+            },
+            Err(err) if err.code() == ErrorCode::NotFound => Ok(EntryLookup::Gone),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check if a manga with the given id exists, fetching only its id
+    ///
+    /// Returns `Ok(false)` for ids MAL doesn't know, such as deleted entries, and an
+    /// error for anything else, such as network errors, so stale ids can be told
+    /// apart from transient failures
+    async fn manga_exists(&self, manga_id: u32) -> Result<bool, MangaApiError> {
+        let fields = MangaDetailFields::new(vec![MangaDetail::id]);
+        let query = GetMangaDetails::new(manga_id, true, Some(&fields))?;
+        match self.get_self().get_details(&query).await {
+            Ok(_) => Ok(true),
+            Err(err) if err.code() == ErrorCode::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Get the ranking of manga
     ///
     /// Corresponds to the [Get manga ranking](https://myanimelist.net/apiconfig/references/api/v2#operation/manga_ranking_get) endpoint
@@ -344,9 +501,7 @@ pub trait MangaApi {
         query: &GetMangaRanking,
     ) -> Result<MangaRanking, MangaApiError> {
         let response = self.get_self().get_ranking(query).await?;
-        let result: MangaRanking = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse MangaList result: {}", err))
-        })?;
+        let result: MangaRanking = parse_response("Manga Ranking", &response)?;
         Ok(result)
     }
 
@@ -360,14 +515,51 @@ pub trait MangaApi {
         query: &GetUserMangaList,
     ) -> Result<MangaList, MangaApiError> {
         if query.user_name == "@me".to_string() {
-            return Err(MangaApiError::new(
+            return Err(MangaApiError::with_code(
+                ErrorCode::Unauthorized,
                 "You can only get your list via an Oauth client".to_string(),
             ));
         }
         let response = self.get_self().get_user(query).await?;
-        let result: MangaList = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
+        let result: MangaList = parse_response("Manga List", &response)?;
+        Ok(result)
+    }
+
+    /// Get every entry of a users manga list by following the pages of the response
+    ///
+    /// Use a large `limit` in the query to keep the number of requests down, or
+    /// enable [with_adaptive_limit](MangaApiClient::with_adaptive_limit) on the client
+    async fn get_full_user_manga_list(
+        &self,
+        query: &GetUserMangaList,
+    ) -> Result<Vec<MangaListNode>, MangaApiError> {
+        let adapted;
+        let query = if self.get_self().adaptive_limit() {
+            adapted = query.with_max_limit();
+            &adapted
+        } else {
+            query
+        };
+        let mut page = self.get_user_manga_list(query).await?;
+        let mut entries = std::mem::take(&mut page.data);
+        while page.next_page().is_some() {
+            page = self.next(&page).await?;
+            entries.append(&mut page.data);
+        }
+        Ok(entries)
+    }
+
+    /// Send the query and deserialize the response into your own type
+    ///
+    /// Use this if your application has its own models for the MAL responses
+    /// instead of the ones provided by this crate
+    async fn get_raw<Q, T>(&self, query: &Q) -> Result<T, MangaApiError>
+    where
+        Q: MangaQuery,
+        T: DeserializeOwned,
+    {
+        let response = query.send(self.get_self()).await?;
+        let result: T = parse_response("raw", &response)?;
         Ok(result)
     }
 
@@ -380,8 +572,7 @@ pub trait MangaApi {
             .get_self()
             .get_next_or_prev(response.next_page())
             .await?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| MangaApiError::new(format!("Failed to fetch next page: {}", err)))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -394,8 +585,7 @@ pub trait MangaApi {
             .get_self()
             .get_next_or_prev(response.prev_page())
             .await?;
-        let result: T = serde_json::from_str(response.as_str())
-            .map_err(|err| MangaApiError::new(format!("Failed to fetch next page: {}", err)))?;
+        let result: T = parse_response("page", &response)?;
         Ok(result)
     }
 
@@ -412,6 +602,44 @@ impl MangaApi for MangaApiClient<Client> {
     }
 }
 
+impl MangaApiClient<Client> {
+    /// Get a users manga list, decoding the entries as they are received
+    ///
+    /// Use this instead of `get_user_manga_list` for large pages, such as when
+    /// exporting lists with `limit=1000`. See the [stream](crate::stream) module.
+    #[cfg(feature = "stream-parse")]
+    pub async fn get_user_manga_list_stream(
+        &self,
+        query: &GetUserMangaList,
+    ) -> Result<ListStream<MangaListNode>, MangaApiError> {
+        if query.user_name == "@me" {
+            return Err(MangaApiError::with_code(
+                ErrorCode::Unauthorized,
+                "You can only get your '@me' list via an Oauth client".to_string(),
+            ));
+        }
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/mangalist", USER_URL, query.user_name)),
+            )
+            .await?
+            .query(&query)
+            .send_through(&self.transport)
+            .await
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(ListStream::new(response)),
+            _ => Err(unexpected_response("Did not recieve OK response", response)
+                .await
+                .into()),
+        }
+    }
+}
+
 #[async_trait]
 impl MangaApi for MangaApiClient<Oauth> {
     type State = MangaApiClient<Oauth>;
@@ -430,14 +658,82 @@ impl MangaApi for MangaApiClient<Oauth> {
         query: &GetUserMangaList,
     ) -> Result<MangaList, MangaApiError> {
         let response = self.get_self().get_user(query).await?;
-        let result: MangaList = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
+        let result: MangaList = parse_response("Manga List", &response)?;
         Ok(result)
     }
 }
 
 impl MangaApiClient<Oauth> {
+    /// Get a list of suggested manga, the counterpart of `get_suggested_anime`
+    ///
+    /// MAL doesn't have this endpoint yet, so this fails with an error whose
+    /// [code](MangaApiError::code) is [ErrorCode::UnsupportedEndpoint], unless the path of the endpoint was set with
+    /// [with_suggestions_path](MangaApiClient::with_suggestions_path). A `404`
+    /// from that path fails the same way.
+    pub async fn get_suggested_manga(
+        &self,
+        query: &GetSuggestedManga,
+    ) -> Result<SuggestedManga, MangaApiError> {
+        let unsupported = UnsupportedEndpoint {
+            endpoint: "Get suggested manga",
+        };
+        let Some(path) = &self.suggestions_path else {
+            return Err(unsupported.into());
+        };
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/{}", MANGA_URL, path.trim_start_matches('/'))),
+            )
+            .await?
+            .query(&query)
+            .send_through(&self.transport)
+            .await
+            .map_err(|err| {
+                MangaApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to fetch suggested manga: {}", err),
+                )
+            })?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(unsupported.into());
+        }
+
+        let response = handle_response(response).await?;
+        let result: SuggestedManga = parse_response("Suggested Manga", &response)?;
+        Ok(result)
+    }
+
+    /// Get a users manga list, decoding the entries as they are received
+    ///
+    /// Use this instead of `get_user_manga_list` for large pages, such as when
+    /// exporting lists with `limit=1000`. See the [stream](crate::stream) module.
+    #[cfg(feature = "stream-parse")]
+    pub async fn get_user_manga_list_stream(
+        &self,
+        query: &GetUserMangaList,
+    ) -> Result<ListStream<MangaListNode>, MangaApiError> {
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/{}/mangalist", USER_URL, query.user_name)),
+            )
+            .await?
+            .query(&query)
+            .send_through(&self.transport)
+            .await
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(ListStream::new(response)),
+            _ => Err(unexpected_response("Did not recieve OK response", response)
+                .await
+                .into()),
+        }
+    }
+
     /// Update the status of a manga for the OAuth user's manga list
     ///
     /// Correspoonds to the [Update my manga list status](https://myanimelist.net/apiconfig/references/api/v2#operation/manga_manga_id_my_list_status_put) endpoint
@@ -449,60 +745,188 @@ impl MangaApiClient<Oauth> {
             MangaApiError::new(format!("Failed to turn request into form data: {}", err))
         })?;
         let response = self
-            .client
-            .put(format!("{}/{}/my_list_status", MANGA_URL, query.manga_id))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
+            .authorize(
+                self.client
+                    .put(format!("{}/{}/my_list_status", MANGA_URL, query.manga_id)),
+            )
+            .await?
             .form(&form_data)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed put request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(ErrorCode::Request, format!("Failed put request: {}", err))
+            })?;
 
         let response = handle_response(response).await?;
-        let result: MangaListStatus = serde_json::from_str(response.as_str()).map_err(|err| {
-            MangaApiError::new(format!("Failed to parse Anime List result: {}", err))
-        })?;
+        let result: MangaListStatus = parse_response("Manga List Status", &response)?;
         Ok(result)
     }
 
+    /// Send many manga list status updates, one at a time
+    ///
+    /// Updates are sent in order, paced by `options.limiter`. Depending on
+    /// `options.policy`, a failed update either aborts the remaining updates or is
+    /// collected with the other results.
+    pub async fn update_manga_list_bulk(
+        &self,
+        queries: Vec<UpdateMyMangaListStatus>,
+        options: &BulkOptions,
+    ) -> BulkResult<MangaListStatus, MangaApiError> {
+        run_bulk(
+            queries,
+            options,
+            |query| query.manga_id,
+            |query| async move { self.update_manga_list_status(&query).await },
+        )
+        .await
+    }
+
     /// Delete a manga entry from the OAuth user's manga list
     ///
+    /// Deleting a manga that is not in the list is not an error, and returns
+    /// [DeleteOutcome::NotInList]
+    ///
     /// Corresponds to the [Delete my manga list item](https://myanimelist.net/apiconfig/references/api/v2#operation/manga_manga_id_my_list_status_delete) endpoint
     pub async fn delete_manga_list_item(
         &self,
         query: &DeleteMyMangaListItem,
-    ) -> Result<(), MangaApiError> {
+    ) -> Result<DeleteOutcome, MangaApiError> {
         let response = self
-            .client
-            .delete(format!("{}/{}/my_list_status", MANGA_URL, query.manga_id))
-            .bearer_auth(&self.access_token.as_ref().unwrap())
-            .send()
+            .authorize(
+                self.client
+                    .delete(format!("{}/{}/my_list_status", MANGA_URL, query.manga_id)),
+            )
+            .await?
+            .send_through(&self.transport)
             .await
-            .map_err(|err| MangaApiError::new(format!("Failed delete request: {}", err)))?;
+            .map_err(|err| {
+                MangaApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed delete request: {}", err),
+                )
+            })?;
 
         match response.status() {
-            reqwest::StatusCode::OK => Ok(()),
-            reqwest::StatusCode::NOT_FOUND => Err(MangaApiError::new(
-                "Manga does not exist in user's manga list".to_string(),
-            )),
-            _ => Err(MangaApiError::new(format!(
-                "Did not recieve expected response: {}",
-                response.status()
-            ))),
+            reqwest::StatusCode::OK => Ok(DeleteOutcome::Deleted),
+            reqwest::StatusCode::NOT_FOUND => Ok(DeleteOutcome::NotInList),
+            _ => Err(
+                unexpected_response("Did not recieve expected response", response)
+                    .await
+                    .into(),
+            ),
         }
     }
 }
 
-async fn handle_response(response: reqwest::Response) -> Result<String, MangaApiError> {
+async fn handle_response(response: reqwest::Response) -> Result<Bytes, MangaApiError> {
     match response.status() {
         reqwest::StatusCode::OK => {
-            let content = response.text().await.map_err(|err| {
-                MangaApiError::new(format!("Failed to get content from response: {}", err))
+            let content = response.bytes().await.map_err(|err| {
+                MangaApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to get content from response: {}", err),
+                )
             })?;
             Ok(content)
         }
-        _ => Err(MangaApiError::new(format!(
-            "Did not recieve OK response: {}",
-            response.status()
-        ))),
+        _ => Err(unexpected_response("Did not recieve OK response", response)
+            .await
+            .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Records the query strings of the user list requests, answering with an empty page
+    #[derive(Default)]
+    struct RecordedUserList {
+        adaptive_limit: bool,
+        queries: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Request for RecordedUserList {
+        async fn get<T>(&self, _query: &T) -> Result<Bytes, MangaApiError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            unimplemented!()
+        }
+
+        async fn get_details(&self, _query: &GetMangaDetails) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+
+        async fn get_ranking(&self, _query: &GetMangaRanking) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+
+        async fn get_user(&self, query: &GetUserMangaList) -> Result<Bytes, MangaApiError> {
+            let query = query.to_query_string().unwrap();
+            self.queries.lock().unwrap().push(query);
+            Ok(Bytes::from_static(br#"{"data": [], "paging": {}}"#))
+        }
+
+        async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+
+        fn adaptive_limit(&self) -> bool {
+            self.adaptive_limit
+        }
+    }
+
+    impl MangaApi for RecordedUserList {
+        type State = RecordedUserList;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limit() {
+        let client_id = MalClientId::new("id");
+        let client = MangaApiClient::from(&client_id).with_adaptive_limit();
+        assert!(client.adaptive_limit());
+        let upgraded = client.upgrade_with_token(&MalAccessToken::new("token"));
+        assert!(upgraded.adaptive_limit());
+
+        let query = GetUserMangaList::builder("hello").build().unwrap();
+        for adaptive_limit in [false, true] {
+            let api = RecordedUserList {
+                adaptive_limit,
+                ..Default::default()
+            };
+            api.get_full_user_manga_list(&query).await.unwrap();
+            let limit = if adaptive_limit {
+                "limit=1000"
+            } else {
+                "limit=100"
+            };
+            let queries = api.queries.lock().unwrap();
+            assert_eq!(queries.len(), 1);
+            assert!(queries[0].split('&').any(|param| param == limit));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_suggested_manga_unsupported() {
+        let api_client = MangaApiClient::<Oauth>::from(&MalAccessToken::new("token"));
+        let query = GetSuggestedManga::builder().limit(500).build();
+
+        // Fails without sending a request
+        let err = api_client.get_suggested_manga(&query).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::UnsupportedEndpoint);
+        assert_eq!(
+            err.message(),
+            "MAL does not have the `Get suggested manga` endpoint"
+        );
+        assert_eq!(
+            query.to_query_string().unwrap(),
+            "nsfw=false&limit=100&offset=0"
+        );
     }
 }
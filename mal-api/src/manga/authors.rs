@@ -0,0 +1,205 @@
+//! Finding the manga of an author
+//!
+//! MAL has no author endpoint, and its manga search only matches titles. So
+//! [find_manga_by_author] searches for a query, follows the pages of results,
+//! and keeps the manga whose `authors` include the author. When searching by
+//! name the name itself is the default query, which finds the manga with the
+//! author's name in their titles; set a [query](AuthorSearch::query) such as a
+//! word of the titles you expect to find more.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let manga = find_manga_by_author(&api_client, "Oda, Eiichiro").await.unwrap();
+//!
+//! let search = AuthorSearch::id(1881).query("One Piece").max_pages(2);
+//! let manga = find_manga_by_author(&api_client, search).await.unwrap();
+//! ```
+
+use super::{
+    api::MangaApi,
+    error::MangaApiError,
+    requests::{GetMangaList, MangaCommonFields, MangaField},
+    responses::{AuthorDetails, MangaListNode},
+};
+use crate::{
+    common::{PagingIter, ValidationError},
+    limits::MANGA_LIST_MAX,
+    titles::normalize_title,
+};
+
+/// Pages of search results [find_manga_by_author] reads by default
+pub const DEFAULT_MAX_PAGES: usize = 5;
+
+/// An author, by MAL id or by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorRef {
+    Id(u32),
+
+    /// Matches `first_name last_name` and `last_name first_name`, compared after
+    /// [normalizing](normalize_title) them
+    Name(String),
+}
+
+impl AuthorRef {
+    /// Check if the author is this one
+    pub fn matches(&self, author: &AuthorDetails) -> bool {
+        match self {
+            AuthorRef::Id(id) => author.id == *id,
+            AuthorRef::Name(name) => {
+                let name = normalize_title(name);
+                let first = author.first_name.as_deref().unwrap_or_default();
+                let last = author.last_name.as_deref().unwrap_or_default();
+                !name.is_empty()
+                    && (name == normalize_title(&format!("{} {}", first, last))
+                        || name == normalize_title(&format!("{} {}", last, first)))
+            }
+        }
+    }
+}
+
+/// The author to look for with [find_manga_by_author], and how to search
+#[derive(Debug, Clone)]
+pub struct AuthorSearch {
+    author: AuthorRef,
+    q: Option<String>,
+    max_pages: usize,
+}
+
+impl AuthorSearch {
+    pub fn new(author: AuthorRef) -> Self {
+        Self {
+            author,
+            q: None,
+            max_pages: DEFAULT_MAX_PAGES,
+        }
+    }
+
+    /// Look for the author with the MAL id. Requires a [query](AuthorSearch::query)
+    pub fn id(author_id: u32) -> Self {
+        Self::new(AuthorRef::Id(author_id))
+    }
+
+    /// Look for the author with the name, which is also the default query
+    pub fn name(name: impl Into<String>) -> Self {
+        Self::new(AuthorRef::Name(name.into()))
+    }
+
+    /// Search for this instead of the author's name
+    pub fn query(mut self, q: impl Into<String>) -> Self {
+        self.q = Some(q.into());
+        self
+    }
+
+    /// Read at most this many pages of search results, of 100 manga each
+    pub fn max_pages(mut self, value: usize) -> Self {
+        self.max_pages = value.max(1);
+        self
+    }
+}
+
+impl From<u32> for AuthorSearch {
+    fn from(value: u32) -> Self {
+        Self::id(value)
+    }
+}
+
+impl From<&str> for AuthorSearch {
+    fn from(value: &str) -> Self {
+        Self::name(value)
+    }
+}
+
+impl From<String> for AuthorSearch {
+    fn from(value: String) -> Self {
+        Self::name(value)
+    }
+}
+
+/// Find the manga of an author in the results of a search
+///
+/// Returns the manga in the order MAL ranked them. Searching by id without a
+/// [query](AuthorSearch::query) fails with [EmptyQuery](ValidationError::EmptyQuery).
+pub async fn find_manga_by_author<M>(
+    api: &M,
+    author: impl Into<AuthorSearch>,
+) -> Result<Vec<MangaListNode>, MangaApiError>
+where
+    M: MangaApi + Sync,
+{
+    let search = author.into();
+    let q = match (&search.q, &search.author) {
+        (Some(q), _) => q.clone(),
+        (None, AuthorRef::Name(name)) => name.clone(),
+        (None, AuthorRef::Id(_)) => return Err(ValidationError::EmptyQuery.into()),
+    };
+    // The names of the authors are only sent when requested
+    let fields = MangaCommonFields::with_formatted(
+        vec![
+            MangaField::id,
+            MangaField::title,
+            MangaField::main_picture,
+            MangaField::alternative_titles,
+            MangaField::authors,
+        ],
+        "id,title,main_picture,alternative_titles,authors{first_name,last_name}",
+    );
+    let query = GetMangaList::new(q, true, Some(&fields), Some(MANGA_LIST_MAX), None)?;
+
+    let mut page = api.get_manga_list(&query).await?;
+    let mut found = Vec::new();
+    for pages_read in 1.. {
+        let has_next = page.next_page().is_some();
+        let next = if has_next && pages_read < search.max_pages {
+            Some(api.next(&page).await?)
+        } else {
+            None
+        };
+        found.extend(
+            page.data
+                .into_iter()
+                .filter(|entry| has_author(entry, &search.author)),
+        );
+        match next {
+            Some(next) => page = next,
+            None => break,
+        }
+    }
+    Ok(found)
+}
+
+fn has_author(entry: &MangaListNode, author: &AuthorRef) -> bool {
+    entry
+        .node
+        .authors
+        .iter()
+        .flatten()
+        .any(|a| author.matches(&a.node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::ONE_PIECE_MANGA_ID;
+
+    #[test]
+    fn test_author_matches() {
+        let entry: MangaListNode = serde_json::from_value(serde_json::json!({
+            "node": {
+                "id": ONE_PIECE_MANGA_ID,
+                "title": "One Piece",
+                "authors": [{
+                    "node": { "id": 1881, "first_name": "Eiichiro", "last_name": "Oda" },
+                    "role": "Story & Art"
+                }]
+            }
+        }))
+        .unwrap();
+        assert!(has_author(&entry, &AuthorRef::Id(1881)));
+        assert!(!has_author(&entry, &AuthorRef::Id(1)));
+        assert!(has_author(&entry, &AuthorRef::Name("Oda, Eiichiro".into())));
+        assert!(has_author(&entry, &AuthorRef::Name("eiichiro oda".into())));
+        assert!(!has_author(&entry, &AuthorRef::Name("Oda".into())));
+        assert!(!has_author(&entry, &AuthorRef::Name("".into())));
+    }
+}
@@ -1,21 +1,14 @@
-use std::error::Error;
-use std::fmt;
-
-#[derive(Debug)]
-pub struct MangaApiError {
-    pub message: String,
-}
-
-impl Error for MangaApiError {}
-
-impl fmt::Display for MangaApiError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
-    }
-}
-
-impl MangaApiError {
-    pub fn new(message: String) -> Self {
-        Self { message }
+use crate::common::{api_error, UnsupportedEndpoint};
+
+api_error!(
+    /// Error returned by the manga endpoints
+    ///
+    /// Use [code](MangaApiError::code) to tell the errors apart
+    MangaApiError
+);
+
+impl From<UnsupportedEndpoint> for MangaApiError {
+    fn from(value: UnsupportedEndpoint) -> Self {
+        Self::with_code(value.code(), value.to_string())
     }
 }
@@ -0,0 +1,189 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::cache::Loader;
+
+use super::{
+    api::MangaApi, error::MangaApiError, requests::GetMangaDetails, responses::MangaDetails,
+};
+
+/// Memoizing loader for the [Get manga details](https://myanimelist.net/apiconfig/references/api/v2#operation/manga_manga_id_get) endpoint
+///
+/// Concurrent loads of the same manga (with the same fields) are coalesced into
+/// a single request, and the results are cached for the given time-to-live.
+#[derive(Debug)]
+pub struct DetailsLoader<C> {
+    client: C,
+    loader: Loader<(u32, bool, Option<String>), MangaDetails>,
+}
+
+impl<C> DetailsLoader<C>
+where
+    C: MangaApi + Send + Sync,
+{
+    /// Create a new [DetailsLoader] around the given MangaApiClient, caching results for `ttl`
+    pub fn new(client: C, ttl: Duration) -> Self {
+        Self {
+            client,
+            loader: Loader::new(ttl),
+        }
+    }
+
+    /// Get the details of a manga, using the cached result if it exists
+    pub async fn load(&self, query: &GetMangaDetails) -> Result<Arc<MangaDetails>, MangaApiError> {
+        self.loader
+            .load_with(Self::key(query), || self.client.get_manga_details(query))
+            .await
+    }
+
+    /// Remove the cached result for the given query
+    pub fn invalidate(&self, query: &GetMangaDetails) {
+        self.loader.invalidate(&Self::key(query));
+    }
+
+    /// Remove all of the cached results
+    pub fn clear(&self) {
+        self.loader.clear();
+    }
+
+    /// Get a reference to the underlying MangaApiClient
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    fn key(query: &GetMangaDetails) -> (u32, bool, Option<String>) {
+        (query.manga_id, query.nsfw, query.fields.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        codes::ErrorCode,
+        manga::{
+            api::Request,
+            requests::{GetMangaRanking, GetUserMangaList},
+        },
+    };
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use serde::Serialize;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Serves the details of any Manga, failing the first `failures` requests
+    struct Details {
+        requests: AtomicU32,
+        failures: u32,
+    }
+
+    #[async_trait]
+    impl Request for Details {
+        async fn get<T>(&self, _query: &T) -> Result<Bytes, MangaApiError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            unimplemented!()
+        }
+
+        async fn get_details(&self, query: &GetMangaDetails) -> Result<Bytes, MangaApiError> {
+            let request = self.requests.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            if request < self.failures {
+                return Err(MangaApiError::with_code(
+                    ErrorCode::Server,
+                    "server error".to_string(),
+                ));
+            }
+            let details = serde_json::json!({
+                "id": query.manga_id,
+                "title": format!("Manga {}", query.manga_id),
+            });
+            Ok(Bytes::from(details.to_string()))
+        }
+        async fn get_ranking(&self, _query: &GetMangaRanking) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+
+        async fn get_user(&self, _query: &GetUserMangaList) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+
+        async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, MangaApiError> {
+            unimplemented!()
+        }
+    }
+
+    impl MangaApi for Details {
+        type State = Details;
+
+        fn get_self(&self) -> &Self::State {
+            self
+        }
+    }
+
+    fn loader(failures: u32, ttl: Duration) -> DetailsLoader<Details> {
+        let client = Details {
+            requests: AtomicU32::new(0),
+            failures,
+        };
+        DetailsLoader::new(client, ttl)
+    }
+
+    fn requests(loader: &DetailsLoader<Details>) -> u32 {
+        loader.client().requests.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_loader_collapses_loads() {
+        let loader = loader(0, Duration::from_secs(60));
+        let query = GetMangaDetails::builder(1).build().unwrap();
+        let (a, b) = tokio::join!(loader.load(&query), loader.load(&query));
+        assert_eq!(a.unwrap().shared_fields.title.as_deref(), Some("Manga 1"));
+        assert_eq!(b.unwrap().shared_fields.title.as_deref(), Some("Manga 1"));
+        assert_eq!(requests(&loader), 1);
+
+        // Cached until invalidated
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 1);
+        loader.invalidate(&query);
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 2);
+
+        let other = GetMangaDetails::builder(2).build().unwrap();
+        loader.load(&other).await.unwrap();
+        assert_eq!(requests(&loader), 3);
+    }
+
+    #[tokio::test]
+    async fn test_loader_refetches_after_ttl() {
+        let loader = loader(0, Duration::from_millis(20));
+        let query = GetMangaDetails::builder(1).build().unwrap();
+        loader.load(&query).await.unwrap();
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 1);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        loader.load(&query).await.unwrap();
+        assert_eq!(requests(&loader), 2);
+    }
+
+    #[tokio::test]
+    async fn test_loader_does_not_cache_errors() {
+        let loader = loader(1, Duration::from_secs(60));
+        let query = GetMangaDetails::builder(1).build().unwrap();
+        let err = loader.load(&query).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Server);
+
+        assert_eq!(
+            loader
+                .load(&query)
+                .await
+                .unwrap()
+                .shared_fields
+                .title
+                .as_deref(),
+            Some("Manga 1")
+        );
+        assert_eq!(requests(&loader), 2);
+    }
+}
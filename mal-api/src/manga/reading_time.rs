@@ -0,0 +1,178 @@
+//! Estimates of the time spent reading a user's manga, and left to read
+//!
+//! MAL only records chapter and volume counts, so the times are estimated from a
+//! configurable [ReadingSpeed]. Entries tracked by volume only, with no chapters
+//! read, are converted to chapters with the speed's `chapters_per_volume`.
+//!
+//! Request the `num_chapters` field for the remaining time and rereads to be
+//! counted.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let list = api_client.get_user_manga_list(&query).await.unwrap();
+//! let time = list.reading_time(&ReadingSpeed::default());
+//! println!(
+//!     "{:.0} hours read, {:.0} hours left",
+//!     time.read.as_secs_f64() / 3600.0,
+//!     time.remaining.as_secs_f64() / 3600.0
+//! );
+//! ```
+
+use std::time::Duration;
+
+use super::{
+    requests::UserMangaListStatus,
+    responses::{MangaList, MangaListNode, MangaListStatus},
+};
+
+/// How fast a user reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadingSpeed {
+    pub minutes_per_chapter: f64,
+
+    /// Chapters counted for each volume of entries tracked by volume only
+    pub chapters_per_volume: u32,
+}
+
+impl ReadingSpeed {
+    pub fn new(minutes_per_chapter: f64) -> Self {
+        Self {
+            minutes_per_chapter,
+            ..Self::default()
+        }
+    }
+
+    /// Set the chapters counted for each volume read
+    pub fn chapters_per_volume(mut self, value: u32) -> Self {
+        self.chapters_per_volume = value;
+        self
+    }
+
+    fn time(&self, chapters: u64) -> Duration {
+        Duration::from_secs_f64(chapters as f64 * self.minutes_per_chapter.max(0.0) * 60.0)
+    }
+}
+
+impl Default for ReadingSpeed {
+    /// 5 minutes per chapter, and 9 chapters per volume
+    fn default() -> Self {
+        Self {
+            minutes_per_chapter: 5.0,
+            chapters_per_volume: 9,
+        }
+    }
+}
+
+/// Estimated reading time of a list
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReadingTime {
+    /// Time spent reading, including rereads
+    pub read: Duration,
+
+    /// Time left to finish the entries
+    pub remaining: Duration,
+
+    /// Chapters read, including those counted from volumes
+    pub chapters_read: u64,
+
+    /// Entries whose remaining time is unknown, e.g. ongoing manga
+    pub unknown_remaining: u32,
+}
+
+/// Estimate the time spent reading the entries, and left to read them
+///
+/// Entries without a list status are skipped. The remaining time of completed
+/// and dropped entries is not counted.
+pub fn reading_time(entries: &[MangaListNode], speed: &ReadingSpeed) -> ReadingTime {
+    let mut time = ReadingTime::default();
+    let mut remaining_chapters = 0;
+    for entry in entries {
+        let status = match &entry.list_status {
+            Some(status) => status,
+            None => continue,
+        };
+        let chapters = read_chapters(status, speed);
+        let rereads = entry
+            .node
+            .num_chapters
+            .map_or(0, |total| total as u64 * status.num_times_reread as u64);
+        time.chapters_read += chapters + rereads;
+
+        if matches!(
+            status.status,
+            Some(UserMangaListStatus::Completed | UserMangaListStatus::Dropped)
+        ) {
+            continue;
+        }
+        match entry.chapters_remaining() {
+            Some(left) => remaining_chapters += left as u64,
+            None => time.unknown_remaining += 1,
+        }
+    }
+    time.read = speed.time(time.chapters_read);
+    time.remaining = speed.time(remaining_chapters);
+    time
+}
+
+impl MangaList {
+    /// Estimate the reading time of the entries of the page, see [reading_time]
+    pub fn reading_time(&self, speed: &ReadingSpeed) -> ReadingTime {
+        reading_time(&self.data, speed)
+    }
+}
+
+impl MangaListNode {
+    /// Estimated time to read the chapters left
+    ///
+    /// Returns `None` in the same cases as [MangaListNode::chapters_remaining]
+    pub fn time_remaining(&self, speed: &ReadingSpeed) -> Option<Duration> {
+        Some(speed.time(self.chapters_remaining()? as u64))
+    }
+}
+
+// Entries tracked by volume only count `chapters_per_volume` per volume
+fn read_chapters(status: &MangaListStatus, speed: &ReadingSpeed) -> u64 {
+    if status.num_chapters_read > 0 {
+        status.num_chapters_read as u64
+    } else {
+        status.num_volumes_read as u64 * speed.chapters_per_volume as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::manga_entry;
+    use crate::well_known::BERSERK_MANGA_ID;
+
+    fn entry(status: &str, chapters: u32, volumes: u32, total: u32) -> MangaListNode {
+        manga_entry(BERSERK_MANGA_ID)
+            .status(status)
+            .read(chapters, volumes)
+            .node(serde_json::json!({ "title": "Berserk", "num_chapters": total }))
+            .build()
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let speed = ReadingSpeed::new(6.0).chapters_per_volume(10);
+        let list = vec![
+            entry("reading", 20, 0, 30),
+            entry("completed", 10, 0, 12),
+            entry("reading", 0, 2, 0),
+            entry("dropped", 5, 0, 100),
+        ];
+        let time = reading_time(&list, &speed);
+        assert_eq!(time.chapters_read, 20 + 10 + 20 + 5);
+        assert_eq!(time.read, Duration::from_secs(55 * 6 * 60));
+        assert_eq!(time.remaining, Duration::from_secs(10 * 6 * 60));
+        assert_eq!(time.unknown_remaining, 1);
+
+        assert_eq!(
+            list[0].time_remaining(&speed),
+            Some(Duration::from_secs(60 * 60))
+        );
+        assert_eq!(list[2].time_remaining(&speed), None);
+    }
+}
@@ -1,6 +1,13 @@
 use super::error::MangaApiError;
+use crate::common::{
+    impl_field_set, impl_to_query_string, FieldError, FieldSet, Priority, QueryField, RewatchValue,
+    ValidationError,
+};
+use crate::limits::{
+    DEFAULT_LIMIT, MANGA_LIST_MAX, MANGA_RANKING_MAX, MIN_LIMIT, SUGGESTED_MANGA_MAX, USER_LIST_MAX,
+};
 use serde::{Deserialize, Serialize};
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
 #[derive(Debug, Serialize)]
 pub struct GetMangaList {
@@ -24,16 +31,16 @@ impl GetMangaList {
         offset: Option<u32>,
     ) -> Result<Self, MangaApiError> {
         let q = q.into();
-        let limit = limit.map(|l| l.clamp(1, 100));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, MANGA_LIST_MAX));
 
         if q.is_empty() {
-            return Err(MangaApiError::new("Query cannot be empty".to_string()));
+            return Err(ValidationError::EmptyQuery.into());
         }
 
         Ok(Self {
             q,
             nsfw,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         })
@@ -76,12 +83,12 @@ impl<'a> GetMangaListBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a MangaCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 100));
+        self.limit = Some(value.clamp(MIN_LIMIT, MANGA_LIST_MAX));
         self
     }
 
@@ -99,9 +106,9 @@ impl<'a> GetMangaListBuilder<'a> {
 pub struct GetMangaDetails {
     #[serde(skip_serializing)]
     pub(crate) manga_id: u32,
-    nsfw: bool,
+    pub(crate) nsfw: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    fields: Option<String>,
+    pub(crate) fields: Option<String>,
 }
 
 impl GetMangaDetails {
@@ -112,9 +119,7 @@ impl GetMangaDetails {
         fields: Option<&MangaDetailFields>,
     ) -> Result<Self, MangaApiError> {
         if manga_id == 0 {
-            return Err(MangaApiError::new(
-                "manga_id must be greater than 0".to_string(),
-            ));
+            return Err(ValidationError::InvalidId("manga_id").into());
         }
 
         Ok(Self {
@@ -156,7 +161,7 @@ impl<'a> GetMangaDetailsBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a MangaDetailFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
@@ -200,12 +205,12 @@ impl GetMangaRanking {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Self {
-        let limit = limit.map(|l| l.clamp(1, 500));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, MANGA_RANKING_MAX));
 
         Self {
             ranking_type,
             nsfw,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         }
@@ -247,12 +252,12 @@ impl<'a> GetMangaRankingBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a MangaCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 500));
+        self.limit = Some(value.clamp(MIN_LIMIT, MANGA_RANKING_MAX));
         self
     }
 
@@ -272,7 +277,93 @@ impl<'a> GetMangaRankingBuilder<'a> {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Query for a `Get suggested manga` endpoint, which MAL doesn't have yet
+///
+/// See [get_suggested_manga](super::api::MangaApiClient::get_suggested_manga)
+#[derive(Debug, Serialize)]
+pub struct GetSuggestedManga {
+    nsfw: bool,
+    limit: u16,
+    offset: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<String>,
+}
+
+impl GetSuggestedManga {
+    /// Create a new `Get suggested manga` query
+    ///
+    /// Limit must be within `[1, 100]`. Defaults to 100
+    pub fn new(
+        nsfw: bool,
+        fields: Option<&MangaCommonFields>,
+        limit: Option<u16>,
+        offset: Option<u32>,
+    ) -> Self {
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, SUGGESTED_MANGA_MAX));
+
+        Self {
+            nsfw,
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
+            offset: offset.unwrap_or(0),
+            fields: fields.map(|f| f.into()),
+        }
+    }
+
+    /// Use builder pattern for building up the query with required arguments
+    pub fn builder() -> GetSuggestedMangaBuilder<'static> {
+        GetSuggestedMangaBuilder::new()
+    }
+}
+
+pub struct GetSuggestedMangaBuilder<'a> {
+    nsfw: bool,
+    fields: Option<&'a MangaCommonFields>,
+    limit: Option<u16>,
+    offset: Option<u32>,
+}
+
+impl<'a> GetSuggestedMangaBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            nsfw: false,
+            fields: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    pub fn enable_nsfw(mut self) -> Self {
+        self.nsfw = true;
+        self
+    }
+
+    pub fn fields(mut self, value: &'a MangaCommonFields) -> Self {
+        self.fields = Some(value);
+        self
+    }
+
+    pub fn limit(mut self, value: u16) -> Self {
+        self.limit = Some(value.clamp(MIN_LIMIT, SUGGESTED_MANGA_MAX));
+        self
+    }
+
+    pub fn offset(mut self, value: u32) -> Self {
+        self.offset = Some(value);
+        self
+    }
+
+    pub fn build(self) -> GetSuggestedManga {
+        GetSuggestedManga::new(self.nsfw, self.fields, self.limit, self.offset)
+    }
+}
+
+impl Default for GetSuggestedMangaBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum UserMangaListStatus {
     Reading,
@@ -282,7 +373,7 @@ pub enum UserMangaListStatus {
     PlanToRead,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum UserMangaListSort {
     ListScore,
@@ -293,7 +384,7 @@ pub enum UserMangaListSort {
     // MangaId,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GetUserMangaList {
     #[serde(skip_serializing)]
     pub(crate) user_name: String,
@@ -321,10 +412,10 @@ impl GetUserMangaList {
         limit: Option<u16>,
         offset: Option<u32>,
     ) -> Result<Self, MangaApiError> {
-        let limit = limit.map(|l| l.clamp(1, 1000));
+        let limit = limit.map(|l| l.clamp(MIN_LIMIT, USER_LIST_MAX));
 
         if user_name.is_empty() {
-            return Err(MangaApiError::new("user_name cannot be empty".to_string()));
+            return Err(ValidationError::EmptyUserName.into());
         }
 
         Ok(Self {
@@ -332,7 +423,7 @@ impl GetUserMangaList {
             nsfw,
             status,
             sort,
-            limit: limit.unwrap_or(100),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
             offset: offset.unwrap_or(0),
             fields: fields.map(|f| f.into()),
         })
@@ -342,6 +433,14 @@ impl GetUserMangaList {
     pub fn builder(user_name: &str) -> GetUserMangaListBuilder<'static> {
         GetUserMangaListBuilder::new(user_name.to_string())
     }
+
+    // The same query with the largest page size the endpoint allows
+    pub(crate) fn with_max_limit(&self) -> Self {
+        Self {
+            limit: USER_LIST_MAX,
+            ..self.clone()
+        }
+    }
 }
 
 pub struct GetUserMangaListBuilder<'a> {
@@ -378,7 +477,7 @@ impl<'a> GetUserMangaListBuilder<'a> {
     }
 
     pub fn fields(mut self, value: &'a MangaCommonFields) -> Self {
-        self.fields = Some(value.into());
+        self.fields = Some(value);
         self
     }
 
@@ -393,7 +492,7 @@ impl<'a> GetUserMangaListBuilder<'a> {
     }
 
     pub fn limit(mut self, value: u16) -> Self {
-        self.limit = Some(value.clamp(1, 1000));
+        self.limit = Some(value.clamp(MIN_LIMIT, USER_LIST_MAX));
         self
     }
 
@@ -430,11 +529,11 @@ pub struct UpdateMyMangaListStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
     num_chapters_read: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    priority: Option<u8>,
+    priority: Option<Priority>,
     #[serde(skip_serializing_if = "Option::is_none")]
     num_times_reread: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    reread_value: Option<u8>,
+    reread_value: Option<RewatchValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tags: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -445,10 +544,6 @@ impl UpdateMyMangaListStatus {
     /// Create new `Update my manga list status` query
     ///
     /// Score must be within `[0-10]`
-    ///
-    /// Priority must be within `[0, 2]`
-    ///
-    /// Reread_value must be within `[0, 5]`
     pub fn new(
         manga_id: u32,
         status: Option<UserMangaListStatus>,
@@ -456,39 +551,21 @@ impl UpdateMyMangaListStatus {
         score: Option<u8>,
         num_volumes_read: Option<u32>,
         num_chapters_read: Option<u32>,
-        priority: Option<u8>,
+        priority: Option<Priority>,
         num_times_reread: Option<u32>,
-        reread_value: Option<u8>,
+        reread_value: Option<RewatchValue>,
         tags: Option<String>,
         comments: Option<String>,
     ) -> Result<Self, MangaApiError> {
         // Instead of clamping, be more verbose with errors so the user is more aware of the values
         if let Some(score) = score {
             if score > 10 {
-                return Err(MangaApiError::new(
-                    "Score must be between 0 and 10 inclusive".to_string(),
-                ));
-            }
-        }
-        if let Some(priority) = priority {
-            if priority > 2 {
-                return Err(MangaApiError::new(
-                    "Priority must be between 0 and 2 inclusive".to_string(),
-                ));
-            }
-        }
-        if let Some(reread_value) = reread_value {
-            if reread_value > 5 {
-                return Err(MangaApiError::new(
-                    "Reread value must be between 0 and 5 inclusive".to_string(),
-                ));
+                return Err(ValidationError::InvalidScore(score).into());
             }
         }
 
         if manga_id == 0 {
-            return Err(MangaApiError::new(
-                "manga_id must be greater than 0".to_string(),
-            ));
+            return Err(ValidationError::InvalidId("manga_id").into());
         }
 
         if !(status.is_some()
@@ -502,9 +579,7 @@ impl UpdateMyMangaListStatus {
             || tags.is_some()
             || comments.is_some())
         {
-            return Err(MangaApiError::new(
-                "At least one of the optional arguments must be Some".to_string(),
-            ));
+            return Err(ValidationError::MissingUpdateField.into());
         }
 
         Ok(Self {
@@ -535,9 +610,9 @@ pub struct UpdateMyMangaListStatusBuilder {
     score: Option<u8>,
     num_volumes_read: Option<u32>,
     num_chapters_read: Option<u32>,
-    priority: Option<u8>,
+    priority: Option<Priority>,
     num_times_reread: Option<u32>,
-    reread_value: Option<u8>,
+    reread_value: Option<RewatchValue>,
     tags: Option<String>,
     comments: Option<String>,
 }
@@ -589,7 +664,7 @@ impl UpdateMyMangaListStatusBuilder {
         self
     }
 
-    pub fn priority(mut self, value: u8) -> Self {
+    pub fn priority(mut self, value: Priority) -> Self {
         self.priority = Some(value);
         self
     }
@@ -599,7 +674,7 @@ impl UpdateMyMangaListStatusBuilder {
         self
     }
 
-    pub fn reread_value(mut self, value: u8) -> Self {
+    pub fn reread_value(mut self, value: RewatchValue) -> Self {
         self.reread_value = Some(value);
         self
     }
@@ -631,8 +706,9 @@ impl UpdateMyMangaListStatusBuilder {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct DeleteMyMangaListItem {
+    #[serde(skip_serializing)]
     pub(crate) manga_id: u32,
 }
 
@@ -643,7 +719,17 @@ impl DeleteMyMangaListItem {
     }
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
+impl_to_query_string!(
+    GetMangaList,
+    GetMangaDetails,
+    GetMangaRanking,
+    GetSuggestedManga,
+    GetUserMangaList,
+    UpdateMyMangaListStatus,
+    DeleteMyMangaListItem,
+);
+
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, IntoStaticStr, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum MangaField {
     id,
@@ -670,7 +756,7 @@ pub enum MangaField {
     authors,
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, IntoStaticStr, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum MangaDetail {
     // Common fields
@@ -706,42 +792,56 @@ pub enum MangaDetail {
     serialization,
 }
 
-/// Wrapper for a vector of valid Manga Common Fields
-#[derive(Debug)]
-pub struct MangaCommonFields(pub Vec<MangaField>);
+/// Set of valid Manga Common Fields
+#[derive(Debug, Clone)]
+pub struct MangaCommonFields(pub FieldSet<MangaField>);
 
-/// Wrapper for a vector of valid Manga Detail Fields
-#[derive(Debug)]
-pub struct MangaDetailFields(pub Vec<MangaDetail>);
+impl_field_set!(MangaCommonFields, MangaField);
 
-impl Into<String> for &MangaCommonFields {
-    fn into(self) -> String {
-        let result = self
-            .0
-            .iter()
-            .map(|e| format!("{:?}", e))
-            .collect::<Vec<String>>()
-            .join(",");
-        result
+/// Set of valid Manga Detail Fields
+#[derive(Debug, Clone)]
+pub struct MangaDetailFields(pub FieldSet<MangaDetail>);
+
+impl_field_set!(MangaDetailFields, MangaDetail);
+
+impl QueryField for MangaField {
+    fn parse(name: &str) -> Result<Self, FieldError> {
+        name.parse().map_err(|_| match name.parse::<MangaDetail>() {
+            Ok(_) => FieldError::DetailOnly(name.to_string()),
+            Err(_) => FieldError::Unknown(name.to_string()),
+        })
     }
 }
 
-impl Into<String> for &MangaDetailFields {
-    fn into(self) -> String {
-        let result = self
-            .0
+impl QueryField for MangaDetail {
+    fn parse(name: &str) -> Result<Self, FieldError> {
+        name.parse()
+            .map_err(|_| FieldError::Unknown(name.to_string()))
+    }
+}
+
+impl TryFrom<&MangaDetailFields> for MangaCommonFields {
+    type Error = FieldError;
+
+    /// Keep the fields of a detail field set, failing on the detail-only ones
+    fn try_from(value: &MangaDetailFields) -> Result<Self, Self::Error> {
+        value
+            .fields()
             .iter()
-            .map(|e| format!("{:?}", e))
-            .collect::<Vec<String>>()
-            .join(",");
-        result
+            .map(|field| MangaField::parse(field.into()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::manga::all_common_fields;
+    use crate::manga::{
+        all_common_fields, MANGA_COMMON_FIELDS, MANGA_COMMON_FIELDS_STR, MANGA_DETAIL_FIELDS,
+        MANGA_DETAIL_FIELDS_STR,
+    };
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_get_manga_list() {
@@ -855,35 +955,9 @@ mod tests {
         );
         assert!(query.is_err());
 
-        let query = UpdateMyMangaListStatus::new(
-            1234,
-            Some(UserMangaListStatus::Completed),
-            None,
-            None,
-            None,
-            None,
-            Some(3),
-            None,
-            None,
-            None,
-            None,
-        );
-        assert!(query.is_err());
+        assert!(Priority::try_from(3).is_err());
 
-        let query = UpdateMyMangaListStatus::new(
-            1234,
-            Some(UserMangaListStatus::Completed),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            Some(6),
-            None,
-            None,
-        );
-        assert!(query.is_err());
+        assert!(RewatchValue::try_from(6).is_err());
 
         let query = UpdateMyMangaListStatus::new(
             1234,
@@ -892,12 +966,22 @@ mod tests {
             Some(10),
             None,
             None,
-            Some(2),
+            Some(Priority::High),
             None,
-            Some(5),
+            Some(RewatchValue::VeryHigh),
             None,
             None,
         );
         assert!(query.is_ok())
     }
+
+    #[test]
+    fn test_all_fields_consts() {
+        assert_eq!(MANGA_COMMON_FIELDS, MangaField::iter().collect::<Vec<_>>());
+        let fields = MangaCommonFields::new(MANGA_COMMON_FIELDS.to_vec());
+        assert_eq!(fields.as_str(), MANGA_COMMON_FIELDS_STR);
+        assert_eq!(MANGA_DETAIL_FIELDS, MangaDetail::iter().collect::<Vec<_>>());
+        let fields = MangaDetailFields::new(MANGA_DETAIL_FIELDS.to_vec());
+        assert_eq!(fields.as_str(), MANGA_DETAIL_FIELDS_STR);
+    }
 }
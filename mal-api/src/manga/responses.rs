@@ -1,9 +1,17 @@
-use std::fmt::Display;
-
-use crate::common::{
-    AlternativeTitles, Genre, MainPicture, Paging, PagingIter, Ranking, RelationType, NSFW,
+use std::collections::HashMap;
+
+use crate::{
+    common::{
+        impl_to_json, AlternativeTitles, EntrySummary, Genre, MainPicture, Paging, PagingIter,
+        Priority, Ranking, RelationType, RewatchValue, SharedList, Truncated, NSFW,
+        SUMMARY_TEXT_CHARS,
+    },
+    titles::{self, TitlePreference},
+    urls,
 };
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "unknown-fields")]
+use serde_json::Value;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaList {
@@ -11,21 +19,21 @@ pub struct MangaList {
     pub paging: Paging,
 }
 
-impl Display for MangaList {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl_to_json!(MangaList);
+
+impl MangaList {
+    /// Move the entries into a [SharedList] that tasks can read concurrently
+    /// without cloning them
+    pub fn into_shared(self) -> SharedList<MangaListNode> {
+        SharedList::new(self.data, self.paging)
     }
 }
 
 impl PagingIter for MangaList {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
-    }
-
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
@@ -37,12 +45,40 @@ pub struct MangaListNode {
     pub list_status: Option<MangaListStatus>,
 }
 
-impl Display for MangaListNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl MangaListNode {
+    /// Fraction of the chapters read, within `[0, 1]`
+    ///
+    /// Returns `None` if the entry has no list status, or if the number of chapters
+    /// is unknown, which MAL reports as 0 for ongoing manga. Request the
+    /// `num_chapters` field for it to be available.
+    pub fn progress_fraction(&self) -> Option<f32> {
+        let read = self.list_status.as_ref()?.num_chapters_read;
+        let total = self.node.num_chapters.filter(|n| *n > 0)?;
+        Some((read as f32 / total as f32).min(1.0))
+    }
+
+    /// Number of chapters left to read
+    ///
+    /// Returns `None` in the same cases as [MangaListNode::progress_fraction]
+    pub fn chapters_remaining(&self) -> Option<u32> {
+        let read = self.list_status.as_ref()?.num_chapters_read;
+        let total = self.node.num_chapters.filter(|n| *n > 0)?;
+        Some(total.saturating_sub(read))
+    }
+
+    /// Number of volumes left to read
+    ///
+    /// Returns `None` if the entry has no list status, or if the number of volumes
+    /// is unknown
+    pub fn volumes_remaining(&self) -> Option<u32> {
+        let read = self.list_status.as_ref()?.num_volumes_read;
+        let total = self.node.num_volumes.filter(|n| *n > 0)?;
+        Some(total.saturating_sub(read))
     }
 }
 
+impl_to_json!(MangaListNode);
+
 // Wrap everything in Options since user controls what fields should be returned
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaFields {
@@ -68,14 +104,62 @@ pub struct MangaFields {
     pub num_volumes: Option<u32>,
     pub num_chapters: Option<u32>,
     pub authors: Option<Vec<Author>>,
+    /// Fields returned by MAL that this crate doesn't know about yet
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-impl Display for MangaFields {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl MangaFields {
+    /// URL of the manga's myanimelist.net page, if the id was returned
+    pub fn url(&self) -> Option<String> {
+        self.id.map(urls::manga_url)
+    }
+
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    ///
+    /// Empty if the title wasn't returned
+    pub fn display_title(&self) -> &str {
+        self.title_in(titles::title_preference())
+    }
+
+    /// Compact view of the manga for logging, with the synopsis truncated
+    pub fn summary(&self) -> EntrySummary<'_> {
+        EntrySummary {
+            id: self.id,
+            title: self.title.as_deref().unwrap_or_default(),
+            mean: self.mean,
+            rank: self.rank,
+            synopsis: self
+                .synopsis
+                .as_deref()
+                .map(|s| Truncated::new(s, SUMMARY_TEXT_CHARS)),
+            background: None,
+        }
+    }
+
+    /// Check if the main title or any of the alternative titles matches the given title
+    ///
+    /// Titles are compared after [normalizing](titles::normalize_title) them
+    pub fn has_title(&self, title: &str) -> bool {
+        self.title
+            .as_deref()
+            .is_some_and(|t| titles::same_title(t, title))
+            || self
+                .alternative_titles
+                .as_ref()
+                .is_some_and(|t| t.has_title(title))
+    }
+
+    /// Title in the given language, falling back to the main title
+    pub fn title_in(&self, preference: TitlePreference) -> &str {
+        let title = self.title.as_deref().unwrap_or_default();
+        titles::select_title(title, self.alternative_titles.as_ref(), preference)
     }
 }
 
+impl_to_json!(MangaFields);
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum MangaMediaType {
@@ -105,11 +189,7 @@ pub struct Author {
     pub role: Option<String>,
 }
 
-impl Display for Author {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Author);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AuthorDetails {
@@ -118,12 +198,14 @@ pub struct AuthorDetails {
     pub last_name: Option<String>,
 }
 
-impl Display for AuthorDetails {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AuthorDetails);
 
+/// The status of a manga in a user's list
+///
+/// Used for the `list_status` of user manga list entries, the `my_list_status`
+/// of manga, and the response of the update endpoint. MAL only returns the
+/// priority, reread, tags, and comments fields in some of these, so they
+/// default to their empty values when missing.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaListStatus {
     pub status: Option<super::requests::UserMangaListStatus>,
@@ -133,19 +215,20 @@ pub struct MangaListStatus {
     pub is_rereading: bool,
     pub start_date: Option<String>,
     pub finish_date: Option<String>,
-    pub priority: u8,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
     pub num_times_reread: u32,
-    pub reread_value: u8,
+    #[serde(default)]
+    pub reread_value: RewatchValue,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub comments: String,
     pub updated_at: String,
 }
 
-impl Display for MangaListStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(MangaListStatus);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaPicture {
@@ -153,11 +236,7 @@ pub struct MangaPicture {
     pub large: String,
 }
 
-impl Display for MangaPicture {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(MangaPicture);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RelatedManga {
@@ -166,11 +245,7 @@ pub struct RelatedManga {
     pub relation_type_formatted: String,
 }
 
-impl Display for RelatedManga {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(RelatedManga);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Recommendation {
@@ -178,11 +253,7 @@ pub struct Recommendation {
     pub num_recommendations: u32,
 }
 
-impl Display for Recommendation {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Recommendation);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Serialization {
@@ -190,11 +261,7 @@ pub struct Serialization {
     pub role: Option<String>,
 }
 
-impl Display for Serialization {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(Serialization);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SerializationNode {
@@ -202,11 +269,7 @@ pub struct SerializationNode {
     pub name: String,
 }
 
-impl Display for SerializationNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(SerializationNode);
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaDetails {
@@ -215,50 +278,170 @@ pub struct MangaDetails {
 
     pub pictures: Option<Vec<MangaPicture>>,
     pub background: Option<String>,
+    #[cfg(feature = "anime")]
     pub related_anime: Option<Vec<crate::anime::responses::RelatedAnime>>,
     pub related_manga: Option<Vec<RelatedManga>>,
     pub recommendations: Option<Vec<Recommendation>>,
     pub serialization: Option<Vec<Serialization>>,
 }
 
-impl Display for MangaDetails {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl MangaDetails {
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub fn display_title(&self) -> &str {
+        self.shared_fields.display_title()
+    }
+
+    /// Compact view of the manga for logging, with the synopsis and background truncated
+    pub fn summary(&self) -> EntrySummary<'_> {
+        EntrySummary {
+            background: self
+                .background
+                .as_deref()
+                .map(|s| Truncated::new(s, SUMMARY_TEXT_CHARS)),
+            ..self.shared_fields.summary()
+        }
+    }
+
+    /// Related manga with the given relation type
+    pub fn related_manga_of(&self, relation_type: RelationType) -> Vec<&RelatedManga> {
+        self.related_manga
+            .iter()
+            .flatten()
+            .filter(|r| r.relation_type == relation_type)
+            .collect()
+    }
+
+    /// Related manga grouped by their relation type
+    pub fn related_manga_by_type(&self) -> HashMap<RelationType, Vec<&RelatedManga>> {
+        let mut groups: HashMap<RelationType, Vec<&RelatedManga>> = HashMap::new();
+        for related in self.related_manga.iter().flatten() {
+            groups
+                .entry(related.relation_type)
+                .or_default()
+                .push(related);
+        }
+        groups
+    }
+
+    pub fn sequels(&self) -> Vec<&RelatedManga> {
+        self.related_manga_of(RelationType::Sequel)
+    }
+
+    pub fn prequels(&self) -> Vec<&RelatedManga> {
+        self.related_manga_of(RelationType::Prequel)
+    }
+
+    pub fn side_stories(&self) -> Vec<&RelatedManga> {
+        self.related_manga_of(RelationType::SideStory)
+    }
+
+    /// Anime related to this manga as an [adaptation](RelationType::Adaptation)
+    ///
+    /// Requires the `related_anime` field
+    #[cfg(feature = "anime")]
+    pub fn adaptations(&self) -> Vec<&crate::anime::responses::RelatedAnime> {
+        self.related_anime
+            .iter()
+            .flatten()
+            .filter(|r| r.relation_type == RelationType::Adaptation)
+            .collect()
+    }
+
+    /// The anime adapted from the manga
+    ///
+    /// Same as [adaptations](MangaDetails::adaptations). Requires the `related_anime` field
+    #[cfg(feature = "anime")]
+    pub fn anime_adaptations(&self) -> Vec<&crate::anime::responses::RelatedAnime> {
+        self.adaptations()
     }
 }
 
+impl_to_json!(MangaDetails);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaRanking {
     pub data: Vec<MangaRankingNode>,
     pub paging: Paging,
 }
 
-impl Display for MangaRanking {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(MangaRanking);
 
 impl PagingIter for MangaRanking {
     type Item = Self;
 
-    fn next_page(&self) -> Option<&String> {
-        self.paging.next.as_ref()
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
+}
+
+/// Response of a `Get suggested manga` endpoint, which MAL doesn't have yet
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SuggestedManga {
+    pub data: Vec<SuggestedMangaNode>,
+    pub paging: Paging,
+}
 
-    fn prev_page(&self) -> Option<&String> {
-        self.paging.previous.as_ref()
+impl_to_json!(SuggestedManga);
+
+impl PagingIter for SuggestedManga {
+    type Item = Self;
+
+    fn paging(&self) -> &Paging {
+        &self.paging
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SuggestedMangaNode {
+    pub node: MangaFields,
+}
+
+impl_to_json!(SuggestedMangaNode);
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MangaRankingNode {
     pub node: MangaFields,
     pub ranking: Ranking,
 }
 
-impl Display for MangaRankingNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl_to_json!(MangaRankingNode);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manga_list_status_shapes() {
+        // `list_status` of a user manga list entry
+        let status: MangaListStatus = serde_json::from_value(serde_json::json!({
+            "status": "reading",
+            "score": 0,
+            "num_volumes_read": 1,
+            "num_chapters_read": 8,
+            "is_rereading": false,
+            "updated_at": "2023-01-01T00:00:00+00:00"
+        }))
+        .unwrap();
+        assert_eq!(status.priority, Priority::Low);
+        assert_eq!(status.reread_value, RewatchValue::NotSet);
+        assert!(status.comments.is_empty());
+
+        // Response of the update endpoint
+        let status: MangaListStatus = serde_json::from_value(serde_json::json!({
+            "status": "completed",
+            "score": 9,
+            "num_volumes_read": 10,
+            "num_chapters_read": 100,
+            "is_rereading": false,
+            "updated_at": "2023-01-01T00:00:00+00:00",
+            "priority": 1,
+            "num_times_reread": 0,
+            "reread_value": 3,
+            "tags": [],
+            "comments": ""
+        }))
+        .unwrap();
+        assert_eq!(status.priority, Priority::Medium);
+        assert_eq!(status.reread_value, RewatchValue::Medium);
     }
 }
@@ -0,0 +1,275 @@
+//! Grouping manga by the magazines they are serialized in
+//!
+//! MAL only returns the `serialization` field from the manga details endpoint,
+//! so a [MagazineIndex] is built from [MangaDetails], either ones already fetched
+//! or with [fetch_magazines]. It can then group any manga by magazine, e.g. the
+//! entries of a user's list or a ranking.
+//!
+//! Magazine names are written inconsistently, e.g. `Shounen Jump (Weekly)` and
+//! `Weekly Shonen Jump`. [normalize_magazine] maps such names to the same key,
+//! which [MagazineIndex::find] uses to look magazines up by name.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let ranking = api_client.get_manga_ranking(&query).await.unwrap();
+//! let manga: Vec<&MangaFields> = ranking.data.iter().map(|e| &e.node).collect();
+//! let index = fetch_magazines(&api_client, manga.iter().copied()).await.unwrap();
+//!
+//! for group in index.group(manga.iter().copied()) {
+//!     println!("{}: {} titles", group.name, group.manga.len());
+//! }
+//! let jump = index.find("Weekly Shonen Jump");
+//! ```
+
+use std::collections::HashMap;
+
+use super::{
+    api::MangaApi,
+    error::MangaApiError,
+    requests::{GetMangaDetails, MangaDetail, MangaDetailFields},
+    responses::{MangaDetails, MangaFields, SerializationNode},
+};
+use crate::titles::normalize_title;
+
+// Publication frequencies, moved to the front of normalized names
+const FREQUENCIES: &[&str] = &["weekly", "biweekly", "monthly", "bimonthly", "quarterly"];
+
+/// Normalize a magazine name for comparison
+///
+/// [Normalizes](normalize_title) the name, spells `shonen` as `shounen`, and
+/// moves the publication frequency to the front, so that e.g.
+/// `Shounen Jump (Weekly)` and `Weekly Shonen Jump` are equal. Magazines with a
+/// different frequency, e.g. `Monthly Shounen Jump`, stay different.
+pub fn normalize_magazine(name: &str) -> String {
+    let normalized = normalize_title(name);
+    let mut frequency = None;
+    let mut words = Vec::new();
+    for word in normalized.split(' ').filter(|w| !w.is_empty()) {
+        if frequency.is_none() && FREQUENCIES.contains(&word) {
+            frequency = Some(word);
+            continue;
+        }
+        words.push(match word {
+            "shonen" => "shounen",
+            "shojo" | "shoujo" => "shoujo",
+            word => word,
+        });
+    }
+    frequency
+        .into_iter()
+        .chain(words)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The manga serialized in a magazine
+#[derive(Debug)]
+pub struct MagazineGroup<'a> {
+    pub id: u32,
+    pub name: String,
+    pub manga: Vec<&'a MangaFields>,
+}
+
+/// The magazines manga are serialized in, by manga id
+#[derive(Debug, Clone, Default)]
+pub struct MagazineIndex {
+    magazines: HashMap<u32, String>,
+    serializations: HashMap<u32, Vec<u32>>,
+}
+
+impl MagazineIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index from the `serialization` field of the details
+    pub fn from_details<'a>(details: impl IntoIterator<Item = &'a MangaDetails>) -> Self {
+        let mut index = Self::new();
+        for details in details {
+            index.insert(details);
+        }
+        index
+    }
+
+    /// Add the magazines of a manga, replacing the ones it had
+    ///
+    /// Details without an `id` are skipped
+    pub fn insert(&mut self, details: &MangaDetails) {
+        let Some(manga_id) = details.shared_fields.id else {
+            return;
+        };
+        let nodes = details.serialization.iter().flatten().map(|s| &s.node);
+        self.insert_magazines(manga_id, nodes);
+    }
+
+    fn insert_magazines<'a>(
+        &mut self,
+        manga_id: u32,
+        magazines: impl IntoIterator<Item = &'a SerializationNode>,
+    ) {
+        let mut ids = Vec::new();
+        for magazine in magazines {
+            self.magazines
+                .entry(magazine.id)
+                .or_insert_with(|| magazine.name.clone());
+            if !ids.contains(&magazine.id) {
+                ids.push(magazine.id);
+            }
+        }
+        self.serializations.insert(manga_id, ids);
+    }
+
+    /// The ids and names of the magazines a manga is serialized in
+    ///
+    /// Empty if the manga isn't indexed
+    pub fn magazines_of(&self, manga_id: u32) -> Vec<(u32, &str)> {
+        self.serializations
+            .get(&manga_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| Some((*id, self.magazines.get(id)?.as_str())))
+            .collect()
+    }
+
+    /// The ids of the indexed manga serialized in the magazine with the name
+    ///
+    /// Names are compared with [normalize_magazine]
+    pub fn find(&self, name: &str) -> Vec<u32> {
+        let name = normalize_magazine(name);
+        let mut manga: Vec<u32> = self
+            .serializations
+            .iter()
+            .filter(|(_, magazines)| {
+                magazines.iter().any(|id| {
+                    self.magazines
+                        .get(id)
+                        .is_some_and(|m| normalize_magazine(m) == name)
+                })
+            })
+            .map(|(manga_id, _)| *manga_id)
+            .collect();
+        manga.sort_unstable();
+        manga
+    }
+
+    /// Group the manga by the magazines they are serialized in
+    ///
+    /// Manga serialized in several magazines are in each of their groups, and
+    /// manga without an id or not in the index are skipped. Groups are ordered
+    /// by their number of manga, then by name, and keep the order of `manga`.
+    pub fn group<'a>(
+        &self,
+        manga: impl IntoIterator<Item = &'a MangaFields>,
+    ) -> Vec<MagazineGroup<'a>> {
+        let mut groups: HashMap<u32, MagazineGroup<'a>> = HashMap::new();
+        for manga in manga {
+            let Some(manga_id) = manga.id else {
+                continue;
+            };
+            for (id, name) in self.magazines_of(manga_id) {
+                groups
+                    .entry(id)
+                    .or_insert_with(|| MagazineGroup {
+                        id,
+                        name: name.to_string(),
+                        manga: vec![],
+                    })
+                    .manga
+                    .push(manga);
+            }
+        }
+
+        let mut groups: Vec<_> = groups.into_values().collect();
+        groups.sort_by(|a, b| {
+            b.manga
+                .len()
+                .cmp(&a.manga.len())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        groups
+    }
+}
+
+/// Fetch the magazines of the manga into a [MagazineIndex]
+///
+/// Sends one details request per manga, one after the other, requesting only the
+/// `serialization` field. Manga without an id are skipped.
+pub async fn fetch_magazines<'a, M>(
+    api: &M,
+    manga: impl IntoIterator<Item = &'a MangaFields>,
+) -> Result<MagazineIndex, MangaApiError>
+where
+    M: MangaApi + Sync,
+{
+    let fields = MangaDetailFields::new(vec![MangaDetail::serialization]);
+    let mut index = MagazineIndex::new();
+    for manga_id in manga.into_iter().filter_map(|m| m.id) {
+        let query = GetMangaDetails::new(manga_id, true, Some(&fields))?;
+        let details = api.get_manga_details(&query).await?;
+        let nodes = details.serialization.iter().flatten().map(|s| &s.node);
+        index.insert_magazines(manga_id, nodes);
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(id: u32, magazines: &[(u32, &str)]) -> MangaDetails {
+        let serialization: Vec<_> = magazines
+            .iter()
+            .map(|(id, name)| serde_json::json!({ "node": { "id": id, "name": name } }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": format!("Manga {}", id),
+            "serialization": serialization
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalize_magazine() {
+        assert_eq!(
+            normalize_magazine("Shounen Jump (Weekly)"),
+            "weekly shounen jump"
+        );
+        assert_eq!(
+            normalize_magazine("Weekly Shonen Jump"),
+            "weekly shounen jump"
+        );
+        assert_eq!(normalize_magazine("Jump SQ."), "jump sq");
+        assert_ne!(
+            normalize_magazine("Shounen Jump (Monthly)"),
+            normalize_magazine("Shounen Jump (Weekly)")
+        );
+    }
+
+    #[test]
+    fn test_magazine_index() {
+        let jump = (83, "Shounen Jump (Weekly)");
+        let sq = (619, "Jump SQ.");
+        let all = [
+            details(13, &[jump]),
+            details(44347, &[(1006, "Tonari no Young Jump")]),
+            details(96792, &[jump, sq]),
+            details(1, &[]),
+        ];
+        let index = MagazineIndex::from_details(&all);
+        assert_eq!(index.find("Weekly Shonen Jump"), vec![13, 96792]);
+        assert_eq!(index.magazines_of(96792), vec![(83, jump.1), (619, sq.1)]);
+        assert!(index.magazines_of(2).is_empty());
+
+        let groups = index.group(all.iter().map(|d| &d.shared_fields));
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.manga.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(jump.1, 2), (sq.1, 1), ("Tonari no Young Jump", 1)]
+        );
+    }
+}
@@ -1,26 +1,66 @@
 //! Module for working through MAL OAuth2 flow
+//!
+//! The OAuth2 flow requires the `oauth` feature, which is enabled by default.
+//! [MalClientId] and [MalAccessToken] are always available, for applications that
+//! only use a ClientId or manage their tokens themselves.
 
-use crate::{OAUTH_TOKEN_URL, OAUTH_URL};
-use oauth2::basic::BasicClient;
-use oauth2::http::Uri;
-use oauth2::reqwest::async_http_client;
-use oauth2::ClientId;
-use oauth2::{
-    AccessToken, AuthUrl, AuthorizationCode, ClientSecret, CsrfToken, PkceCodeChallenge,
-    PkceCodeVerifier, RedirectUrl, RefreshToken, TokenResponse, TokenUrl,
-};
-use serde::{Deserialize, Serialize};
-use std::marker::PhantomData;
-use std::path::Path;
-use std::time::{Duration, SystemTime};
-use std::{env, fs};
+use std::env;
 use thiserror::Error;
-use toml;
-use url::Url;
 
-// Expiration date for access tokens is one month
-// We use 28 days in seconds to be safe
-const EXPIRATION_IN_SECONDS: u64 = 2415600;
+use crate::codes::ErrorCode;
+
+#[cfg(feature = "oauth")]
+use {
+    crate::{OAUTH_TOKEN_URL, OAUTH_URL},
+    clock::{Clock, SystemClock, TokenExpiry},
+    oauth2::basic::BasicClient,
+    oauth2::http::Uri,
+    oauth2::reqwest::async_http_client,
+    oauth2::ClientId,
+    oauth2::{
+        AccessToken, AuthUrl, AuthorizationCode, ClientSecret, CsrfToken, PkceCodeChallenge,
+        PkceCodeVerifier, RedirectUrl, RefreshToken, TokenResponse, TokenUrl,
+    },
+    pending::{DynPendingAuthStore, MemoryPendingAuthStore, PendingAuth, PendingAuthStore},
+    serde::{Deserialize, Serialize},
+    std::fs,
+    std::marker::PhantomData,
+    std::path::Path,
+    std::sync::Arc,
+    std::time::Duration,
+    url::Url,
+};
+
+/// Actix-web integration for the OAuth flow
+#[cfg(feature = "actix")]
+pub mod actix;
+
+/// Time sources and token expiry
+#[cfg(feature = "oauth")]
+pub mod clock;
+
+/// Axum integration for the OAuth flow
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// Loopback redirect helper for desktop applications
+#[cfg(feature = "desktop")]
+pub mod desktop;
+
+/// Storage for logins waiting for the redirect from MAL
+#[cfg(feature = "oauth")]
+pub mod pending;
+
+/// Credentials of the API clients
+pub mod provider;
+
+/// Token storage with refresh token rotation
+#[cfg(feature = "oauth")]
+pub mod store;
+
+// Users have this long to complete a web login on MAL
+#[cfg(feature = "oauth")]
+const PENDING_LOGIN_TTL: Duration = Duration::from_secs(600);
 
 #[derive(Debug, Error)]
 pub enum OauthError {
@@ -77,39 +117,142 @@ pub enum OauthError {
 
     #[error("missing the code or state from response")]
     MissingCodeOrState,
+
+    #[error("failed to listen for the redirect")]
+    LoopbackFailure,
+
+    #[error("failed to open the browser")]
+    BrowserFailure,
+
+    #[error("invalid authorization or token url")]
+    InvalidEndpointUrl,
+
+    #[error("failed to access the pending logins")]
+    PendingAuthStoreFailure(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl OauthError {
+    // HTTP status of the error when returned by the web framework integrations
+    #[cfg(any(feature = "axum", feature = "actix"))]
+    pub(crate) fn http_status(&self) -> u16 {
+        match self {
+            OauthError::BadTokenResponse => 502,
+            OauthError::InvalidRedirectUrl => 500,
+            _ => 400,
+        }
+    }
+
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            OauthError::MissingEnvVar => ErrorCode::OauthMissingEnvVar,
+            OauthError::MissingClientId => ErrorCode::OauthMissingClientId,
+            OauthError::MissingClientSecret => ErrorCode::OauthMissingClientSecret,
+            OauthError::MissingRedirectUrl => ErrorCode::OauthMissingRedirectUrl,
+            OauthError::StateMismatch => ErrorCode::OauthStateMismatch,
+            OauthError::BadTokenResponse => ErrorCode::OauthBadTokenResponse,
+            OauthError::InvalidRedirectUrl => ErrorCode::OauthInvalidRedirectUrl,
+            OauthError::InvalidRedirectResponse => ErrorCode::OauthInvalidRedirectResponse,
+            OauthError::MissingAccessToken => ErrorCode::OauthMissingAccessToken,
+            OauthError::MissingRefreshToken => ErrorCode::OauthMissingRefreshToken,
+            OauthError::MissingTokenExpiration => ErrorCode::OauthMissingTokenExpiration,
+            OauthError::MissingConfig => ErrorCode::OauthMissingConfig,
+            OauthError::InvalidConfigFormat => ErrorCode::OauthInvalidConfigFormat,
+            OauthError::ConfigCreationFailure => ErrorCode::OauthConfigCreationFailure,
+            OauthError::NoSystemTime => ErrorCode::OauthNoSystemTime,
+            OauthError::InvalidExpirationTime => ErrorCode::OauthInvalidExpirationTime,
+            OauthError::FailedToRefreshToken => ErrorCode::OauthRefreshFailed,
+            OauthError::MissingCodeOrState => ErrorCode::OauthMissingCodeOrState,
+            OauthError::LoopbackFailure => ErrorCode::OauthLoopbackFailure,
+            OauthError::BrowserFailure => ErrorCode::OauthBrowserFailure,
+            OauthError::InvalidEndpointUrl => ErrorCode::OauthInvalidEndpointUrl,
+            OauthError::PendingAuthStoreFailure(_) => ErrorCode::OauthPendingAuthStoreFailure,
+        }
+    }
+}
+
+impl From<crate::clock::ClockError> for OauthError {
+    fn from(_: crate::clock::ClockError) -> Self {
+        OauthError::NoSystemTime
+    }
 }
 
 /// If you only need to access public information on MAL that does
 /// not require an Oauth access token, you can use the [MalClientId]
 /// as your authorization client
-#[derive(Debug)]
-pub struct MalClientId(pub ClientId);
+#[derive(Debug, Clone)]
+pub struct MalClientId(String);
 
 impl MalClientId {
     /// Create a [MalClientId] by passing in your ClientId as a string
     ///
     /// Useful if you want to control how your program fetches your MAL `MAL_CLIENT_ID`
     pub fn new<T: Into<String>>(id: T) -> Self {
-        let client_id = ClientId::new(id.into());
-        Self(client_id)
+        Self(id.into())
     }
 
     /// Try to load your MAL ClientId from the environment variable `MAL_CLIENT_ID`
     pub fn try_from_env() -> Result<Self, OauthError> {
-        let client_id = OauthClient::load_client_id_from_env()?;
-        Ok(Self(ClientId::new(client_id)))
+        let client_id = env::var("MAL_CLIENT_ID").map_err(|_| OauthError::MissingClientId)?;
+        Ok(Self(client_id))
+    }
+
+    /// Get the client id as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "oauth")]
+impl From<ClientId> for MalClientId {
+    fn from(value: ClientId) -> Self {
+        Self(value.to_string())
+    }
+}
+
+/// An Oauth access token for MAL
+///
+/// Wraps the token so that creating API clients from a token you stored yourself
+/// doesn't require depending on the `oauth2` crate
+#[derive(Clone)]
+pub struct MalAccessToken(String);
+
+impl MalAccessToken {
+    pub fn new<T: Into<String>>(secret: T) -> Self {
+        Self(secret.into())
+    }
+
+    /// Get the secret value of the token
+    pub fn secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for MalAccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MalAccessToken([redacted])")
+    }
+}
+
+#[cfg(feature = "oauth")]
+impl From<AccessToken> for MalAccessToken {
+    fn from(value: AccessToken) -> Self {
+        Self(value.secret().clone())
     }
 }
 
 /// State struct for separating an Authenticated and Unauthenticated OAuthClient
+#[cfg(feature = "oauth")]
 #[derive(Debug)]
 pub struct Unauthenticated;
 
 /// State struct for separating an Authenticated and Unauthenticated OAuthClient
+#[cfg(feature = "oauth")]
 #[derive(Debug)]
 pub struct Authenticated;
 
 /// Client used to navigate and manage Oauth credentials with MAL
+#[cfg(feature = "oauth")]
 #[derive(Debug)]
 pub struct OauthClient<State = Unauthenticated> {
     client: BasicClient,
@@ -119,21 +262,120 @@ pub struct OauthClient<State = Unauthenticated> {
     access_token: AccessToken,
     refresh_token: RefreshToken,
     expires_at: u64,
+
+    // Added to the authorization URL
+    extra_params: Vec<(String, String)>,
+
+    clock: Arc<dyn Clock>,
 }
 
-impl OauthClient<Unauthenticated> {
-    /// Creates a new [OauthClient] for the PKCE flow
-    pub fn new<T: Into<String>>(
-        client_id: T,
-        client_secret: Option<T>,
-        redirect_url: T,
-    ) -> Result<Self, OauthError> {
-        let (client_id, redirect_url) = (client_id.into(), redirect_url.into());
-        let client_secret = client_secret.map(|c| c.into());
+/// Builder for an [OauthClient] with custom endpoints or parameters
+///
+/// Use it when the authorization or token requests go through a proxy, when
+/// the authorization URL needs extra query parameters, or when the port of the
+/// redirect URL is only known at runtime, e.g. for a loopback redirect.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut oauth_client = OauthClient::builder(client_id)
+///     .redirect_url("http://127.0.0.1/callback")
+///     .redirect_port(listener.local_addr()?.port())
+///     .token_url("https://auth.example.com/mal/token")
+///     .extra_param("prompt", "consent")
+///     .build()
+///     .unwrap();
+/// let auth_url = oauth_client.generate_auth_url();
+/// ```
+#[cfg(feature = "oauth")]
+#[derive(Debug, Clone)]
+pub struct OauthClientBuilder {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: Option<String>,
+    redirect_port: Option<u16>,
+    auth_url: String,
+    token_url: String,
+    extra_params: Vec<(String, String)>,
+    clock: Arc<dyn Clock>,
+}
+
+#[cfg(feature = "oauth")]
+impl OauthClientBuilder {
+    pub fn new<T: Into<String>>(client_id: T) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_url: None,
+            redirect_port: None,
+            auth_url: OAUTH_URL.to_string(),
+            token_url: OAUTH_TOKEN_URL.to_string(),
+            extra_params: Vec::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
 
-        let client = Self::create_oauth2_client(client_id, client_secret, redirect_url)?;
+    pub fn client_secret<T: Into<String>>(mut self, value: T) -> Self {
+        self.client_secret = Some(value.into());
+        self
+    }
+
+    pub fn redirect_url<T: Into<String>>(mut self, value: T) -> Self {
+        self.redirect_url = Some(value.into());
+        self
+    }
+
+    /// Replace the port of the redirect URL
+    pub fn redirect_port(mut self, value: u16) -> Self {
+        self.redirect_port = Some(value);
+        self
+    }
+
+    /// Send users to this authorization URL instead of MAL's
+    pub fn auth_url<T: Into<String>>(mut self, value: T) -> Self {
+        self.auth_url = value.into();
+        self
+    }
+
+    /// Exchange and refresh tokens through this URL instead of MAL's
+    pub fn token_url<T: Into<String>>(mut self, value: T) -> Self {
+        self.token_url = value.into();
+        self
+    }
+
+    /// Add a query parameter to the authorization URL
+    pub fn extra_param<T: Into<String>>(mut self, name: T, value: T) -> Self {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Read the current time from this clock instead of the system's
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Create the client
+    ///
+    /// Fails if the redirect URL isn't set, or if any of the URLs is invalid
+    pub fn build(self) -> Result<OauthClient<Unauthenticated>, OauthError> {
+        let mut redirect_url = self.redirect_url.ok_or(OauthError::MissingRedirectUrl)?;
+        if let Some(port) = self.redirect_port {
+            let mut url = Url::parse(&redirect_url).map_err(|_| OauthError::InvalidRedirectUrl)?;
+            url.set_port(Some(port))
+                .map_err(|_| OauthError::InvalidRedirectUrl)?;
+            redirect_url = url.to_string();
+        }
 
-        Ok(Self {
+        let client = OauthClient::create_oauth2_client(
+            self.client_id,
+            self.client_secret,
+            redirect_url,
+            self.auth_url,
+            self.token_url,
+        )?;
+
+        Ok(OauthClient {
             client,
             pkce_verifier: PkceCodeVerifier::new("".to_string()),
             csrf: CsrfToken::new(String::from("")),
@@ -141,21 +383,83 @@ impl OauthClient<Unauthenticated> {
             access_token: AccessToken::new("".to_string()),
             refresh_token: RefreshToken::new("".to_string()),
             expires_at: Duration::new(0, 0).as_secs(),
+            extra_params: self.extra_params,
+            clock: self.clock,
+        })
+    }
+
+    /// Create an authenticated client from stored tokens
+    ///
+    /// Unlike [OauthClient::load_from_values], this keeps custom endpoints and
+    /// extra parameters, so refreshing the tokens goes through the same token URL
+    /// as the login did.
+    ///
+    /// Expired tokens still load, so [OauthClient::refresh_if_expired] can refresh
+    /// them against the client's [Clock]. Fails with [OauthError::InvalidExpirationTime]
+    /// if `expires_at` is 0, which means the expiry was never stored.
+    pub fn build_authenticated<T: Into<String>>(
+        self,
+        access_token: T,
+        refresh_token: T,
+        expires_at: u64,
+    ) -> Result<OauthClient<Authenticated>, OauthError> {
+        if expires_at == 0 {
+            return Err(OauthError::InvalidExpirationTime);
+        }
+        let client = self.build()?;
+
+        Ok(OauthClient::<Authenticated> {
+            client: client.client,
+            csrf: CsrfToken::new(String::default()),
+            pkce_verifier: PkceCodeVerifier::new(String::default()),
+            state: PhantomData::<Authenticated>,
+            access_token: AccessToken::new(access_token.into()),
+            refresh_token: RefreshToken::new(refresh_token.into()),
+            expires_at,
+            extra_params: client.extra_params,
+            clock: client.clock,
         })
     }
+}
+
+#[cfg(feature = "oauth")]
+impl OauthClient<Unauthenticated> {
+    /// Creates a new [OauthClient] for the PKCE flow
+    ///
+    /// Use [OauthClient::builder] for custom endpoints or parameters
+    pub fn new<T: Into<String>>(
+        client_id: T,
+        client_secret: Option<T>,
+        redirect_url: T,
+    ) -> Result<Self, OauthError> {
+        let mut builder = OauthClientBuilder::new(client_id).redirect_url(redirect_url);
+        if let Some(client_secret) = client_secret {
+            builder = builder.client_secret(client_secret);
+        }
+        builder.build()
+    }
+
+    /// Use builder pattern for creating the client with custom endpoints or parameters
+    pub fn builder<T: Into<String>>(client_id: T) -> OauthClientBuilder {
+        OauthClientBuilder::new(client_id)
+    }
 
     fn create_oauth2_client(
         client_id: String,
         client_secret: Option<String>,
         redirect_url: String,
+        auth_url: String,
+        token_url: String,
     ) -> Result<BasicClient, OauthError> {
+        let auth_url = AuthUrl::new(auth_url).map_err(|_| OauthError::InvalidEndpointUrl)?;
+        let token_url = TokenUrl::new(token_url).map_err(|_| OauthError::InvalidEndpointUrl)?;
         match client_secret {
             Some(c) => {
                 let client = BasicClient::new(
                     ClientId::new(client_id),
                     Some(ClientSecret::new(c.into())),
-                    AuthUrl::new(OAUTH_URL.to_string()).unwrap(),
-                    Some(TokenUrl::new(OAUTH_TOKEN_URL.to_string()).unwrap()),
+                    auth_url,
+                    Some(token_url),
                 )
                 .set_redirect_uri(
                     RedirectUrl::new(redirect_url).map_err(|_| OauthError::InvalidRedirectUrl)?,
@@ -164,16 +468,13 @@ impl OauthClient<Unauthenticated> {
                 Ok(client)
             }
             None => {
-                let client = BasicClient::new(
-                    ClientId::new(client_id),
-                    None,
-                    AuthUrl::new(OAUTH_URL.to_string()).unwrap(),
-                    Some(TokenUrl::new(OAUTH_TOKEN_URL.to_string()).unwrap()),
-                )
-                .set_redirect_uri(
-                    RedirectUrl::new(redirect_url).map_err(|_| OauthError::InvalidRedirectUrl)?,
-                )
-                .set_auth_type(oauth2::AuthType::RequestBody);
+                let client =
+                    BasicClient::new(ClientId::new(client_id), None, auth_url, Some(token_url))
+                        .set_redirect_uri(
+                            RedirectUrl::new(redirect_url)
+                                .map_err(|_| OauthError::InvalidRedirectUrl)?,
+                        )
+                        .set_auth_type(oauth2::AuthType::RequestBody);
                 Ok(client)
             }
         }
@@ -184,11 +485,14 @@ impl OauthClient<Unauthenticated> {
     pub fn generate_auth_url(&mut self) -> String {
         let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_plain();
 
-        let (auth_url, csrf_token) = self
+        let mut request = self
             .client
             .authorize_url(CsrfToken::new_random)
-            .set_pkce_challenge(pkce_challenge)
-            .url();
+            .set_pkce_challenge(pkce_challenge);
+        for (name, value) in &self.extra_params {
+            request = request.add_extra_param(name.as_str(), value.as_str());
+        }
+        let (auth_url, csrf_token) = request.url();
 
         self.csrf = csrf_token;
         self.pkce_verifier = pkce_verifier;
@@ -196,6 +500,25 @@ impl OauthClient<Unauthenticated> {
         auth_url.to_string()
     }
 
+    /// Get the state and PKCE verifier of the last authorization URL, to
+    /// complete the login from another client with [OauthClient::with_pending_auth]
+    ///
+    /// Fails if the client's [Clock] can't read the time
+    pub fn pending_auth(&self) -> Result<PendingAuth, OauthError> {
+        Ok(PendingAuth::new(
+            self.csrf.secret().as_str(),
+            self.pkce_verifier.secret().as_str(),
+            self.clock.now()?,
+        ))
+    }
+
+    /// Resume a login started by another client with the same credentials
+    pub fn with_pending_auth(mut self, pending: PendingAuth) -> Self {
+        self.csrf = CsrfToken::new(pending.state);
+        self.pkce_verifier = PkceCodeVerifier::new(pending.pkce_verifier);
+        self
+    }
+
     /// Try and authenticate the client using a redirect response to
     /// get an authenticated Oauth client back
     pub async fn authenticate(
@@ -215,54 +538,21 @@ impl OauthClient<Unauthenticated> {
             .await
             .map_err(|_| OauthError::BadTokenResponse)?;
 
-        let now = calculate_current_system_time()?;
+        let now = self.clock.now()?;
 
         Ok(OauthClient::<Authenticated> {
             client: self.client,
             csrf: self.csrf,
             pkce_verifier: PkceCodeVerifier::new("".to_string()),
             state: PhantomData::<Authenticated>,
+            extra_params: self.extra_params,
+            clock: self.clock,
             access_token: token_result.access_token().to_owned(),
             refresh_token: token_result
                 .refresh_token()
                 .ok_or_else(|| OauthError::MissingRefreshToken)?
                 .to_owned(),
-            expires_at: now
-                + token_result
-                    .expires_in()
-                    .unwrap_or(Duration::from_secs(EXPIRATION_IN_SECONDS))
-                    .as_secs(),
-        })
-    }
-
-    /// Load Oauth credentials from the environment
-    ///
-    /// `Note`: This is expected to work after saving the credentials from an
-    /// authenticated OauthClient
-    fn load_from_env() -> Result<OauthClient<Authenticated>, OauthError> {
-        let (client_id, redirect_url) = (
-            Self::load_client_id_from_env()?,
-            Self::load_redirect_url_from_env()?,
-        );
-        let client_secret = Self::load_client_secret_from_env().ok();
-
-        let client = Self::create_oauth2_client(client_id, client_secret, redirect_url)?;
-
-        let access_token = Self::load_env_var("MAL_ACCESS_TOKEN")?;
-        let refresh_token = Self::load_env_var("MAL_REFRESH_TOKEN")?;
-        let expires_at = Self::load_env_var("MAL_TOKEN_EXPIRES_AT")
-            .map_err(|_| OauthError::MissingTokenExpiration)?
-            .parse::<u64>()
-            .map_err(|_| OauthError::InvalidExpirationTime)?;
-
-        Ok(OauthClient::<Authenticated> {
-            client,
-            csrf: CsrfToken::new(String::default()),
-            pkce_verifier: PkceCodeVerifier::new(String::default()),
-            state: PhantomData::<Authenticated>,
-            access_token: AccessToken::new(access_token),
-            refresh_token: RefreshToken::new(refresh_token),
-            expires_at,
+            expires_at: TokenExpiry::from_expires_in(now, token_result.expires_in()).expires_at(),
         })
     }
 
@@ -294,7 +584,26 @@ impl OauthClient<Unauthenticated> {
             "MAL_TOKEN_EXPIRES_AT",
             parsed_toml.mal_token_expires_at.to_string(),
         );
-        Self::load_from_env()
+
+        let mut builder = OauthClientBuilder::new(Self::load_client_id_from_env()?)
+            .redirect_url(Self::load_redirect_url_from_env()?);
+        if let Ok(client_secret) = Self::load_client_secret_from_env() {
+            builder = builder.client_secret(client_secret);
+        }
+        if let Some(auth_url) = parsed_toml.mal_auth_url {
+            builder = builder.auth_url(auth_url);
+        }
+        if let Some(token_url) = parsed_toml.mal_token_url {
+            builder = builder.token_url(token_url);
+        }
+        for (name, value) in parsed_toml.mal_extra_params {
+            builder = builder.extra_param(name, value);
+        }
+        builder.build_authenticated(
+            parsed_toml.mal_access_token,
+            parsed_toml.mal_refresh_token,
+            parsed_toml.mal_token_expires_at,
+        )
     }
 
     /// Load an authenticated OauthClient by passing the necessary values
@@ -302,6 +611,13 @@ impl OauthClient<Unauthenticated> {
     /// It's recommended to refresh the client after to ensure that
     /// the given values are still valid credentials.
     ///
+    /// An expired access token still loads. Call [OauthClient::refresh_if_expired]
+    /// to refresh it. Fails with [OauthError::InvalidExpirationTime] if `expires_at`
+    /// is 0.
+    ///
+    /// The client uses MAL's endpoints. Use [OauthClientBuilder::build_authenticated]
+    /// to keep a custom token URL or extra parameters.
+    ///
     /// `Note`: This method still relies on the `MAL_CLIENT_ID`, `MAL_CLIENT_SECRET`, and
     /// `MAL_REDIRECT_URL` environment variables being set
     pub fn load_from_values<T: Into<String>>(
@@ -312,33 +628,11 @@ impl OauthClient<Unauthenticated> {
         redirect_url: T,
         expires_at: u64,
     ) -> Result<OauthClient<Authenticated>, OauthError> {
-        let (access_token, refresh_token) = (access_token.into(), refresh_token.into());
-        let (client_id, client_secret, redirect_url) = (
-            client_id.into(),
-            client_secret.map(|c| c.into()),
-            redirect_url.into(),
-        );
-
-        let unix_epoch = SystemTime::UNIX_EPOCH
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map_err(|_| OauthError::NoSystemTime)?
-            .as_secs();
-
-        if expires_at < unix_epoch {
-            return Err(OauthError::InvalidExpirationTime);
+        let mut builder = OauthClientBuilder::new(client_id).redirect_url(redirect_url);
+        if let Some(client_secret) = client_secret {
+            builder = builder.client_secret(client_secret);
         }
-
-        let client = Self::create_oauth2_client(client_id, client_secret, redirect_url)?;
-
-        Ok(OauthClient::<Authenticated> {
-            client,
-            csrf: CsrfToken::new(String::default()),
-            pkce_verifier: PkceCodeVerifier::new(String::default()),
-            state: PhantomData::<Authenticated>,
-            access_token: AccessToken::new(access_token),
-            refresh_token: RefreshToken::new(refresh_token),
-            expires_at,
-        })
+        builder.build_authenticated(access_token, refresh_token, expires_at)
     }
 
     fn load_env_var(name: &str) -> Result<String, OauthError> {
@@ -368,17 +662,36 @@ impl OauthClient<Unauthenticated> {
     }
 }
 
+#[cfg(feature = "oauth")]
+impl<State> OauthClient<State> {
+    /// Read the current time from this clock instead of the system's
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+#[cfg(feature = "oauth")]
 #[derive(Debug, Serialize, Deserialize)]
 struct MalCredentialsConfig {
     mal_access_token: String,
     mal_refresh_token: String,
     mal_token_expires_at: u64,
+
+    // Only saved for clients with custom endpoints or parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mal_auth_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mal_token_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mal_extra_params: Vec<(String, String)>,
 }
 
+#[cfg(feature = "oauth")]
 impl OauthClient<Authenticated> {
-    /// Get the access token for the OauthClient
-    pub(crate) fn get_access_token(&self) -> &AccessToken {
-        &self.access_token
+    /// Get a copy of the access token, e.g. for creating API clients later
+    pub fn access_token(&self) -> MalAccessToken {
+        MalAccessToken::new(self.access_token.secret().clone())
     }
 
     /// Get the access token secret value
@@ -398,10 +711,24 @@ impl OauthClient<Authenticated> {
         &self.expires_at
     }
 
+    /// Get the [TokenExpiry] of the access token
+    pub fn expiry(&self) -> TokenExpiry {
+        TokenExpiry::at(self.expires_at)
+    }
+
+    /// Check whether the access token has expired, according to the client's [Clock]
+    ///
+    /// Fails if the clock can't read the time
+    pub fn is_expired(&self) -> Result<bool, OauthError> {
+        Ok(self.expiry().is_expired(self.clock.now()?))
+    }
+
     /// Save the Oauth credentials to the config
     ///
     /// This method is available if you want to persist your
-    /// access, refresh, and expires_at values on the host
+    /// access, refresh, and expires_at values on the host. Custom endpoints and
+    /// extra parameters are saved with them, and restored by
+    /// [load_from_config](OauthClient::load_from_config)
     pub fn save_to_config<T: Into<String>>(&self, path: T) -> Result<(), OauthError> {
         let path: String = path.into();
         let dir = env::current_dir().map_err(|_| OauthError::MissingConfig)?;
@@ -411,6 +738,13 @@ impl OauthClient<Authenticated> {
             mal_access_token: self.access_token.secret().clone(),
             mal_refresh_token: self.refresh_token.secret().clone(),
             mal_token_expires_at: *self.get_expires_at(),
+            mal_auth_url: Some(self.client.auth_url().to_string()).filter(|url| url != OAUTH_URL),
+            mal_token_url: self
+                .client
+                .token_url()
+                .map(|url| url.to_string())
+                .filter(|url| url != OAUTH_TOKEN_URL),
+            mal_extra_params: self.extra_params.clone(),
         };
         let toml = toml::to_string(&config).map_err(|_| OauthError::InvalidConfigFormat)?;
 
@@ -424,6 +758,11 @@ impl OauthClient<Authenticated> {
 
     /// Refresh the access token using the refresh token
     pub async fn refresh(self) -> Result<Self, OauthError> {
+        self.refreshed().await
+    }
+
+    // Refreshes the access token into a new client, keeping this one usable if it fails
+    pub(crate) async fn refreshed(&self) -> Result<Self, OauthError> {
         let refresh_result = self
             .client
             .exchange_refresh_token(&self.refresh_token)
@@ -431,30 +770,165 @@ impl OauthClient<Authenticated> {
             .await
             .map_err(|_| OauthError::FailedToRefreshToken)?;
 
-        let now = calculate_current_system_time()?;
+        let now = self.clock.now()?;
 
         Ok(OauthClient::<Authenticated> {
-            client: self.client,
-            csrf: self.csrf,
+            client: self.client.clone(),
+            csrf: self.csrf.clone(),
             pkce_verifier: PkceCodeVerifier::new("".to_string()),
             state: PhantomData::<Authenticated>,
+            extra_params: self.extra_params.clone(),
+            clock: Arc::clone(&self.clock),
             access_token: refresh_result.access_token().to_owned(),
-            refresh_token: refresh_result.refresh_token().unwrap().to_owned(),
-            expires_at: now
-                + refresh_result
-                    .expires_in()
-                    .unwrap_or(Duration::from_secs(EXPIRATION_IN_SECONDS))
-                    .as_secs(),
+            // MAL rotates the refresh token, but the old one stays valid if a
+            // token response doesn't have a new one
+            refresh_token: refresh_result
+                .refresh_token()
+                .unwrap_or(&self.refresh_token)
+                .to_owned(),
+            expires_at: TokenExpiry::from_expires_in(now, refresh_result.expires_in()).expires_at(),
         })
     }
+
+    /// Refresh the access token if it has expired, otherwise return the client as is
+    pub async fn refresh_if_expired(self) -> Result<Self, OauthError> {
+        if self.is_expired()? {
+            self.refresh().await
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Shared state for web login routes
+///
+/// Each call to [MalOauthState::authorize_url] creates a fresh [OauthClient] and
+/// stores its CSRF state and PKCE verifier in a [PendingAuthStore], so the redirect
+/// callback can complete the flow without any cookie or session middleware.
+#[cfg(feature = "oauth")]
+#[derive(Debug, Clone)]
+pub struct MalOauthState {
+    inner: Arc<Inner>,
+    clock: Arc<dyn Clock>,
+    token_url: Option<String>,
 }
 
+#[cfg(feature = "oauth")]
+#[derive(Debug)]
+struct Inner {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: String,
+    pending: Box<dyn DynPendingAuthStore>,
+}
+
+#[cfg(feature = "oauth")]
+impl MalOauthState {
+    /// Create a new [MalOauthState] from your MAL app credentials
+    pub fn new<T: Into<String>>(client_id: T, client_secret: Option<T>, redirect_url: T) -> Self {
+        Self::with_pending_store(
+            client_id,
+            client_secret,
+            redirect_url,
+            MemoryPendingAuthStore::new(),
+        )
+    }
+
+    /// Create a new [MalOauthState] keeping the pending logins in `store`
+    ///
+    /// Share the store between workers to complete a login on any of them
+    pub fn with_pending_store<T: Into<String>, S: PendingAuthStore + 'static>(
+        client_id: T,
+        client_secret: Option<T>,
+        redirect_url: T,
+        store: S,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client_id: client_id.into(),
+                client_secret: client_secret.map(|c| c.into()),
+                redirect_url: redirect_url.into(),
+                pending: Box::new(store),
+            }),
+            clock: Arc::new(SystemClock),
+            token_url: None,
+        }
+    }
+
+    /// Read the current time from this clock instead of the system's
+    ///
+    /// The clock dates new logins, expires stale ones, and is passed on to the
+    /// authenticated clients
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Exchange and refresh tokens through this URL instead of MAL's
+    pub fn with_token_url<T: Into<String>>(mut self, token_url: T) -> Self {
+        self.token_url = Some(token_url.into());
+        self
+    }
+
+    /// Create a new [MalOauthState] from the `MAL_CLIENT_ID`, `MAL_CLIENT_SECRET`,
+    /// and `MAL_REDIRECT_URL` environment variables
+    pub fn try_from_env() -> Result<Self, OauthError> {
+        let client_id = OauthClient::load_client_id_from_env()?;
+        let client_secret = OauthClient::load_client_secret_from_env().ok();
+        let redirect_url = OauthClient::load_redirect_url_from_env()?;
+        Ok(Self::new(client_id, client_secret, redirect_url))
+    }
+
+    /// Begin a new login, returning the MAL authorization URL to redirect the user to
+    pub async fn authorize_url(&self) -> Result<String, OauthError> {
+        let mut client = self.client()?;
+        let url = client.generate_auth_url();
+
+        self.inner
+            .pending
+            .dyn_insert(client.pending_auth()?)
+            .await?;
+        Ok(url)
+    }
+
+    /// Complete a pending login using the redirect response from MAL
+    pub async fn complete(
+        &self,
+        response: RedirectResponse,
+    ) -> Result<OauthClient<Authenticated>, OauthError> {
+        let pending = self.inner.pending.dyn_take(&response.state).await?;
+        let now = self.clock.now()?;
+        let pending = pending
+            .filter(|pending| !pending.is_expired(now))
+            .ok_or(OauthError::StateMismatch)?;
+        self.client()?
+            .with_pending_auth(pending)
+            .authenticate(response)
+            .await
+    }
+
+    fn client(&self) -> Result<OauthClient<Unauthenticated>, OauthError> {
+        let mut builder = OauthClient::builder(self.inner.client_id.clone())
+            .redirect_url(self.inner.redirect_url.clone())
+            .clock(Arc::clone(&self.clock));
+        if let Some(client_secret) = &self.inner.client_secret {
+            builder = builder.client_secret(client_secret.clone());
+        }
+        if let Some(token_url) = &self.token_url {
+            builder = builder.token_url(token_url.clone());
+        }
+        builder.build()
+    }
+}
+
+#[cfg(feature = "oauth")]
 #[derive(Debug, Deserialize)]
 pub struct RedirectResponse {
     code: String,
     state: String,
 }
 
+#[cfg(feature = "oauth")]
 impl RedirectResponse {
     /// Create a new RedirectResponse from given code and state
     pub fn new<T: Into<String>>(code: T, state: T) -> Self {
@@ -479,6 +953,7 @@ impl RedirectResponse {
     }
 }
 
+#[cfg(feature = "oauth")]
 impl TryFrom<String> for RedirectResponse {
     type Error = OauthError;
 
@@ -487,19 +962,134 @@ impl TryFrom<String> for RedirectResponse {
             .parse::<Url>()
             .map_err(|_| OauthError::InvalidRedirectResponse)?;
 
-        let query_params = query_string
-            .query()
-            .ok_or_else(|| OauthError::MissingCodeOrState)?;
+        Self::from_query(query_string.query())
+    }
+}
 
-        serde_urlencoded::from_str::<RedirectResponse>(&query_params)
+#[cfg(feature = "oauth")]
+impl RedirectResponse {
+    // Parse the `code` and `state` from the query of a redirect
+    pub(crate) fn from_query(query: Option<&str>) -> Result<Self, OauthError> {
+        let query = query.ok_or(OauthError::MissingCodeOrState)?;
+        serde_urlencoded::from_str::<RedirectResponse>(query)
             .map_err(|_| OauthError::MissingCodeOrState)
     }
 }
 
-fn calculate_current_system_time() -> Result<u64, OauthError> {
-    let now = SystemTime::UNIX_EPOCH
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .map_err(|_| OauthError::NoSystemTime)?
-        .as_secs();
-    Ok(now)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg(feature = "oauth")]
+    async fn test_complete_unknown_state() {
+        let state = MalOauthState::new("id", None, "http://localhost:8080/callback");
+        let url = state.authorize_url().await.unwrap();
+        assert!(url.starts_with(crate::OAUTH_URL));
+
+        let response = RedirectResponse::new("code", "unknown");
+        let result = state.complete(response).await;
+        assert!(matches!(result, Err(OauthError::StateMismatch)));
+    }
+
+    #[test]
+    #[cfg(feature = "oauth")]
+    fn test_oauth_client_builder() {
+        let mut client = OauthClient::builder("id")
+            .redirect_url("http://127.0.0.1/callback")
+            .redirect_port(49152)
+            .auth_url("https://auth.example.com/authorize")
+            .extra_param("prompt", "consent")
+            .build()
+            .unwrap();
+        let url = Url::parse(&client.generate_auth_url()).unwrap();
+        assert!(url
+            .as_str()
+            .starts_with("https://auth.example.com/authorize?"));
+
+        let params: std::collections::HashMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["prompt"], "consent");
+        assert_eq!(params["redirect_uri"], "http://127.0.0.1:49152/callback");
+
+        assert!(matches!(
+            OauthClient::builder("id").build(),
+            Err(OauthError::MissingRedirectUrl)
+        ));
+        assert!(matches!(
+            OauthClient::builder("id")
+                .redirect_url("http://127.0.0.1/callback")
+                .token_url("not a url")
+                .build(),
+            Err(OauthError::InvalidEndpointUrl)
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "oauth")]
+    async fn test_refresh_keeps_refresh_token() {
+        let token_url = crate::fixtures::token_endpoint(serde_json::json!({
+            "access_token": "new access",
+            "token_type": "Bearer",
+            "expires_in": 3600
+        }))
+        .await;
+        let client = OauthClient::builder("id")
+            .redirect_url("http://127.0.0.1/callback")
+            .token_url(token_url)
+            .clock(clock::ManualClock::new(1_700_000_000))
+            .build_authenticated("access", "refresh", 1_700_000_000)
+            .unwrap();
+
+        // The response has no refresh token, so the current one is kept
+        let client = client.refresh().await.unwrap();
+        assert_eq!(client.get_access_token_secret(), "new access");
+        assert_eq!(client.get_refresh_token_secret(), "refresh");
+        assert_eq!(*client.get_expires_at(), 1_700_003_600);
+    }
+
+    #[test]
+    #[cfg(feature = "oauth")]
+    fn test_config_keeps_custom_endpoints() {
+        let client = OauthClient::builder("id")
+            .redirect_url("http://127.0.0.1/callback")
+            .token_url("https://auth.example.com/mal/token")
+            .extra_param("prompt", "consent")
+            .build_authenticated("access", "refresh", 1_700_003_600)
+            .unwrap();
+        let path = env::temp_dir().join(format!("mal-api-config-{}.toml", std::process::id()));
+        client.save_to_config(path.to_str().unwrap()).unwrap();
+
+        env::set_var("MAL_CLIENT_ID", "id");
+        env::set_var("MAL_REDIRECT_URL", "http://127.0.0.1/callback");
+        let loaded = OauthClient::load_from_config(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.get_access_token_secret(), "access");
+        assert_eq!(
+            loaded.client.token_url().unwrap().as_str(),
+            "https://auth.example.com/mal/token"
+        );
+        assert_eq!(loaded.client.auth_url().as_str(), OAUTH_URL);
+        assert_eq!(
+            loaded.extra_params,
+            vec![("prompt".to_string(), "consent".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_credential_wrappers() {
+        let token = MalAccessToken::new("secret");
+        assert_eq!(token.secret(), "secret");
+        assert_eq!(format!("{:?}", token), "MalAccessToken([redacted])");
+        assert_eq!(MalClientId::new("id").as_str(), "id");
+
+        #[cfg(feature = "oauth")]
+        {
+            let token = MalAccessToken::from(AccessToken::new("secret".to_string()));
+            assert_eq!(token.secret(), "secret");
+
+            let client_id = MalClientId::from(ClientId::new("id".to_string()));
+            assert_eq!(client_id.as_str(), "id");
+        }
+    }
 }
@@ -0,0 +1,146 @@
+//! Actix-web integration for the MAL OAuth2 flow
+//!
+//! Enable the `actix` feature to use this module.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use actix_web::{App, HttpResponse, HttpServer};
+//! use mal_api::oauth::MalOauthState;
+//!
+//! let state = MalOauthState::try_from_env().unwrap();
+//! HttpServer::new(move || {
+//!     App::new().service(mal_api::oauth::actix::scope("/mal", state.clone(), |client| async move {
+//!         let _ = client.save_to_config(".mal/config.toml");
+//!         HttpResponse::Ok().body("Logged in!")
+//!     }))
+//! })
+//! .bind(("127.0.0.1", 8080))?
+//! .run()
+//! .await
+//! ```
+
+use std::future::Future;
+
+use actix_web::{
+    http::{header, StatusCode},
+    web, HttpResponse, ResponseError, Scope,
+};
+
+use super::{Authenticated, MalOauthState, OauthClient, OauthError, RedirectResponse};
+
+/// Create a scope mounted at `path` containing the login endpoints
+///
+/// - `{path}/login` redirects the user to the MAL authorization page
+/// - `{path}/callback` completes the OAuth flow and passes the authenticated
+///   client to `on_login`, whose response is returned to the user
+///
+/// Your `MAL_REDIRECT_URL` should point to the `{path}/callback` endpoint.
+pub fn scope<F, Fut>(path: &str, state: MalOauthState, on_login: F) -> Scope
+where
+    F: Fn(OauthClient<Authenticated>) -> Fut + Clone + 'static,
+    Fut: Future<Output = HttpResponse> + 'static,
+{
+    web::scope(path)
+        .app_data(web::Data::new(state))
+        .route("/login", web::get().to(login))
+        .route(
+            "/callback",
+            web::get().to(
+                move |state: web::Data<MalOauthState>, query: web::Query<RedirectResponse>| {
+                    let on_login = on_login.clone();
+                    async move {
+                        match state.complete(query.into_inner()).await {
+                            Ok(client) => on_login(client).await,
+                            Err(err) => err.error_response(),
+                        }
+                    }
+                },
+            ),
+        )
+}
+
+/// Actix-web handler that redirects the user to the MAL authorization page
+///
+/// Requires a [MalOauthState] to be registered as app data.
+pub async fn login(state: web::Data<MalOauthState>) -> Result<HttpResponse, OauthError> {
+    let url = state.authorize_url().await?;
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url))
+        .finish())
+}
+
+impl ResponseError for OauthError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.http_status()).unwrap_or(StatusCode::BAD_REQUEST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::pending_login;
+    use actix_web::{
+        body::to_bytes,
+        dev::ServiceResponse,
+        test::{call_service, init_service, TestRequest},
+        App,
+    };
+
+    // Call the scope's callback, responding with the access token on login
+    async fn callback(state: MalOauthState, query: &str) -> ServiceResponse {
+        let app = init_service(
+            App::new().service(scope("/mal", state, |client| async move {
+                HttpResponse::Ok().body(client.get_access_token_secret().clone())
+            })),
+        )
+        .await;
+        let request = TestRequest::get()
+            .uri(&format!("/mal/callback?{}", query))
+            .to_request();
+        call_service(&app, request).await
+    }
+
+    #[tokio::test]
+    async fn test_callback_state_mismatch() {
+        let (state, _) = pending_login().await;
+        let response = callback(state, "code=code&state=unknown").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, OauthError::StateMismatch.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_callback_missing_code() {
+        let (state, csrf) = pending_login().await;
+        let response = callback(state, &format!("state={}", csrf)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_callback_exchanges_code() {
+        let (state, csrf) = pending_login().await;
+        let response = callback(state.clone(), &format!("code=code&state={}", csrf)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "access");
+
+        // The login is completed, so its state can't be used again
+        let response = callback(state, &format!("code=code&state={}", csrf)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_login_redirects() {
+        let (state, _) = pending_login().await;
+        let app = init_service(App::new().service(scope("/mal", state, |_| async {
+            HttpResponse::Ok().finish()
+        })))
+        .await;
+        let request = TestRequest::get().uri("/mal/login").to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::FOUND);
+        let location = response.headers().get(header::LOCATION).unwrap();
+        assert!(location.to_str().unwrap().starts_with(crate::OAUTH_URL));
+    }
+}
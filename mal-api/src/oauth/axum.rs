@@ -0,0 +1,147 @@
+//! Axum integration for the MAL OAuth2 flow
+//!
+//! Enable the `axum` feature to use this module.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use axum::{routing::get, Router};
+//! use mal_api::oauth::axum::{login, MalCallback};
+//! use mal_api::oauth::MalOauthState;
+//!
+//! async fn callback(MalCallback(client): MalCallback) -> String {
+//!     format!("Logged in! Token expires at {}", client.get_expires_at())
+//! }
+//!
+//! let state = MalOauthState::new(client_id, Some(client_secret), redirect_url);
+//! let app: Router = Router::new()
+//!     .route("/login", get(login))
+//!     .route("/callback", get(callback))
+//!     .with_state(state);
+//! ```
+
+use ::axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+
+use super::{Authenticated, MalOauthState, OauthClient, OauthError, RedirectResponse};
+
+/// Axum handler that redirects the user to the MAL authorization page
+pub async fn login(State(state): State<MalOauthState>) -> Result<Redirect, OauthRejection> {
+    let url = state.authorize_url().await?;
+    Ok(Redirect::to(&url))
+}
+
+/// Extractor that completes the OAuth flow from the redirect callback's
+/// `code` and `state` query parameters
+///
+/// Requires a [MalOauthState] to be extractable from the router state.
+#[derive(Debug)]
+pub struct MalCallback(pub OauthClient<Authenticated>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for MalCallback
+where
+    MalOauthState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = OauthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let response = RedirectResponse::from_request_parts(parts, state).await?;
+        let client = MalOauthState::from_ref(state).complete(response).await?;
+        Ok(Self(client))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RedirectResponse
+where
+    S: Send + Sync,
+{
+    type Rejection = OauthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(RedirectResponse::from_query(parts.uri.query())?)
+    }
+}
+
+/// Rejection returned by the axum handlers and extractors when the OAuth flow fails
+#[derive(Debug)]
+pub struct OauthRejection(pub OauthError);
+
+impl From<OauthError> for OauthRejection {
+    fn from(value: OauthError) -> Self {
+        Self(value)
+    }
+}
+
+impl IntoResponse for OauthRejection {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.0.http_status()).unwrap_or(StatusCode::BAD_REQUEST);
+        (status, self.0.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{codes::ErrorCode, fixtures::pending_login};
+    use ::axum::http::Request;
+
+    async fn callback(state: &MalOauthState, query: &str) -> Result<MalCallback, OauthRejection> {
+        let request = Request::get(format!("/callback?{}", query))
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        MalCallback::from_request_parts(&mut parts, state).await
+    }
+
+    #[tokio::test]
+    async fn test_callback_state_mismatch() {
+        let (state, _) = pending_login().await;
+        let rejection = callback(&state, "code=code&state=unknown")
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.0.code(), ErrorCode::OauthStateMismatch);
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_callback_missing_code() {
+        let (state, csrf) = pending_login().await;
+        let rejection = callback(&state, &format!("state={}", csrf))
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.0.code(), ErrorCode::OauthMissingCodeOrState);
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_callback_exchanges_code() {
+        let (state, csrf) = pending_login().await;
+        let MalCallback(client) = callback(&state, &format!("code=code&state={}", csrf))
+            .await
+            .unwrap();
+        assert_eq!(client.get_access_token_secret(), "access");
+        assert_eq!(client.get_refresh_token_secret(), "refresh");
+
+        // The login is completed, so its state can't be used again
+        let rejection = callback(&state, &format!("code=code&state={}", csrf))
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.0.code(), ErrorCode::OauthStateMismatch);
+    }
+
+    #[tokio::test]
+    async fn test_login_redirects() {
+        let (state, _) = pending_login().await;
+        let response = login(State(state)).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response.headers()["location"].to_str().unwrap();
+        assert!(location.starts_with(crate::OAUTH_URL));
+    }
+}
@@ -0,0 +1,191 @@
+//! Time sources and token expiry
+//!
+//! An [OauthClient] reads the current time from its [Clock] when it computes
+//! the [TokenExpiry] of new tokens, and when it checks whether the access token
+//! has expired. It uses the [SystemClock] by default. Pass a [ManualClock] to test
+//! expiry without waiting, or your own [Clock] where `SystemTime` isn't
+//! available, such as on WASM.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let clock = Arc::new(ManualClock::new(1_700_000_000));
+//! let client = OauthClient::load_from_values(access, refresh, client_id, None, redirect_url, expires_at)
+//!     .unwrap()
+//!     .with_clock(Arc::clone(&clock));
+//!
+//! clock.advance(Duration::from_secs(3600));
+//! let client = client.refresh_if_expired().await.unwrap();
+//!
+//! // Check stored tokens the same way the client does
+//! let expiry = TokenExpiry::at(stored.expires_at);
+//! if expiry.expires_within(clock::now()?, Duration::from_secs(300)) {
+//!     println!("refreshing soon");
+//! }
+//! ```
+
+use std::time::Duration;
+
+use super::OauthError;
+
+pub use crate::clock::{Clock, ClockError, ManualClock, SystemClock};
+
+#[cfg(doc)]
+use super::OauthClient;
+
+// Used when MAL doesn't return an expiration for an access token. Access tokens
+// are valid for a month, so we use 28 days in seconds to be safe
+const DEFAULT_EXPIRES_IN: Duration = Duration::from_secs(2415600);
+
+/// The current time of the host, in seconds since the Unix Epoch
+///
+/// Reads the [SystemClock], so it fails the same way, if the system clock is
+/// set before the Unix Epoch
+pub fn now() -> Result<u64, OauthError> {
+    Ok(SystemClock.now()?)
+}
+
+/// When an access token expires
+///
+/// Times are in seconds since the Unix Epoch. A token is expired from its
+/// expiration time on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenExpiry {
+    expires_at: u64,
+}
+
+impl TokenExpiry {
+    /// The expiry of a token that expires at `expires_at`
+    pub fn at(expires_at: u64) -> Self {
+        Self { expires_at }
+    }
+
+    /// The expiry of a token issued at `now` that's valid for `expires_in`
+    ///
+    /// MAL returns `expires_in` with every token. If it's missing, the token
+    /// is assumed to be valid for 28 days.
+    pub fn from_expires_in(now: u64, expires_in: Option<Duration>) -> Self {
+        let expires_in = expires_in.unwrap_or(DEFAULT_EXPIRES_IN);
+        Self::at(now.saturating_add(expires_in.as_secs()))
+    }
+
+    /// The expiration time
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    /// Check whether the token has expired at `now`
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Check whether the token will have expired within `duration` of `now`
+    pub fn expires_within(&self, now: u64, duration: Duration) -> bool {
+        self.is_expired(now.saturating_add(duration.as_secs()))
+    }
+
+    /// How long the token is still valid from `now`, zero if it has expired
+    pub fn remaining(&self, now: u64) -> Duration {
+        Duration::from_secs(self.expires_at.saturating_sub(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oauth::OauthClient;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_manual_clock_expiry() {
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let client = OauthClient::load_from_values(
+            "access",
+            "refresh",
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            1_700_003_600,
+        )
+        .unwrap()
+        .with_clock(Arc::clone(&clock));
+        assert!(!client.is_expired().unwrap());
+
+        // Not expired yet, so this doesn't send a request
+        let client = client.refresh_if_expired().await.unwrap();
+        assert_eq!(client.get_access_token_secret(), "access");
+
+        clock.set(1_700_003_599);
+        assert!(!client.is_expired().unwrap());
+        clock.advance(Duration::from_secs(1));
+        assert!(client.is_expired().unwrap());
+
+        clock.set(0);
+        assert!(!client.is_expired().unwrap());
+        assert_eq!(now().unwrap(), SystemClock.now().unwrap());
+    }
+
+    #[test]
+    fn test_clock_error() {
+        // A clock set before the Unix Epoch
+        #[derive(Debug)]
+        struct BrokenClock;
+
+        impl Clock for BrokenClock {
+            fn now(&self) -> Result<u64, ClockError> {
+                Err(ClockError)
+            }
+        }
+
+        let client = OauthClient::builder("id")
+            .redirect_url("http://localhost:8080/callback")
+            .clock(Arc::new(BrokenClock))
+            .build_authenticated("access", "refresh", 1_700_003_600)
+            .unwrap();
+        assert!(matches!(client.is_expired(), Err(OauthError::NoSystemTime)));
+    }
+
+    #[test]
+    fn test_load_expired_tokens() {
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let client = OauthClient::builder("id")
+            .redirect_url("http://localhost:8080/callback")
+            .clock(Arc::clone(&clock))
+            .build_authenticated("access", "refresh", 1_600_000_000)
+            .unwrap();
+        assert!(client.is_expired().unwrap());
+        assert_eq!(client.get_refresh_token_secret(), "refresh");
+
+        assert!(matches!(
+            OauthClient::load_from_values(
+                "access",
+                "refresh",
+                "id",
+                None,
+                "http://localhost:8080/callback",
+                0,
+            ),
+            Err(OauthError::InvalidExpirationTime)
+        ));
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let expiry = TokenExpiry::from_expires_in(1_000, Some(Duration::from_secs(3_600)));
+        assert_eq!(expiry, TokenExpiry::at(4_600));
+        assert_eq!(
+            TokenExpiry::from_expires_in(1_000, None).expires_at(),
+            1_000 + 2_415_600
+        );
+
+        assert!(!expiry.is_expired(4_599));
+        assert!(expiry.is_expired(4_600));
+        assert!(expiry.expires_within(4_000, Duration::from_secs(600)));
+        assert!(!expiry.expires_within(4_000, Duration::from_secs(599)));
+        assert_eq!(expiry.remaining(4_000), Duration::from_secs(600));
+        assert_eq!(expiry.remaining(5_000), Duration::ZERO);
+
+        // The host clock is past any token issued before this crate existed
+        assert!(TokenExpiry::at(1_600_000_000).is_expired(now().unwrap()));
+    }
+}
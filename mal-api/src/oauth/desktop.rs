@@ -0,0 +1,235 @@
+//! Loopback redirect helper for desktop applications
+//!
+//! Enable the `desktop` feature to use this module.
+//!
+//! Desktop apps (Tauri, egui, etc.) usually can't host a redirect server of their own.
+//! [LoopbackLogin] listens on the loopback address from your redirect URL, such as
+//! `http://127.0.0.1:8080/callback`, and completes the flow once MAL redirects the
+//! user's browser back to it.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let oauth_client = OauthClient::new(client_id, None, "http://127.0.0.1:8080/callback").unwrap();
+//!
+//! // Opens the system browser and waits for the redirect
+//! let authenticated = mal_api::oauth::desktop::login_with_browser(oauth_client).await.unwrap();
+//! ```
+
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use url::Url;
+
+use super::{Authenticated, OauthClient, OauthError, RedirectResponse, Unauthenticated};
+
+// Request heads larger than this are not redirects from MAL
+const MAX_REQUEST_HEAD_SIZE: usize = 8192;
+
+// Browsers open speculative connections they may never send a request on,
+// so connections are dropped if the request doesn't arrive in time
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SUCCESS_PAGE: &str =
+    "<html><body><h3>Login complete. You can close this window.</h3></body></html>";
+
+const FAILURE_PAGE: &str =
+    "<html><body><h3>Login failed. Please return to the application.</h3></body></html>";
+
+/// A pending login listening on the loopback redirect URL
+#[derive(Debug)]
+pub struct LoopbackLogin {
+    client: OauthClient<Unauthenticated>,
+    listener: TcpListener,
+    redirect_path: String,
+    auth_url: String,
+}
+
+impl LoopbackLogin {
+    /// Start listening on the client's redirect URL and generate the authorization URL
+    ///
+    /// The redirect URL must use `localhost`, `127.0.0.1`, or `[::1]` as its host.
+    pub async fn bind(mut client: OauthClient<Unauthenticated>) -> Result<Self, OauthError> {
+        let redirect_url = client
+            .client
+            .redirect_url()
+            .ok_or(OauthError::MissingRedirectUrl)?
+            .url()
+            .clone();
+        let address = loopback_address(&redirect_url)?;
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|_| OauthError::LoopbackFailure)?;
+        let auth_url = client.generate_auth_url();
+
+        Ok(Self {
+            client,
+            listener,
+            redirect_path: redirect_url.path().to_string(),
+            auth_url,
+        })
+    }
+
+    /// Get the authorization URL the user needs to visit
+    ///
+    /// Use this if your application opens URLs itself, e.g. through a Tauri shell plugin
+    pub fn auth_url(&self) -> &str {
+        &self.auth_url
+    }
+
+    /// Open the authorization URL in the system browser
+    pub fn open_browser(&self) -> Result<(), OauthError> {
+        open::that(&self.auth_url).map_err(|_| OauthError::BrowserFailure)
+    }
+
+    /// Wait for MAL to redirect the browser back to the loopback address
+    /// and authenticate the client
+    pub async fn finish(self) -> Result<OauthClient<Authenticated>, OauthError> {
+        loop {
+            let (mut stream, target) = next_request(&self.listener, REQUEST_READ_TIMEOUT).await?;
+            let url = match Url::parse("http://localhost")
+                .and_then(|base| base.join(&target))
+                .ok()
+            {
+                Some(url) => url,
+                None => continue,
+            };
+
+            // Ignore anything that isn't the redirect, such as favicon requests
+            if url.path() != self.redirect_path {
+                let _ = respond(&mut stream, "404 Not Found", "").await;
+                continue;
+            }
+
+            match RedirectResponse::try_from(url.to_string()) {
+                Ok(response) => {
+                    let result = self.client.authenticate(response).await;
+                    let page = if result.is_ok() {
+                        SUCCESS_PAGE
+                    } else {
+                        FAILURE_PAGE
+                    };
+                    let _ = respond(&mut stream, "200 OK", page).await;
+                    return result;
+                }
+                Err(err) => {
+                    let _ = respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// Open the system browser to authorize the client, then wait for the
+/// redirect on the loopback address
+pub async fn login_with_browser(
+    client: OauthClient<Unauthenticated>,
+) -> Result<OauthClient<Authenticated>, OauthError> {
+    let login = LoopbackLogin::bind(client).await?;
+    login.open_browser()?;
+    login.finish().await
+}
+
+fn loopback_address(redirect_url: &Url) -> Result<(&str, u16), OauthError> {
+    let host = match redirect_url.host_str() {
+        Some("localhost") => "localhost",
+        Some("127.0.0.1") => "127.0.0.1",
+        Some("[::1]") => "::1",
+        _ => return Err(OauthError::InvalidRedirectUrl),
+    };
+    let port = redirect_url
+        .port_or_known_default()
+        .ok_or(OauthError::InvalidRedirectUrl)?;
+    Ok((host, port))
+}
+
+// Accept connections until one sends a request, skipping connections that
+// stay idle for longer than `read_timeout`
+async fn next_request(
+    listener: &TcpListener,
+    read_timeout: Duration,
+) -> Result<(TcpStream, String), OauthError> {
+    loop {
+        let (mut stream, _) = listener
+            .accept()
+            .await
+            .map_err(|_| OauthError::LoopbackFailure)?;
+
+        if let Ok(Some(target)) =
+            tokio::time::timeout(read_timeout, read_request_target(&mut stream)).await
+        {
+            return Ok((stream, target));
+        }
+    }
+}
+
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 1024];
+    while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 || buffer.len() + read > MAX_REQUEST_HEAD_SIZE {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    // Request line looks like: GET /callback?code=...&state=... HTTP/1.1
+    let head = String::from_utf8_lossy(&buffer);
+    let mut parts = head.lines().next()?.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_address() {
+        let url = Url::parse("http://127.0.0.1:8080/callback").unwrap();
+        assert_eq!(loopback_address(&url).unwrap(), ("127.0.0.1", 8080));
+
+        let url = Url::parse("http://localhost/callback").unwrap();
+        assert_eq!(loopback_address(&url).unwrap(), ("localhost", 80));
+
+        let url = Url::parse("https://example.com/callback").unwrap();
+        assert!(loopback_address(&url).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_next_request_skips_idle_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // A preconnect that never sends a request must not block the redirect
+        let _idle = TcpStream::connect(address).await.unwrap();
+        let mut redirect = TcpStream::connect(address).await.unwrap();
+        redirect
+            .write_all(b"GET /callback?code=a&state=b HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n")
+            .await
+            .unwrap();
+
+        let (_, target) = next_request(&listener, Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(target, "/callback?code=a&state=b");
+    }
+}
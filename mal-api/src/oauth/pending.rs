@@ -0,0 +1,270 @@
+//! Storage for logins that are waiting for the redirect from MAL
+//!
+//! Between the authorization URL and the redirect callback, a login only needs
+//! its CSRF state and PKCE verifier. [MalOauthState] keeps them in a
+//! [PendingAuthStore] keyed by the state, so a web service can have many logins
+//! in flight, and the callback can be handled by any worker sharing the store.
+//!
+//! The [MemoryPendingAuthStore] is used by default. Implement [PendingAuthStore]
+//! on top of a shared cache or database when the callback can land on another
+//! process than the login. Errors of the store are returned as the source of
+//! [OauthError::PendingAuthStoreFailure].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let store = RedisPendingAuthStore::new(redis_client);
+//! let state = MalOauthState::with_pending_store(client_id, None, redirect_url, store);
+//!
+//! // Worker A
+//! let url = state.authorize_url().await.unwrap();
+//!
+//! // Worker B, with another MalOauthState sharing the same Redis
+//! let client = state.complete(response).await.unwrap();
+//! ```
+
+use std::{convert::Infallible, fmt, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{OauthError, PENDING_LOGIN_TTL};
+use crate::cache::TtlCache;
+
+#[cfg(doc)]
+use super::MalOauthState;
+
+/// A login waiting for the redirect from MAL
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingAuth {
+    /// The CSRF state sent in the authorization URL
+    pub state: String,
+
+    /// The PKCE verifier of the code challenge sent in the authorization URL
+    pub pkce_verifier: String,
+
+    /// Number of seconds since the Unix Epoch when the login began
+    pub created_at: u64,
+}
+
+impl PendingAuth {
+    /// Create a pending login that began at `created_at`, in seconds since the Unix Epoch
+    pub fn new<T: Into<String>>(state: T, pkce_verifier: T, created_at: u64) -> Self {
+        Self {
+            state: state.into(),
+            pkce_verifier: pkce_verifier.into(),
+            created_at,
+        }
+    }
+
+    /// Check whether the user took too long to complete the login
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.created_at.saturating_add(PENDING_LOGIN_TTL.as_secs())
+    }
+}
+
+impl fmt::Debug for PendingAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingAuth")
+            .field("state", &self.state)
+            .field("pkce_verifier", &"[redacted]")
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
+/// Storage for pending logins, keyed by their CSRF state
+///
+/// Implementations shared by several workers must make [take](PendingAuthStore::take)
+/// atomic, so each login can only be completed once.
+#[async_trait]
+pub trait PendingAuthStore: fmt::Debug + Send + Sync {
+    /// The error of the underlying storage
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Store a pending login under its state
+    async fn insert(&self, auth: PendingAuth) -> Result<(), Self::Error>;
+
+    /// Remove the pending login with the given state, returning it if it exists
+    async fn take(&self, state: &str) -> Result<Option<PendingAuth>, Self::Error>;
+}
+
+#[async_trait]
+impl<S: PendingAuthStore + ?Sized> PendingAuthStore for Arc<S> {
+    type Error = S::Error;
+
+    async fn insert(&self, auth: PendingAuth) -> Result<(), Self::Error> {
+        (**self).insert(auth).await
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<PendingAuth>, Self::Error> {
+        (**self).take(state).await
+    }
+}
+
+// Object safe version of PendingAuthStore, so MalOauthState can hold any store
+// without a type parameter. Store errors become OauthError::PendingAuthStoreFailure
+#[async_trait]
+pub(crate) trait DynPendingAuthStore: fmt::Debug + Send + Sync {
+    async fn dyn_insert(&self, auth: PendingAuth) -> Result<(), OauthError>;
+
+    async fn dyn_take(&self, state: &str) -> Result<Option<PendingAuth>, OauthError>;
+}
+
+#[async_trait]
+impl<S: PendingAuthStore> DynPendingAuthStore for S {
+    async fn dyn_insert(&self, auth: PendingAuth) -> Result<(), OauthError> {
+        self.insert(auth)
+            .await
+            .map_err(|err| OauthError::PendingAuthStoreFailure(Box::new(err)))
+    }
+
+    async fn dyn_take(&self, state: &str) -> Result<Option<PendingAuth>, OauthError> {
+        self.take(state)
+            .await
+            .map_err(|err| OauthError::PendingAuthStoreFailure(Box::new(err)))
+    }
+}
+
+/// In-memory [PendingAuthStore]
+///
+/// Only works when the login and the callback are handled by the same process
+#[derive(Debug)]
+pub struct MemoryPendingAuthStore {
+    pending: TtlCache<String, PendingAuth>,
+}
+
+impl MemoryPendingAuthStore {
+    pub fn new() -> Self {
+        Self {
+            pending: TtlCache::new(PENDING_LOGIN_TTL),
+        }
+    }
+}
+
+impl Default for MemoryPendingAuthStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PendingAuthStore for MemoryPendingAuthStore {
+    type Error = Infallible;
+
+    async fn insert(&self, auth: PendingAuth) -> Result<(), Self::Error> {
+        self.pending.purge_expired();
+        self.pending.insert(auth.state.clone(), auth);
+        Ok(())
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<PendingAuth>, Self::Error> {
+        Ok(self.pending.remove(&state.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::oauth::{MalOauthState, RedirectResponse};
+
+    fn query(url: &str) -> HashMap<String, String> {
+        url::Url::parse(url)
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect()
+    }
+
+    #[derive(Debug)]
+    struct SealedStore;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("store is sealed")]
+    struct SealedError;
+
+    #[async_trait]
+    impl PendingAuthStore for SealedStore {
+        type Error = SealedError;
+
+        async fn insert(&self, _auth: PendingAuth) -> Result<(), Self::Error> {
+            Err(SealedError)
+        }
+
+        async fn take(&self, _state: &str) -> Result<Option<PendingAuth>, Self::Error> {
+            Err(SealedError)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_auth_store() {
+        let clock = Arc::new(ManualClock::new(1_700_000_000));
+        let store = Arc::new(MemoryPendingAuthStore::new());
+        let worker_a = MalOauthState::with_pending_store(
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            Arc::clone(&store),
+        )
+        .with_clock(Arc::clone(&clock));
+        let worker_b = MalOauthState::with_pending_store(
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            Arc::clone(&store),
+        )
+        .with_clock(Arc::clone(&clock));
+
+        let first = query(&worker_a.authorize_url().await.unwrap());
+        let second = query(&worker_a.authorize_url().await.unwrap());
+        assert_ne!(first["state"], second["state"]);
+
+        // Either worker sees both logins, with the verifier of their challenge
+        let auth = store.take(&second["state"]).await.unwrap().unwrap();
+        assert_eq!(auth.pkce_verifier, second["code_challenge"]);
+        assert_eq!(auth.created_at, 1_700_000_000);
+        assert!(store.take(&second["state"]).await.unwrap().is_none());
+        assert!(format!("{:?}", auth).contains("[redacted]"));
+
+        // Logins past the TTL of the clock are rejected before any request is sent
+        clock.advance(PENDING_LOGIN_TTL);
+        let result = worker_b
+            .complete(RedirectResponse::new("code", first["state"].as_str()))
+            .await;
+        assert!(matches!(result, Err(OauthError::StateMismatch)));
+        assert!(store.take(&first["state"]).await.unwrap().is_none());
+
+        let auth = PendingAuth::new("state", "verifier", 1_000);
+        assert!(!auth.is_expired(1_000));
+        assert!(auth.is_expired(1_000 + PENDING_LOGIN_TTL.as_secs()));
+    }
+
+    #[tokio::test]
+    async fn test_pending_auth_store_error() {
+        let state = MalOauthState::with_pending_store(
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            SealedStore,
+        );
+
+        let err = state.authorize_url().await.unwrap_err();
+        assert_eq!(
+            err.code(),
+            crate::codes::ErrorCode::OauthPendingAuthStoreFailure
+        );
+        assert_eq!(
+            std::error::Error::source(&err).unwrap().to_string(),
+            "store is sealed"
+        );
+
+        let result = state.complete(RedirectResponse::new("code", "state")).await;
+        assert!(matches!(
+            result,
+            Err(OauthError::PendingAuthStoreFailure(_))
+        ));
+    }
+}
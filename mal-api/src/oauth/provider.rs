@@ -0,0 +1,373 @@
+//! Credentials the API clients send with their requests
+//!
+//! Every API client gets its credentials from an [AuthProvider] before each
+//! request. [MalClientId], [MalAccessToken], and authenticated [OauthClient]s
+//! are providers, and the `From` implementations of the API clients use them.
+//!
+//! Implement [AuthProvider] to get the credentials from somewhere else, e.g. a
+//! secrets service shared by several workers, and create the API clients with
+//! `from_auth_provider`. The [State](AuthProvider::State) of the provider picks
+//! the endpoints of the API clients: [Client] for client ids, [Oauth] for access
+//! tokens. Use [RefreshingAuth] to refresh an [OauthClient] when its access
+//! token expires.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! #[derive(Debug)]
+//! struct VaultAuth {
+//!     vault: VaultClient,
+//! }
+//!
+//! #[async_trait]
+//! impl AuthProvider for VaultAuth {
+//!     type State = Oauth;
+//!
+//!     async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+//!         let token = self
+//!             .vault
+//!             .read("mal/access_token")
+//!             .await
+//!             .map_err(|err| AuthProviderError::new(err.to_string()))?;
+//!         Ok(AuthHeader::Bearer(MalAccessToken::new(token)))
+//!     }
+//! }
+//!
+//! let api_client = AnimeApiClient::from_auth_provider(VaultAuth { vault });
+//! ```
+
+use std::{fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use super::{MalAccessToken, MalClientId, OauthError};
+use crate::codes::ErrorCode;
+
+#[cfg(feature = "oauth")]
+use {
+    super::{
+        store::{DynTokenStore, RefreshError, TokenStore},
+        Authenticated, OauthClient,
+    },
+    tokio::sync::Mutex,
+};
+
+/// State of the API clients that send a client id
+#[derive(Debug)]
+pub struct Client {}
+
+/// State of the API clients that send an access token
+#[derive(Debug)]
+pub struct Oauth {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Client {}
+    impl Sealed for super::Oauth {}
+}
+
+/// The state of API clients with credentials, either [Client] or [Oauth]
+pub trait AuthState: private::Sealed + Send + Sync + 'static {}
+
+impl AuthState for Client {}
+impl AuthState for Oauth {}
+
+/// The credentials of a request
+#[derive(Debug, Clone)]
+pub enum AuthHeader {
+    /// Sends the `X-MAL-CLIENT-ID` header
+    ClientId(MalClientId),
+
+    /// Sends the access token as a bearer token
+    Bearer(MalAccessToken),
+}
+
+impl AuthHeader {
+    // Adds the credentials to the request
+    #[cfg(api)]
+    pub(crate) fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            AuthHeader::ClientId(client_id) => {
+                request.header("X-MAL-CLIENT-ID", client_id.as_str())
+            }
+            AuthHeader::Bearer(token) => request.bearer_auth(token.secret()),
+        }
+    }
+}
+
+/// Error from an [AuthProvider] that couldn't get the credentials
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct AuthProviderError {
+    pub message: String,
+
+    code: ErrorCode,
+}
+
+impl AuthProviderError {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            code: ErrorCode::AuthProvider,
+        }
+    }
+
+    #[cfg(feature = "oauth")]
+    pub(crate) fn with_code(message: String, code: ErrorCode) -> Self {
+        Self { message, code }
+    }
+
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl From<OauthError> for AuthProviderError {
+    fn from(value: OauthError) -> Self {
+        Self {
+            message: value.to_string(),
+            code: value.code(),
+        }
+    }
+}
+
+/// A source of the credentials of the API clients
+#[async_trait]
+pub trait AuthProvider: Debug + Send + Sync {
+    /// The state of the API clients created from this provider
+    ///
+    /// Use [Oauth] when the provider returns access tokens, and [Client] when it
+    /// returns client ids
+    type State: AuthState;
+
+    /// Get the credentials for the next request
+    ///
+    /// Called before every request, so implementations can refresh or rotate
+    /// the credentials in between
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError>;
+}
+
+#[async_trait]
+impl<P: AuthProvider + ?Sized> AuthProvider for Arc<P> {
+    type State = P::State;
+
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+        (**self).auth_header().await
+    }
+}
+
+#[async_trait]
+impl AuthProvider for MalClientId {
+    type State = Client;
+
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+        Ok(AuthHeader::ClientId(self.clone()))
+    }
+}
+
+#[async_trait]
+impl AuthProvider for MalAccessToken {
+    type State = Oauth;
+
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+        Ok(AuthHeader::Bearer(self.clone()))
+    }
+}
+
+/// Sends the current access token of the client, without refreshing it
+#[cfg(feature = "oauth")]
+#[async_trait]
+impl AuthProvider for OauthClient<Authenticated> {
+    type State = Oauth;
+
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+        Ok(AuthHeader::Bearer(self.access_token()))
+    }
+}
+
+/// An [AuthProvider] that refreshes its [OauthClient] when the access token expires
+///
+/// Share it through an [Arc] between API clients so that they all use the
+/// refreshed token. Concurrent requests wait for a single refresh.
+///
+/// MAL rotates the refresh token on every refresh, so give it the [TokenStore]
+/// the tokens were loaded from with [with_store](RefreshingAuth::with_store).
+/// Otherwise the refreshed tokens are only kept in memory, and the stored
+/// refresh token no longer works after a restart.
+#[cfg(feature = "oauth")]
+#[derive(Debug)]
+pub struct RefreshingAuth {
+    client: Mutex<OauthClient<Authenticated>>,
+    store: Option<(Arc<dyn DynTokenStore>, String)>,
+}
+
+#[cfg(feature = "oauth")]
+impl RefreshingAuth {
+    pub fn new(client: OauthClient<Authenticated>) -> Self {
+        Self {
+            client: Mutex::new(client),
+            store: None,
+        }
+    }
+
+    /// Rotate the tokens stored under `name` on every refresh, see
+    /// [refresh_with_store](OauthClient::refresh_with_store)
+    pub fn with_store<S, T>(mut self, store: Arc<S>, name: T) -> Self
+    where
+        S: TokenStore + Debug + Send + Sync + 'static,
+        T: Into<String>,
+    {
+        self.store = Some((store, name.into()));
+        self
+    }
+
+    /// Get the current access token, e.g. to save it
+    pub async fn access_token(&self) -> MalAccessToken {
+        self.client.lock().await.access_token()
+    }
+
+    /// Return the client, with the newest tokens
+    pub fn into_inner(self) -> OauthClient<Authenticated> {
+        self.client.into_inner()
+    }
+}
+
+#[cfg(feature = "oauth")]
+#[async_trait]
+impl AuthProvider for RefreshingAuth {
+    type State = Oauth;
+
+    async fn auth_header(&self) -> Result<AuthHeader, AuthProviderError> {
+        let mut client = self.client.lock().await;
+        if !client.is_expired()? {
+            return Ok(AuthHeader::Bearer(client.access_token()));
+        }
+        let Some((store, name)) = &self.store else {
+            *client = client.refreshed().await?;
+            return Ok(AuthHeader::Bearer(client.access_token()));
+        };
+        match client.refreshed_with_store(&**store, name).await {
+            Ok(refreshed) => *client = refreshed,
+            Err(RefreshError::Oauth(err)) => return Err(err.into()),
+            // Keep using the new tokens so they aren't lost, the next refresh
+            // tries to save them again
+            Err(RefreshError::Save {
+                client: refreshed,
+                error,
+            }) => {
+                *client = *refreshed;
+                return Err(error);
+            }
+            // The new tokens were saved, only the old ones are left over
+            Err(RefreshError::Cleanup {
+                client: refreshed, ..
+            }) => *client = *refreshed,
+        }
+        Ok(AuthHeader::Bearer(client.access_token()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_auth_providers() {
+        let provider: Arc<dyn AuthProvider<State = Client>> = Arc::new(MalClientId::new("id"));
+        match provider.auth_header().await.unwrap() {
+            AuthHeader::ClientId(client_id) => assert_eq!(client_id.as_str(), "id"),
+            header => panic!("unexpected header {:?}", header),
+        }
+
+        let provider = Arc::new(MalAccessToken::new("token"));
+        match provider.auth_header().await.unwrap() {
+            AuthHeader::Bearer(token) => assert_eq!(token.secret(), "token"),
+            header => panic!("unexpected header {:?}", header),
+        }
+
+        let err = AuthProviderError::from(OauthError::FailedToRefreshToken);
+        assert_eq!(err.code(), ErrorCode::OauthRefreshFailed);
+        assert_eq!(
+            AuthProviderError::new("vault is sealed".to_string()).code(),
+            ErrorCode::AuthProvider
+        );
+    }
+
+    #[cfg(feature = "oauth")]
+    #[tokio::test]
+    async fn test_refreshing_auth() {
+        use crate::clock::ManualClock;
+
+        let client = OauthClient::load_from_values(
+            "access",
+            "refresh",
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            1_700_003_600,
+        )
+        .unwrap()
+        .with_clock(ManualClock::new(1_700_000_000));
+        let provider = RefreshingAuth::new(client);
+
+        // Not expired, so this doesn't send a request
+        match provider.auth_header().await.unwrap() {
+            AuthHeader::Bearer(token) => assert_eq!(token.secret(), "access"),
+            header => panic!("unexpected header {:?}", header),
+        }
+        assert_eq!(provider.into_inner().get_refresh_token_secret(), "refresh");
+    }
+
+    #[cfg(feature = "oauth")]
+    #[tokio::test]
+    async fn test_refreshing_auth_store() {
+        use crate::{
+            clock::ManualClock,
+            oauth::store::{MemoryTokenStore, StoredTokens},
+        };
+
+        let token_url = crate::fixtures::token_endpoint(serde_json::json!({
+            "access_token": "new access",
+            "refresh_token": "new refresh",
+            "token_type": "Bearer",
+            "expires_in": 3600
+        }))
+        .await;
+        let client = OauthClient::builder("id")
+            .redirect_url("http://127.0.0.1/callback")
+            .token_url(token_url)
+            .clock(ManualClock::new(1_700_000_000))
+            .build_authenticated("access", "refresh", 1_700_000_000)
+            .unwrap();
+        let store = Arc::new(MemoryTokenStore::default());
+        store
+            .save_token_set("bot", &StoredTokens::from(&client))
+            .unwrap();
+        let provider = RefreshingAuth::new(client).with_store(store.clone(), "bot");
+
+        // Expired, so the tokens are refreshed and rotated in the store
+        match provider.auth_header().await.unwrap() {
+            AuthHeader::Bearer(token) => assert_eq!(token.secret(), "new access"),
+            header => panic!("unexpected header {:?}", header),
+        }
+        let stored = store.load_token_set("bot").unwrap().unwrap();
+        assert_eq!(stored.refresh_token, "new refresh");
+        assert_eq!(stored.expires_at, 1_700_003_600);
+        store.delete_token_set("bot", "new refresh").unwrap();
+        assert!(store.load_token_set("bot").unwrap().is_none());
+    }
+
+    #[cfg(feature = "anime")]
+    #[test]
+    fn test_provider_state() {
+        use crate::anime::api::AnimeApiClient;
+
+        // The state of the API client follows the provider
+        let _: AnimeApiClient<Oauth> =
+            AnimeApiClient::from_auth_provider(Arc::new(MalAccessToken::new("token")));
+        let _: AnimeApiClient<Client> = AnimeApiClient::from_auth_provider(MalClientId::new("id"));
+    }
+}
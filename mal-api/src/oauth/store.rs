@@ -0,0 +1,375 @@
+//! Token storage that survives refresh token rotation
+//!
+//! MAL invalidates the refresh token as soon as it's used. If an application
+//! refreshes its client and crashes before saving the new tokens, the only
+//! refresh token it has left is the old one, and the user has to log in again.
+//!
+//! [OauthClient::refresh_with_store] refreshes the client, saves the new tokens
+//! into a [TokenStore], and only then deletes the old ones. At any point, the
+//! store holds a token set that was valid when it was written, and loading it
+//! returns the newest one.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let store = SqliteStore::open("mal.db").unwrap();
+//! let tokens = store.load_token_set("me").unwrap().unwrap();
+//! let client = OauthClient::load_from_values(
+//!     tokens.access_token, tokens.refresh_token, client_id, None, redirect_url, tokens.expires_at,
+//! ).unwrap();
+//!
+//! let client = client.refresh_with_store(&store, "me").await.unwrap();
+//! ```
+
+use std::{collections::HashMap, convert::Infallible, fmt, future::Future, sync::Mutex};
+
+use thiserror::Error;
+
+use super::{provider::AuthProviderError, Authenticated, OauthClient, OauthError};
+use crate::codes::ErrorCode;
+
+/// OAuth tokens loaded from a store
+///
+/// Use [OauthClient::load_from_values] to create an authenticated client from them
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: u64,
+}
+
+impl From<&OauthClient<Authenticated>> for StoredTokens {
+    fn from(client: &OauthClient<Authenticated>) -> Self {
+        Self {
+            access_token: client.get_access_token_secret().clone(),
+            refresh_token: client.get_refresh_token_secret().clone(),
+            expires_at: *client.get_expires_at(),
+        }
+    }
+}
+
+/// Storage for the tokens of authenticated clients
+///
+/// Implementations must keep the tokens that were already stored under a name
+/// if saving new ones fails. They can replace them when saving succeeds, as
+/// long as the replacement is atomic.
+pub trait TokenStore {
+    type Error: std::error::Error;
+
+    /// Save a token set under `name`
+    fn save_token_set(&self, name: &str, tokens: &StoredTokens) -> Result<(), Self::Error>;
+
+    /// Load the most recently saved token set under `name`
+    fn load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, Self::Error>;
+
+    /// Delete the token set under `name` with the given refresh token, if it's
+    /// still stored
+    fn delete_token_set(&self, name: &str, refresh_token: &str) -> Result<(), Self::Error>;
+}
+
+// Object safe version of TokenStore, so RefreshingAuth can hold any store
+// without a type parameter. Store errors become AuthProviderErrors
+pub(crate) trait DynTokenStore: fmt::Debug + Send + Sync {
+    fn dyn_save_token_set(
+        &self,
+        name: &str,
+        tokens: &StoredTokens,
+    ) -> Result<(), AuthProviderError>;
+
+    fn dyn_load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, AuthProviderError>;
+
+    fn dyn_delete_token_set(
+        &self,
+        name: &str,
+        refresh_token: &str,
+    ) -> Result<(), AuthProviderError>;
+}
+
+fn store_error<E: std::error::Error>(err: E) -> AuthProviderError {
+    AuthProviderError::with_code(
+        format!("failed to access the token store: {}", err),
+        ErrorCode::Store,
+    )
+}
+
+impl<S: TokenStore + fmt::Debug + Send + Sync> DynTokenStore for S {
+    fn dyn_save_token_set(
+        &self,
+        name: &str,
+        tokens: &StoredTokens,
+    ) -> Result<(), AuthProviderError> {
+        self.save_token_set(name, tokens).map_err(store_error)
+    }
+
+    fn dyn_load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, AuthProviderError> {
+        self.load_token_set(name).map_err(store_error)
+    }
+
+    fn dyn_delete_token_set(
+        &self,
+        name: &str,
+        refresh_token: &str,
+    ) -> Result<(), AuthProviderError> {
+        self.delete_token_set(name, refresh_token)
+            .map_err(store_error)
+    }
+}
+
+impl TokenStore for dyn DynTokenStore + '_ {
+    type Error = AuthProviderError;
+
+    fn save_token_set(&self, name: &str, tokens: &StoredTokens) -> Result<(), Self::Error> {
+        self.dyn_save_token_set(name, tokens)
+    }
+
+    fn load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, Self::Error> {
+        self.dyn_load_token_set(name)
+    }
+
+    fn delete_token_set(&self, name: &str, refresh_token: &str) -> Result<(), Self::Error> {
+        self.dyn_delete_token_set(name, refresh_token)
+    }
+}
+
+/// In-memory [TokenStore]
+///
+/// Keeps every saved token set until it's deleted
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    token_sets: Mutex<HashMap<String, Vec<StoredTokens>>>,
+}
+
+impl TokenStore for MemoryTokenStore {
+    type Error = Infallible;
+
+    fn save_token_set(&self, name: &str, tokens: &StoredTokens) -> Result<(), Self::Error> {
+        let mut token_sets = self.token_sets.lock().unwrap();
+        token_sets
+            .entry(name.to_string())
+            .or_default()
+            .push(tokens.clone());
+        Ok(())
+    }
+
+    fn load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, Self::Error> {
+        let token_sets = self.token_sets.lock().unwrap();
+        Ok(token_sets.get(name).and_then(|sets| sets.last().cloned()))
+    }
+
+    fn delete_token_set(&self, name: &str, refresh_token: &str) -> Result<(), Self::Error> {
+        let mut token_sets = self.token_sets.lock().unwrap();
+        if let Some(sets) = token_sets.get_mut(name) {
+            sets.retain(|tokens| tokens.refresh_token != refresh_token);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RefreshError<E: std::error::Error> {
+    /// The client couldn't be refreshed. The stored tokens are unchanged.
+    #[error("{0}")]
+    Oauth(#[from] OauthError),
+
+    /// The client was refreshed, but the new tokens couldn't be saved
+    ///
+    /// The old refresh token is no longer valid, so save the tokens of the
+    /// returned client some other way, or retry saving them
+    #[error("failed to save the refreshed tokens: {error}")]
+    Save {
+        client: Box<OauthClient<Authenticated>>,
+        error: E,
+    },
+
+    /// The new tokens were saved, but the old ones couldn't be deleted
+    ///
+    /// The store returns the new tokens, so the returned client can be used
+    /// as if the refresh succeeded
+    #[error("failed to delete the old tokens: {error}")]
+    Cleanup {
+        client: Box<OauthClient<Authenticated>>,
+        error: E,
+    },
+}
+
+impl<E: std::error::Error> RefreshError<E> {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            RefreshError::Oauth(err) => err.code(),
+            RefreshError::Save { .. } | RefreshError::Cleanup { .. } => ErrorCode::Store,
+        }
+    }
+}
+
+impl OauthClient<Authenticated> {
+    /// Refresh the access token, and rotate the tokens stored under `name`
+    ///
+    /// The new tokens are saved before the old ones are deleted, so the store
+    /// never loses the only valid refresh token
+    pub async fn refresh_with_store<S: TokenStore + ?Sized>(
+        self,
+        store: &S,
+        name: &str,
+    ) -> Result<Self, RefreshError<S::Error>> {
+        self.refreshed_with_store(store, name).await
+    }
+
+    // Like refresh_with_store, keeping this client usable if it fails
+    pub(crate) async fn refreshed_with_store<S: TokenStore + ?Sized>(
+        &self,
+        store: &S,
+        name: &str,
+    ) -> Result<Self, RefreshError<S::Error>> {
+        let old_refresh_token = self.get_refresh_token_secret().clone();
+        rotate(store, name, &old_refresh_token, self.refreshed()).await
+    }
+}
+
+async fn rotate<S, F>(
+    store: &S,
+    name: &str,
+    old_refresh_token: &str,
+    refresh: F,
+) -> Result<OauthClient<Authenticated>, RefreshError<S::Error>>
+where
+    S: TokenStore + ?Sized,
+    F: Future<Output = Result<OauthClient<Authenticated>, OauthError>>,
+{
+    let client = refresh.await?;
+
+    if let Err(error) = store.save_token_set(name, &StoredTokens::from(&client)) {
+        return Err(RefreshError::Save {
+            client: Box::new(client),
+            error,
+        });
+    }
+    if client.get_refresh_token_secret() != old_refresh_token {
+        if let Err(error) = store.delete_token_set(name, old_refresh_token) {
+            return Err(RefreshError::Cleanup {
+                client: Box::new(client),
+                error,
+            });
+        }
+    }
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::fmt;
+
+    fn authenticated(refresh_token: &str) -> OauthClient<Authenticated> {
+        OauthClient::load_from_values(
+            "access",
+            refresh_token,
+            "id",
+            None,
+            "http://localhost:8080/callback",
+            1_700_003_600,
+        )
+        .unwrap()
+        .with_clock(ManualClock::new(1_700_000_000))
+    }
+
+    fn tokens(refresh_token: &str) -> StoredTokens {
+        StoredTokens::from(&authenticated(refresh_token))
+    }
+
+    // Records every write, and fails the ones after `fail_after`
+    #[derive(Debug, Default)]
+    struct RecordingStore {
+        inner: MemoryTokenStore,
+        log: Mutex<Vec<String>>,
+        fail_after: Option<usize>,
+    }
+
+    #[derive(Debug)]
+    struct StoreFailure;
+
+    impl fmt::Display for StoreFailure {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "store failure")
+        }
+    }
+
+    impl std::error::Error for StoreFailure {}
+
+    impl RecordingStore {
+        fn record(&self, entry: String) -> Result<(), StoreFailure> {
+            let mut log = self.log.lock().unwrap();
+            if self.fail_after.is_some_and(|n| log.len() >= n) {
+                return Err(StoreFailure);
+            }
+            log.push(entry);
+            Ok(())
+        }
+    }
+
+    impl TokenStore for RecordingStore {
+        type Error = StoreFailure;
+
+        fn save_token_set(&self, name: &str, tokens: &StoredTokens) -> Result<(), StoreFailure> {
+            self.record(format!("save {}", tokens.refresh_token))?;
+            self.inner.save_token_set(name, tokens).unwrap();
+            Ok(())
+        }
+
+        fn load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, StoreFailure> {
+            Ok(self.inner.load_token_set(name).unwrap())
+        }
+
+        fn delete_token_set(&self, name: &str, refresh_token: &str) -> Result<(), StoreFailure> {
+            self.record(format!("delete {}", refresh_token))?;
+            self.inner.delete_token_set(name, refresh_token).unwrap();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotate() {
+        let store = RecordingStore::default();
+        store.inner.save_token_set("me", &tokens("old")).unwrap();
+
+        let client = rotate(&store, "me", "old", async { Ok(authenticated("new")) })
+            .await
+            .unwrap();
+        assert_eq!(client.get_refresh_token_secret(), "new");
+        assert_eq!(*store.log.lock().unwrap(), vec!["save new", "delete old"]);
+        assert_eq!(store.load_token_set("me").unwrap(), Some(tokens("new")));
+
+        // A failed refresh leaves the store alone
+        let result = rotate(&store, "me", "new", async {
+            Err(OauthError::FailedToRefreshToken)
+        })
+        .await;
+        assert!(matches!(result, Err(RefreshError::Oauth(_))));
+        assert_eq!(store.log.lock().unwrap().len(), 2);
+
+        // A failed save keeps the old tokens and hands back the new client
+        let store = RecordingStore {
+            fail_after: Some(0),
+            ..Default::default()
+        };
+        store.inner.save_token_set("me", &tokens("old")).unwrap();
+        match rotate(&store, "me", "old", async { Ok(authenticated("new")) }).await {
+            Err(RefreshError::Save { client, .. }) => {
+                assert_eq!(client.get_refresh_token_secret(), "new")
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(store.load_token_set("me").unwrap(), Some(tokens("old")));
+
+        // A failed cleanup still leaves the new tokens as the ones loaded
+        let store = RecordingStore {
+            fail_after: Some(1),
+            ..Default::default()
+        };
+        store.inner.save_token_set("me", &tokens("old")).unwrap();
+        let result = rotate(&store, "me", "old", async { Ok(authenticated("new")) }).await;
+        assert_eq!(result.unwrap_err().code(), ErrorCode::Store);
+        assert_eq!(store.load_token_set("me").unwrap(), Some(tokens("new")));
+    }
+}
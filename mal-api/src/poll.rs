@@ -0,0 +1,457 @@
+//! Module for scheduling polls of many users' lists
+//!
+//! Bots that watch the lists of many users can't poll all of them often without
+//! running into MAL's rate limits. A [PollScheduler] polls users whose lists
+//! changed recently more often: every poll that finds no changes doubles the
+//! user's interval, up to a maximum, and a poll that finds changes resets it to
+//! the minimum. Intervals are jittered so users added at the same time spread
+//! out, and polls are paced by a [RateLimiter] that can be shared with the
+//! other requests of the bot.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut scheduler = PollScheduler::new(PollSchedule::default());
+//! for user_name in ["user_a", "user_b"] {
+//!     scheduler.add_user(user_name, Instant::now());
+//! }
+//!
+//! while let Some(user_name) = scheduler.next().await {
+//!     let list = api_client.get_full_user_anime_list(&query_for(&user_name)).await?;
+//!     let diff = diff_lists(&snapshots[&user_name], &list);
+//!     scheduler.record_diff(&user_name, &diff, Instant::now());
+//!     // ...notify about the diff, and store the new snapshot
+//! }
+//! ```
+
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::RandomState, BinaryHeap, HashMap},
+    hash::BuildHasher,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{codes::ErrorCode, diff::ListDiff, rate_limit::RateLimiter};
+
+// Instant has no maximum, so polls that would overflow it are due this far out
+const FAR_FUTURE: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PollScheduleError {
+    #[error("the minimum interval must be positive and at most the maximum interval")]
+    InvalidInterval,
+
+    #[error("the backoff must be a finite factor of at least 1, got {0}")]
+    InvalidBackoff(f64),
+
+    #[error("the jitter must be within [0, 1], got {0}")]
+    InvalidJitter(f64),
+}
+
+impl PollScheduleError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidValue
+    }
+}
+
+/// How often a [PollScheduler] polls users
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollSchedule {
+    min_interval: Duration,
+    max_interval: Duration,
+    backoff: f64,
+    jitter: f64,
+}
+
+impl PollSchedule {
+    /// Create a schedule
+    ///
+    /// - `min_interval`: interval of users whose last poll found changes
+    /// - `max_interval`: longest interval of inactive users
+    /// - `backoff`: factor the interval grows by after each poll without changes
+    /// - `jitter`: fraction of the interval it is randomly lengthened or
+    ///   shortened by, within `[0, 1]`
+    pub fn new(
+        min_interval: Duration,
+        max_interval: Duration,
+        backoff: f64,
+        jitter: f64,
+    ) -> Result<Self, PollScheduleError> {
+        if min_interval.is_zero() || min_interval > max_interval {
+            return Err(PollScheduleError::InvalidInterval);
+        }
+        if !backoff.is_finite() || backoff < 1.0 {
+            return Err(PollScheduleError::InvalidBackoff(backoff));
+        }
+        if !(0.0..=1.0).contains(&jitter) {
+            return Err(PollScheduleError::InvalidJitter(jitter));
+        }
+        Ok(Self {
+            min_interval,
+            max_interval,
+            backoff,
+            jitter,
+        })
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    pub fn max_interval(&self) -> Duration {
+        self.max_interval
+    }
+
+    pub fn backoff(&self) -> f64 {
+        self.backoff
+    }
+
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+}
+
+impl Default for PollSchedule {
+    /// Between 5 minutes and 6 hours, doubling, with 10% jitter
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(5 * 60),
+            max_interval: Duration::from_secs(6 * 60 * 60),
+            backoff: 2.0,
+            jitter: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct UserPoll {
+    interval: Duration,
+    next_poll: Instant,
+}
+
+/// Schedules the polls of users' lists by how active they are
+#[derive(Debug, Clone)]
+pub struct PollScheduler {
+    schedule: PollSchedule,
+    users: HashMap<String, UserPoll>,
+    // Polls by when they're due, ties broken by user name so polls are taken
+    // in a stable order. Rescheduled and removed users leave stale entries,
+    // which are dropped once they reach the top
+    queue: BinaryHeap<Reverse<(Instant, String)>>,
+    limiter: RateLimiter,
+}
+
+impl PollScheduler {
+    pub fn new(schedule: PollSchedule) -> Self {
+        Self {
+            schedule,
+            users: HashMap::new(),
+            queue: BinaryHeap::new(),
+            limiter: RateLimiter::default(),
+        }
+    }
+
+    /// Pace the polls taken by [next](PollScheduler::next) with a limiter
+    /// shared with other requests, instead of one of their own
+    pub fn with_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Add a user, due to be polled at `now`. Users that were already added keep
+    /// their schedule
+    pub fn add_user(&mut self, user_name: &str, now: Instant) {
+        if self.users.contains_key(user_name) {
+            return;
+        }
+        self.users.insert(
+            user_name.to_string(),
+            UserPoll {
+                interval: self.schedule.min_interval,
+                next_poll: now,
+            },
+        );
+        self.schedule_poll(user_name, now);
+    }
+
+    /// Stop polling a user
+    pub fn remove_user(&mut self, user_name: &str) {
+        self.users.remove(user_name);
+        self.drop_stale();
+    }
+
+    /// Number of users polled
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// Current interval of a user, without jitter
+    pub fn interval(&self, user_name: &str) -> Option<Duration> {
+        self.users.get(user_name).map(|u| u.interval)
+    }
+
+    /// When the next poll is due, or `None` if there are no users
+    pub fn next_poll_at(&self) -> Option<Instant> {
+        let Reverse((next_poll, _)) = self.queue.peek()?;
+        Some(*next_poll)
+    }
+
+    /// Take the user to poll at `now`, if one is due
+    ///
+    /// The user isn't returned again until their poll is [recorded](PollScheduler::record),
+    /// or their current interval passes. Polls aren't paced, use
+    /// [next](PollScheduler::next) to wait for the rate limiter too.
+    pub fn take_due(&mut self, now: Instant) -> Option<String> {
+        if self.next_poll_at()? > now {
+            return None;
+        }
+        let Reverse((_, user_name)) = self.queue.pop()?;
+        let next_poll = after(now, self.users.get(&user_name)?.interval);
+        self.schedule_poll(&user_name, next_poll);
+        Some(user_name)
+    }
+
+    /// Wait until a poll is due and the rate limiter lets it through, and take
+    /// its user, see [take_due](PollScheduler::take_due)
+    ///
+    /// Returns `None` if there are no users
+    pub async fn next(&mut self) -> Option<String> {
+        loop {
+            let at = self.next_poll_at()?;
+            tokio::time::sleep_until(at.into()).await;
+            self.limiter.wait().await;
+            if let Some(user_name) = self.take_due(Instant::now()) {
+                return Some(user_name);
+            }
+        }
+    }
+
+    /// Reschedule a user after a poll that found changes or not
+    ///
+    /// Users that weren't added are ignored
+    pub fn record(&mut self, user_name: &str, changed: bool, now: Instant) {
+        let schedule = self.schedule;
+        let Some(user) = self.users.get_mut(user_name) else {
+            return;
+        };
+        user.interval = if changed {
+            schedule.min_interval
+        } else {
+            scaled(user.interval, schedule.backoff)
+                .clamp(schedule.min_interval, schedule.max_interval)
+        };
+        let next_poll = after(now, jittered(user.interval, schedule.jitter, user_name));
+        // The queue already has an entry for an unchanged poll
+        if next_poll != user.next_poll {
+            self.schedule_poll(user_name, next_poll);
+        }
+    }
+
+    /// Reschedule a user after a poll, by whether the diff of their list is empty
+    pub fn record_diff<T>(&mut self, user_name: &str, diff: &ListDiff<T>, now: Instant) {
+        self.record(user_name, !diff.is_empty(), now);
+    }
+
+    fn schedule_poll(&mut self, user_name: &str, next_poll: Instant) {
+        let Some(user) = self.users.get_mut(user_name) else {
+            return;
+        };
+        user.next_poll = next_poll;
+        self.queue.push(Reverse((next_poll, user_name.to_string())));
+        // Rebuild the queue once stale entries outnumber the users, so it
+        // doesn't grow with every poll
+        if self.queue.len() > 2 * self.users.len() {
+            self.queue = self
+                .users
+                .iter()
+                .map(|(name, user)| Reverse((user.next_poll, name.clone())))
+                .collect();
+        }
+        self.drop_stale();
+    }
+
+    // Pops entries of removed or rescheduled users off the top of the queue,
+    // so its top is always the next poll
+    fn drop_stale(&mut self) {
+        while let Some(Reverse((next_poll, user_name))) = self.queue.peek() {
+            match self.users.get(user_name) {
+                Some(user) if user.next_poll == *next_poll => break,
+                _ => {
+                    self.queue.pop();
+                }
+            }
+        }
+    }
+}
+
+// `now + delay`, saturating instead of overflowing
+fn after(now: Instant, delay: Duration) -> Instant {
+    now.checked_add(delay)
+        .or_else(|| now.checked_add(FAR_FUTURE))
+        .unwrap_or(now)
+}
+
+// The interval multiplied by a non-negative factor, saturating at Duration::MAX
+fn scaled(interval: Duration, factor: f64) -> Duration {
+    Duration::try_from_secs_f64(interval.as_secs_f64() * factor).unwrap_or(Duration::MAX)
+}
+
+// The interval randomly scaled within `[1 - jitter, 1 + jitter]`
+fn jittered(interval: Duration, jitter: f64, seed: &str) -> Duration {
+    let jitter = jitter.clamp(0.0, 1.0);
+    if jitter == 0.0 {
+        return interval;
+    }
+    let random = RandomState::new().hash_one((seed, Instant::now())) % 10_001;
+    let factor = 1.0 + jitter * (random as f64 / 5_000.0 - 1.0);
+    scaled(interval, factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_scheduler() {
+        let schedule =
+            PollSchedule::new(Duration::from_secs(60), Duration::from_secs(300), 2.0, 0.0).unwrap();
+        let start = Instant::now();
+        let mut scheduler = PollScheduler::new(schedule);
+        scheduler.add_user("b", start);
+        scheduler.add_user("a", start);
+
+        assert_eq!(scheduler.take_due(start).as_deref(), Some("a"));
+        assert_eq!(scheduler.take_due(start).as_deref(), Some("b"));
+        assert_eq!(scheduler.take_due(start), None);
+        assert_eq!(
+            scheduler.next_poll_at(),
+            Some(start + Duration::from_secs(60))
+        );
+        let now = start + Duration::from_secs(1);
+
+        // Inactive users back off up to the maximum, active ones reset
+        for _ in 0..4 {
+            scheduler.record("a", false, now);
+        }
+        assert_eq!(scheduler.interval("a"), Some(Duration::from_secs(300)));
+        scheduler.record("b", true, now);
+        assert_eq!(scheduler.interval("b"), Some(Duration::from_secs(60)));
+        assert_eq!(
+            scheduler.next_poll_at(),
+            Some(now + Duration::from_secs(60))
+        );
+
+        scheduler.remove_user("b");
+        assert_eq!(
+            scheduler.next_poll_at(),
+            Some(now + Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn test_poll_schedule() {
+        let minute = Duration::from_secs(60);
+        assert_eq!(
+            PollSchedule::new(Duration::ZERO, minute, 2.0, 0.0),
+            Err(PollScheduleError::InvalidInterval)
+        );
+        assert_eq!(
+            PollSchedule::new(minute * 2, minute, 2.0, 0.0),
+            Err(PollScheduleError::InvalidInterval)
+        );
+        assert!(matches!(
+            PollSchedule::new(minute, minute, f64::NAN, 0.0),
+            Err(PollScheduleError::InvalidBackoff(_))
+        ));
+        assert_eq!(
+            PollSchedule::new(minute, minute, f64::INFINITY, 0.0),
+            Err(PollScheduleError::InvalidBackoff(f64::INFINITY))
+        );
+        assert_eq!(
+            PollSchedule::new(minute, minute, 2.0, 1.5),
+            Err(PollScheduleError::InvalidJitter(1.5))
+        );
+    }
+
+    #[test]
+    fn test_poll_scheduler_saturates() {
+        let schedule =
+            PollSchedule::new(Duration::from_secs(60), Duration::MAX, f64::MAX, 1.0).unwrap();
+        let now = Instant::now();
+        let mut scheduler = PollScheduler::new(schedule);
+        scheduler.add_user("a", now);
+        scheduler.add_user("b", now);
+        assert_eq!(scheduler.take_due(now).as_deref(), Some("a"));
+        for _ in 0..3 {
+            scheduler.record("a", false, now);
+        }
+        assert_eq!(scheduler.interval("a"), Some(Duration::MAX));
+        assert_eq!(scheduler.take_due(now).as_deref(), Some("b"));
+        scheduler.record("b", false, now);
+        assert!(scheduler.next_poll_at().unwrap() > now);
+        assert_eq!(scheduler.take_due(now), None);
+    }
+
+    #[test]
+    fn test_poll_scheduler_queue() {
+        let schedule =
+            PollSchedule::new(Duration::from_secs(60), Duration::from_secs(60), 1.0, 0.0).unwrap();
+        let start = Instant::now();
+        let mut scheduler = PollScheduler::new(schedule);
+        for user_name in ["a", "b", "c"] {
+            scheduler.add_user(user_name, start);
+        }
+
+        // Polls rotate through the users, and the queue stays bounded
+        let mut now = start;
+        let mut polled = Vec::new();
+        for _ in 0..30 {
+            let user_name = scheduler.take_due(now).unwrap();
+            scheduler.record(&user_name, false, now);
+            polled.push(user_name);
+            now += Duration::from_secs(20);
+        }
+        assert_eq!(&polled[..6], ["a", "b", "c", "a", "b", "c"]);
+        assert!(scheduler.queue.len() <= 2 * scheduler.len());
+
+        scheduler.remove_user("a");
+        scheduler.remove_user("b");
+        scheduler.remove_user("c");
+        assert_eq!(scheduler.next_poll_at(), None);
+    }
+
+    #[tokio::test]
+    async fn test_poll_scheduler_next() {
+        let start = Instant::now();
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let mut scheduler = PollScheduler::new(PollSchedule::default()).with_limiter(limiter);
+        scheduler.add_user("a", start);
+        scheduler.add_user("b", start);
+
+        // Both users are due, but the polls are paced by the limiter
+        assert_eq!(scheduler.next().await.as_deref(), Some("a"));
+        assert_eq!(scheduler.next().await.as_deref(), Some("b"));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        scheduler.remove_user("a");
+        scheduler.remove_user("b");
+        assert_eq!(scheduler.next().await, None);
+    }
+
+    #[test]
+    fn test_jittered() {
+        let interval = Duration::from_secs(100);
+        for _ in 0..100 {
+            let jittered = jittered(interval, 0.1, "user");
+            assert!(jittered >= Duration::from_secs(89) && jittered <= Duration::from_secs(111));
+        }
+        assert_eq!(jittered(interval, 0.0, "user"), interval);
+    }
+}
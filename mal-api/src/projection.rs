@@ -0,0 +1,165 @@
+//! Module for deserializing responses into your own structs
+//!
+//! A projection is a struct with the subset of the anime or manga fields your
+//! application needs. [MalProjection] ties the struct to the fields to request,
+//! so the `fields` query parameter always matches what the struct reads.
+//!
+//! With the `derive` feature, `#[derive(MalProjection)]` implements the trait
+//! and the deserializer from the names of the struct fields, which must be MAL
+//! field names. Unknown names fail to compile. Set the endpoints the struct is
+//! used with through `#[mal(anime)]`, `#[mal(anime_details)]`, `#[mal(manga)]`,
+//! or `#[mal(manga_details)]`. Fields MAL may leave out, such as `mean`, should
+//! be `Option`s.
+//!
+//! Send the query with `get_raw`, and read list endpoints as a [ProjectedList].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! #[derive(Debug, MalProjection)]
+//! #[mal(anime)]
+//! struct Entry {
+//!     id: u32,
+//!     title: String,
+//!     mean: Option<f32>,
+//! }
+//!
+//! let fields = AnimeCommonFields::from(Entry::fields());
+//! let query = GetAnimeList::builder("frieren").fields(&fields).build().unwrap();
+//! let list: ProjectedList<Entry> = api_client.get_raw(&query).await.unwrap();
+//! for entry in list.into_nodes() {
+//!     println!("{} ({:?})", entry.title, entry.mean);
+//! }
+//! ```
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::common::{FieldSet, Paging, PagingIter};
+
+#[cfg(feature = "derive")]
+pub use mal_api_derive::MalProjection;
+
+/// A struct built from a subset of the fields of an endpoint
+pub trait MalProjection: DeserializeOwned {
+    /// The field enum of the endpoints, e.g. [AnimeField](crate::anime::requests::AnimeField)
+    type Field;
+
+    /// The fields to request, one per struct field
+    fn fields() -> FieldSet<Self::Field>;
+}
+
+/// A page of a list endpoint, with the entries deserialized as `T`
+///
+/// Everything next to the `node` of an entry, such as the `list_status` of the
+/// user list endpoints, is ignored
+#[derive(Debug, Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+pub struct ProjectedList<T> {
+    pub data: Vec<ProjectedNode<T>>,
+    pub paging: Paging,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+pub struct ProjectedNode<T> {
+    pub node: T,
+}
+
+impl<T> ProjectedList<T> {
+    /// Take the entries out of the page
+    pub fn into_nodes(self) -> Vec<T> {
+        self.data.into_iter().map(|n| n.node).collect()
+    }
+}
+
+impl<T> PagingIter for ProjectedList<T> {
+    type Item = Self;
+
+    fn paging(&self) -> &Paging {
+        &self.paging
+    }
+}
+
+// Used by the code generated by `#[derive(MalProjection)]`
+#[doc(hidden)]
+pub mod __private {
+    use serde::{de::DeserializeOwned, de::Error};
+    pub use serde::{Deserialize, Deserializer};
+    use serde_json::{Map, Value};
+
+    pub fn object<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Map<String, Value>, D::Error> {
+        Map::deserialize(deserializer)
+    }
+
+    pub fn take<T: DeserializeOwned, E: Error>(
+        object: &mut Map<String, Value>,
+        name: &'static str,
+    ) -> Result<T, E> {
+        match object.remove(name) {
+            Some(value) => serde_json::from_value(value)
+                .map_err(|err| E::custom(format!("invalid field `{}`: {}", name, err))),
+            // Lets `Option` fields be missing
+            None => serde_json::from_value(Value::Null).map_err(|_| E::missing_field(name)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "derive", feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::well_known::{COWBOY_BEBOP_ANIME_ID, FRIEREN_ANIME_ID};
+
+    #[derive(Debug, PartialEq, MalProjection)]
+    #[mal(anime)]
+    struct Entry {
+        id: u32,
+        title: String,
+        mean: Option<f32>,
+        my_list_status: Option<serde_json::Value>,
+    }
+
+    #[test]
+    fn test_derive_mal_projection() {
+        assert_eq!(Entry::fields().as_str(), "id,title,mean,my_list_status");
+
+        let list: ProjectedList<Entry> = serde_json::from_value(serde_json::json!({
+            "data": [
+                { "node": { "id": FRIEREN_ANIME_ID, "title": "Sousou no Frieren", "mean": 9.3 } },
+                { "node": { "id": COWBOY_BEBOP_ANIME_ID, "title": "Cowboy Bebop", "rank": 45 } }
+            ],
+            "paging": {}
+        }))
+        .unwrap();
+        assert_eq!(
+            list.into_nodes(),
+            vec![
+                Entry {
+                    id: FRIEREN_ANIME_ID,
+                    title: "Sousou no Frieren".to_string(),
+                    mean: Some(9.3),
+                    my_list_status: None,
+                },
+                Entry {
+                    id: COWBOY_BEBOP_ANIME_ID,
+                    title: "Cowboy Bebop".to_string(),
+                    mean: None,
+                    my_list_status: None,
+                },
+            ]
+        );
+
+        let missing = serde_json::from_value::<Entry>(serde_json::json!({ "id": 1 }));
+        assert!(missing
+            .unwrap_err()
+            .to_string()
+            .contains("missing field `title`"));
+        let invalid =
+            serde_json::from_value::<Entry>(serde_json::json!({ "id": "1", "title": "" }));
+        assert!(invalid
+            .unwrap_err()
+            .to_string()
+            .contains("invalid field `id`"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Module for picking random anime and manga
+//!
+//! MAL has no random endpoint, and guessing ids mostly hits deleted or
+//! nonexistent entries. Instead, a random offset is picked within a ranking or
+//! a season and the entry at that offset is fetched.
+//!
+//! The randomness is only meant for features like a bot's "surprise me"
+//! command. It is not suitable for anything security related.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let filter = RandomAnimeFilter::Ranking {
+//!     ranking_type: RankingType::Tv,
+//!     top: 1000,
+//! };
+//! if let Some(anime) = random::get_random_anime(&api_client, &filter, None).await.unwrap() {
+//!     println!("How about {}?", anime.title);
+//! }
+//! ```
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "anime")]
+use crate::anime::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeCommonFields, GetAnimeRanking, GetSeasonalAnime, RankingType, Season},
+    responses::AnimeFields,
+};
+#[cfg(feature = "manga")]
+use crate::manga::{
+    api::MangaApi,
+    error::MangaApiError,
+    requests::{GetMangaRanking, MangaCommonFields, MangaRankingType},
+    responses::MangaFields,
+};
+
+/// Largest page MAL returns for seasonal queries
+#[cfg(feature = "anime")]
+const SEASON_PAGE_LIMIT: u16 = 500;
+
+/// Where to pick a random anime from
+#[cfg(feature = "anime")]
+#[derive(Debug, Clone)]
+pub enum RandomAnimeFilter {
+    /// One of the first `top` entries of a ranking
+    Ranking { ranking_type: RankingType, top: u32 },
+
+    /// Any anime of a season
+    Season { year: u16, season: Season },
+}
+
+/// Pick a random anime matching the filter
+///
+/// NSFW entries are excluded. Returns `None` if nothing matched, e.g. when
+/// `top` is larger than the ranking.
+#[cfg(feature = "anime")]
+pub async fn get_random_anime<C>(
+    api: &C,
+    filter: &RandomAnimeFilter,
+    fields: Option<&AnimeCommonFields>,
+) -> Result<Option<AnimeFields>, AnimeApiError>
+where
+    C: AnimeApi + Sync,
+{
+    match filter {
+        RandomAnimeFilter::Ranking { ranking_type, top } => {
+            let offset = random_below(*top);
+            let query =
+                GetAnimeRanking::new(ranking_type.clone(), false, fields, Some(1), Some(offset));
+            let mut ranking = api.get_anime_ranking(&query).await?;
+            Ok(ranking.data.pop().map(|e| e.node))
+        }
+        RandomAnimeFilter::Season { year, season } => {
+            let query = GetSeasonalAnime::new(
+                *year,
+                season.clone(),
+                false,
+                fields,
+                None,
+                Some(SEASON_PAGE_LIMIT),
+                None,
+            );
+            let mut seasonal = api.get_seasonal_anime(&query).await?;
+            if seasonal.data.is_empty() {
+                return Ok(None);
+            }
+            let index = random_below(seasonal.data.len() as u32) as usize;
+            Ok(Some(seasonal.data.swap_remove(index).node))
+        }
+    }
+}
+
+/// Pick a random manga from the first `top` entries of a ranking
+///
+/// NSFW entries are excluded. Returns `None` if `top` is larger than the ranking.
+#[cfg(feature = "manga")]
+pub async fn get_random_manga<C>(
+    api: &C,
+    ranking_type: MangaRankingType,
+    top: u32,
+    fields: Option<&MangaCommonFields>,
+) -> Result<Option<MangaFields>, MangaApiError>
+where
+    C: MangaApi + Sync,
+{
+    let offset = random_below(top);
+    let query = GetMangaRanking::new(ranking_type, false, fields, Some(1), Some(offset));
+    let mut ranking = api.get_manga_ranking(&query).await?;
+    Ok(ranking.data.pop().map(|e| e.node))
+}
+
+// Random number within `[0, n)`, or 0 if `n` is 0
+fn random_below(n: u32) -> u32 {
+    if n == 0 {
+        return 0;
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    (RandomState::new().hash_one(nanos) % u64::from(n)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_below() {
+        assert_eq!(random_below(0), 0);
+        assert_eq!(random_below(1), 0);
+        for _ in 0..100 {
+            assert!(random_below(10) < 10);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Module for merging ranking pages without duplicate entries
+//!
+//! Rankings can shift while they are being paged through, so an entry at the end
+//! of one page can show up again at the start of the next. [MergedRanking] keeps
+//! the first occurrence of each entry.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetAnimeRanking::new(RankingType::All, false, None, Some(500), None);
+//! let mut page = api_client.get_anime_ranking(&query).await.unwrap();
+//! let mut merged = MergedRanking::new();
+//!
+//! merged.extend(&mut page);
+//! while let Ok(next) = api_client.next(&page).await {
+//!     page = next;
+//!     merged.extend(&mut page);
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::{AnimeRanking, AnimeRankingNode};
+use crate::common::PagingIter;
+#[cfg(feature = "manga")]
+use crate::manga::responses::{MangaRanking, MangaRankingNode};
+
+/// A page of an anime or manga ranking
+pub trait RankingPage: PagingIter {
+    type Node;
+
+    /// Take the entries out of the page, leaving its paging intact
+    fn take_nodes(&mut self) -> Vec<Self::Node>;
+
+    /// Get the MAL id of an entry, if it was returned
+    fn node_id(node: &Self::Node) -> Option<u32>;
+}
+
+#[cfg(feature = "anime")]
+impl RankingPage for AnimeRanking {
+    type Node = AnimeRankingNode;
+
+    fn take_nodes(&mut self) -> Vec<Self::Node> {
+        std::mem::take(&mut self.data)
+    }
+
+    fn node_id(node: &Self::Node) -> Option<u32> {
+        Some(node.node.id)
+    }
+}
+
+#[cfg(feature = "manga")]
+impl RankingPage for MangaRanking {
+    type Node = MangaRankingNode;
+
+    fn take_nodes(&mut self) -> Vec<Self::Node> {
+        std::mem::take(&mut self.data)
+    }
+
+    fn node_id(node: &Self::Node) -> Option<u32> {
+        node.node.id
+    }
+}
+
+/// Ranking entries merged across pages, without duplicates
+#[derive(Debug)]
+pub struct MergedRanking<N> {
+    entries: Vec<N>,
+    seen: HashSet<u32>,
+}
+
+impl<N> MergedRanking<N> {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Move the entries of the page into the merged ranking, skipping the ones
+    /// that were already merged. Returns the number of entries added.
+    ///
+    /// The page keeps its paging, so it can still be passed to `next`.
+    pub fn extend<P>(&mut self, page: &mut P) -> usize
+    where
+        P: RankingPage<Node = N>,
+    {
+        let before = self.entries.len();
+        for node in page.take_nodes() {
+            match P::node_id(&node) {
+                Some(id) if !self.seen.insert(id) => continue,
+                _ => self.entries.push(node),
+            }
+        }
+        self.entries.len() - before
+    }
+
+    /// Get the merged entries, in ranking order
+    pub fn entries(&self) -> &[N] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<N> {
+        self.entries
+    }
+}
+
+impl<N> Default for MergedRanking<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+
+    fn page(ids: &[u32]) -> AnimeRanking {
+        let data: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                serde_json::json!({
+                    "node": { "id": id, "title": format!("Anime {}", id) },
+                    "ranking": { "rank": id }
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "data": data,
+            "paging": { "next": "https://api.myanimelist.net/v2/anime/ranking?offset=3" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merged_ranking() {
+        let mut merged = MergedRanking::new();
+
+        let mut first = page(&[1, 2, 3]);
+        assert_eq!(merged.extend(&mut first), 3);
+        assert!(first.data.is_empty());
+        assert!(first.next_page().is_some());
+
+        assert_eq!(merged.extend(&mut page(&[3, 4, 5])), 2);
+        assert_eq!(
+            merged
+                .entries()
+                .iter()
+                .map(|e| e.node.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Module for pacing requests to stay within MAL's rate limits
+//!
+//! MAL doesn't document its rate limits, and answers clients that send too many
+//! requests with `403`s or `429`s for a while. A [RateLimiter] spaces requests
+//! out by a minimum interval. Clones share their pacing, so one limiter can be
+//! handed to every helper that sends many requests, e.g. bulk updates, fetching
+//! the lists of many users, and polling, and their requests are spaced out
+//! across all of them.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let limiter = RateLimiter::new(Duration::from_secs(1));
+//! let options = BulkOptions::new(BulkErrorPolicy::ContinueOnError).limiter(limiter.clone());
+//! api_client.update_anime_list_bulk(updates, &options).await;
+//!
+//! let lists = community::fetch_many_user_lists(&api_client, &user_names, 4, &limiter).await;
+//! ```
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Default shortest time between two requests
+pub const DEFAULT_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Spaces out requests by a minimum interval
+///
+/// Requests are let through in the order they [wait](RateLimiter::wait), so
+/// the rate stays the same however many are sent at the same time.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter that lets a request through every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Create a limiter that doesn't wait
+    pub fn unlimited() -> Self {
+        Self::new(Duration::ZERO)
+    }
+
+    /// Get the shortest time between two requests
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Wait until the next request can be sent
+    pub async fn wait(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slot.filter(|slot| *slot > now).unwrap_or(now);
+            *next_slot = slot.checked_add(self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+impl Default for RateLimiter {
+    /// A limiter with the [DEFAULT_REQUEST_INTERVAL]
+    fn default() -> Self {
+        Self::new(DEFAULT_REQUEST_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let shared = limiter.clone();
+        let start = Instant::now();
+        futures::future::join_all((0..3).map(|_| shared.wait())).await;
+        limiter.wait().await;
+        assert!(start.elapsed() >= Duration::from_millis(60));
+
+        let start = Instant::now();
+        RateLimiter::unlimited().wait().await;
+        RateLimiter::unlimited().wait().await;
+        assert!(start.elapsed() < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,321 @@
+//! Module for rendering anime details as terminal output
+//!
+//! [render_anime_details] lays out the details that were requested as a list of
+//! styled [Line]s. The lines map directly onto the line and span types of TUI
+//! libraries such as ratatui, or can be printed with [to_ansi] and [to_plain].
+//!
+//! Request the `alternative_titles`, `media_type`, `status`, `num_episodes`,
+//! `start_season`, `mean`, `rank`, `popularity`, `genres`, `studios`, `synopsis`,
+//! `opening_themes`, and `ending_themes` fields for a full page. Missing fields
+//! are left out.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let details = api_client.get_anime_details(&query).await.unwrap();
+//! let lines = render::render_anime_details(&details, 80);
+//! println!("{}", render::to_ansi(&lines));
+//! ```
+
+use serde::Serialize;
+
+use crate::anime::responses::AnimeDetails;
+
+/// How a [Span] should be displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Plain,
+
+    /// Used for the title and section headings
+    Bold,
+
+    /// Used for secondary information, such as alternative titles
+    Dim,
+
+    /// Used for scores and rankings
+    Accent,
+}
+
+impl Style {
+    fn ansi_code(&self) -> Option<&'static str> {
+        match self {
+            Style::Plain => None,
+            Style::Bold => Some("\x1b[1m"),
+            Style::Dim => Some("\x1b[2m"),
+            Style::Accent => Some("\x1b[36m"),
+        }
+    }
+}
+
+/// A piece of text with a single style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn new<T: Into<String>>(text: T, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// A line of output, made of styled spans
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Line {
+    pub spans: Vec<Span>,
+}
+
+impl Line {
+    fn styled<T: Into<String>>(text: T, style: Style) -> Self {
+        Self {
+            spans: vec![Span::new(text, style)],
+        }
+    }
+
+    /// The text of the line without any styling
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+/// Lay out the anime details as lines no wider than `width` characters
+///
+/// Only the synopsis is wrapped. A `width` of 0 disables wrapping.
+pub fn render_anime_details(details: &AnimeDetails, width: usize) -> Vec<Line> {
+    let anime = &details.shared_fields;
+    let mut lines = vec![Line::styled(details.display_title(), Style::Bold)];
+
+    let alternative_titles = anime.alternative_titles.iter().flat_map(|t| t.titles());
+    for title in alternative_titles {
+        if title != details.display_title() {
+            lines.push(Line::styled(title, Style::Dim));
+        }
+    }
+
+    let mut info = vec![];
+    if let Some(media_type) = variant(&anime.media_type) {
+        info.push(
+            media_type
+                .split('_')
+                .map(media_type_word)
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+    }
+    match anime.num_episodes {
+        Some(1) => info.push("1 episode".to_string()),
+        Some(n) if n > 1 => info.push(format!("{} episodes", n)),
+        _ => {}
+    }
+    if let Some(status) = variant(&anime.status) {
+        info.push(capitalize(&status.replace('_', " ")));
+    }
+    if let Some(start_season) = &anime.start_season {
+        if let Some(season) = variant(&Some(&start_season.season)) {
+            info.push(format!("{} {}", capitalize(&season), start_season.year));
+        }
+    }
+    if !info.is_empty() {
+        lines.push(Line::styled(info.join(" · "), Style::Plain));
+    }
+
+    let mut stats = vec![];
+    if let Some(mean) = anime.mean {
+        stats.push(format!("Score {:.2}", mean));
+    }
+    if let Some(rank) = anime.rank {
+        stats.push(format!("Ranked #{}", rank));
+    }
+    if let Some(popularity) = anime.popularity {
+        stats.push(format!("Popularity #{}", popularity));
+    }
+    if !stats.is_empty() {
+        lines.push(Line::styled(stats.join(" · "), Style::Accent));
+    }
+
+    let genres: Vec<&str> = anime
+        .genres
+        .iter()
+        .flatten()
+        .map(|g| g.name.as_str())
+        .collect();
+    if !genres.is_empty() {
+        lines.push(labeled("Genres", genres.join(", ")));
+    }
+    let studios: Vec<&str> = anime
+        .studios
+        .iter()
+        .flatten()
+        .map(|s| s.name.as_str())
+        .collect();
+    if !studios.is_empty() {
+        lines.push(labeled("Studios", studios.join(", ")));
+    }
+
+    if let Some(synopsis) = anime.synopsis.as_deref().filter(|s| !s.is_empty()) {
+        lines.push(Line::default());
+        for paragraph in synopsis.lines() {
+            lines.extend(
+                wrap(paragraph, width)
+                    .into_iter()
+                    .map(|l| Line::styled(l, Style::Plain)),
+            );
+        }
+    }
+
+    for (heading, themes) in [
+        ("Openings", details.openings()),
+        ("Endings", details.endings()),
+    ] {
+        if themes.is_empty() {
+            continue;
+        }
+        lines.push(Line::default());
+        lines.push(Line::styled(heading, Style::Bold));
+        for theme in themes {
+            lines.push(Line::styled(format!("  {}", theme), Style::Plain));
+        }
+    }
+
+    lines
+}
+
+/// Join the lines into text with ANSI escape codes for the styles
+pub fn to_ansi(lines: &[Line]) -> String {
+    let mut output = String::new();
+    for line in lines {
+        for span in &line.spans {
+            match span.style.ansi_code() {
+                Some(code) => {
+                    output.push_str(code);
+                    output.push_str(&span.text);
+                    output.push_str("\x1b[0m");
+                }
+                None => output.push_str(&span.text),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Join the lines into text without any styling
+pub fn to_plain(lines: &[Line]) -> String {
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&line.text());
+        output.push('\n');
+    }
+    output
+}
+
+fn labeled(label: &str, value: String) -> Line {
+    Line {
+        spans: vec![
+            Span::new(format!("{}: ", label), Style::Bold),
+            Span::new(value, Style::Plain),
+        ],
+    }
+}
+
+// The name MAL uses for an enum variant, e.g. `finished_airing`
+fn variant<T: Serialize>(value: &Option<T>) -> Option<String> {
+    match serde_json::to_value(value.as_ref()?).ok()? {
+        serde_json::Value::String(name) => Some(name),
+        _ => None,
+    }
+}
+
+// Abbreviations such as `tv` and `ova` are uppercased
+fn media_type_word(word: &str) -> String {
+    if word.len() <= 3 {
+        word.to_uppercase()
+    } else {
+        capitalize(word)
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Greedy word wrap, counting characters rather than bytes
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return vec![text.to_string()];
+    }
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let line_width = line.chars().count();
+        if line_width > 0 && line_width + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::FRIEREN_ANIME_ID;
+
+    #[test]
+    fn test_render_anime_details() {
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": FRIEREN_ANIME_ID,
+            "title": "Sousou no Frieren",
+            "alternative_titles": {
+                "synonyms": [],
+                "en": "Frieren: Beyond Journey's End",
+                "ja": "葬送のフリーレン"
+            },
+            "media_type": "tv",
+            "num_episodes": 28,
+            "status": "finished_airing",
+            "start_season": { "year": 2023, "season": "fall" },
+            "mean": 9.3,
+            "genres": [{ "id": 2, "name": "Adventure" }, { "id": 8, "name": "Drama" }],
+            "synopsis": "During their decade-long quest to defeat the Demon King",
+            "opening_themes": [
+                { "id": 1, "anime_id": FRIEREN_ANIME_ID, "text": "#1: \"Yuusha (勇者)\" by YOASOBI (eps 1-16)" }
+            ]
+        }))
+        .unwrap();
+
+        let output = to_plain(&render_anime_details(&details, 30));
+        assert_eq!(
+            output,
+            "Sousou no Frieren
+Frieren: Beyond Journey's End
+葬送のフリーレン
+TV · 28 episodes · Finished airing · Fall 2023
+Score 9.30
+Genres: Adventure, Drama
+
+During their decade-long quest
+to defeat the Demon King
+
+Openings
+  \"Yuusha (勇者)\" by YOASOBI
+"
+        );
+
+        let ansi = to_ansi(&render_anime_details(&details, 0));
+        assert!(ansi.starts_with("\x1b[1mSousou no Frieren\x1b[0m\n"));
+    }
+}
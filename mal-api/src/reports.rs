@@ -0,0 +1,250 @@
+//! Module for generating top-N reports from anime and manga rankings
+//!
+//! A [RankingReport] lists the rank, the change since the previous ranking, the
+//! title, the mean score, and the number of members of each entry. It can be
+//! written as CSV, JSON, or a markdown table, e.g. for weekly newsletter bots.
+//!
+//! Request the `mean` and `num_list_users` fields for the score and member
+//! columns. Missing values are left empty.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetAnimeRanking::builder(RankingType::Airing)
+//!     .fields(&fields)
+//!     .limit(10)
+//!     .build()
+//!     .unwrap();
+//! let ranking = api_client.get_anime_ranking(&query).await.unwrap();
+//!
+//! let report = RankingReport::top(&ranking.data, 10);
+//! println!("{}", report.render(ReportFormat::Markdown));
+//! ```
+
+use serde::Serialize;
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::AnimeRankingNode;
+#[cfg(feature = "manga")]
+use crate::manga::responses::MangaRankingNode;
+
+/// Output format of a [RankingReport]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+/// A single entry of a [RankingReport]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportRow {
+    pub rank: u32,
+    pub previous_rank: Option<u32>,
+
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub title: String,
+    pub mean: Option<f32>,
+
+    /// Number of users with the entry in their list
+    pub members: Option<u32>,
+}
+
+impl ReportRow {
+    /// Number of places the entry moved up since the previous ranking, negative
+    /// if it moved down, or `None` if it's new to the ranking
+    pub fn change(&self) -> Option<i64> {
+        self.previous_rank
+            .map(|previous| i64::from(previous) - i64::from(self.rank))
+    }
+
+    // e.g. `+3`, `-2`, `0`, or `new`
+    fn formatted_change(&self) -> String {
+        match self.change() {
+            Some(change) if change > 0 => format!("+{}", change),
+            Some(change) => change.to_string(),
+            None => "new".to_string(),
+        }
+    }
+
+    fn formatted_mean(&self) -> String {
+        self.mean.map(|m| format!("{:.2}", m)).unwrap_or_default()
+    }
+
+    fn formatted_members(&self) -> String {
+        self.members.map(|m| m.to_string()).unwrap_or_default()
+    }
+}
+
+/// A ranking entry that can be added to a [RankingReport]
+pub trait ReportEntry {
+    fn report_row(&self) -> ReportRow;
+}
+
+#[cfg(feature = "anime")]
+impl ReportEntry for AnimeRankingNode {
+    fn report_row(&self) -> ReportRow {
+        ReportRow {
+            rank: self.ranking.rank,
+            previous_rank: self.ranking.previous_rank,
+            title: self.node.display_title().to_string(),
+            mean: self.node.mean,
+            members: self.node.num_list_users,
+        }
+    }
+}
+
+#[cfg(feature = "manga")]
+impl ReportEntry for MangaRankingNode {
+    fn report_row(&self) -> ReportRow {
+        ReportRow {
+            rank: self.ranking.rank,
+            previous_rank: self.ranking.previous_rank,
+            title: self.node.display_title().to_string(),
+            mean: self.node.mean,
+            members: self.node.num_list_users,
+        }
+    }
+}
+
+/// The top entries of a ranking
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RankingReport {
+    pub rows: Vec<ReportRow>,
+}
+
+impl RankingReport {
+    /// Create a report of the `n` best ranked entries
+    ///
+    /// Works with a single ranking page, or with the entries of a
+    /// [MergedRanking](crate::ranking::MergedRanking) for reports longer than a page
+    pub fn top<E: ReportEntry>(entries: &[E], n: usize) -> Self {
+        let mut rows: Vec<ReportRow> = entries.iter().map(|e| e.report_row()).collect();
+        rows.sort_by_key(|r| r.rank);
+        rows.truncate(n);
+        Self { rows }
+    }
+
+    /// Write the report in the given format
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Write the report as CSV, with a header row
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("rank,change,title,mean,members\n");
+        for row in &self.rows {
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                row.rank,
+                row.formatted_change(),
+                csv_field(&row.title),
+                row.formatted_mean(),
+                row.formatted_members()
+            ));
+        }
+        output
+    }
+
+    /// Write the report as a JSON array, with the change as a number or `null`
+    pub fn to_json(&self) -> String {
+        let rows: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| {
+                serde_json::json!({
+                    "rank": row.rank,
+                    "previous_rank": row.previous_rank,
+                    "change": row.change(),
+                    "title": row.title,
+                    "mean": row.mean,
+                    "members": row.members,
+                })
+            })
+            .collect();
+        serde_json::to_string(&rows).unwrap_or_default()
+    }
+
+    /// Write the report as a markdown table
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::from(
+            "| Rank | Change | Title | Mean | Members |\n| ---: | ---: | --- | ---: | ---: |\n",
+        );
+        for row in &self.rows {
+            output.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                row.rank,
+                row.formatted_change(),
+                row.title.replace('|', "\\|"),
+                row.formatted_mean(),
+                row.formatted_members()
+            ));
+        }
+        output
+    }
+}
+
+// Quotes the field if it contains a separator, a quote, or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::anime::responses::AnimeRanking;
+
+    #[test]
+    fn test_ranking_report() {
+        let ranking: AnimeRanking = serde_json::from_value(serde_json::json!({
+            "data": [
+                {
+                    "node": { "id": 2, "title": "Anime, Part 2", "mean": 8.9 },
+                    "ranking": { "rank": 2 }
+                },
+                {
+                    "node": { "id": 1, "title": "Anime | 1", "mean": 9.1, "num_list_users": 1000 },
+                    "ranking": { "rank": 1, "previous_rank": 3 }
+                },
+                {
+                    "node": { "id": 3, "title": "Anime 3" },
+                    "ranking": { "rank": 3, "previous_rank": 2 }
+                }
+            ],
+            "paging": {}
+        }))
+        .unwrap();
+
+        let report = RankingReport::top(&ranking.data, 2);
+        assert_eq!(report.rows.len(), 2);
+        assert_eq!(report.rows[0].change(), Some(2));
+
+        assert_eq!(
+            report.to_csv(),
+            "rank,change,title,mean,members\n1,+2,Anime | 1,9.10,1000\n2,new,\"Anime, Part 2\",8.90,\n"
+        );
+        assert_eq!(
+            report.render(ReportFormat::Markdown),
+            "| Rank | Change | Title | Mean | Members |
+| ---: | ---: | --- | ---: | ---: |
+| 1 | +2 | Anime \\| 1 | 9.10 | 1000 |
+| 2 | new | Anime, Part 2 | 8.90 |  |
+"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json[0]["change"], 2);
+        assert!(json[1]["change"].is_null());
+
+        let report = RankingReport::top(&ranking.data, 10);
+        assert_eq!(report.rows[2].formatted_change(), "-1");
+    }
+}
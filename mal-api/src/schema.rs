@@ -0,0 +1,134 @@
+//! Module for detecting differences between MAL's responses and this crate's types
+//!
+//! MAL occasionally adds fields and enum variants to its responses without
+//! notice. [check] parses a raw payload into one of the response types and
+//! reports everything that didn't make it, so the change can be reported and
+//! supported before it breaks anyone.
+//!
+//! Fields kept by the `unknown-fields` feature are not reported.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let payload = std::fs::read_to_string("anime_details.json").unwrap();
+//! for issue in schema::check::<AnimeDetails>(&payload) {
+//!     println!("{}", issue);
+//! }
+//! ```
+
+use std::fmt::Display;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Difference between a MAL payload and the expected structure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// A field that isn't part of the response type, e.g. `data[0].node.new_field`
+    UnknownField { path: String },
+
+    /// The payload couldn't be parsed at all, e.g. because of an unknown enum variant
+    Invalid { message: String },
+}
+
+impl Display for SchemaIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaIssue::UnknownField { path } => write!(f, "unknown field: {}", path),
+            SchemaIssue::Invalid { message } => write!(f, "invalid payload: {}", message),
+        }
+    }
+}
+
+/// Compare a raw JSON payload against the response type `T`
+///
+/// Returns an empty list if the payload matches the type
+pub fn check<T>(payload: &str) -> Vec<SchemaIssue>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let raw: Value = match serde_json::from_str(payload) {
+        Ok(raw) => raw,
+        Err(err) => {
+            return vec![SchemaIssue::Invalid {
+                message: err.to_string(),
+            }]
+        }
+    };
+
+    // Anything that doesn't survive a round trip through `T` is unknown to the crate
+    let parsed = serde_json::from_value::<T>(raw.clone()).and_then(|t| serde_json::to_value(&t));
+    match parsed {
+        Ok(parsed) => {
+            let mut issues = vec![];
+            compare(&raw, &parsed, String::new(), &mut issues);
+            issues
+        }
+        Err(err) => vec![SchemaIssue::Invalid {
+            message: err.to_string(),
+        }],
+    }
+}
+
+fn compare(raw: &Value, parsed: &Value, path: String, issues: &mut Vec<SchemaIssue>) {
+    match (raw, parsed) {
+        (Value::Object(raw), Value::Object(parsed)) => {
+            for (key, value) in raw {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match parsed.get(key) {
+                    Some(parsed) => compare(value, parsed, field_path, issues),
+                    None => issues.push(SchemaIssue::UnknownField { path: field_path }),
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(parsed)) => {
+            for (index, (raw, parsed)) in raw.iter().zip(parsed).enumerate() {
+                compare(raw, parsed, format!("{}[{}]", path, index), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::anime::responses::AnimeList;
+
+    #[test]
+    fn test_check() {
+        let payload = serde_json::json!({
+            "data": [{
+                "node": { "id": 1, "title": "Anime 1", "new_field": true }
+            }],
+            "paging": {},
+            "season": { "year": 2023, "season": "fall" }
+        })
+        .to_string();
+        let mut issues = check::<AnimeList>(&payload);
+        issues.sort_by_key(|i| i.to_string());
+
+        #[cfg(not(feature = "unknown-fields"))]
+        assert_eq!(
+            issues,
+            vec![
+                SchemaIssue::UnknownField {
+                    path: "data[0].node.new_field".to_string()
+                },
+                SchemaIssue::UnknownField {
+                    path: "season".to_string()
+                },
+            ]
+        );
+
+        let payload = r#"{"data": [{"node": {"id": 1, "title": "Anime 1", "media_type": "tv_special_2"}}], "paging": {}}"#;
+        assert!(matches!(
+            check::<AnimeList>(payload)[..],
+            [SchemaIssue::Invalid { .. }]
+        ));
+    }
+}
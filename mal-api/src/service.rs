@@ -0,0 +1,327 @@
+//! Module for sending the API clients' requests through a `tower::Service`
+//!
+//! [MalService] implements `tower::Service<MalRequest>` by sending the requests
+//! with a `reqwest::Client`. Wrap it in any tower layers, such as timeouts,
+//! load shedding, or tracing, and hand the result to an API client's
+//! `with_service`. Every request of that client, including the list updates and
+//! the page requests, then goes through the layers instead of the client's own
+//! HTTP client.
+//!
+//! The service returns every response MAL sends, including error responses,
+//! so that layers such as retries can inspect the status. The API clients turn
+//! them into errors as usual.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use tower::ServiceBuilder;
+//!
+//! let service = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(10))
+//!     .service(MalService::new());
+//! let api_client = AnimeApiClient::from(&client_id).with_service(service);
+//!
+//! let query = GetAnimeRanking::builder(RankingType::All).build().unwrap();
+//! let ranking = api_client.get_anime_ranking(&query).await.unwrap();
+//! ```
+
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use tower_service::Service;
+
+use crate::common::MalError;
+
+#[cfg(api)]
+use {
+    futures::future::BoxFuture,
+    std::{fmt, future::poll_fn, sync::Arc},
+};
+
+/// Error of a service the API clients send their requests through
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// A request to the MAL API
+///
+/// The credentials of the API client are already in the `headers`
+#[derive(Debug, Clone)]
+pub struct MalRequest {
+    pub method: Method,
+
+    /// Full URL of the request, including the query parameters
+    pub url: Url,
+
+    pub headers: HeaderMap,
+
+    /// URL-encoded form body, sent by the update requests
+    pub body: Option<Bytes>,
+}
+
+impl TryFrom<reqwest::Request> for MalRequest {
+    type Error = BoxError;
+
+    fn try_from(request: reqwest::Request) -> Result<Self, Self::Error> {
+        let body = match request.body() {
+            Some(body) => Some(Bytes::copy_from_slice(
+                body.as_bytes()
+                    .ok_or("streamed request bodies can't be sent")?,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body,
+        })
+    }
+}
+
+/// A response from the MAL API, successful or not
+#[derive(Debug, Clone)]
+pub struct MalResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl MalResponse {
+    /// Returns true if MAL responded with a 2xx status
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    /// Parse the body into one of the response types, or your own
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// The error body MAL sent, if it rejected the request
+    pub fn mal_error(&self) -> Option<MalError> {
+        if self.is_success() {
+            return None;
+        }
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+impl From<MalResponse> for reqwest::Response {
+    fn from(value: MalResponse) -> Self {
+        let mut response = ::http::Response::new(value.body);
+        *response.status_mut() = value.status;
+        *response.headers_mut() = value.headers;
+        reqwest::Response::from(response)
+    }
+}
+
+/// A `tower::Service` that sends [MalRequest]s with a `reqwest::Client`
+#[derive(Debug, Clone, Default)]
+pub struct MalService {
+    client: reqwest::Client,
+}
+
+impl MalService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send requests through the given HTTP client instead of the default one
+    ///
+    /// Use [HttpClientBuilder](crate::http::HttpClientBuilder) to configure it
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl Service<MalRequest> for MalService {
+    type Response = MalResponse;
+    type Error = reqwest::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<MalResponse, reqwest::Error>> + Send>>;
+
+    // reqwest queues requests in its own connection pool, so the service is always ready
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: MalRequest) -> Self::Future {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        Box::pin(async move {
+            let response = builder.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes().await?;
+            Ok(MalResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+// Object safe version of the services, so the API clients can hold any of them
+// without a type parameter
+#[cfg(api)]
+pub(crate) trait DynMalService: Send + Sync {
+    fn dyn_call(&self, request: MalRequest) -> BoxFuture<'static, Result<MalResponse, BoxError>>;
+}
+
+#[cfg(api)]
+impl<S> DynMalService for S
+where
+    S: Service<MalRequest, Response = MalResponse> + Clone + Send + Sync + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    // Each request gets its own clone, like tower's buffered services, so
+    // concurrent requests don't wait on each other's readiness
+    fn dyn_call(&self, request: MalRequest) -> BoxFuture<'static, Result<MalResponse, BoxError>> {
+        let mut service = self.clone();
+        Box::pin(async move {
+            poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .map_err(Into::into)?;
+            service.call(request).await.map_err(Into::into)
+        })
+    }
+}
+
+// The service an API client sends its requests through
+#[cfg(api)]
+#[derive(Clone)]
+pub(crate) struct SharedService(Arc<dyn DynMalService>);
+
+#[cfg(api)]
+impl SharedService {
+    pub(crate) fn new<S: DynMalService + 'static>(service: S) -> Self {
+        Self(Arc::new(service))
+    }
+
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, BoxError> {
+        let request = MalRequest::try_from(request.build()?)?;
+        let response = self.0.dyn_call(request).await?;
+        Ok(response.into())
+    }
+}
+
+#[cfg(api)]
+impl fmt::Debug for SharedService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedService").finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::{
+        anime::{
+            api::{AnimeApi, AnimeApiClient},
+            requests::GetAnimeDetails,
+        },
+        codes::ErrorCode,
+        oauth::MalClientId,
+    };
+    use std::sync::Mutex;
+
+    // Answers every request with `status` and `body`, logging the requests
+    #[derive(Clone)]
+    struct Canned {
+        status: StatusCode,
+        body: &'static str,
+        requests: Arc<Mutex<Vec<MalRequest>>>,
+    }
+
+    impl Canned {
+        fn new(status: StatusCode, body: &'static str) -> Self {
+            Self {
+                status,
+                body,
+                requests: Arc::new(Mutex::new(vec![])),
+            }
+        }
+    }
+
+    impl Service<MalRequest> for Canned {
+        type Response = MalResponse;
+        type Error = BoxError;
+        type Future = BoxFuture<'static, Result<MalResponse, BoxError>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: MalRequest) -> Self::Future {
+            self.requests.lock().unwrap().push(request);
+            let response = MalResponse {
+                status: self.status,
+                headers: HeaderMap::new(),
+                body: Bytes::from_static(self.body.as_bytes()),
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_through_service() {
+        let service = Canned::new(StatusCode::OK, r#"{"id": 1, "title": "Anime 1"}"#);
+        let api_client =
+            AnimeApiClient::from(&MalClientId::new("id")).with_service(service.clone());
+
+        let query = GetAnimeDetails::builder(1).build().unwrap();
+        let details = api_client.get_anime_details(&query).await.unwrap();
+        assert_eq!(details.shared_fields.title, "Anime 1");
+
+        let requests = service.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::GET);
+        assert_eq!(requests[0].url.path(), "/v2/anime/1");
+        assert_eq!(requests[0].headers["X-MAL-CLIENT-ID"], "id");
+    }
+
+    #[tokio::test]
+    async fn test_client_reads_service_errors() {
+        let service = Canned::new(
+            StatusCode::NOT_FOUND,
+            r#"{"error": "not_found", "message": ""}"#,
+        );
+        let api_client = AnimeApiClient::from(&MalClientId::new("id")).with_service(service);
+
+        let query = GetAnimeDetails::builder(1).build().unwrap();
+        let err = api_client.get_anime_details(&query).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::NotFound);
+        assert_eq!(err.mal_error().unwrap().error, "not_found");
+    }
+
+    #[test]
+    fn test_mal_response() {
+        let response = MalResponse {
+            status: StatusCode::NOT_FOUND,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(br#"{"error": "not_found"}"#),
+        };
+        assert!(!response.is_success());
+        assert_eq!(response.mal_error().unwrap().error, "not_found");
+
+        let response = reqwest::Response::from(response);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
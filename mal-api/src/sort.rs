@@ -0,0 +1,187 @@
+//! Module for sorting fetched lists and rankings
+//!
+//! MAL's `sort` parameter only covers a few orders for user lists, and rankings
+//! can't be sorted at all. These helpers sort a response after it was fetched.
+//!
+//! Entries missing the value being sorted by, e.g. unscored entries when sorting
+//! by score, are always placed last. Entries with equal values keep the order MAL
+//! returned them in.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut list = api_client.get_user_anime_list(&query).await.unwrap();
+//! list.sort_by(SortKey::Score, Order::Descending);
+//!
+//! let ranking = api_client
+//!     .get_anime_ranking(&query)
+//!     .await
+//!     .unwrap()
+//!     .sorted_by(SortKey::StartDate, Order::Ascending);
+//! ```
+
+use std::cmp::{Ordering, Reverse};
+
+use crate::{dates::parse_timestamp, diff::ListEntry, titles::normalize_title};
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::{AnimeList, AnimeRanking};
+#[cfg(feature = "manga")]
+use crate::manga::responses::{MangaList, MangaRanking};
+
+/// Value to sort entries by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// The score the user gave the entry. Unscored entries are placed last
+    Score,
+
+    /// The mean score of the entry on MAL. Request the `mean` field
+    Mean,
+
+    /// The main title of the entry, ignoring case and punctuation
+    Title,
+
+    /// When the user last updated the entry
+    UpdatedAt,
+
+    /// When the anime started airing, or the manga started publishing. Request the
+    /// `start_date` field
+    StartDate,
+}
+
+/// Direction to sort entries in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Sort a slice of entries in place
+pub fn sort_entries<T: ListEntry>(entries: &mut [T], key: SortKey, order: Order) {
+    match key {
+        SortKey::Score => entries.sort_by(|a, b| by_option(a.list_score(), b.list_score(), order)),
+        SortKey::Mean => entries.sort_by(|a, b| by_option(a.mean(), b.mean(), order)),
+        // Titles are normalized once per entry instead of once per comparison
+        SortKey::Title => match order {
+            Order::Ascending => entries.sort_by_cached_key(|e| normalize_title(e.title())),
+            Order::Descending => {
+                entries.sort_by_cached_key(|e| Reverse(normalize_title(e.title())))
+            }
+        },
+        // Timestamps are compared as instants, as their offsets can differ
+        SortKey::UpdatedAt => {
+            let updated_at = |e: &T| e.list_updated_at().and_then(parse_timestamp);
+            match order {
+                Order::Ascending => entries.sort_by_cached_key(|e| {
+                    let updated_at = updated_at(e);
+                    (updated_at.is_none(), updated_at)
+                }),
+                Order::Descending => entries.sort_by_cached_key(|e| {
+                    let updated_at = updated_at(e);
+                    (updated_at.is_none(), Reverse(updated_at))
+                }),
+            }
+        }
+        SortKey::StartDate => {
+            entries.sort_by(|a, b| by_option(a.start_date(), b.start_date(), order))
+        }
+    }
+}
+
+// Missing values are placed last regardless of the order
+fn by_option<V: PartialOrd>(a: Option<V>, b: Option<V>, order: Order) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            match order {
+                Order::Ascending => ordering,
+                Order::Descending => ordering.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// Implements `sort_by` and `sorted_by` for the given responses
+#[cfg(lists)]
+macro_rules! impl_sort {
+    ($($response:ty),* $(,)?) => {
+        $(
+            impl $response {
+                /// Sort the entries of this response in place
+                pub fn sort_by(&mut self, key: SortKey, order: Order) {
+                    sort_entries(&mut self.data, key, order);
+                }
+
+                /// Sort the entries of this response, returning it
+                pub fn sorted_by(mut self, key: SortKey, order: Order) -> Self {
+                    self.sort_by(key, order);
+                    self
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "anime")]
+impl_sort!(AnimeList, AnimeRanking);
+
+#[cfg(feature = "manga")]
+impl_sort!(MangaList, MangaRanking);
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{anime_entry, anime_list};
+    use serde_json::json;
+
+    fn list() -> AnimeList {
+        anime_list(vec![
+            anime_entry(1)
+                .score(7)
+                .updated_at("2023-01-02T00:00:00+00:00")
+                .node(json!({ "title": "b anime", "start_date": "2020-04" }))
+                .build(),
+            anime_entry(2)
+                .status("watching")
+                .updated_at("2023-01-02T06:00:00+09:00")
+                .node(json!({ "title": "A Anime" }))
+                .build(),
+            anime_entry(3)
+                .score(9)
+                .node(json!({ "title": "C: Anime", "start_date": "2019-10-01" }))
+                .build(),
+        ])
+    }
+
+    fn ids(list: &AnimeList) -> Vec<u32> {
+        list.data.iter().map(|e| e.node.id).collect()
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let mut anime = list();
+        anime.sort_by(SortKey::Score, Order::Descending);
+        assert_eq!(ids(&anime), vec![3, 1, 2]);
+        anime.sort_by(SortKey::Score, Order::Ascending);
+        // Unscored entries stay last
+        assert_eq!(ids(&anime), vec![1, 3, 2]);
+
+        let anime = list().sorted_by(SortKey::Title, Order::Ascending);
+        assert_eq!(ids(&anime), vec![2, 1, 3]);
+        let anime = anime.sorted_by(SortKey::Title, Order::Descending);
+        assert_eq!(ids(&anime), vec![3, 1, 2]);
+
+        // 2023-01-02T06:00:00+09:00 is before 2023-01-02T00:00:00+00:00
+        let anime = list().sorted_by(SortKey::UpdatedAt, Order::Descending);
+        assert_eq!(ids(&anime), vec![1, 2, 3]);
+        let anime = list().sorted_by(SortKey::UpdatedAt, Order::Ascending);
+        assert_eq!(ids(&anime), vec![3, 2, 1]);
+
+        let anime = list().sorted_by(SortKey::StartDate, Order::Ascending);
+        assert_eq!(ids(&anime), vec![3, 1, 2]);
+    }
+}
@@ -0,0 +1,649 @@
+//! Module for persisting MAL data into SQLite
+//!
+//! Enable the `sqlite` feature to use this module.
+//!
+//! The [SqliteStore] persists user lists, anime and manga details, and OAuth token
+//! sets using a stable, versioned schema. Each row keeps the original response as
+//! JSON alongside a few indexed columns, so stored data can always be loaded back
+//! into the crate's response types.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let mut store = SqliteStore::open("mal.db").unwrap();
+//!
+//! let list = api_client.get_user_anime_list(&query).await.unwrap();
+//! let diff = store.sync_anime_list("user", &list.data).unwrap();
+//! println!("{} added, {} updated, {} removed", diff.added.len(), diff.updated.len(), diff.removed.len());
+//! ```
+
+use std::{path::Path, sync::Arc};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{
+    anime::responses::{AnimeDetails, AnimeListNode},
+    clock::{Clock, ClockError, SystemClock},
+    codes::ErrorCode,
+    diff::{diff_lists, ListDiff, ListEntry},
+    manga::responses::{MangaDetails, MangaListNode},
+    oauth::{store::TokenStore, Authenticated, OauthClient},
+    stats::{StatsSnapshot, StatsStore},
+};
+
+pub use crate::oauth::store::StoredTokens;
+
+/// Version of the schema created by this module. Stored in `PRAGMA user_version`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS anime_list_entries (
+    user_name TEXT NOT NULL,
+    anime_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    status TEXT,
+    score INTEGER,
+    updated_at TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (user_name, anime_id)
+);
+CREATE TABLE IF NOT EXISTS manga_list_entries (
+    user_name TEXT NOT NULL,
+    manga_id INTEGER NOT NULL,
+    title TEXT,
+    status TEXT,
+    score INTEGER,
+    updated_at TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (user_name, manga_id)
+);
+CREATE TABLE IF NOT EXISTS anime_details (
+    anime_id INTEGER PRIMARY KEY,
+    data TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS manga_details (
+    manga_id INTEGER PRIMARY KEY,
+    data TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS anime_stats (
+    anime_id INTEGER NOT NULL,
+    fetched_at INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS anime_stats_anime_id ON anime_stats (anime_id, fetched_at);
+CREATE TABLE IF NOT EXISTS tokens (
+    name TEXT PRIMARY KEY,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Error)]
+pub enum SqliteStoreError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("failed to serialize or deserialize stored data: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("database schema version {0} is newer than the supported version")]
+    UnsupportedSchemaVersion(u32),
+
+    #[error("failed to read the time: {0}")]
+    Clock(#[from] ClockError),
+}
+
+impl SqliteStoreError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SqliteStoreError::Sqlite(_) => ErrorCode::Store,
+            SqliteStoreError::Serialization(_) => ErrorCode::StoreData,
+            SqliteStoreError::UnsupportedSchemaVersion(_) => ErrorCode::StoreSchema,
+            SqliteStoreError::Clock(err) => err.code(),
+        }
+    }
+}
+
+/// Stored details along with when they were fetched
+#[derive(Debug)]
+pub struct StoredDetails<T> {
+    pub details: T,
+
+    /// Number of seconds since the Unix Epoch when the details were stored
+    pub fetched_at: u64,
+}
+
+/// SQLite backed storage for lists, details, and tokens
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Connection,
+    clock: Arc<dyn Clock>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path`, creating the schema if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SqliteStoreError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Open a new in-memory database
+    pub fn open_in_memory() -> Result<Self, SqliteStoreError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Use an existing connection, creating the schema if needed
+    pub fn from_connection(conn: Connection) -> Result<Self, SqliteStoreError> {
+        let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(SqliteStoreError::UnsupportedSchemaVersion(version));
+        }
+
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self {
+            conn,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Read the time details are stored at from this clock instead of the system's
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Get a reference to the underlying connection, e.g. for custom queries
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Load the stored anime list of a user
+    pub fn load_anime_list(&self, user_name: &str) -> Result<Vec<AnimeListNode>, SqliteStoreError> {
+        self.load_list("anime_list_entries", "anime_id", user_name)
+    }
+
+    /// Compare the given entries against the stored anime list of a user
+    pub fn diff_anime_list<'a>(
+        &self,
+        user_name: &str,
+        entries: &'a [AnimeListNode],
+    ) -> Result<ListDiff<&'a AnimeListNode>, SqliteStoreError> {
+        let stored = self.load_anime_list(user_name)?;
+        Ok(diff_lists(&stored, entries))
+    }
+
+    /// Insert or update the given anime list entries of a user
+    ///
+    /// Stored entries that are not in `entries` are kept, which makes this
+    /// suitable for storing a list page by page
+    pub fn upsert_anime_list(
+        &mut self,
+        user_name: &str,
+        entries: &[AnimeListNode],
+    ) -> Result<(), SqliteStoreError> {
+        let tx = self.conn.transaction()?;
+        for entry in entries {
+            upsert_anime_entry(&tx, user_name, entry)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the stored anime list of a user with the complete list `entries`,
+    /// returning what changed
+    pub fn sync_anime_list<'a>(
+        &mut self,
+        user_name: &str,
+        entries: &'a [AnimeListNode],
+    ) -> Result<ListDiff<&'a AnimeListNode>, SqliteStoreError> {
+        let diff = self.diff_anime_list(user_name, entries)?;
+
+        let tx = self.conn.transaction()?;
+        for entry in diff.added.iter().chain(diff.updated.iter()) {
+            upsert_anime_entry(&tx, user_name, entry)?;
+        }
+        for id in diff.removed.iter() {
+            tx.execute(
+                "DELETE FROM anime_list_entries WHERE user_name = ?1 AND anime_id = ?2",
+                params![user_name, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(diff)
+    }
+
+    /// Load the stored manga list of a user
+    pub fn load_manga_list(&self, user_name: &str) -> Result<Vec<MangaListNode>, SqliteStoreError> {
+        self.load_list("manga_list_entries", "manga_id", user_name)
+    }
+
+    /// Compare the given entries against the stored manga list of a user
+    pub fn diff_manga_list<'a>(
+        &self,
+        user_name: &str,
+        entries: &'a [MangaListNode],
+    ) -> Result<ListDiff<&'a MangaListNode>, SqliteStoreError> {
+        let stored = self.load_manga_list(user_name)?;
+        Ok(diff_lists(&stored, entries))
+    }
+
+    /// Insert or update the given manga list entries of a user
+    ///
+    /// Entries without an id are skipped
+    pub fn upsert_manga_list(
+        &mut self,
+        user_name: &str,
+        entries: &[MangaListNode],
+    ) -> Result<(), SqliteStoreError> {
+        let tx = self.conn.transaction()?;
+        for entry in entries {
+            upsert_manga_entry(&tx, user_name, entry)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the stored manga list of a user with the complete list `entries`,
+    /// returning what changed
+    pub fn sync_manga_list<'a>(
+        &mut self,
+        user_name: &str,
+        entries: &'a [MangaListNode],
+    ) -> Result<ListDiff<&'a MangaListNode>, SqliteStoreError> {
+        let diff = self.diff_manga_list(user_name, entries)?;
+
+        let tx = self.conn.transaction()?;
+        for entry in diff.added.iter().chain(diff.updated.iter()) {
+            upsert_manga_entry(&tx, user_name, entry)?;
+        }
+        for id in diff.removed.iter() {
+            tx.execute(
+                "DELETE FROM manga_list_entries WHERE user_name = ?1 AND manga_id = ?2",
+                params![user_name, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(diff)
+    }
+
+    /// Store the details of an anime, replacing any previously stored details
+    pub fn save_anime_details(&self, details: &AnimeDetails) -> Result<(), SqliteStoreError> {
+        self.conn.execute(
+            "INSERT INTO anime_details (anime_id, data, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (anime_id) DO UPDATE SET data = excluded.data, fetched_at = excluded.fetched_at",
+            params![
+                details.shared_fields.id,
+                serde_json::to_string(details)?,
+                self.clock.now()?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Load the stored details of an anime
+    pub fn load_anime_details(
+        &self,
+        anime_id: u32,
+    ) -> Result<Option<StoredDetails<AnimeDetails>>, SqliteStoreError> {
+        self.load_details("anime_details", "anime_id", anime_id)
+    }
+
+    /// Store the details of a manga, replacing any previously stored details
+    ///
+    /// Details without an id are not stored
+    pub fn save_manga_details(&self, details: &MangaDetails) -> Result<(), SqliteStoreError> {
+        let id = match details.shared_fields.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        self.conn.execute(
+            "INSERT INTO manga_details (manga_id, data, fetched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (manga_id) DO UPDATE SET data = excluded.data, fetched_at = excluded.fetched_at",
+            params![id, serde_json::to_string(details)?, self.clock.now()?],
+        )?;
+        Ok(())
+    }
+
+    /// Load the stored details of a manga
+    pub fn load_manga_details(
+        &self,
+        manga_id: u32,
+    ) -> Result<Option<StoredDetails<MangaDetails>>, SqliteStoreError> {
+        self.load_details("manga_details", "manga_id", manga_id)
+    }
+
+    /// Store the tokens of an authenticated client under `name`
+    pub fn save_tokens(
+        &self,
+        name: &str,
+        client: &OauthClient<Authenticated>,
+    ) -> Result<(), SqliteStoreError> {
+        self.save_token_set(name, &StoredTokens::from(client))
+    }
+
+    /// Load the tokens stored under `name`
+    pub fn load_tokens(&self, name: &str) -> Result<Option<StoredTokens>, SqliteStoreError> {
+        let tokens = self
+            .conn
+            .query_row(
+                "SELECT access_token, refresh_token, expires_at FROM tokens WHERE name = ?1",
+                params![name],
+                |row| {
+                    Ok(StoredTokens {
+                        access_token: row.get(0)?,
+                        refresh_token: row.get(1)?,
+                        expires_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(tokens)
+    }
+
+    /// Delete the tokens stored under `name`
+    pub fn delete_tokens(&self, name: &str) -> Result<(), SqliteStoreError> {
+        self.conn
+            .execute("DELETE FROM tokens WHERE name = ?1", params![name])?;
+        Ok(())
+    }
+
+    fn load_list<T: DeserializeOwned>(
+        &self,
+        table: &str,
+        id_column: &str,
+        user_name: &str,
+    ) -> Result<Vec<T>, SqliteStoreError> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT data FROM {} WHERE user_name = ?1 ORDER BY {}",
+            table, id_column
+        ))?;
+        let rows = statement.query_map(params![user_name], |row| row.get::<_, String>(0))?;
+
+        let mut entries = vec![];
+        for data in rows {
+            entries.push(serde_json::from_str(&data?)?);
+        }
+        Ok(entries)
+    }
+
+    fn load_details<T: DeserializeOwned>(
+        &self,
+        table: &str,
+        id_column: &str,
+        id: u32,
+    ) -> Result<Option<StoredDetails<T>>, SqliteStoreError> {
+        let row = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT data, fetched_at FROM {} WHERE {} = ?1",
+                    table, id_column
+                ),
+                params![id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?)),
+            )
+            .optional()?;
+
+        match row {
+            Some((data, fetched_at)) => Ok(Some(StoredDetails {
+                details: serde_json::from_str(&data)?,
+                fetched_at,
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+impl StatsStore for SqliteStore {
+    type Error = SqliteStoreError;
+
+    fn save_snapshot(&self, snapshot: &StatsSnapshot) -> Result<(), Self::Error> {
+        self.conn.execute(
+            "INSERT INTO anime_stats (anime_id, fetched_at, data) VALUES (?1, ?2, ?3)",
+            params![
+                snapshot.anime_id,
+                snapshot.fetched_at,
+                serde_json::to_string(snapshot)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn load_snapshots(&self, anime_id: u32) -> Result<Vec<StatsSnapshot>, Self::Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT data FROM anime_stats WHERE anime_id = ?1 ORDER BY fetched_at, rowid",
+        )?;
+        let rows = statement.query_map(params![anime_id], |row| row.get::<_, String>(0))?;
+
+        let mut snapshots = vec![];
+        for data in rows {
+            snapshots.push(serde_json::from_str(&data?)?);
+        }
+        Ok(snapshots)
+    }
+}
+
+// The tokens table keeps one token set per name, so saving replaces the old
+// set atomically and there's nothing left to delete
+impl TokenStore for SqliteStore {
+    type Error = SqliteStoreError;
+
+    fn save_token_set(&self, name: &str, tokens: &StoredTokens) -> Result<(), Self::Error> {
+        self.conn.execute(
+            "INSERT INTO tokens (name, access_token, refresh_token, expires_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (name) DO UPDATE SET access_token = excluded.access_token,
+                refresh_token = excluded.refresh_token, expires_at = excluded.expires_at",
+            params![
+                name,
+                tokens.access_token,
+                tokens.refresh_token,
+                tokens.expires_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn load_token_set(&self, name: &str) -> Result<Option<StoredTokens>, Self::Error> {
+        self.load_tokens(name)
+    }
+
+    fn delete_token_set(&self, name: &str, refresh_token: &str) -> Result<(), Self::Error> {
+        self.conn.execute(
+            "DELETE FROM tokens WHERE name = ?1 AND refresh_token = ?2",
+            params![name, refresh_token],
+        )?;
+        Ok(())
+    }
+}
+
+fn upsert_anime_entry(
+    tx: &Transaction,
+    user_name: &str,
+    entry: &AnimeListNode,
+) -> Result<(), SqliteStoreError> {
+    let status = entry.list_status.as_ref();
+    tx.execute(
+        "INSERT INTO anime_list_entries (user_name, anime_id, title, status, score, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (user_name, anime_id) DO UPDATE SET title = excluded.title,
+            status = excluded.status, score = excluded.score,
+            updated_at = excluded.updated_at, data = excluded.data",
+        params![
+            user_name,
+            entry.node.id,
+            entry.node.title,
+            status.and_then(|s| enum_to_string(&s.status)),
+            status.map(|s| s.score),
+            status.map(|s| s.updated_at.as_str()),
+            serde_json::to_string(entry)?
+        ],
+    )?;
+    Ok(())
+}
+
+fn upsert_manga_entry(
+    tx: &Transaction,
+    user_name: &str,
+    entry: &MangaListNode,
+) -> Result<(), SqliteStoreError> {
+    let id = match entry.entry_id() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+    let status = entry.list_status.as_ref();
+    tx.execute(
+        "INSERT INTO manga_list_entries (user_name, manga_id, title, status, score, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (user_name, manga_id) DO UPDATE SET title = excluded.title,
+            status = excluded.status, score = excluded.score,
+            updated_at = excluded.updated_at, data = excluded.data",
+        params![
+            user_name,
+            id,
+            entry.node.title,
+            status.and_then(|s| enum_to_string(&s.status)),
+            status.map(|s| s.score),
+            status.map(|s| s.updated_at.as_str()),
+            serde_json::to_string(entry)?
+        ],
+    )?;
+    Ok(())
+}
+
+// Store enums the same way MAL returns them, e.g. "plan_to_watch"
+fn enum_to_string<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::fixtures::{anime_details, anime_entry};
+    use crate::well_known::{COWBOY_BEBOP_ANIME_ID, ONE_PIECE_ANIME_ID};
+
+    fn entry(id: u32, status: &str, score: u8) -> AnimeListNode {
+        anime_entry(id).status(status).score(score).build()
+    }
+
+    #[test]
+    fn test_sync_anime_list() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+
+        let list = vec![entry(1, "watching", 0), entry(2, "completed", 8)];
+        let diff = store.sync_anime_list("user", &list).unwrap();
+        assert_eq!(diff.added.len(), 2);
+
+        let list = vec![entry(1, "completed", 9), entry(3, "plan_to_watch", 0)];
+        let diff = store.sync_anime_list("user", &list).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(diff.removed, vec![2]);
+
+        let stored = store.load_anime_list("user").unwrap();
+        assert_eq!(
+            stored.iter().map(|e| e.node.id).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert!(store.load_anime_list("other").unwrap().is_empty());
+
+        let status: String = store
+            .connection()
+            .query_row(
+                "SELECT status FROM anime_list_entries WHERE anime_id = 3",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(status, "plan_to_watch");
+    }
+
+    #[test]
+    fn test_anime_details() {
+        let store = SqliteStore::open_in_memory()
+            .unwrap()
+            .with_clock(ManualClock::new(1_700_000_000));
+        let details = anime_details(
+            ONE_PIECE_ANIME_ID,
+            serde_json::json!({ "title": "One Piece" }),
+        );
+
+        store.save_anime_details(&details).unwrap();
+        let stored = store
+            .load_anime_details(ONE_PIECE_ANIME_ID)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.details.shared_fields.title, "One Piece");
+        assert_eq!(stored.fetched_at, 1_700_000_000);
+        assert!(store
+            .load_anime_details(COWBOY_BEBOP_ANIME_ID)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_token_store() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let tokens = |refresh_token: &str| StoredTokens {
+            access_token: "access".to_string(),
+            refresh_token: refresh_token.to_string(),
+            expires_at: 1,
+        };
+
+        store.save_token_set("me", &tokens("old")).unwrap();
+        store.save_token_set("me", &tokens("new")).unwrap();
+        // The old set was already replaced, so this must not delete the new one
+        store.delete_token_set("me", "old").unwrap();
+        assert_eq!(store.load_token_set("me").unwrap(), Some(tokens("new")));
+
+        store.delete_token_set("me", "new").unwrap();
+        assert_eq!(store.load_token_set("me").unwrap(), None);
+    }
+
+    #[test]
+    fn test_stats_snapshots() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": ONE_PIECE_ANIME_ID,
+            "title": "One Piece",
+            "num_list_users": 100,
+            "statistics": {
+                "num_list_users": 100,
+                "status": {
+                    "watching": "10",
+                    "completed": "20",
+                    "on_hold": "30",
+                    "dropped": "40",
+                    "plan_to_watch": "0"
+                }
+            }
+        }))
+        .unwrap();
+        store
+            .save_snapshot(&StatsSnapshot::from_details(details, 100))
+            .unwrap();
+
+        let history = crate::stats::history(&store, 21).unwrap();
+        assert_eq!(history.num_list_users(), vec![(100, 100)]);
+        assert_eq!(history.watching(), vec![(100, 10)]);
+    }
+
+    #[test]
+    fn test_schema_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            SqliteStore::from_connection(conn),
+            Err(SqliteStoreError::UnsupportedSchemaVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+//! Module for tracking the statistics of an anime over time
+//!
+//! Each call to [track] fetches the current statistics of an anime and saves them
+//! as a [StatsSnapshot] in a [StatsStore]. The stored snapshots can then be loaded
+//! as a [StatsHistory] for charting the popularity of a show.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let api_client = AnimeApiClient::from(&client_id);
+//! let store = MemoryStatsStore::default();
+//!
+//! // Run this periodically, e.g. once a day
+//! mal_api::stats::track(&api_client, 52991, &store).await.unwrap();
+//!
+//! let history = mal_api::stats::history(&store, 52991).unwrap();
+//! for (fetched_at, members) in history.num_list_users() {
+//!     println!("{}: {}", fetched_at, members);
+//! }
+//! ```
+
+use std::{collections::HashMap, convert::Infallible, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::anime::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeDetail, AnimeDetailFields, GetAnimeDetails},
+    responses::{AnimeDetails, Statistics},
+};
+use crate::clock::{Clock, ClockError, SystemClock};
+use crate::codes::ErrorCode;
+
+/// The statistics of an anime at a point in time
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatsSnapshot {
+    pub anime_id: u32,
+
+    /// Number of seconds since the Unix Epoch when the statistics were fetched
+    pub fetched_at: u64,
+
+    pub mean: Option<f32>,
+    pub rank: Option<u32>,
+    pub popularity: Option<u32>,
+    pub num_list_users: Option<u32>,
+    pub num_scoring_users: Option<u32>,
+    pub statistics: Option<Statistics>,
+}
+
+impl StatsSnapshot {
+    /// Create a snapshot from anime details fetched at `fetched_at`
+    pub fn from_details(details: AnimeDetails, fetched_at: u64) -> Self {
+        let fields = details.shared_fields;
+        Self {
+            anime_id: fields.id,
+            fetched_at,
+            mean: fields.mean,
+            rank: fields.rank,
+            popularity: fields.popularity,
+            num_list_users: fields.num_list_users,
+            num_scoring_users: fields.num_scoring_users,
+            statistics: details.statistics,
+        }
+    }
+}
+
+/// Storage for [StatsSnapshot]s
+pub trait StatsStore {
+    type Error: std::error::Error;
+
+    /// Save a snapshot
+    fn save_snapshot(&self, snapshot: &StatsSnapshot) -> Result<(), Self::Error>;
+
+    /// Load all of the snapshots of an anime, oldest first
+    fn load_snapshots(&self, anime_id: u32) -> Result<Vec<StatsSnapshot>, Self::Error>;
+}
+
+/// In-memory [StatsStore]
+#[derive(Debug, Default)]
+pub struct MemoryStatsStore {
+    snapshots: Mutex<HashMap<u32, Vec<StatsSnapshot>>>,
+}
+
+impl StatsStore for MemoryStatsStore {
+    type Error = Infallible;
+
+    fn save_snapshot(&self, snapshot: &StatsSnapshot) -> Result<(), Self::Error> {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        let history = snapshots.entry(snapshot.anime_id).or_default();
+        let index = history.partition_point(|s| s.fetched_at <= snapshot.fetched_at);
+        history.insert(index, snapshot.clone());
+        Ok(())
+    }
+
+    fn load_snapshots(&self, anime_id: u32) -> Result<Vec<StatsSnapshot>, Self::Error> {
+        let snapshots = self.snapshots.lock().unwrap();
+        Ok(snapshots.get(&anime_id).cloned().unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TrackError<E: std::error::Error> {
+    #[error("failed to fetch statistics: {0}")]
+    Api(#[from] AnimeApiError),
+
+    #[error("failed to store statistics: {0}")]
+    Store(E),
+
+    #[error("failed to read the time: {0}")]
+    Clock(#[from] ClockError),
+}
+
+impl<E: std::error::Error> TrackError<E> {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TrackError::Api(err) => err.code(),
+            TrackError::Store(_) => ErrorCode::Store,
+            TrackError::Clock(err) => err.code(),
+        }
+    }
+}
+
+/// The fields requested by [track]
+pub fn tracked_fields() -> AnimeDetailFields {
+    AnimeDetailFields::new(vec![
+        AnimeDetail::id,
+        AnimeDetail::mean,
+        AnimeDetail::rank,
+        AnimeDetail::popularity,
+        AnimeDetail::num_list_users,
+        AnimeDetail::num_scoring_users,
+        AnimeDetail::statistics,
+    ])
+}
+
+/// Fetch the current statistics of an anime and save them in the store
+pub async fn track<C, S>(
+    api: &C,
+    anime_id: u32,
+    store: &S,
+) -> Result<StatsSnapshot, TrackError<S::Error>>
+where
+    C: AnimeApi + Sync,
+    S: StatsStore,
+{
+    let query = GetAnimeDetails::new(anime_id, Some(&tracked_fields()))?;
+    let details = api.get_anime_details(&query).await?;
+
+    let snapshot = StatsSnapshot::from_details(details, SystemClock.now()?);
+    store.save_snapshot(&snapshot).map_err(TrackError::Store)?;
+    Ok(snapshot)
+}
+
+/// Load the stored statistics history of an anime
+pub fn history<S: StatsStore>(store: &S, anime_id: u32) -> Result<StatsHistory, S::Error> {
+    Ok(StatsHistory {
+        snapshots: store.load_snapshots(anime_id)?,
+    })
+}
+
+/// The stored snapshots of an anime, oldest first
+#[derive(Debug)]
+pub struct StatsHistory {
+    pub snapshots: Vec<StatsSnapshot>,
+}
+
+impl StatsHistory {
+    /// Time series of the mean score
+    pub fn mean(&self) -> Vec<(u64, f32)> {
+        self.series(|s| s.mean)
+    }
+
+    /// Time series of the rank
+    pub fn rank(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.rank)
+    }
+
+    /// Time series of the popularity ranking
+    pub fn popularity(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.popularity)
+    }
+
+    /// Time series of the number of users with the anime in their list
+    pub fn num_list_users(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.num_list_users)
+    }
+
+    /// Time series of the number of users that scored the anime
+    pub fn num_scoring_users(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.num_scoring_users)
+    }
+
+    /// Time series of the number of users watching the anime
+    pub fn watching(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.statistics.as_ref().map(|st| st.status.watching))
+    }
+
+    /// Time series of the number of users that completed the anime
+    pub fn completed(&self) -> Vec<(u64, u32)> {
+        self.series(|s| s.statistics.as_ref().map(|st| st.status.completed))
+    }
+
+    /// Change in the number of list users between the first and last snapshot
+    pub fn num_list_users_growth(&self) -> Option<i64> {
+        let series = self.num_list_users();
+        let (_, first) = series.first()?;
+        let (_, last) = series.last()?;
+        Some(*last as i64 - *first as i64)
+    }
+
+    fn series<T, F>(&self, value: F) -> Vec<(u64, T)>
+    where
+        F: Fn(&StatsSnapshot) -> Option<T>,
+    {
+        self.snapshots
+            .iter()
+            .filter_map(|s| Some((s.fetched_at, value(s)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::FRIEREN_ANIME_ID;
+
+    fn snapshot(fetched_at: u64, num_list_users: u32, watching: &str) -> StatsSnapshot {
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": FRIEREN_ANIME_ID,
+            "title": "Sousou no Frieren",
+            "mean": 9.0,
+            "num_list_users": num_list_users,
+            "statistics": {
+                "num_list_users": num_list_users,
+                "status": {
+                    "watching": watching,
+                    "completed": "0",
+                    "on_hold": "0",
+                    "dropped": "0",
+                    "plan_to_watch": "0"
+                }
+            }
+        }))
+        .unwrap();
+        StatsSnapshot::from_details(details, fetched_at)
+    }
+
+    #[test]
+    fn test_history() {
+        let store = MemoryStatsStore::default();
+        store.save_snapshot(&snapshot(200, 1500, "900")).unwrap();
+        store.save_snapshot(&snapshot(100, 1000, "600")).unwrap();
+
+        let history = history(&store, FRIEREN_ANIME_ID).unwrap();
+        assert_eq!(history.num_list_users(), vec![(100, 1000), (200, 1500)]);
+        assert_eq!(history.watching(), vec![(100, 600), (200, 900)]);
+        assert_eq!(history.mean(), vec![(100, 9.0), (200, 9.0)]);
+        assert!(history.rank().is_empty());
+        assert_eq!(history.num_list_users_growth(), Some(500));
+
+        assert!(super::history(&store, 1).unwrap().snapshots.is_empty());
+    }
+}
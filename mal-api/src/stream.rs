@@ -0,0 +1,235 @@
+//! Module for reading large list pages one entry at a time
+//!
+//! Enable the `stream-parse` feature to use this module.
+//!
+//! A user list page with `limit=1000` and all fields can be several megabytes. Instead of
+//! buffering the whole body, a [ListStream] decodes each entry of the `data` array as
+//! soon as it has been received and keeps only the entry currently being read in memory.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetUserAnimeList::builder("@me").limit(1000).build().unwrap();
+//! let mut stream = api_client.get_user_anime_list_stream(&query).await.unwrap();
+//!
+//! while let Some(entry) = stream.next_entry().await {
+//!     let entry = entry.unwrap();
+//!     println!("{}", entry.node.title);
+//! }
+//! println!("Next page: {:?}", stream.paging().and_then(|p| p.next.as_ref()));
+//! ```
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use thiserror::Error;
+
+use crate::{codes::ErrorCode, common::Paging};
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("failed to read response: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("failed to parse entry: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("response is not a list page")]
+    Malformed,
+}
+
+impl StreamError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StreamError::Request(_) => ErrorCode::Request,
+            StreamError::Parse(_) | StreamError::Malformed => ErrorCode::Parse,
+        }
+    }
+}
+
+/// Entries of a list page, decoded as the response body is received
+#[derive(Debug)]
+pub struct ListStream<T> {
+    response: Option<reqwest::Response>,
+    parser: EntryParser,
+    paging: Option<Paging>,
+    entry: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> ListStream<T> {
+    #[cfg(lists)]
+    pub(crate) fn new(response: reqwest::Response) -> Self {
+        Self {
+            response: Some(response),
+            parser: EntryParser::default(),
+            paging: None,
+            entry: PhantomData,
+        }
+    }
+
+    /// Get the next entry of the page, or `None` once the whole page has been read
+    pub async fn next_entry(&mut self) -> Option<Result<T, StreamError>> {
+        loop {
+            if let Some(entry) = self.parser.entries.pop_front() {
+                return Some(serde_json::from_slice(&entry).map_err(StreamError::from));
+            }
+
+            let response = self.response.as_mut()?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Err(err) = self.parser.feed(&chunk) {
+                        self.response = None;
+                        return Some(Err(err));
+                    }
+                }
+                Ok(None) => {
+                    self.response = None;
+                    match self.parser.finish() {
+                        Ok(paging) => self.paging = paging,
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+                Err(err) => {
+                    self.response = None;
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
+
+    /// Get the paging of the page
+    ///
+    /// MAL sends the paging after the entries, so this is only available
+    /// once [ListStream::next_entry] has returned `None`
+    pub fn paging(&self) -> Option<&Paging> {
+        self.paging.as_ref()
+    }
+}
+
+// Splits a list page into the raw bytes of each entry of its `data` array.
+// Everything outside of the array is kept so the paging can be parsed at the end.
+#[derive(Debug, Default)]
+struct EntryParser {
+    entries: VecDeque<Vec<u8>>,
+    entry: Vec<u8>,
+    rest: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    in_data: bool,
+    key: Vec<u8>,
+    last_key: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct PageRest {
+    paging: Option<Paging>,
+}
+
+impl EntryParser {
+    fn feed(&mut self, chunk: &[u8]) -> Result<(), StreamError> {
+        for &byte in chunk {
+            let depth = self.depth;
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if depth == 1 {
+                        self.last_key = std::mem::take(&mut self.key);
+                    }
+                } else if depth == 1 {
+                    self.key.push(byte);
+                }
+            } else {
+                match byte {
+                    b'"' => {
+                        self.in_string = true;
+                        self.key.clear();
+                    }
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => {
+                        self.depth = self.depth.checked_sub(1).ok_or(StreamError::Malformed)?
+                    }
+                    _ => {}
+                }
+            }
+
+            if !self.in_data {
+                self.rest.push(byte);
+                if depth == 1 && byte == b'[' && !self.in_string && self.last_key == b"data" {
+                    self.in_data = true;
+                }
+            } else if depth >= 3 || (depth == 2 && byte == b'{' && !self.in_string) {
+                self.entry.push(byte);
+                if self.depth == 2 {
+                    self.entries.push_back(std::mem::take(&mut self.entry));
+                }
+            } else if depth == 2 && byte == b']' && !self.in_string {
+                self.in_data = false;
+                self.rest.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Option<Paging>, StreamError> {
+        if self.depth != 0 || self.in_string {
+            return Err(StreamError::Malformed);
+        }
+        let rest: PageRest = serde_json::from_slice(&self.rest)?;
+        Ok(rest.paging)
+    }
+}
+
+#[cfg(all(test, feature = "anime"))]
+mod tests {
+    use super::*;
+    use crate::anime::responses::AnimeListNode;
+
+    const PAGE: &str = r#"{"data": [
+        {"node": {"id": 1, "title": "Brackets ] and } in \"titles\""}},
+        {"node": {"id": 2, "title": "Second", "genres": [{"id": 1, "name": "Action"}]}}
+    ], "paging": {"next": "https://api.myanimelist.net/v2/users/@me/animelist?offset=2"}}"#;
+
+    fn parse(chunk_size: usize) -> (Vec<AnimeListNode>, Option<Paging>) {
+        let mut parser = EntryParser::default();
+        for chunk in PAGE.as_bytes().chunks(chunk_size) {
+            parser.feed(chunk).unwrap();
+        }
+        let paging = parser.finish().unwrap();
+        let entries = parser
+            .entries
+            .iter()
+            .map(|e| serde_json::from_slice(e).unwrap())
+            .collect();
+        (entries, paging)
+    }
+
+    #[test]
+    fn test_entry_parser() {
+        for chunk_size in [1, 7, PAGE.len()] {
+            let (entries, paging) = parse(chunk_size);
+            assert_eq!(
+                entries.iter().map(|e| e.node.id).collect::<Vec<_>>(),
+                vec![1, 2]
+            );
+            assert_eq!(entries[0].node.title, "Brackets ] and } in \"titles\"");
+            assert!(paging.unwrap().next.unwrap().ends_with("offset=2"));
+        }
+    }
+
+    #[test]
+    fn test_entry_parser_malformed() {
+        let mut parser = EntryParser::default();
+        parser.feed(br#"{"data": [{"node": {"id": 1"#).unwrap();
+        assert!(parser.finish().is_err());
+
+        let mut parser = EntryParser::default();
+        assert!(parser.feed(b"}").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Module for computing the genre taste of a user from their anime list
+//!
+//! A [TasteProfile] gives each genre a weight from how a user scores the anime
+//! with it compared to their mean score. Genres of anime they score above their
+//! mean get positive weights, the others negative ones. [similarity] then rates
+//! how well an anime fits the profile, and [TasteProfile::explain] tells which
+//! genres made it fit, e.g. for "suggested because you like..." messages.
+//!
+//! Request the `genres` field of the list entries, and only pass scored entries
+//! you want counted, e.g. without the plan to watch ones.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let query = GetUserAnimeList::builder("@me")
+//!     .fields(&anime_common_fields!(AnimeField::genres))
+//!     .limit(1000)
+//!     .build()
+//!     .unwrap();
+//! let list = api_client.get_full_user_anime_list(&query).await.unwrap();
+//! let profile = TasteProfile::from_list(&list);
+//!
+//! for suggestion in suggestions.data {
+//!     let score = mal_api::taste::similarity(&suggestion.node, &profile);
+//!     let reasons: Vec<_> = profile.explain(&suggestion.node).into_iter().take(2).collect();
+//!     println!("{} ({:.2}), because of {:?}", suggestion.node.title, score, reasons);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    anime::responses::{AnimeFields, AnimeListNode},
+    common::Genre,
+};
+
+// Genres with few scored anime are pulled towards 0, as if this many more anime
+// of the genre were scored at the mean
+const PRIOR_WEIGHT: f64 = 3.0;
+
+/// The weight of a genre in a [TasteProfile]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenreWeight {
+    pub genre_id: u32,
+    pub name: String,
+
+    /// Mean difference between the scores of the anime with the genre and the
+    /// user's mean score, shrunk towards 0 for genres with few anime
+    pub weight: f64,
+
+    /// Number of scored anime with the genre
+    pub count: u32,
+}
+
+/// The genre weights of a user's scored anime
+#[derive(Debug, Clone, Default)]
+pub struct TasteProfile {
+    genres: HashMap<u32, GenreWeight>,
+
+    /// Mean score of the scored entries
+    pub mean_score: Option<f64>,
+}
+
+impl TasteProfile {
+    /// Build the profile of a user from their list entries
+    ///
+    /// Unscored entries and entries without `genres` are skipped
+    pub fn from_list(list: &[AnimeListNode]) -> Self {
+        let scored: Vec<(&AnimeListNode, f64)> = list
+            .iter()
+            .filter_map(|e| {
+                let score = e.list_status.as_ref()?.score;
+                (score > 0 && e.node.genres.is_some()).then_some((e, score as f64))
+            })
+            .collect();
+        if scored.is_empty() {
+            return Self::default();
+        }
+        let mean = scored.iter().map(|(_, score)| score).sum::<f64>() / scored.len() as f64;
+
+        let mut sums: HashMap<u32, (String, f64, u32)> = HashMap::new();
+        for (entry, score) in &scored {
+            for genre in genres(&entry.node) {
+                let sum = sums
+                    .entry(genre.id)
+                    .or_insert_with(|| (genre.name.clone(), 0.0, 0));
+                sum.1 += score - mean;
+                sum.2 += 1;
+            }
+        }
+
+        let genres = sums
+            .into_iter()
+            .map(|(genre_id, (name, deviation, count))| {
+                let weight = deviation / (count as f64 + PRIOR_WEIGHT);
+                let weight = GenreWeight {
+                    genre_id,
+                    name,
+                    weight,
+                    count,
+                };
+                (genre_id, weight)
+            })
+            .collect();
+        Self {
+            genres,
+            mean_score: Some(mean),
+        }
+    }
+
+    /// The weight of a genre, `0` for genres the user hasn't scored
+    pub fn weight(&self, genre_id: u32) -> f64 {
+        self.genres.get(&genre_id).map(|g| g.weight).unwrap_or(0.0)
+    }
+
+    /// The weights of all of the genres, highest first
+    pub fn weights(&self) -> Vec<&GenreWeight> {
+        let mut weights: Vec<_> = self.genres.values().collect();
+        weights.sort_by(|a, b| {
+            b.weight
+                .total_cmp(&a.weight)
+                .then(a.genre_id.cmp(&b.genre_id))
+        });
+        weights
+    }
+
+    /// The genres of the anime the user likes, highest weight first
+    ///
+    /// Only genres with a positive weight are returned, e.g. to explain why an
+    /// anime was suggested
+    pub fn explain(&self, anime: &AnimeFields) -> Vec<&GenreWeight> {
+        let mut liked: Vec<_> = genres(anime)
+            .filter_map(|genre| self.genres.get(&genre.id))
+            .filter(|g| g.weight > 0.0)
+            .collect();
+        liked.sort_by(|a, b| {
+            b.weight
+                .total_cmp(&a.weight)
+                .then(a.genre_id.cmp(&b.genre_id))
+        });
+        liked
+    }
+
+    fn norm(&self) -> f64 {
+        self.genres
+            .values()
+            .map(|g| g.weight * g.weight)
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// How well an anime fits a profile, from `-1` to `1`
+///
+/// The cosine similarity between the genres of the anime and the profile's genre
+/// weights. Anime without `genres`, and empty profiles, get `0`.
+pub fn similarity(anime: &AnimeFields, profile: &TasteProfile) -> f64 {
+    let ids: Vec<u32> = genres(anime).map(|g| g.id).collect();
+    let norm = profile.norm();
+    if ids.is_empty() || norm == 0.0 {
+        return 0.0;
+    }
+    let dot: f64 = ids.iter().map(|id| profile.weight(*id)).sum();
+    dot / (norm * (ids.len() as f64).sqrt())
+}
+
+/// How often two genres are on the same anime
+#[derive(Debug, Clone, Default)]
+pub struct GenreCooccurrence {
+    names: HashMap<u32, String>,
+    counts: HashMap<(u32, u32), u32>,
+}
+
+impl GenreCooccurrence {
+    /// Count the pairs of genres of the anime
+    pub fn from_anime<'a>(anime: impl IntoIterator<Item = &'a AnimeFields>) -> Self {
+        let mut cooccurrence = Self::default();
+        for anime in anime {
+            let mut ids: Vec<u32> = genres(anime).map(|g| g.id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            for genre in genres(anime) {
+                cooccurrence
+                    .names
+                    .entry(genre.id)
+                    .or_insert_with(|| genre.name.clone());
+            }
+            for (i, a) in ids.iter().enumerate() {
+                for b in &ids[i + 1..] {
+                    *cooccurrence.counts.entry((*a, *b)).or_default() += 1;
+                }
+            }
+        }
+        cooccurrence
+    }
+
+    /// Number of anime with both genres
+    pub fn count(&self, genre_a: u32, genre_b: u32) -> u32 {
+        let key = (genre_a.min(genre_b), genre_a.max(genre_b));
+        self.counts.get(&key).copied().unwrap_or(0)
+    }
+
+    /// The genres most often on the same anime as `genre_id`, with their counts
+    pub fn related(&self, genre_id: u32) -> Vec<(&str, u32)> {
+        let mut related: Vec<(u32, u32)> = self
+            .counts
+            .iter()
+            .filter_map(|((a, b), count)| match genre_id {
+                id if id == *a => Some((*b, *count)),
+                id if id == *b => Some((*a, *count)),
+                _ => None,
+            })
+            .collect();
+        related.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        related
+            .into_iter()
+            .filter_map(|(id, count)| Some((self.names.get(&id)?.as_str(), count)))
+            .collect()
+    }
+}
+
+fn genres(anime: &AnimeFields) -> impl Iterator<Item = &Genre> {
+    anime.genres.iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::anime_entry;
+
+    fn entry(id: u32, score: u8, genres: &[(u32, &str)]) -> AnimeListNode {
+        let genres: Vec<_> = genres
+            .iter()
+            .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+            .collect();
+        anime_entry(id)
+            .score(score)
+            .node(serde_json::json!({ "genres": genres }))
+            .build()
+    }
+
+    const ACTION: (u32, &str) = (1, "Action");
+    const COMEDY: (u32, &str) = (4, "Comedy");
+    const DRAMA: (u32, &str) = (8, "Drama");
+
+    #[test]
+    fn test_taste_profile() {
+        let list = vec![
+            entry(1, 9, &[DRAMA]),
+            entry(2, 10, &[DRAMA, ACTION]),
+            entry(3, 5, &[COMEDY]),
+            entry(4, 4, &[COMEDY, ACTION]),
+            entry(5, 0, &[COMEDY]),
+        ];
+        let profile = TasteProfile::from_list(&list);
+        assert_eq!(profile.mean_score, Some(7.0));
+        assert!(profile.weight(DRAMA.0) > 0.0);
+        assert!(profile.weight(COMEDY.0) < 0.0);
+        assert_eq!(profile.weight(99), 0.0);
+        assert_eq!(profile.weights()[0].name, "Drama");
+
+        let drama = entry(6, 0, &[DRAMA]).node;
+        let comedy = entry(7, 0, &[COMEDY]).node;
+        assert!(similarity(&drama, &profile) > 0.0);
+        assert!(similarity(&comedy, &profile) < 0.0);
+        assert!(similarity(&drama, &TasteProfile::default()) == 0.0);
+
+        let mixed = entry(8, 0, &[COMEDY, DRAMA]).node;
+        let reasons: Vec<_> = profile.explain(&mixed).iter().map(|g| &g.name).collect();
+        assert_eq!(reasons, vec!["Drama"]);
+    }
+
+    #[test]
+    fn test_genre_cooccurrence() {
+        let list = [
+            entry(1, 9, &[DRAMA, ACTION]),
+            entry(2, 10, &[ACTION, DRAMA]),
+            entry(3, 5, &[ACTION, COMEDY]),
+        ];
+        let cooccurrence = GenreCooccurrence::from_anime(list.iter().map(|e| &e.node));
+        assert_eq!(cooccurrence.count(DRAMA.0, ACTION.0), 2);
+        assert_eq!(cooccurrence.count(ACTION.0, DRAMA.0), 2);
+        assert_eq!(cooccurrence.count(DRAMA.0, COMEDY.0), 0);
+        assert_eq!(
+            cooccurrence.related(ACTION.0),
+            vec![("Drama", 2), ("Comedy", 1)]
+        );
+    }
+}
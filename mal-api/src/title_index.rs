@@ -0,0 +1,244 @@
+//! Module for searching the titles of fetched anime and manga offline
+//!
+//! A [TitleIndex] maps ids to their [normalized](normalize_title) titles, so an
+//! app can filter the entries it already has as the user types, without sending
+//! a request per keystroke. Build it from lists, seasonal anime, or any other
+//! response, and query it with [prefix](TitleIndex::prefix) or
+//! [fuzzy](TitleIndex::fuzzy) search.
+//!
+//! Request the `alternative_titles` field so that the English and Japanese
+//! titles are indexed too.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let list = api_client.get_full_user_anime_list(&query).await.unwrap();
+//! let index = TitleIndex::from_anime(list.iter().map(|e| &e.node));
+//!
+//! // The ids of the entries with a title or a word of one starting with "fri"
+//! let ids = index.prefix("fri");
+//! // The ids of the entries with a title close to the query, despite the typo
+//! let matches = index.fuzzy("sosou no frieren", 0.7);
+//! ```
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::titles::{normalize_title, title_similarity};
+
+#[cfg(feature = "anime")]
+use crate::anime::responses::AnimeFields;
+#[cfg(feature = "manga")]
+use crate::manga::responses::MangaFields;
+
+/// An in-memory index of the titles of anime or manga
+///
+/// Don't mix anime and manga in the same index, their ids overlap.
+#[derive(Debug, Clone, Default)]
+pub struct TitleIndex {
+    titles: HashMap<u32, Vec<String>>,
+
+    // Every normalized title, and every part of one starting at a word, so that
+    // prefix searches also match words in the middle of titles
+    keys: BTreeSet<(String, u32)>,
+}
+
+impl TitleIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index of the main and alternative titles of the anime
+    #[cfg(feature = "anime")]
+    pub fn from_anime<'a>(anime: impl IntoIterator<Item = &'a AnimeFields>) -> Self {
+        let mut index = Self::new();
+        for anime in anime {
+            index.insert_anime(anime);
+        }
+        index
+    }
+
+    /// Build an index of the main and alternative titles of the manga
+    ///
+    /// Manga without an `id` are skipped
+    #[cfg(feature = "manga")]
+    pub fn from_manga<'a>(manga: impl IntoIterator<Item = &'a MangaFields>) -> Self {
+        let mut index = Self::new();
+        for manga in manga {
+            index.insert_manga(manga);
+        }
+        index
+    }
+
+    /// Index the main and alternative titles of an anime
+    #[cfg(feature = "anime")]
+    pub fn insert_anime(&mut self, anime: &AnimeFields) {
+        let alternatives = anime.alternative_titles.iter().flat_map(|t| t.titles());
+        self.insert(
+            anime.id,
+            std::iter::once(anime.title.as_str()).chain(alternatives),
+        );
+    }
+
+    /// Index the main and alternative titles of a manga
+    #[cfg(feature = "manga")]
+    pub fn insert_manga(&mut self, manga: &MangaFields) {
+        let Some(id) = manga.id else {
+            return;
+        };
+        let alternatives = manga.alternative_titles.iter().flat_map(|t| t.titles());
+        self.insert(id, manga.title.as_deref().into_iter().chain(alternatives));
+    }
+
+    /// Index the titles of an entry, replacing the titles it had
+    ///
+    /// Titles that are empty once normalized are skipped
+    pub fn insert<'a>(&mut self, id: u32, titles: impl IntoIterator<Item = &'a str>) {
+        self.remove(id);
+        let mut normalized: Vec<String> = Vec::new();
+        for title in titles {
+            let title = normalize_title(title);
+            if !title.is_empty() && !normalized.contains(&title) {
+                normalized.push(title);
+            }
+        }
+        for title in &normalized {
+            for (start, _) in title.match_indices(' ').chain(std::iter::once((0, ""))) {
+                let key = title[start..].trim_start();
+                self.keys.insert((key.to_string(), id));
+            }
+        }
+        if !normalized.is_empty() {
+            self.titles.insert(id, normalized);
+        }
+    }
+
+    /// Remove an entry from the index
+    pub fn remove(&mut self, id: u32) {
+        if self.titles.remove(&id).is_some() {
+            self.keys.retain(|(_, key_id)| *key_id != id);
+        }
+    }
+
+    /// The normalized titles of an entry
+    pub fn titles(&self, id: u32) -> Option<&[String]> {
+        self.titles.get(&id).map(Vec::as_slice)
+    }
+
+    /// Number of indexed entries
+    pub fn len(&self) -> usize {
+        self.titles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.titles.is_empty()
+    }
+
+    /// The ids of the entries with a title, or a word of one, starting with `prefix`
+    ///
+    /// Entries whose title itself starts with the prefix come first, then those
+    /// matching a later word, each in alphabetical order of the matching title.
+    /// An empty prefix matches nothing.
+    pub fn prefix(&self, prefix: &str) -> Vec<u32> {
+        let prefix = normalize_title(prefix);
+        if prefix.is_empty() {
+            return vec![];
+        }
+
+        let (mut title_matches, mut word_matches) = (Vec::new(), Vec::new());
+        let start = (prefix.clone(), 0);
+        for (key, id) in self.keys.range(start..) {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let is_title = self.titles[id].iter().any(|title| title == key);
+            if is_title {
+                title_matches.push(*id);
+            } else {
+                word_matches.push(*id);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        title_matches
+            .into_iter()
+            .chain(word_matches)
+            .filter(|id| seen.insert(*id))
+            .collect()
+    }
+
+    /// The entries with a title similar to `query`, most similar first
+    ///
+    /// Each entry is scored by its closest title with [title_similarity], and
+    /// only those scoring at least `min_similarity`, from 0 to 1, are returned.
+    pub fn fuzzy(&self, query: &str, min_similarity: f64) -> Vec<(u32, f64)> {
+        let query = normalize_title(query);
+        let mut matches: Vec<(u32, f64)> = self
+            .titles
+            .iter()
+            .filter_map(|(id, titles)| {
+                let score = titles
+                    .iter()
+                    .map(|title| title_similarity(&query, title))
+                    .fold(0.0, f64::max);
+                (score > 0.0 && score >= min_similarity).then_some((*id, score))
+            })
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::{
+        COWBOY_BEBOP_ANIME_ID, FRIEREN_ANIME_ID, FULLMETAL_ALCHEMIST_BROTHERHOOD_ANIME_ID,
+    };
+
+    fn index() -> TitleIndex {
+        let mut index = TitleIndex::new();
+        index.insert(
+            FRIEREN_ANIME_ID,
+            ["Sousou no Frieren", "Frieren: Beyond Journey's End"],
+        );
+        index.insert(
+            FULLMETAL_ALCHEMIST_BROTHERHOOD_ANIME_ID,
+            ["Fullmetal Alchemist: Brotherhood"],
+        );
+        index.insert(COWBOY_BEBOP_ANIME_ID, ["Cowboy Bebop", ""]);
+        index
+    }
+
+    #[test]
+    fn test_prefix() {
+        let index = index();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.prefix("fr"), vec![FRIEREN_ANIME_ID]);
+        assert_eq!(
+            index.prefix("F"),
+            vec![FRIEREN_ANIME_ID, FULLMETAL_ALCHEMIST_BROTHERHOOD_ANIME_ID]
+        );
+        assert_eq!(index.prefix("journey"), vec![FRIEREN_ANIME_ID]);
+        assert_eq!(index.prefix("bebop"), vec![COWBOY_BEBOP_ANIME_ID]);
+        assert!(index.prefix("  ").is_empty());
+        assert!(index.prefix("zz").is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_and_remove() {
+        let mut index = index();
+        let matches = index.fuzzy("Sosou no Frieren", 0.7);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, FRIEREN_ANIME_ID);
+        assert!(index.fuzzy("", 0.0).is_empty());
+
+        index.insert(COWBOY_BEBOP_ANIME_ID, ["Cowboy Bebop: The Movie"]);
+        assert_eq!(
+            index.titles(COWBOY_BEBOP_ANIME_ID),
+            Some(&["cowboy bebop the movie".to_string()][..])
+        );
+        index.remove(FRIEREN_ANIME_ID);
+        assert!(index.prefix("frieren").is_empty());
+        assert!(index.titles(FRIEREN_ANIME_ID).is_none());
+    }
+}
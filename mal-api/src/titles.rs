@@ -0,0 +1,182 @@
+//! Module for choosing which language titles are displayed in
+//!
+//! MAL returns the romanized title of an anime or manga as its `title`, with the
+//! English and Japanese titles in `alternative_titles`. Set the [TitlePreference]
+//! once, e.g. from your app's language setting, and `display_title` on the anime
+//! and manga responses will use it everywhere.
+//!
+//! Request the `alternative_titles` field for the English and Japanese titles to
+//! be available. Without them, the romanized title is used.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! mal_api::titles::set_title_preference(TitlePreference::English);
+//!
+//! let details = api_client.get_anime_details(&query).await.unwrap();
+//! println!("{}", details.display_title());
+//! ```
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Language to display titles in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitlePreference {
+    /// The main MAL title, e.g. `Sousou no Frieren`
+    #[default]
+    Romaji,
+
+    /// e.g. `Frieren: Beyond Journey's End`
+    English,
+
+    /// e.g. `葬送のフリーレン`
+    Japanese,
+}
+
+static TITLE_PREFERENCE: AtomicU8 = AtomicU8::new(0);
+
+/// Set the language `display_title` uses, for the whole application
+pub fn set_title_preference(preference: TitlePreference) {
+    let value = match preference {
+        TitlePreference::Romaji => 0,
+        TitlePreference::English => 1,
+        TitlePreference::Japanese => 2,
+    };
+    TITLE_PREFERENCE.store(value, Ordering::Relaxed);
+}
+
+/// Get the language `display_title` uses
+pub fn title_preference() -> TitlePreference {
+    match TITLE_PREFERENCE.load(Ordering::Relaxed) {
+        1 => TitlePreference::English,
+        2 => TitlePreference::Japanese,
+        _ => TitlePreference::Romaji,
+    }
+}
+
+// Falls back to the main title when the preferred one is missing or empty
+#[cfg(lists)]
+pub(crate) fn select_title<'a>(
+    title: &'a str,
+    alternative_titles: Option<&'a crate::common::AlternativeTitles>,
+    preference: TitlePreference,
+) -> &'a str {
+    let alternative = alternative_titles.and_then(|titles| match preference {
+        TitlePreference::Romaji => None,
+        TitlePreference::English => titles.en.as_deref(),
+        TitlePreference::Japanese => titles.ja.as_deref(),
+    });
+    match alternative {
+        Some(alternative) if !alternative.is_empty() => alternative,
+        _ => title,
+    }
+}
+
+/// Normalize a title for comparison
+///
+/// Lowercases the title, drops punctuation, and collapses whitespace, so that e.g.
+/// `Frieren: Beyond Journey's End` and `frieren beyond journeys end` are equal
+pub fn normalize_title(title: &str) -> String {
+    let mut normalized = String::with_capacity(title.len());
+    for word in title.split_whitespace() {
+        let word: String = word
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if word.is_empty() {
+            continue;
+        }
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(&word);
+    }
+    normalized
+}
+
+// Empty titles never match
+pub(crate) fn same_title(a: &str, b: &str) -> bool {
+    let a = normalize_title(a);
+    !a.is_empty() && a == normalize_title(b)
+}
+
+/// How similar two titles are, from 0 for nothing in common to 1 for the same title
+///
+/// Compares the pairs of adjacent characters of the [normalized](normalize_title)
+/// titles (the Sørensen–Dice coefficient), so it tolerates typos, missing words,
+/// and different word orders, e.g. when matching user input against search results
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_title(a);
+    let b = normalize_title(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let mut a_pairs = bigrams(&a);
+    let b_pairs = bigrams(&b);
+    let total = a_pairs.len() + b_pairs.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut shared = 0;
+    for pair in b_pairs {
+        if let Some(i) = a_pairs.iter().position(|p| *p == pair) {
+            a_pairs.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+fn bigrams(title: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = title.chars().collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("Frieren", "frieren!"), 1.0);
+        assert_eq!(title_similarity("", "Frieren"), 0.0);
+        assert!(title_similarity("Sousou no Frieren", "Sosou no Frieren") > 0.8);
+        assert!(
+            title_similarity("Frieren", "Sousou no Frieren")
+                > title_similarity("Frieren", "Fire Force")
+        );
+    }
+
+    #[cfg(feature = "anime")]
+    #[test]
+    fn test_title_in() {
+        use crate::anime::responses::AnimeFields;
+        use crate::well_known::FRIEREN_ANIME_ID;
+        let anime: AnimeFields = serde_json::from_value(serde_json::json!({
+            "id": FRIEREN_ANIME_ID,
+            "title": "Sousou no Frieren",
+            "alternative_titles": {
+                "synonyms": [],
+                "en": "Frieren: Beyond Journey's End",
+                "ja": ""
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            anime.title_in(TitlePreference::English),
+            "Frieren: Beyond Journey's End"
+        );
+        assert_eq!(anime.title_in(TitlePreference::Romaji), "Sousou no Frieren");
+        // Empty titles fall back to the main title
+        assert_eq!(
+            anime.title_in(TitlePreference::Japanese),
+            "Sousou no Frieren"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Module for building the canonical myanimelist.net page URLs
+//!
+//! These are the human-facing pages, not the API endpoints.
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+const MAL_WEB_URL: &str = "https://myanimelist.net";
+
+// Characters that are escaped in a path segment, all but the unreserved ones
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// URL of a user's profile page
+pub fn profile_url(user_name: &str) -> String {
+    format!("{}/profile/{}", MAL_WEB_URL, path_segment(user_name))
+}
+
+/// URL of a user's anime list page
+pub fn anime_list_url(user_name: &str) -> String {
+    format!("{}/animelist/{}", MAL_WEB_URL, path_segment(user_name))
+}
+
+/// URL of a user's manga list page
+pub fn manga_list_url(user_name: &str) -> String {
+    format!("{}/mangalist/{}", MAL_WEB_URL, path_segment(user_name))
+}
+
+/// URL of an anime's page
+pub fn anime_url(anime_id: u32) -> String {
+    format!("{}/anime/{}", MAL_WEB_URL, anime_id)
+}
+
+/// URL of a manga's page
+pub fn manga_url(manga_id: u32) -> String {
+    format!("{}/manga/{}", MAL_WEB_URL, manga_id)
+}
+
+/// URL of a forum topic's page
+pub fn forum_topic_url(topic_id: u32) -> String {
+    format!("{}/forum/?topicid={}", MAL_WEB_URL, topic_id)
+}
+
+/// URL of a forum board's page
+pub fn forum_board_url(board_id: u32) -> String {
+    format!("{}/forum/?board={}", MAL_WEB_URL, board_id)
+}
+
+/// URL of a forum subboard's page
+pub fn forum_subboard_url(subboard_id: u32) -> String {
+    format!("{}/forum/?subboard={}", MAL_WEB_URL, subboard_id)
+}
+
+fn path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            profile_url("Xinil"),
+            "https://myanimelist.net/profile/Xinil"
+        );
+        assert_eq!(
+            anime_list_url("Xinil"),
+            "https://myanimelist.net/animelist/Xinil"
+        );
+        assert_eq!(
+            manga_list_url("Xinil"),
+            "https://myanimelist.net/mangalist/Xinil"
+        );
+        assert_eq!(
+            profile_url("a b/../c?d"),
+            "https://myanimelist.net/profile/a%20b%2F..%2Fc%3Fd"
+        );
+        assert_eq!(anime_url(21), "https://myanimelist.net/anime/21");
+        assert_eq!(manga_url(13), "https://myanimelist.net/manga/13");
+        assert_eq!(
+            forum_topic_url(1),
+            "https://myanimelist.net/forum/?topicid=1"
+        );
+    }
+}
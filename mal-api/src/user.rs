@@ -1,7 +1,6 @@
 //! Module for interacting with the `user` endpoint
 
 use self::requests::{UserField, UserFields};
-use strum::IntoEnumIterator;
 
 /// User API client
 pub mod api;
@@ -15,11 +14,25 @@ pub mod requests;
 /// User API responses
 pub mod responses;
 
+/// All of the possible [UserField] fields
+pub const USER_FIELDS: &[UserField] = &[
+    UserField::id,
+    UserField::name,
+    UserField::picture,
+    UserField::gender,
+    UserField::birthday,
+    UserField::location,
+    UserField::joined_at,
+    UserField::anime_statistics,
+    UserField::time_zone,
+    UserField::is_supporter,
+];
+
+/// All of the possible [UserField] fields, formatted for the `fields` query parameter
+pub const USER_FIELDS_STR: &str =
+    "id,name,picture,gender,birthday,location,joined_at,anime_statistics,time_zone,is_supporter";
+
 /// Return all of the possible User Fields
 pub fn all_fields() -> UserFields {
-    let mut vec = Vec::with_capacity(UserField::iter().len());
-    for variant in UserField::iter() {
-        vec.push(variant);
-    }
-    UserFields(vec)
+    UserFields::with_formatted(USER_FIELDS.to_vec(), USER_FIELDS_STR)
 }
@@ -1,12 +1,23 @@
-use oauth2::AccessToken;
-use reqwest;
-use serde::Serialize;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use reqwest::{self, RequestBuilder};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    oauth::{Authenticated, OauthClient},
+    codes::ErrorCode,
+    common::{parse_response, unexpected_response},
+    http::{self, SendThrough, Transport},
+    oauth::{
+        provider::{AuthProvider, Oauth},
+        MalAccessToken,
+    },
     USER_URL,
 };
 
+#[cfg(feature = "oauth")]
+use crate::oauth::{Authenticated, OauthClient};
+
 use super::{error::UserApiError, requests::GetUserInformation, responses::User};
 
 /// The UserApiClient provides functions for interacting with the various
@@ -60,70 +71,164 @@ use super::{error::UserApiError, requests::GetUserInformation, responses::User};
 
 pub struct UserApiClient {
     client: reqwest::Client,
-    access_token: String,
+    transport: Transport,
+    auth: Arc<dyn AuthProvider<State = Oauth>>,
 }
 
-impl From<&AccessToken> for UserApiClient {
-    fn from(value: &AccessToken) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            access_token: value.secret().clone(),
-        }
+impl From<&MalAccessToken> for UserApiClient {
+    fn from(value: &MalAccessToken) -> Self {
+        Self::from_auth_provider(value.clone())
     }
 }
 
+#[cfg(feature = "oauth")]
 impl From<&OauthClient<Authenticated>> for UserApiClient {
     fn from(value: &OauthClient<Authenticated>) -> Self {
-        UserApiClient {
+        Self::from_auth_provider(value.access_token())
+    }
+}
+
+impl UserApiClient {
+    /// Create a UserApiClient that gets its access token from the provider before
+    /// every request
+    ///
+    /// See the [provider](crate::oauth::provider) module
+    pub fn from_auth_provider<P: AuthProvider<State = Oauth> + 'static>(provider: P) -> Self {
+        Self {
             client: reqwest::Client::new(),
-            access_token: value.get_access_token().secret().clone(),
+            transport: Transport::default(),
+            auth: Arc::new(provider),
         }
     }
+
+    /// Send requests through the given HTTP client instead of the default one
+    ///
+    /// Use [HttpClientBuilder](crate::http::HttpClientBuilder) to configure it
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send requests through the given tower service instead of the HTTP client
+    ///
+    /// Wrap a [MalService](crate::service::MalService) in layers to apply them
+    /// to every request of this client. The HTTP client of the service sends
+    /// the requests then, so configure that one instead of
+    /// [with_http_client](Self::with_http_client). See the [service](crate::service) module
+    #[cfg(feature = "tower")]
+    pub fn with_service<S>(mut self, service: S) -> Self
+    where
+        S: tower_service::Service<
+                crate::service::MalRequest,
+                Response = crate::service::MalResponse,
+            > + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Error: Into<crate::service::BoxError>,
+        S::Future: Send,
+    {
+        self.transport = Transport::with_service(crate::service::SharedService::new(service));
+        self
+    }
+
+    /// Open a connection to the MAL API ahead of the first request
+    ///
+    /// Resolves DNS and completes the TLS handshake so that the first request your
+    /// users wait on doesn't pay for them. The connection stays in the client's pool.
+    pub async fn warm_up(&self) -> Result<(), UserApiError> {
+        http::warm_up(&self.client, &self.transport)
+            .await
+            .map_err(|err| {
+                UserApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to warm up connection: {}", err),
+                )
+            })
+    }
+
+    // Adds the credentials of the auth provider to the request
+    async fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, UserApiError> {
+        let header = self.auth.auth_header().await?;
+        Ok(header.apply(request))
+    }
 }
 
 impl UserApiClient {
-    async fn get<T>(&self, query: &T) -> Result<String, UserApiError>
+    async fn get<T>(&self, user_name: &str, query: &T) -> Result<Bytes, UserApiError>
     where
         T: Serialize,
     {
         let response = self
-            .client
-            .get(format!("{}/@me", USER_URL))
-            .bearer_auth(&self.access_token)
+            .authorize(self.client.get(format!("{}/{}", USER_URL, user_name)))
+            .await?
             .query(&query)
-            .send()
+            .send_through(&self.transport)
             .await
-            .map_err(|err| UserApiError::new(format!("Failed get request: {}", err)))?;
+            .map_err(|err| {
+                UserApiError::with_code(ErrorCode::Request, format!("Failed get request: {}", err))
+            })?;
 
         handle_response(response).await
     }
 
     /// Get information about the OAuth user
     ///
+    /// Always requests `@me`, regardless of the user the query was created for
+    ///
     /// Corresponds to the [Get my user information](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_get) endpoint
     pub async fn get_my_user_information(
         &self,
         query: &GetUserInformation,
     ) -> Result<User, UserApiError> {
-        let response = self.get(query).await?;
-        let result: User = serde_json::from_str(response.as_str()).map_err(|err| {
-            UserApiError::new(format!("Failed to parse AnimeList result: {}", err))
-        })?;
+        let response = self.get("@me", query).await?;
+        let result: User = parse_response("User", &response)?;
+        Ok(result)
+    }
+
+    /// Get information about the user the query was created for
+    ///
+    /// MAL currently only allows `@me`, and rejects other users with the error
+    /// [code](UserApiError::code) of its response, e.g. [ErrorCode::BadRequest]
+    ///
+    /// Corresponds to the [Get my user information](https://myanimelist.net/apiconfig/references/api/v2#operation/users_user_id_get) endpoint
+    pub async fn get_user_information(
+        &self,
+        query: &GetUserInformation,
+    ) -> Result<User, UserApiError> {
+        let response = self.get(&query.user_name, query).await?;
+        let result: User = parse_response("User", &response)?;
+        Ok(result)
+    }
+
+    /// Get information about the user the query was created for, deserialized into
+    /// your own type
+    ///
+    /// Use this if your application has its own models for the MAL responses
+    /// instead of the ones provided by this crate
+    pub async fn get_raw<T>(&self, query: &GetUserInformation) -> Result<T, UserApiError>
+    where
+        T: DeserializeOwned,
+    {
+        let response = self.get(&query.user_name, query).await?;
+        let result: T = parse_response("raw", &response)?;
         Ok(result)
     }
 }
 
-async fn handle_response(response: reqwest::Response) -> Result<String, UserApiError> {
+async fn handle_response(response: reqwest::Response) -> Result<Bytes, UserApiError> {
     match response.status() {
         reqwest::StatusCode::OK => {
-            let content = response.text().await.map_err(|err| {
-                UserApiError::new(format!("Failed to get content from response: {}", err))
+            let content = response.bytes().await.map_err(|err| {
+                UserApiError::with_code(
+                    ErrorCode::Request,
+                    format!("Failed to get content from response: {}", err),
+                )
             })?;
             Ok(content)
         }
-        _ => Err(UserApiError::new(format!(
-            "Did not recieve OK response: {}",
-            response.status()
-        ))),
+        _ => Err(unexpected_response("Did not recieve OK response", response)
+            .await
+            .into()),
     }
 }
@@ -1,24 +1,53 @@
 // Structs for crafting User Endpoint requests
 use serde::Serialize;
 
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
+
+use super::error::UserApiError;
+use crate::common::{
+    impl_field_set, impl_to_query_string, FieldError, FieldSet, QueryField, ValidationError,
+};
 
 #[derive(Debug, Serialize)]
 pub struct GetUserInformation {
+    #[serde(skip_serializing)]
+    pub(crate) user_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     fields: Option<String>,
 }
 
 impl GetUserInformation {
-    /// Create new `Get user information` query
+    /// Create new `Get user information` query for yourself
     pub fn new(fields: Option<&UserFields>) -> Self {
         Self {
+            user_name: "@me".to_string(),
             fields: fields.map(|f| f.into()),
         }
     }
+
+    /// Create new `Get user information` query for another user
+    ///
+    /// Note: MAL currently only supports `@me`. Requests for other users fail
+    /// with the error MAL responds with, and will start working once MAL allows them
+    pub fn for_user<T: Into<String>>(
+        user_name: T,
+        fields: Option<&UserFields>,
+    ) -> Result<Self, UserApiError> {
+        let user_name = user_name.into();
+        if user_name.is_empty() {
+            return Err(ValidationError::EmptyUserName.into());
+        }
+
+        Ok(Self {
+            user_name,
+            fields: fields.map(|f| f.into()),
+        })
+    }
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
+impl_to_query_string!(GetUserInformation);
+
+#[derive(Debug, Clone, Copy, Display, EnumIter, EnumString, IntoStaticStr, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum UserField {
     id,
@@ -33,16 +62,46 @@ pub enum UserField {
     is_supporter,
 }
 
-pub struct UserFields(pub Vec<UserField>);
-
-impl Into<String> for &UserFields {
-    fn into(self) -> String {
-        let result = self
-            .0
-            .iter()
-            .map(|e| format!("{:?}", e))
-            .collect::<Vec<String>>()
-            .join(",");
-        result
+/// Set of valid User Fields
+#[derive(Debug, Clone)]
+pub struct UserFields(pub FieldSet<UserField>);
+
+impl_field_set!(UserFields, UserField);
+
+impl QueryField for UserField {
+    fn parse(name: &str) -> Result<Self, FieldError> {
+        name.parse()
+            .map_err(|_| FieldError::Unknown(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{all_fields, USER_FIELDS, USER_FIELDS_STR};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_get_user_information() {
+        let query = GetUserInformation::new(None);
+        assert_eq!(query.user_name, "@me");
+
+        let fields = UserFields::from(vec![UserField::id, UserField::name]);
+        let query = GetUserInformation::for_user("someone", Some(&fields)).unwrap();
+        assert_eq!(query.user_name, "someone");
+        assert_eq!(
+            serde_urlencoded::to_string(&query).unwrap(),
+            "fields=id%2Cname"
+        );
+
+        assert!(GetUserInformation::for_user("", None).is_err());
+    }
+
+    #[test]
+    fn test_all_fields_consts() {
+        assert_eq!(USER_FIELDS, UserField::iter().collect::<Vec<_>>());
+        let fields = UserFields::new(USER_FIELDS.to_vec());
+        assert_eq!(fields.as_str(), USER_FIELDS_STR);
+        assert_eq!(all_fields().as_str(), USER_FIELDS_STR);
     }
 }
@@ -1,12 +1,18 @@
-use std::fmt::Display;
+#[cfg(feature = "unknown-fields")]
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "unknown-fields")]
+use serde_json::Value;
+
+use crate::{common::impl_to_json, urls};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
     pub id: u32,
     pub name: String,
-    pub picture: String,
+    /// Not returned for users without a profile picture
+    pub picture: Option<String>,
     pub gender: Option<String>,
     pub birthday: Option<String>,
     pub location: Option<String>,
@@ -14,14 +20,31 @@ pub struct User {
     pub anime_statistics: Option<AnimeStatistics>,
     pub time_zone: Option<String>,
     pub is_supporter: bool,
+    /// Fields returned by MAL that this crate doesn't know about yet
+    #[cfg(feature = "unknown-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-impl Display for User {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
+impl User {
+    /// URL of the user's myanimelist.net profile page
+    pub fn profile_url(&self) -> String {
+        urls::profile_url(&self.name)
+    }
+
+    /// URL of the user's myanimelist.net anime list page
+    pub fn list_url(&self) -> String {
+        urls::anime_list_url(&self.name)
+    }
+
+    /// URL of the user's myanimelist.net manga list page
+    pub fn manga_list_url(&self) -> String {
+        urls::manga_list_url(&self.name)
     }
 }
 
+impl_to_json!(User);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AnimeStatistics {
     pub num_items_watching: u32,
@@ -41,8 +64,4 @@ pub struct AnimeStatistics {
     pub mean_score: f32,
 }
 
-impl Display for AnimeStatistics {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap_or_default())
-    }
-}
+impl_to_json!(AnimeStatistics);
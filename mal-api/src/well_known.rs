@@ -0,0 +1,199 @@
+//! Module with the ids of a few well-known anime and manga
+//!
+//! Useful in examples, tests, and health checks that need an entry that exists
+//! and won't be removed, instead of scattering magic numbers around.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use mal_api::well_known::ONE_PIECE_ANIME_ID;
+//!
+//! let query = GetAnimeDetails::builder(ONE_PIECE_ANIME_ID)
+//!     .fields(&detail_fields)
+//!     .build()
+//!     .unwrap();
+//! let details = api_client.get_anime_details(&query).await.unwrap();
+//! ```
+
+#[cfg(feature = "anime")]
+use crate::anime::{
+    api::AnimeApi,
+    error::AnimeApiError,
+    requests::{AnimeDetailFields, GetAnimeDetails},
+    responses::AnimeDetails,
+};
+#[cfg(feature = "manga")]
+use crate::manga::{
+    api::MangaApi,
+    error::MangaApiError,
+    requests::{GetMangaDetails, MangaDetailFields},
+    responses::MangaDetails,
+};
+
+/// Cowboy Bebop, the first anime on MAL
+pub const COWBOY_BEBOP_ANIME_ID: u32 = 1;
+
+/// One Piece, the TV series
+pub const ONE_PIECE_ANIME_ID: u32 = 21;
+
+/// Fullmetal Alchemist: Brotherhood
+pub const FULLMETAL_ALCHEMIST_BROTHERHOOD_ANIME_ID: u32 = 5114;
+
+/// Steins;Gate
+pub const STEINS_GATE_ANIME_ID: u32 = 9253;
+
+/// Gintama' (2011), the second season of Gintama
+pub const GINTAMA_PRIME_ANIME_ID: u32 = 9969;
+
+/// Sousou no Frieren (Frieren: Beyond Journey's End)
+pub const FRIEREN_ANIME_ID: u32 = 52991;
+
+/// Monster, the first manga on MAL
+pub const MONSTER_MANGA_ID: u32 = 1;
+
+/// Berserk
+pub const BERSERK_MANGA_ID: u32 = 2;
+
+/// One Piece
+pub const ONE_PIECE_MANGA_ID: u32 = 13;
+
+/// One Punch-Man
+pub const ONE_PUNCH_MAN_MANGA_ID: u32 = 44347;
+
+/// All of the well-known anime ids
+pub const ANIME_IDS: &[u32] = &[
+    COWBOY_BEBOP_ANIME_ID,
+    ONE_PIECE_ANIME_ID,
+    FULLMETAL_ALCHEMIST_BROTHERHOOD_ANIME_ID,
+    STEINS_GATE_ANIME_ID,
+    GINTAMA_PRIME_ANIME_ID,
+    FRIEREN_ANIME_ID,
+];
+
+/// All of the well-known manga ids
+pub const MANGA_IDS: &[u32] = &[
+    MONSTER_MANGA_ID,
+    BERSERK_MANGA_ID,
+    ONE_PIECE_MANGA_ID,
+    ONE_PUNCH_MAN_MANGA_ID,
+];
+
+/// Get the details of every well-known anime, in the order of [ANIME_IDS]
+///
+/// Sends one request per anime, one after the other
+#[cfg(feature = "anime")]
+pub async fn fetch_anime<A>(
+    api: &A,
+    fields: Option<&AnimeDetailFields>,
+) -> Result<Vec<AnimeDetails>, AnimeApiError>
+where
+    A: AnimeApi + Sync,
+{
+    let mut details = Vec::with_capacity(ANIME_IDS.len());
+    for anime_id in ANIME_IDS {
+        let query = GetAnimeDetails::new(*anime_id, fields)?;
+        details.push(api.get_anime_details(&query).await?);
+    }
+    Ok(details)
+}
+
+/// Get the details of every well-known manga, in the order of [MANGA_IDS]
+///
+/// Sends one request per manga, one after the other
+#[cfg(feature = "manga")]
+pub async fn fetch_manga<M>(
+    api: &M,
+    fields: Option<&MangaDetailFields>,
+) -> Result<Vec<MangaDetails>, MangaApiError>
+where
+    M: MangaApi + Sync,
+{
+    let mut details = Vec::with_capacity(MANGA_IDS.len());
+    for manga_id in MANGA_IDS {
+        let query = GetMangaDetails::new(*manga_id, false, fields)?;
+        details.push(api.get_manga_details(&query).await?);
+    }
+    Ok(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_ids() {
+        let anime: HashSet<_> = ANIME_IDS.iter().collect();
+        assert_eq!(anime.len(), ANIME_IDS.len());
+        let manga: HashSet<_> = MANGA_IDS.iter().collect();
+        assert_eq!(manga.len(), MANGA_IDS.len());
+
+        // Anime and manga ids are separate, so the first of each is 1
+        assert_eq!(COWBOY_BEBOP_ANIME_ID, MONSTER_MANGA_ID);
+        assert_ne!(ONE_PIECE_ANIME_ID, ONE_PIECE_MANGA_ID);
+    }
+
+    #[cfg(feature = "anime")]
+    #[tokio::test]
+    async fn test_fetch_anime() {
+        use crate::anime::{
+            api::Request,
+            requests::{GetAnimeRanking, GetSeasonalAnime, GetUserAnimeList},
+        };
+        use async_trait::async_trait;
+        use bytes::Bytes;
+        use serde::Serialize;
+
+        // Serves the details of any anime, without sending requests
+        struct Catalog;
+
+        #[async_trait]
+        impl Request for Catalog {
+            async fn get<T>(&self, _query: &T) -> Result<Bytes, AnimeApiError>
+            where
+                T: Serialize + Send + Sync,
+            {
+                unimplemented!()
+            }
+
+            async fn get_details(&self, query: &GetAnimeDetails) -> Result<Bytes, AnimeApiError> {
+                let details = serde_json::json!({
+                    "id": query.anime_id,
+                    "title": format!("Anime {}", query.anime_id)
+                });
+                Ok(Bytes::from(details.to_string()))
+            }
+
+            async fn get_ranking(&self, _query: &GetAnimeRanking) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_seasonal(
+                &self,
+                _query: &GetSeasonalAnime,
+            ) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_user(&self, _query: &GetUserAnimeList) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+
+            async fn get_next_or_prev(&self, _query: Option<&str>) -> Result<Bytes, AnimeApiError> {
+                unimplemented!()
+            }
+        }
+
+        impl AnimeApi for Catalog {
+            type State = Catalog;
+
+            fn get_self(&self) -> &Self::State {
+                self
+            }
+        }
+
+        let details = fetch_anime(&Catalog, None).await.unwrap();
+        let ids: Vec<u32> = details.iter().map(|d| d.shared_fields.id).collect();
+        assert_eq!(ids, ANIME_IDS);
+    }
+}
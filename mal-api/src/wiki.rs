@@ -0,0 +1,369 @@
+//! Module for generating markdown pages from anime details
+//!
+//! [WikiPageGenerator] renders [AnimeDetails] into a markdown page, e.g. for a
+//! personal wiki or an Obsidian vault. The built-in [DEFAULT_TEMPLATE] writes
+//! YAML front matter followed by the title, the artwork, the main facts, the
+//! synopsis, the theme songs, and links to related anime.
+//!
+//! Templates use the [TinyTemplate](https://docs.rs/tinytemplate) syntax, and
+//! are rendered with the fields of [WikiPage]. Values aren't HTML-escaped. Use
+//! the `yaml` formatter, as in `{title | yaml}`, to quote a value for the front
+//! matter.
+//!
+//! Request the `main_picture`, `alternative_titles`, `media_type`, `status`,
+//! `num_episodes`, `start_season`, `mean`, `rank`, `genres`, `studios`,
+//! `synopsis`, `background`, `opening_themes`, `ending_themes`, and
+//! `related_anime` fields for a full page. Missing fields are left out.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let details = api_client.get_anime_details(&query).await.unwrap();
+//!
+//! let generator = WikiPageGenerator::with_template(
+//!     "# {title}\n\n{{ for genre in genres }}#{genre} {{ endfor }}\n",
+//! )
+//! .unwrap();
+//! std::fs::write("vault/anime.md", generator.render(&details).unwrap()).unwrap();
+//! ```
+
+use serde::Serialize;
+use thiserror::Error;
+use tinytemplate::TinyTemplate;
+
+use crate::{anime::responses::AnimeDetails, codes::ErrorCode, urls::anime_url};
+
+/// Template used by [WikiPageGenerator::new]
+pub const DEFAULT_TEMPLATE: &str = "---
+mal_id: {id}
+title: {title | yaml}
+url: {url}
+genres:{{ for genre in genres }}
+  - {genre | yaml}{{ endfor }}
+---
+
+# {title}
+{{ if english_title }}
+*{english_title}*
+{{ endif }}
+{{ if picture }}![{title}]({picture})
+
+{{ endif }}{{ if media_type }}- **Type:** {media_type}
+{{ endif }}{{ if num_episodes }}- **Episodes:** {num_episodes}
+{{ endif }}{{ if status }}- **Status:** {status}
+{{ endif }}{{ if season }}- **Season:** {season}
+{{ endif }}{{ if mean }}- **Score:** {mean}
+{{ endif }}{{ if rank }}- **Ranked:** #{rank}
+{{ endif }}{{ if studios }}- **Studios:** {{ for studio in studios }}{studio}{{ if @last }}{{ else }}, {{ endif }}{{ endfor }}
+{{ endif }}{{ if synopsis }}
+## Synopsis
+
+{synopsis}
+{{ endif }}{{ if background }}
+## Background
+
+{background}
+{{ endif }}{{ if openings }}
+## Openings
+
+{{ for theme in openings }}- {theme}
+{{ endfor }}{{ endif }}{{ if endings }}
+## Endings
+
+{{ for theme in endings }}- {theme}
+{{ endfor }}{{ endif }}{{ if related }}
+## Related
+
+{{ for anime in related }}- {anime.relation}: [{anime.title}]({anime.url})
+{{ endfor }}{{ endif }}";
+
+#[derive(Debug, Error)]
+pub enum WikiError {
+    #[error("template error: {0}")]
+    Template(#[from] tinytemplate::error::Error),
+}
+
+impl WikiError {
+    /// The stable code of the error, see [codes](crate::codes)
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            WikiError::Template(_) => ErrorCode::Template,
+        }
+    }
+}
+
+/// The values a template is rendered with
+///
+/// Missing fields are `null` or empty, which templates treat as false in `if` blocks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WikiPage {
+    pub id: u32,
+
+    /// Title in the language set with [set_title_preference](crate::titles::set_title_preference)
+    pub title: String,
+    pub english_title: Option<String>,
+    pub japanese_title: Option<String>,
+    pub synonyms: Vec<String>,
+    pub url: String,
+
+    /// URL of the artwork, the large version if available
+    pub picture: Option<String>,
+
+    /// e.g. `TV`
+    pub media_type: Option<String>,
+    pub num_episodes: Option<u32>,
+
+    /// e.g. `Finished airing`
+    pub status: Option<String>,
+
+    /// e.g. `Fall 2023`
+    pub season: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+
+    /// Formatted with two decimals
+    pub mean: Option<String>,
+    pub rank: Option<u32>,
+    pub popularity: Option<u32>,
+    pub genres: Vec<String>,
+    pub studios: Vec<String>,
+    pub synopsis: Option<String>,
+    pub background: Option<String>,
+    pub openings: Vec<String>,
+    pub endings: Vec<String>,
+    pub related: Vec<WikiRelatedAnime>,
+}
+
+/// A related anime, as used in [WikiPage]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WikiRelatedAnime {
+    pub id: u32,
+    pub title: String,
+    pub url: String,
+
+    /// e.g. `Sequel`
+    pub relation: String,
+}
+
+impl WikiPage {
+    pub fn new(details: &AnimeDetails) -> Self {
+        let anime = &details.shared_fields;
+        let alternative_titles = anime.alternative_titles.as_ref();
+
+        Self {
+            id: anime.id,
+            title: details.display_title().to_string(),
+            english_title: alternative_titles
+                .and_then(|t| t.en.clone())
+                .filter(|t| !t.is_empty() && t != details.display_title()),
+            japanese_title: alternative_titles
+                .and_then(|t| t.ja.clone())
+                .filter(|t| !t.is_empty()),
+            synonyms: alternative_titles
+                .and_then(|t| t.synonyms.clone())
+                .unwrap_or_default(),
+            url: anime_url(anime.id),
+            picture: anime.main_picture.as_ref().and_then(|picture| {
+                [&picture.large, &picture.medium]
+                    .into_iter()
+                    .find(|url| !url.is_empty())
+                    .cloned()
+            }),
+            media_type: variant(&anime.media_type).map(|media_type| {
+                media_type
+                    .split('_')
+                    .map(|word| {
+                        // Abbreviations such as `tv` and `ova` are uppercased
+                        if word.len() <= 3 {
+                            word.to_uppercase()
+                        } else {
+                            capitalize(word)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }),
+            num_episodes: anime.num_episodes.filter(|n| *n > 0),
+            status: variant(&anime.status).map(|status| capitalize(&status.replace('_', " "))),
+            season: anime.start_season.as_ref().and_then(|start_season| {
+                let season = variant(&Some(&start_season.season))?;
+                Some(format!("{} {}", capitalize(&season), start_season.year))
+            }),
+            start_date: anime.start_date.clone(),
+            end_date: anime.end_date.clone(),
+            mean: anime.mean.map(|mean| format!("{:.2}", mean)),
+            rank: anime.rank,
+            popularity: anime.popularity,
+            genres: anime
+                .genres
+                .iter()
+                .flatten()
+                .map(|g| g.name.clone())
+                .collect(),
+            studios: anime
+                .studios
+                .iter()
+                .flatten()
+                .map(|s| s.name.clone())
+                .collect(),
+            synopsis: anime
+                .synopsis
+                .as_deref()
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+            background: details.background.clone().filter(|s| !s.is_empty()),
+            openings: details.openings().iter().map(|t| t.to_string()).collect(),
+            endings: details.endings().iter().map(|t| t.to_string()).collect(),
+            related: details
+                .related_anime
+                .iter()
+                .flatten()
+                .map(|related| WikiRelatedAnime {
+                    id: related.node.id,
+                    title: related.node.display_title().to_string(),
+                    url: anime_url(related.node.id),
+                    relation: related.relation_type_formatted.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Renders anime details into markdown pages with a template
+#[derive(Debug, Clone)]
+pub struct WikiPageGenerator {
+    template: String,
+}
+
+impl Default for WikiPageGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WikiPageGenerator {
+    /// Create a generator with the [DEFAULT_TEMPLATE]
+    pub fn new() -> Self {
+        Self {
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+
+    /// Create a generator with a custom template
+    ///
+    /// Returns an error if the template can't be parsed
+    pub fn with_template<T: Into<String>>(template: T) -> Result<Self, WikiError> {
+        let generator = Self {
+            template: template.into(),
+        };
+        generator.registry()?;
+        Ok(generator)
+    }
+
+    /// Render the page of an anime
+    pub fn render(&self, details: &AnimeDetails) -> Result<String, WikiError> {
+        self.render_page(&WikiPage::new(details))
+    }
+
+    /// Render a page from values that were already collected, e.g. to add to them
+    pub fn render_page(&self, page: &WikiPage) -> Result<String, WikiError> {
+        Ok(self.registry()?.render("page", page)?)
+    }
+
+    fn registry(&self) -> Result<TinyTemplate<'_>, WikiError> {
+        let mut registry = TinyTemplate::new();
+        registry.set_default_formatter(&tinytemplate::format_unescaped);
+        registry.add_formatter("yaml", |value, output| {
+            // JSON strings are valid YAML double-quoted strings
+            output.push_str(&value.to_string());
+            Ok(())
+        });
+        registry.add_template("page", &self.template)?;
+        Ok(registry)
+    }
+}
+
+// The name MAL uses for an enum variant, e.g. `finished_airing`
+fn variant<T: Serialize>(value: &Option<T>) -> Option<String> {
+    match serde_json::to_value(value.as_ref()?).ok()? {
+        serde_json::Value::String(name) => Some(name),
+        _ => None,
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::well_known::FRIEREN_ANIME_ID;
+
+    #[test]
+    fn test_wiki_page_generator() {
+        let details: AnimeDetails = serde_json::from_value(serde_json::json!({
+            "id": FRIEREN_ANIME_ID,
+            "title": "Sousou no Frieren",
+            "alternative_titles": { "en": "Frieren: Beyond Journey's End", "ja": "葬送のフリーレン" },
+            "media_type": "tv",
+            "num_episodes": 28,
+            "status": "finished_airing",
+            "start_season": { "year": 2023, "season": "fall" },
+            "mean": 9.3,
+            "genres": [{ "id": 2, "name": "Adventure" }, { "id": 8, "name": "Drama" }],
+            "studios": [{ "id": 11, "name": "Madhouse" }],
+            "synopsis": "The adventure is over.",
+            "related_anime": [{
+                "node": { "id": 56885, "title": "Sousou no Frieren: Marumaru no Mahou" },
+                "relation_type": "side_story",
+                "relation_type_formatted": "Side Story"
+            }]
+        }))
+        .unwrap();
+
+        let page = WikiPageGenerator::new().render(&details).unwrap();
+        assert_eq!(
+            page,
+            "---
+mal_id: 52991
+title: \"Sousou no Frieren\"
+url: https://myanimelist.net/anime/52991
+genres:
+  - \"Adventure\"
+  - \"Drama\"
+---
+
+# Sousou no Frieren
+
+*Frieren: Beyond Journey's End*
+
+- **Type:** TV
+- **Episodes:** 28
+- **Status:** Finished airing
+- **Season:** Fall 2023
+- **Score:** 9.30
+- **Studios:** Madhouse
+
+## Synopsis
+
+The adventure is over.
+
+## Related
+
+- Side Story: [Sousou no Frieren: Marumaru no Mahou](https://myanimelist.net/anime/56885)
+"
+        );
+
+        let generator = WikiPageGenerator::with_template("[[{title}]] ({season})").unwrap();
+        assert_eq!(
+            generator.render(&details).unwrap(),
+            "[[Sousou no Frieren]] (Fall 2023)"
+        );
+        assert!(WikiPageGenerator::with_template("{{ if title }}").is_err());
+    }
+}